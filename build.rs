@@ -0,0 +1,24 @@
+use std::{env, path::Path, process::Command};
+
+// Embeds the revision and the compiler the exporter is built with into `raspi_exporter_build_info`
+fn main() {
+    let revision = env::var("RASPI_EXPORTER_REVISION").ok().or_else(|| output("git", &["rev-parse", "--short=12", "HEAD"]));
+    let rustc = output(&env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string()), &["--version"])
+        .and_then(|version| version.split_whitespace().nth(1).map(ToString::to_string));
+
+    println!("cargo:rustc-env=RASPI_EXPORTER_REVISION={}", revision.as_deref().unwrap_or("unknown"));
+    println!("cargo:rustc-env=RASPI_EXPORTER_RUSTC={}", rustc.as_deref().unwrap_or("unknown"));
+    println!("cargo:rerun-if-env-changed=RASPI_EXPORTER_REVISION");
+    // Cargo reruns the script on every build when a file doesn't exist, e.g. outside a git checkout
+    for file in [".git/HEAD", ".git/refs", ".git/packed-refs"] {
+        if Path::new(file).exists() {
+            println!("cargo:rerun-if-changed={file}");
+        }
+    }
+}
+
+fn output(command: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(command).args(args).output().ok().filter(|output| output.status.success())?;
+
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
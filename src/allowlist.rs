@@ -0,0 +1,111 @@
+use std::{net::IpAddr, str::FromStr};
+
+/// Network in CIDR notation such as `192.168.1.0/24`, or a single address without the prefix length.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IpNetwork {
+    address: IpAddr,
+    prefix_length: u8,
+}
+
+/// Networks allowed to scrape.
+#[derive(Clone, Debug)]
+pub struct Allowlist {
+    networks: Vec<IpNetwork>,
+}
+
+impl IpNetwork {
+    pub fn contains(&self, address: IpAddr) -> bool {
+        match (self.address, address.to_canonical()) {
+            (IpAddr::V4(network), IpAddr::V4(address)) => {
+                let mask = u32::MAX.checked_shl(32 - u32::from(self.prefix_length)).unwrap_or(0);
+                u32::from(network) & mask == u32::from(address) & mask
+            },
+            (IpAddr::V6(network), IpAddr::V6(address)) => {
+                let mask = u128::MAX.checked_shl(128 - u32::from(self.prefix_length)).unwrap_or(0);
+                u128::from(network) & mask == u128::from(address) & mask
+            },
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for IpNetwork {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (address, prefix_length) = match s.split_once('/') {
+            Some((address, prefix_length)) => (address, Some(prefix_length)),
+            None => (s, None),
+        };
+        let address = address.parse::<IpAddr>().map_err(|err| format!("invalid address: {err}"))?;
+        let max_prefix_length = if address.is_ipv4() { 32 } else { 128 };
+        let prefix_length = match prefix_length {
+            Some(prefix_length) => prefix_length.parse::<u8>().map_err(|err| format!("invalid prefix length: {err}"))?,
+            None => max_prefix_length,
+        };
+        if prefix_length > max_prefix_length {
+            return Err(format!("prefix length is longer than {max_prefix_length}"));
+        }
+
+        Ok(Self {
+            address,
+            prefix_length,
+        })
+    }
+}
+
+impl Allowlist {
+    pub fn new(networks: Vec<IpNetwork>) -> Self {
+        Self {
+            networks,
+        }
+    }
+
+    pub fn allows(&self, address: IpAddr) -> bool {
+        self.networks.iter().any(|network| network.contains(address))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::IpAddr;
+
+    use crate::allowlist::{Allowlist, IpNetwork};
+
+    fn ip(address: &str) -> IpAddr {
+        address.parse().unwrap()
+    }
+
+    #[test]
+    fn contains() {
+        let network = "192.168.1.0/24".parse::<IpNetwork>().unwrap();
+        assert!(network.contains(ip("192.168.1.10")));
+        assert!(!network.contains(ip("192.168.2.10")));
+        // Accepted on a dual-stack socket
+        assert!(network.contains(ip("::ffff:192.168.1.10")));
+
+        let network = "fd00::/8".parse::<IpNetwork>().unwrap();
+        assert!(network.contains(ip("fd12::1")));
+        assert!(!network.contains(ip("fe80::1")));
+        assert!(!network.contains(ip("192.168.1.10")));
+
+        let network = "0.0.0.0/0".parse::<IpNetwork>().unwrap();
+        assert!(network.contains(ip("203.0.113.1")));
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!("10.0.0.1".parse::<IpNetwork>(), "10.0.0.1/32".parse::<IpNetwork>());
+        assert!("10.0.0.0/33".parse::<IpNetwork>().is_err());
+        assert!("10.0.0/8".parse::<IpNetwork>().is_err());
+    }
+
+    #[test]
+    fn allows() {
+        let allowlist = Allowlist::new(vec!["127.0.0.1".parse().unwrap(), "10.0.0.0/8".parse().unwrap()]);
+
+        assert!(allowlist.allows(ip("127.0.0.1")));
+        assert!(allowlist.allows(ip("10.1.2.3")));
+        assert!(!allowlist.allows(ip("192.168.1.10")));
+    }
+}
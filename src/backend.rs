@@ -0,0 +1,127 @@
+//! Sources of the metrics that both vcgencmd and sysfs provide, since containers and distributions other than Raspberry Pi
+//! OS often lack vcgencmd.
+
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
+
+use clap::ValueEnum;
+use strum::Display as StrumDisplay;
+
+use crate::{command::CommandExecutor, config::CollectorConfig, executor::Executor, file::FileExecutor};
+
+const VCHIQ: &str = "/dev/vchiq";
+const THERMAL_ZONE: &str = "/sys/class/thermal/thermal_zone0/temp";
+// Written by the firmware driver of the Raspberry Pi kernel in hexadecimal
+const FIRMWARE_THROTTLED: &str = "/sys/devices/platform/soc/soc:firmware/get_throttled";
+
+/// Source of the temperature and the throttled state, picked by what the board has with `auto`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum, StrumDisplay)]
+#[strum(serialize_all = "snake_case")]
+pub enum Backend {
+    /// vcgencmd for throttling when it is in PATH and sysfs otherwise, and the other way around for the temperature
+    #[default]
+    Auto,
+    /// vcgencmd measure_temp and get_throttled
+    Vcgencmd,
+    /// Thermal zone and the get_throttled file of the firmware driver
+    Sysfs,
+}
+
+/// Executor of the source picked by a backend.
+#[derive(Debug)]
+pub enum BackendExecutor {
+    Command(CommandExecutor<String, Vec<String>>),
+    File(FileExecutor<PathBuf>),
+}
+
+impl Backend {
+    /// Executor of the throttled state, a configured command or path taking precedence over probing in `auto`.
+    pub fn throttled(self, config: &CollectorConfig) -> BackendExecutor {
+        let backend = match self {
+            Self::Auto if config.command.is_some() => Self::Vcgencmd,
+            Self::Auto if config.path.is_some() => Self::Sysfs,
+            Self::Auto => Self::pick(&[(Self::Vcgencmd, find_command("vcgencmd").is_some()), (Self::Sysfs, Path::new(FIRMWARE_THROTTLED).exists())]),
+            backend => backend,
+        };
+
+        match backend {
+            Self::Sysfs => BackendExecutor::File(FileExecutor::new(config.path(FIRMWARE_THROTTLED))),
+            _ => {
+                let (command, args) = config.command("vcgencmd", &["get_throttled"]);
+                BackendExecutor::Command(CommandExecutor::new(command, args))
+            },
+        }
+    }
+
+    /// Executor of the SoC temperature, a configured command or path taking precedence over probing in `auto`.
+    pub fn temperature(self, config: &CollectorConfig) -> BackendExecutor {
+        let backend = match self {
+            Self::Auto if config.path.is_some() => Self::Sysfs,
+            Self::Auto if config.command.is_some() => Self::Vcgencmd,
+            Self::Auto => Self::pick(&[(Self::Sysfs, Path::new(THERMAL_ZONE).exists()), (Self::Vcgencmd, find_command("vcgencmd").is_some())]),
+            backend => backend,
+        };
+
+        match backend {
+            Self::Vcgencmd => {
+                let (command, args) = config.command("vcgencmd", &["measure_temp"]);
+                BackendExecutor::Command(CommandExecutor::new(command, args))
+            },
+            _ => BackendExecutor::File(FileExecutor::new(config.path(THERMAL_ZONE))),
+        }
+    }
+
+    // First available of `candidates`, or the first one when none is, so that its error tells what is missing
+    fn pick(candidates: &[(Self, bool)]) -> Self {
+        let backend = candidates.iter().find(|(_, available)| *available).unwrap_or(&candidates[0]).0;
+        tracing::debug!("picked {backend} backend");
+        backend
+    }
+}
+
+impl Executor for BackendExecutor {
+    async fn execute(&self) -> anyhow::Result<String> {
+        match self {
+            Self::Command(executor) => executor.execute().await,
+            Self::File(executor) => executor.execute().await,
+        }
+    }
+}
+
+/// Whether the VideoCore firmware is reachable through vcgencmd or its driver, only on Raspberry Pi.
+pub fn has_videocore() -> bool {
+    Path::new(VCHIQ).exists() || Path::new(FIRMWARE_THROTTLED).exists()
+}
+
+/// Path of `command` in PATH.
+pub(crate) fn find_command(command: &str) -> Option<PathBuf> {
+    env::split_paths(&env::var_os("PATH")?).map(|dir| dir.join(command)).find(|path| path.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        backend::{Backend, BackendExecutor},
+        config::CollectorConfig,
+    };
+
+    #[test]
+    fn configured() {
+        let command = CollectorConfig {
+            command: Some(vec!["/opt/vc/bin/vcgencmd".to_string(), "measure_temp".to_string()]),
+            ..Default::default()
+        };
+        let path = CollectorConfig {
+            path: Some("/sys/class/thermal/thermal_zone1/temp".into()),
+            ..Default::default()
+        };
+
+        assert!(matches!(Backend::Auto.temperature(&command), BackendExecutor::Command(_)));
+        assert!(matches!(Backend::Auto.throttled(&path), BackendExecutor::File(_)));
+        // The override wins over the configured source
+        assert!(matches!(Backend::Sysfs.temperature(&command), BackendExecutor::File(_)));
+        assert!(matches!(Backend::Vcgencmd.throttled(&CollectorConfig::default()), BackendExecutor::Command(_)));
+    }
+}
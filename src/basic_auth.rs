@@ -0,0 +1,116 @@
+use std::{collections::{HashMap, HashSet}, sync::{Arc, Mutex, RwLock}};
+
+use base64::{prelude::BASE64_STANDARD, Engine};
+use sha2::{Digest, Sha256};
+
+/// Users allowed by HTTP basic authentication, mapped to their passwords hashed with bcrypt.
+#[derive(Debug)]
+pub struct BasicAuth {
+    users: RwLock<Arc<HashMap<String, String>>>,
+    // bcrypt is slow on purpose, which would be paid on every scrape without remembering verified credentials
+    verified: Mutex<HashSet<Vec<u8>>>,
+}
+
+impl BasicAuth {
+    pub fn new(users: HashMap<String, String>) -> Self {
+        Self {
+            users: RwLock::new(Arc::new(users)),
+            verified: Mutex::default(),
+        }
+    }
+
+    /// Takes the users of `basic_auth`, e.g. built from a reloaded web config, forgetting the credentials verified so far.
+    pub fn reload(&self, basic_auth: Self) {
+        let mut current = self.users.write().expect("failed to lock users");
+        *current = basic_auth.users.into_inner().expect("failed to lock users");
+        self.verified.lock().expect("failed to lock verified mutex").clear();
+    }
+
+    /// Returns whether the value of an `Authorization` header has the credentials of a user.
+    pub async fn verify(&self, authorization: &str) -> bool {
+        let Some((username, password)) = authorization
+            .strip_prefix("Basic ")
+            .and_then(|v| BASE64_STANDARD.decode(v).ok())
+            .and_then(|v| String::from_utf8(v).ok())
+            .and_then(|v| v.split_once(':').map(|(username, password)| (username.to_string(), password.to_string())))
+        else {
+            return false;
+        };
+
+        let key = Sha256::new()
+            .chain_update(&username)
+            .chain_update([0])
+            .chain_update(&password)
+            .finalize()
+            .to_vec();
+        if self.verified.lock().expect("failed to lock verified mutex").contains(&key) {
+            return true;
+        }
+
+        let users = self.users.read().expect("failed to lock users").clone();
+        let verified = tokio::task::spawn_blocking({
+            let users = users.clone();
+            move || {
+                match users.get(&username) {
+                    Some(hash) => bcrypt::verify(&password, hash).unwrap_or(false),
+                    // Takes as long as a known user so that valid usernames can't be told by response times
+                    None => {
+                        if let Some(hash) = users.values().next() {
+                            let _ = bcrypt::verify(&password, hash);
+                        }
+                        false
+                    },
+                }
+            }
+        }).await.unwrap_or(false);
+
+        // Unless the users were replaced meanwhile, which would remember credentials of a removed user
+        let current = self.users.read().expect("failed to lock users");
+        if verified && Arc::ptr_eq(&users, &current) {
+            self.verified.lock().expect("failed to lock verified mutex").insert(key);
+        }
+
+        verified
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use base64::{prelude::BASE64_STANDARD, Engine};
+
+    use crate::basic_auth::BasicAuth;
+
+    fn authorization(credentials: &str) -> String {
+        format!("Basic {}", BASE64_STANDARD.encode(credentials))
+    }
+
+    #[tokio::test]
+    async fn verify() {
+        let basic_auth = BasicAuth::new(HashMap::from([
+            ("prometheus".to_string(), bcrypt::hash("secret", 4).unwrap()),
+        ]));
+
+        assert!(basic_auth.verify(&authorization("prometheus:secret")).await);
+        // Cached
+        assert!(basic_auth.verify(&authorization("prometheus:secret")).await);
+        assert!(!basic_auth.verify(&authorization("prometheus:wrong")).await);
+        assert!(!basic_auth.verify(&authorization("unknown:secret")).await);
+        assert!(!basic_auth.verify("Bearer token").await);
+    }
+
+    #[tokio::test]
+    async fn reload() {
+        let basic_auth = BasicAuth::new(HashMap::from([
+            ("prometheus".to_string(), bcrypt::hash("secret", 4).unwrap()),
+        ]));
+        assert!(basic_auth.verify(&authorization("prometheus:secret")).await);
+
+        basic_auth.reload(BasicAuth::new(HashMap::from([
+            ("grafana".to_string(), bcrypt::hash("secret", 4).unwrap()),
+        ])));
+        assert!(!basic_auth.verify(&authorization("prometheus:secret")).await);
+        assert!(basic_auth.verify(&authorization("grafana:secret")).await);
+    }
+}
@@ -0,0 +1,168 @@
+use std::{
+    collections::HashMap,
+    sync::{self, Arc},
+    time::{Duration, Instant},
+};
+
+use tokio::sync::{Mutex, OnceCell};
+
+use crate::executor::Executor;
+
+tokio::task_local! {
+    static SCRAPE: ScrapeCache;
+}
+
+/// Reuses the output of the inner executor until it is older than `ttl`.
+///
+/// Failures are not cached, so the next execution retries immediately.
+#[derive(Debug)]
+pub struct CachedExecutor<E> {
+    executor: E,
+    ttl: Duration,
+    cache: Mutex<Option<(Instant, String)>>,
+}
+
+/// Outputs of the commands run and the files read in a scrape by the command or the path, so that the collectors reading
+/// the same source share a single read.
+///
+/// Failures are not cached, so another collector reading the source retries.
+#[derive(Debug, Default)]
+pub struct ScrapeCache {
+    outputs: sync::Mutex<HashMap<String, Arc<OnceCell<String>>>>,
+}
+
+impl<E> CachedExecutor<E> {
+    pub fn new(executor: E, ttl: Duration) -> Self {
+        Self {
+            executor,
+            ttl,
+            cache: Mutex::new(None),
+        }
+    }
+}
+
+impl<E> Executor for CachedExecutor<E>
+where
+    E: Executor + Send + Sync,
+{
+    async fn execute(&self) -> anyhow::Result<String> {
+        // Held during execution so that concurrent scrapes wait for a single run
+        let mut cache = self.cache.lock().await;
+        if let Some((executed_at, output)) = cache.as_ref()
+            && executed_at.elapsed() < self.ttl
+        {
+            tracing::debug!("using cached output");
+            return Ok(output.clone());
+        }
+
+        let output = self.executor.execute().await?;
+        *cache = Some((Instant::now(), output.clone()));
+
+        Ok(output)
+    }
+}
+
+impl ScrapeCache {
+    /// Runs `future` with a cache of its own, which the executions of sources in it share.
+    pub async fn scope<F>(future: F) -> F::Output
+    where
+        F: Future,
+    {
+        SCRAPE.scope(Self::default(), future).await
+    }
+
+    /// Output of `source` read by `execute`, the one read earlier in the scope if any, and always read outside a scope.
+    pub(crate) async fn execute<F>(source: String, execute: F) -> anyhow::Result<String>
+    where
+        F: Future<Output = anyhow::Result<String>>,
+    {
+        let Ok(cell) = SCRAPE.try_with(|cache| {
+            let mut outputs = cache.outputs.lock().expect("failed to lock scrape cache mutex");
+            outputs.entry(source).or_default().clone()
+        }) else {
+            return execute.await;
+        };
+
+        if cell.initialized() {
+            tracing::debug!("using output read earlier in the scrape");
+        }
+        cell.get_or_try_init(|| execute).await.cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, time::Duration};
+
+    use futures::future::{err, ok};
+
+    use crate::{cache::{CachedExecutor, ScrapeCache}, executor::{Executor, MockExecutor}, file::FileExecutor};
+
+    #[tokio::test]
+    async fn execute_cached() {
+        let mut mock_executor = MockExecutor::new();
+        mock_executor
+            .expect_execute()
+            .times(1)
+            .returning(|| Box::pin(ok("output".to_string())));
+
+        let cached_executor = CachedExecutor::new(mock_executor, Duration::from_secs(60));
+
+        assert_eq!(cached_executor.execute().await.unwrap(), "output");
+        assert_eq!(cached_executor.execute().await.unwrap(), "output");
+    }
+
+    #[tokio::test]
+    async fn execute_expired() {
+        let mut mock_executor = MockExecutor::new();
+        mock_executor
+            .expect_execute()
+            .times(2)
+            .returning(|| Box::pin(ok("output".to_string())));
+
+        let cached_executor = CachedExecutor::new(mock_executor, Duration::ZERO);
+
+        assert_eq!(cached_executor.execute().await.unwrap(), "output");
+        assert_eq!(cached_executor.execute().await.unwrap(), "output");
+    }
+
+    #[tokio::test]
+    async fn execute_error_not_cached() {
+        let mut mock_executor = MockExecutor::new();
+        let mut sequence = mockall::Sequence::new();
+        mock_executor
+            .expect_execute()
+            .times(1)
+            .in_sequence(&mut sequence)
+            .returning(|| Box::pin(err(anyhow::anyhow!("failed"))));
+        mock_executor
+            .expect_execute()
+            .times(1)
+            .in_sequence(&mut sequence)
+            .returning(|| Box::pin(ok("output".to_string())));
+
+        let cached_executor = CachedExecutor::new(mock_executor, Duration::from_secs(60));
+
+        assert!(cached_executor.execute().await.is_err());
+        assert_eq!(cached_executor.execute().await.unwrap(), "output");
+    }
+
+    #[tokio::test]
+    async fn scrape_cache() {
+        let path = std::env::temp_dir().join(format!("raspi-exporter-scrape-cache-{}", std::process::id()));
+        fs::write(&path, "first").unwrap();
+        let executor = FileExecutor::new(path.clone());
+        let other = FileExecutor::new(path.clone());
+
+        let (output, other_output) = ScrapeCache::scope(async {
+            let output = executor.execute().await.unwrap();
+            fs::write(&path, "second").unwrap();
+            (output, other.execute().await.unwrap())
+        }).await;
+        let outside = executor.execute().await.unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!((output.as_str(), other_output.as_str()), ("first", "first"));
+        assert_eq!(outside, "second");
+    }
+}
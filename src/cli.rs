@@ -1,54 +1,898 @@
-use std::fmt::Display;
+use std::{collections::HashMap, env, ffi::OsString, fmt::Display, net::{IpAddr, SocketAddr}, path::{Path, PathBuf}, sync::LazyLock, time::Duration};
 
-use clap::{Args, Parser, ValueEnum};
+use clap::{builder::PossibleValue, Arg, error::ErrorKind, parser::ValueSource, Args, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+use hyper::Uri;
 use strum::Display as StrumDisplay;
 
+use crate::{allowlist::IpNetwork, backend::{self, Backend}, completions::Shell, config::{CollectorConfig, Config}, relabel::Rule, server::ListenAddress, threshold::Threshold};
+
+/// Options given before or after the subcommand, which serves the metrics when omitted.
 #[derive(Debug, Parser)]
-#[command(version, about)]
+#[command(version, about, mut_args = |arg: Arg| arg.global(true))]
 pub struct Cli {
+    /// TOML file of options keyed by their long names, e.g. `port = 9100`, overridden by environment variables such as
+    /// RASPI_EXPORTER_PORT=9100, which the options on the command line override in turn
+    ///
+    /// Reloaded on SIGHUP along with the web config: enabled metrics, the TLS certificate, basic auth users, relabel rules,
+    /// thresholds, collector settings, which rebuild the collectors of the metrics whose settings changed, --hook,
+    /// --webhook-url, --webhook-debounce, collector timeouts and --fail-on-collector-error. Changes to other options are
+    /// logged as taking a restart, as is turning TLS or basic auth on or off
+    #[arg(long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Address to listen on, e.g. 127.0.0.1 to only accept scrapes through a reverse proxy, or :: for both IPv6 and IPv4
+    ///
+    /// Repeat it to listen on several addresses. An address can have its own port such as 192.168.1.1:9100, or be a unix
+    /// socket such as unix:/run/raspi-exporter.sock
+    #[arg(short, long, visible_alias = "listen", value_parser = parse_listen, default_value = "0.0.0.0")]
+    pub address: Vec<Listen>,
+
     #[arg(short, long, default_value_t = 8021)]
     pub port: u16,
 
+    /// Path to serve the metrics on, e.g. /raspi/metrics behind a reverse proxy shared with other services
+    #[arg(long = "web.telemetry-path", value_parser = parse_path, default_value = "/metrics")]
+    pub metrics_path: String,
+
+    /// Address to serve the health, readiness and events endpoints on instead of the metrics port, e.g. 127.0.0.1:8022, along
+    /// with the endpoints to list, enable, disable and collect the metrics under /collectors
+    ///
+    /// It must be a loopback address unless the web config has basic auth users, which the endpoints then require
+    #[arg(long)]
+    pub admin_address: Option<SocketAddr>,
+
+    /// Networks allowed to scrape in CIDR notation, e.g. 192.168.1.10/32, all of them when omitted
+    #[arg(long, value_delimiter = ',')]
+    pub allowed_networks: Vec<IpNetwork>,
+
+    /// Origins of browser-based dashboards allowed to fetch from the exporter, e.g. http://192.168.1.20:3000, or * for any
+    #[arg(long, value_delimiter = ',')]
+    pub cors_origins: Vec<String>,
+
+    /// Only accepts IPv6 connections when listening on an IPv6 address
+    #[arg(long)]
+    pub ipv6_only: bool,
+
+    /// PEM file of the certificate chain to serve HTTPS with, reloaded on SIGHUP along with the key
+    #[arg(long, requires = "tls_key", conflicts_with = "web_config_file")]
+    pub tls_cert: Option<PathBuf>,
+
+    /// PEM file of the private key of the certificate
+    #[arg(long, requires = "tls_cert")]
+    pub tls_key: Option<PathBuf>,
+
+    /// Directory to generate a self-signed certificate into on the first start and serve HTTPS with, for scrapers with
+    /// insecure_skip_verify until a real certificate is set up
+    #[arg(long, conflicts_with_all = ["tls_cert", "web_config_file"])]
+    pub tls_self_signed: Option<PathBuf>,
+
+    /// Writes the metrics into this file on an interval for the textfile collector of node_exporter, instead of serving them
+    #[arg(long, value_name = "FILE")]
+    pub textfile_output: Option<PathBuf>,
+
+    /// Interval of writing the --textfile-output file
+    #[arg(long, value_parser = humantime::parse_duration, default_value = "15s")]
+    pub textfile_interval: Duration,
+
+    /// URL of a Prometheus remote write endpoint to push the metrics to on an interval, in addition to serving them
+    #[arg(long, value_name = "URL")]
+    pub remote_write_url: Option<Uri>,
+
+    /// Interval of pushing to --remote-write-url
+    #[arg(long, value_parser = humantime::parse_duration, default_value = "15s")]
+    pub remote_write_interval: Duration,
+
+    /// Username of basic authentication to the remote write endpoint
+    #[arg(long, requires = "remote_write_password")]
+    pub remote_write_username: Option<String>,
+
+    /// Password of basic authentication to the remote write endpoint
+    #[arg(long, requires = "remote_write_username")]
+    pub remote_write_password: Option<String>,
+
+    /// Bearer token to authenticate to the remote write endpoint with
+    #[arg(long, conflicts_with = "remote_write_username")]
+    pub remote_write_bearer_token: Option<String>,
+
+    /// Write endpoint of InfluxDB with its parameters to write the metrics to on an interval, in addition to serving them, such
+    /// as http://influxdb:8086/write?db=raspi or http://influxdb:8086/api/v2/write?org=home&bucket=raspi
+    #[arg(long, value_name = "URL")]
+    pub influxdb_url: Option<Uri>,
+
+    /// Interval of writing to --influxdb-url
+    #[arg(long, value_parser = humantime::parse_duration, default_value = "15s")]
+    pub influxdb_interval: Duration,
+
+    /// Username of basic authentication to the InfluxDB v1 API
+    #[arg(long, requires = "influxdb_password")]
+    pub influxdb_username: Option<String>,
+
+    /// Password of basic authentication to the InfluxDB v1 API
+    #[arg(long, requires = "influxdb_username")]
+    pub influxdb_password: Option<String>,
+
+    /// API token of the InfluxDB v2 API
+    #[arg(long, conflicts_with = "influxdb_username")]
+    pub influxdb_token: Option<String>,
+
+    /// Carbon to send the metrics to on an interval with the Graphite plaintext protocol, as a host with an optional port,
+    /// in addition to serving them
+    #[arg(long, value_name = "HOST[:PORT]")]
+    pub graphite_carbon: Option<String>,
+
+    /// Interval of sending to --graphite-carbon
+    #[arg(long, value_parser = humantime::parse_duration, default_value = "15s")]
+    pub graphite_interval: Duration,
+
+    /// Dotted path prepended to the metric paths, such as home.raspberrypi
+    #[arg(long, default_value = "")]
+    pub graphite_prefix: String,
+
+    /// StatsD server to send the metrics to on an interval over UDP, as a host with an optional port, in addition to serving
+    /// them
+    #[arg(long, value_name = "HOST[:PORT]")]
+    pub statsd_address: Option<String>,
+
+    /// Interval of sending to --statsd-address
+    #[arg(long, value_parser = humantime::parse_duration, default_value = "15s")]
+    pub statsd_interval: Duration,
+
+    /// Dotted path prepended to the StatsD metric names
+    #[arg(long, default_value = "")]
+    pub statsd_prefix: String,
+
+    /// Sends labels as DogStatsD tags rather than appending their values to the StatsD metric names
+    #[arg(long)]
+    pub statsd_tags: bool,
+
+    /// MQTT broker to publish the metrics to on an interval, as a host with an optional port, in addition to serving them
+    #[cfg(feature = "mqtt")]
+    #[arg(long, value_name = "HOST[:PORT]")]
+    pub mqtt_broker: Option<String>,
+
+    /// Interval of publishing to --mqtt-broker
+    #[cfg(feature = "mqtt")]
+    #[arg(long, value_parser = humantime::parse_duration, default_value = "15s")]
+    pub mqtt_interval: Duration,
+
+    /// Topic prefix of the published metrics, followed by the metric name and its label values
+    #[cfg(feature = "mqtt")]
+    #[arg(long, default_value = "raspi")]
+    pub mqtt_topic_prefix: String,
+
+    /// Client identifier to connect to the MQTT broker with, unique among the exporters
+    #[cfg(feature = "mqtt")]
+    #[arg(long, default_value = "raspi_exporter")]
+    pub mqtt_client_id: String,
+
+    /// Username to connect to the MQTT broker with
+    #[cfg(feature = "mqtt")]
+    #[arg(long, requires = "mqtt_password")]
+    pub mqtt_username: Option<String>,
+
+    /// Password to connect to the MQTT broker with
+    #[cfg(feature = "mqtt")]
+    #[arg(long, requires = "mqtt_username")]
+    pub mqtt_password: Option<String>,
+
+    /// Quality of service of the published messages, either 0 (at most once) or 1 (at least once)
+    #[cfg(feature = "mqtt")]
+    #[arg(long, value_parser = clap::value_parser!(u8).range(0..=1), default_value_t = 0)]
+    pub mqtt_qos: u8,
+
+    /// Makes the MQTT broker retain the last values for subscribers coming later
+    #[cfg(feature = "mqtt")]
+    #[arg(long)]
+    pub mqtt_retain: bool,
+
+    /// Also publishes Home Assistant discovery configs under this prefix, usually homeassistant, so that the SoC temperature
+    /// and throttling show up as entities
+    #[cfg(feature = "mqtt")]
+    #[arg(long, value_name = "PREFIX", requires = "mqtt_broker")]
+    pub mqtt_discovery_prefix: Option<String>,
+
+    /// URL to POST a JSON payload to when undervoltage or throttling becomes active, such as a chat webhook, sampled on
+    /// --sampling-interval whether throttled is enabled or not
+    #[arg(long, value_name = "URL")]
+    pub webhook_url: Option<Uri>,
+
+    /// How long undervoltage or throttling must stay active before --webhook-url is notified
+    #[arg(long, value_parser = humantime::parse_duration, default_value = "30s")]
+    pub webhook_debounce: Duration,
+
+    /// Executable run when undervoltage or throttling starts or clears, sampled on --sampling-interval, or when a sample
+    /// exceeds a threshold of the config file or stops exceeding it, evaluated on scrapes
+    ///
+    /// RASPI_EVENT is throttling_started, throttling_cleared, threshold_exceeded or threshold_cleared, and the details are in
+    /// RASPI_THROTTLING_KIND, or in RASPI_THRESHOLD and RASPI_LABEL_<NAME> of the labels of the sample.
+    #[arg(long, value_name = "EXECUTABLE")]
+    pub hook: Option<PathBuf>,
+
+    /// sysfs directory of a PWM channel driving a fan, such as /sys/class/pwm/pwmchip0/pwm0, exported if missing, whose duty
+    /// follows --fan-curve at the SoC temperature sampled on --sampling-interval
+    #[arg(long, value_name = "DIR", conflicts_with = "fan_gpio")]
+    pub fan_pwm: Option<PathBuf>,
+
+    /// sysfs directory of a GPIO switching a fan, such as /sys/class/gpio/gpio14, exported if missing, turned on whenever
+    /// --fan-curve gives a duty above zero
+    #[arg(long, value_name = "DIR")]
+    pub fan_gpio: Option<PathBuf>,
+
+    /// Period of the PWM signal of --fan-pwm
+    #[arg(long, value_parser = humantime::parse_duration, default_value = "40us")]
+    pub fan_pwm_period: Duration,
+
+    /// Duties of the fan in percent at SoC temperatures in Celsius, as points joined with : and linear in between
+    #[arg(long, value_name = "TEMP:DUTY", value_parser = parse_fan_point, value_delimiter = ',', default_value = "50:0,60:50,70:100")]
+    pub fan_curve: Vec<(f64, f64)>,
+
+    /// How many degrees Celsius the SoC temperature must drop before the duty of the fan lowers
+    #[arg(long, value_name = "CELSIUS", default_value_t = 2.0)]
+    pub fan_hysteresis: f64,
+
+    /// SQLite database to record the metrics into on --history-interval with the sqlite3 command, for when Prometheus can't
+    /// reach the Pi, served on /history?metric=<name>&since=<duration> as JSON
+    #[arg(long, value_name = "FILE")]
+    pub history_file: Option<PathBuf>,
+
+    /// Interval of recording the metrics into --history-file
+    #[arg(long, value_parser = humantime::parse_duration, default_value = "1m")]
+    pub history_interval: Duration,
+
+    /// How long the samples of --history-file are kept
+    #[arg(long, value_parser = humantime::parse_duration, default_value = "7days")]
+    pub history_retention: Duration,
+
+    /// Number of the latest notable events, such as throttling starting, a collector failing or a threshold being exceeded,
+    /// kept to serve as JSON on /events, none with 0
+    #[arg(long, value_name = "COUNT", default_value_t = 100)]
+    pub events_capacity: usize,
+
+    /// Advertises the exporter as _prometheus-http._tcp over mDNS with the port and the metrics path in its TXT record, so
+    /// that discovery tooling on the LAN finds it. The address of the host is left to the responder of the system such as
+    /// avahi-daemon
+    #[arg(long)]
+    pub mdns: bool,
+
+    /// Metrics endpoint of another instance of the exporter, such as http://pi-01:9100/metrics, whose metrics are served
+    /// on /fleet along with the others labeled by instance. Repeat it to aggregate several instances
+    ///
+    /// The instances are also listed on /sd for the HTTP service discovery of Prometheus, to scrape them directly.
+    #[arg(long = "fleet-instance", value_name = "URL")]
+    pub fleet_instances: Vec<Uri>,
+
+    /// How long scraping an instance of --fleet-instance can take before it is given up
+    #[arg(long, value_parser = humantime::parse_duration, default_value = "10s")]
+    pub fleet_timeout: Duration,
+
+    /// PEM file of the CA certificates to verify https endpoints pushed to, scraped with or notified
+    #[arg(long, default_value = "/etc/ssl/certs/ca-certificates.crt")]
+    pub push_ca_file: PathBuf,
+
+    /// Stops offering HTTP/2 to TLS clients
+    #[arg(long)]
+    pub disable_http2: bool,
+
+    /// Web configuration file compatible with the Prometheus exporter-toolkit (TLS and basic authentication)
+    #[arg(long = "web.config.file")]
+    pub web_config_file: Option<PathBuf>,
+
+    /// How long a request can take before it is answered with 503
+    #[arg(long, value_parser = humantime::parse_duration, default_value = "30s")]
+    pub request_timeout: Duration,
+
+    /// Maximum number of open connections, the ones beyond are closed right away
+    #[arg(long, default_value_t = 64)]
+    pub max_connections: usize,
+
+    /// Maximum number of scrapes handled at the same time, the ones beyond are answered with 503
+    #[arg(long, default_value_t = 16)]
+    pub max_in_flight_requests: usize,
+
     #[arg(long, value_enum, default_value_t = Log::Plain)]
     pub log: Log,
 
+    /// Source of the temperature and the throttled state, picked by what the board has when auto
+    #[arg(long, value_enum, default_value_t = Backend::Auto)]
+    pub backend: Backend,
+
+    /// Keeps the metrics needing the VideoCore firmware, failing their collections, on boards without it rather than
+    /// leaving them out
+    #[arg(long)]
+    pub no_fallback: bool,
+
+    /// Interval of the background sampling for metrics accumulated between scrapes
+    #[arg(long, value_parser = humantime::parse_duration, default_value = "5s")]
+    pub sampling_interval: Duration,
+
+    /// How long a collector can take in a scrape before it is given up
+    #[arg(long, value_parser = humantime::parse_duration, default_value = "10s")]
+    pub collector_timeout: Duration,
+
+    /// Timeouts of particular collectors overriding --collector-timeout, such as throttled=2s
+    #[arg(long, value_parser = parse_collector_timeout, value_delimiter = ',')]
+    pub collector_timeouts: Vec<(String, Duration)>,
+
+    /// Answers a scrape with 500 and fails collect when any collector fails, instead of exposing the metrics of the others
+    #[arg(long)]
+    pub fail_on_collector_error: bool,
+
+    /// Prefix of the metric names followed by an underscore, or none when empty
+    #[arg(long, default_value = "raspi")]
+    pub metric_prefix: String,
+
+    /// Label added to every metric, such as site=home, for outputs that can't relabel. Repeat it to add several labels
+    #[arg(long = "label", value_name = "NAME=VALUE", value_parser = parse_label)]
+    pub labels: Vec<(String, String)>,
+
+    /// Adds the hostname of the system as the hostname label to every metric
+    #[arg(long)]
+    pub hostname_label: bool,
+
+    /// Rules of the `relabel` section of the config file
+    #[arg(skip)]
+    pub relabel: Vec<Rule>,
+
+    /// Settings of the `collectors` section of the config file
+    #[arg(skip)]
+    pub collectors: HashMap<Metric, CollectorConfig>,
+
+    /// Thresholds of the `thresholds` section of the config file
+    #[arg(skip)]
+    pub thresholds: Vec<Threshold>,
+
+    /// Effective values of the options and where they come from, for `check` to show
+    #[arg(skip)]
+    pub settings: Vec<Setting>,
+
     #[command(flatten)]
     pub metrics: Metrics,
+
+    /// File keeping the throttling occurrences, so that neither restarts nor reboots reset raspi_throttling_occurred
+    ///
+    /// Delete it to clear the occurrences.
+    #[arg(long, value_name = "FILE")]
+    pub throttled_state_file: Option<PathBuf>,
+
+    /// cgroups (relative to /sys/fs/cgroup) whose OOM kills are also counted
+    #[arg(long, value_delimiter = ',')]
+    pub oom_kill_cgroups: Vec<String>,
+
+    /// How long the result of checking pending package updates is reused
+    #[arg(long, value_parser = humantime::parse_duration, default_value = "1h")]
+    pub package_update_interval: Duration,
+
+    /// Prefixes of kernel messages counted separately, the others are counted as "other"
+    #[arg(long, value_delimiter = ',', default_value = "mmc,usb,brcmfmac")]
+    pub kmsg_subsystems: Vec<String>,
+
+    /// cgroups (relative to /sys/fs/cgroup) whose resource usage is collected
+    #[arg(long, value_delimiter = ',')]
+    pub cgroups: Vec<String>,
+
+    /// Socket of the Docker Engine API to collect container metrics from
+    #[arg(long, default_value = "/var/run/docker.sock")]
+    pub container_socket: PathBuf,
+
+    /// WiFi interfaces in access point mode whose stations are collected
+    #[arg(long, value_delimiter = ',', default_value = "wlan0")]
+    pub access_point_interfaces: Vec<String>,
+
+    /// Labels SSH authentication failures with the method such as password or publickey
+    #[arg(long)]
+    pub ssh_auth_failures_by_method: bool,
+
+    /// Boot configuration files whose changes are detected
+    #[arg(long, value_delimiter = ',', default_value = "/boot/firmware/config.txt,/boot/firmware/cmdline.txt")]
+    pub boot_config_files: Vec<PathBuf>,
+
+    /// SSH destination of a Raspberry Pi whose metrics are served on /probe?target=<DESTINATION> with the target label,
+    /// such as pi-01 of ~/.ssh/config. Repeat it to probe several Pis
+    ///
+    /// The enabled metrics that only run a command or read a file, such as throttled and temperature, are collected by
+    /// running them over SSH. The targets are listed on /sd for the HTTP service discovery of Prometheus.
+    #[cfg(feature = "ssh")]
+    #[arg(long = "probe-target", value_name = "DESTINATION")]
+    pub probe_targets: Vec<String>,
+
+    /// Prints every metric that --enable-metrics takes with its description and whether it is enabled by default
+    #[arg(long)]
+    pub list_collectors: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+// Subcommands of the exporter, serve being the default. Not a doc comment, which clap would take as the about of the
+// exporter
+#[derive(Debug, Clone, Subcommand)]
+pub enum Command {
+    /// Serves the metrics, which the exporter does without a subcommand too
+    Serve,
+    /// Runs the enabled collectors once and prints the metrics, exiting with 1 if any of them failed
+    Collect {
+        #[arg(long, value_enum, default_value_t = CollectFormat::Text)]
+        format: CollectFormat,
+    },
+    /// Validates the options, the config files and the TLS material without serving, exiting with 1 if any is invalid
+    Check,
+    /// Probes the board for what the collectors depend on, and tells which collectors work and why the others don't
+    Doctor,
+    /// Prints a line of a Nagios or Icinga plugin from the SoC temperature and throttling, exiting with 0 when OK, 1 when
+    /// warning, 2 when critical and 3 when they can't be read
+    CheckHealth {
+        /// SoC temperature in Celsius from which the check warns, as it also does while undervoltage or throttling is active
+        #[arg(long, value_name = "CELSIUS", default_value_t = 70.0)]
+        warn_temp: f64,
+        /// SoC temperature in Celsius from which the check is critical
+        #[arg(long, value_name = "CELSIUS", default_value_t = 80.0)]
+        crit_temp: f64,
+    },
+    /// Collects the enabled metrics on an interval into a file until interrupted, e.g. for thermal testing without a server
+    Record {
+        /// Interval of the collections
+        #[arg(long, value_parser = humantime::parse_duration, default_value = "10s")]
+        interval: Duration,
+        /// File to write the samples into, overwritten if it exists
+        #[arg(long, value_name = "FILE")]
+        out: PathBuf,
+        /// Format of the file, Parquet when --out ends with .parquet and CSV otherwise by default
+        #[arg(long, value_enum)]
+        format: Option<RecordFormat>,
+    },
+    /// Prints the completion script of a shell, e.g. to install as /usr/share/bash-completion/completions/raspi-exporter
+    Completions {
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+    /// Prints the man page in roff, e.g. to install as /usr/share/man/man1/raspi-exporter.1
+    Man,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum CollectFormat {
+    /// Prometheus text format
+    Text,
+    /// Array of objects with the name, labels and value of each sample
+    Json,
+}
+
+/// Format of the file of the record subcommand, having a row of the time, the name, the labels as a JSON object and the
+/// value of each sample.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum RecordFormat {
+    /// Comma-separated values with a header, timed in RFC 3339
+    Csv,
+    /// Parquet, timed in milliseconds, readable once the recording has stopped
+    Parquet,
+}
+
+impl RecordFormat {
+    /// Format of `path` by its extension, CSV unless it is .parquet.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("parquet")) {
+            true => Self::Parquet,
+            false => Self::Csv,
+        }
+    }
+}
+
+impl Cli {
+    /// Parses the command line, taking the options it doesn't give from the environment variables and then from the
+    /// `--config` file.
+    pub fn parse_with_config() -> Self {
+        Self::try_parse_with_config().unwrap_or_else(|err| err.exit())
+    }
+
+    /// Same as [`Cli::parse_with_config`], but returns errors instead of exiting, e.g. to reload the config file.
+    pub fn try_parse_with_config() -> Result<Self, clap::Error> {
+        Self::try_parse_layered(env::args_os().collect(), env::vars_os())
+    }
+
+    // Parses `args` over the options of `vars` over the ones of the config file
+    fn try_parse_layered(
+        mut args: Vec<OsString>,
+        vars: impl IntoIterator<Item = (OsString, OsString)>,
+    ) -> Result<Self, clap::Error> {
+        let command_line = Self::command().try_get_matches_from(&args)?;
+        let given = |id: &str| command_line.value_source(id) == Some(ValueSource::CommandLine);
+        let environment = Config::from_env(vars, &Self::command())
+            .map_err(|err| Self::command().error(ErrorKind::InvalidValue, format!("{err:#}")))?;
+        args.splice(1..1, environment.args(given));
+
+        // The config file can be given by an environment variable too
+        let matches = Self::command().try_get_matches_from(&args)?;
+        let config = match matches.get_one::<PathBuf>("config") {
+            Some(path) => Config::from_file(path, &Self::command())
+                .map_err(|err| Self::command().error(ErrorKind::Io, format!("{err:#}")))?,
+            None => Config::default(),
+        };
+        args.splice(1..1, config.args(|id| given(id) || environment.contains(id)));
+
+        let matches = Self::command().try_get_matches_from(&args)?;
+        let source = |id: &str| match id {
+            id if given(id) => Source::CommandLine,
+            id if environment.contains(id) => Source::Environment,
+            id if config.contains(id) => Source::File,
+            _ => Source::Default,
+        };
+        let settings = Self::command()
+            .get_arguments()
+            .filter_map(|arg| {
+                let values = matches.get_raw(arg.get_id().as_str())?;
+                Some(Setting {
+                    name: arg.get_long()?.to_string(),
+                    values: values.map(|value| value.to_string_lossy().into_owned()).collect(),
+                    source: source(arg.get_id().as_str()),
+                })
+            })
+            .collect();
+
+        Ok(Self {
+            relabel: config.relabel().to_vec(),
+            collectors: config.collectors().clone(),
+            thresholds: config.thresholds().to_vec(),
+            settings,
+            ..Self::from_arg_matches(&matches)?
+        })
+    }
+
+    /// Settings of the collectors of `metric`, the defaults unless the config file has them.
+    pub fn collector(&self, metric: Metric) -> CollectorConfig {
+        self.collectors.get(&metric).cloned().unwrap_or_default()
+    }
+
+    /// Long names of the options whose values differ from `settings`, e.g. those in effect before a reload.
+    pub fn changed(&self, settings: &[Setting]) -> Vec<String> {
+        let values = |settings: &[Setting], name: &str| {
+            settings.iter().find(|setting| setting.name == name).map(|setting| setting.values.clone())
+        };
+        self.settings
+            .iter()
+            .map(|setting| setting.name.as_str())
+            .chain(settings.iter().map(|setting| setting.name.as_str()).filter(|name| values(&self.settings, name).is_none()))
+            .filter(|name| values(&self.settings, name) != values(settings, name))
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    /// Whether to run in the fallback mode with the metrics of sysfs and procfs only, on boards other than Raspberry Pi
+    /// such as x86 machines of development and CI.
+    pub fn fallback(&self) -> bool {
+        !self.no_fallback && !backend::has_videocore()
+    }
+}
+
+/// Address given to `--address`, completed with `--port` when it has no port.
+#[derive(Debug, Clone)]
+pub enum Listen {
+    Ip(IpAddr),
+    Socket(SocketAddr),
+    Unix(PathBuf),
 }
 
 #[derive(Debug, Clone, Args)]
 pub struct Metrics {
+    /// Metrics to collect, or all of them with `all`
     #[arg(
         long,
         value_enum,
         value_delimiter = ',',
-        default_values_t = [
-            Metric::Throttled,
-        ],
+        default_values_t = DEFAULT_METRICS.map(MetricSelection::Metric),
     )]
-    pub enable_metrics: Vec<Metric>,
+    pub enable_metrics: Vec<MetricSelection>,
+
+    /// Metrics not to collect even if enabled, e.g. `--enable-metrics all --disable-metrics kmsg`
+    #[arg(long, value_enum, value_delimiter = ',', hide_possible_values = true)]
+    pub disable_metrics: Vec<Metric>,
 }
 
+/// Metrics enabled unless `--enable-metrics` is given.
+pub const DEFAULT_METRICS: [Metric; 1] = [
+    Metric::Throttled,
+];
+
 #[derive(Debug, Clone, ValueEnum)]
 pub enum Log {
     Plain,
     Json,
 }
 
-#[derive(Debug, Clone, ValueEnum, StrumDisplay, PartialEq, Eq)]
+/// Where the effective value of an option comes from, the first one taking precedence.
+#[derive(Debug, Clone, Copy, StrumDisplay, PartialEq, Eq)]
+pub enum Source {
+    #[strum(serialize = "command line")]
+    CommandLine,
+    #[strum(serialize = "environment")]
+    Environment,
+    #[strum(serialize = "config file")]
+    File,
+    #[strum(serialize = "default")]
+    Default,
+}
+
+/// Effective value of an option after merging the command line, the environment variables and the config file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Setting {
+    /// Long name of the option
+    pub name: String,
+    pub values: Vec<String>,
+    pub source: Source,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum, StrumDisplay, PartialEq, Eq, Hash)]
 #[strum(serialize_all = "snake_case")]
 pub enum Metric {
+    /// Throttling and undervoltage reported by the firmware through vcgencmd
     Throttled,
+    /// How long and how many times throttling has been active and when it last was, sampled in the background
+    ThrottledHistory,
+    /// Processes killed by the OOM killer, in total and in cgroups
+    OomKill,
+    /// File descriptors allocated by the system and opened by the exporter
+    FileDescriptor,
+    /// Size and free space of the mounted filesystems
+    Filesystem,
+    /// Clock offset and synchronisation state of chrony
+    Chrony,
+    /// Entries of the ARP and NDP neighbor tables against their garbage collection thresholds
+    Neighbor,
+    /// Handshakes and traffic of WireGuard peers
+    Wireguard,
+    /// Pending package updates, including security ones
+    PackageUpdate,
+    /// Whether a reboot is required, e.g. after a kernel update
+    RebootRequired,
+    /// Rates and usage of the SoC clocks from debugfs
+    ClockTree,
+    /// SoC temperature, with its extremes sampled between scrapes
+    Temperature,
+    /// Firmware version of the VL805 USB controller
+    Vl805,
+    /// Cause of the last reset reported by the firmware
+    Reset,
+    /// Kernel messages by subsystem
+    Kmsg,
+    /// CPU and memory usage of cgroups
+    Cgroup,
+    /// State and resource usage of Docker or Podman containers
+    Container,
+    /// Stations connected to WiFi access points and their signal
+    AccessPoint,
+    /// Brightness of display backlights
+    Backlight,
+    /// Packets and bytes of nftables counters and chains
+    Nftables,
+    /// Duration of the last boot and its phases from systemd-analyze
+    BootTime,
+    /// Failed SSH authentications from the journal
+    SshAuthFailure,
+    /// CPU vulnerabilities and their mitigations
+    CpuVulnerability,
+    /// Changes of the boot configuration files since boot
+    BootConfig,
+    /// IP, TCP and UDP counters of the kernel network stack
+    Snmp,
+}
+
+/// Value of `--enable-metrics`, which takes `all` besides the metrics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricSelection {
+    All,
+    Metric(Metric),
+}
+
+impl ValueEnum for MetricSelection {
+    fn value_variants<'a>() -> &'a [Self] {
+        static VARIANTS: LazyLock<Vec<MetricSelection>> = LazyLock::new(|| {
+            [MetricSelection::All].into_iter().chain(Metric::value_variants().iter().copied().map(MetricSelection::Metric)).collect()
+        });
+
+        &VARIANTS
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        match self {
+            Self::All => Some(PossibleValue::new("all").help("Every metric below")),
+            Self::Metric(metric) => metric.to_possible_value(),
+        }
+    }
+}
+
+impl Metric {
+    /// Returns the name given to `--enable-metrics`, which differs from the collector names in using hyphens.
+    pub fn name(&self) -> String {
+        self.to_possible_value().map(|value| value.get_name().to_string()).unwrap_or_default()
+    }
+
+    pub fn description(&self) -> String {
+        self.to_possible_value().and_then(|value| value.get_help().map(ToString::to_string)).unwrap_or_default()
+    }
+
+    pub fn is_default(&self) -> bool {
+        DEFAULT_METRICS.contains(self)
+    }
+
+    /// Whether the collectors of the metric read from the VideoCore firmware, which boards other than Raspberry Pi lack.
+    pub fn needs_videocore(&self) -> bool {
+        matches!(self, Self::Throttled | Self::ThrottledHistory | Self::Vl805 | Self::Reset)
+    }
 }
 
 impl Metrics {
-    pub fn has_throttled(&self) -> bool {
-        self.enable_metrics.contains(&Metric::Throttled)
+    /// Returns the metrics to collect, in the order given to `--enable-metrics`.
+    pub fn enabled(&self) -> Vec<Metric> {
+        let mut enabled = Vec::new();
+        for selection in &self.enable_metrics {
+            let metrics = match selection {
+                MetricSelection::All => Metric::value_variants(),
+                MetricSelection::Metric(metric) => std::slice::from_ref(metric),
+            };
+            for metric in metrics {
+                if !enabled.contains(metric) && !self.disable_metrics.contains(metric) {
+                    enabled.push(*metric);
+                }
+            }
+        }
+
+        enabled
+    }
+
+    pub fn has(&self, metric: Metric) -> bool {
+        self.enabled().contains(&metric)
     }
 }
 
 impl Display for Metrics {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.enable_metrics.iter().map(ToString::to_string).collect::<Vec<_>>().join(","))
+        write!(f, "{}", self.enabled().iter().map(ToString::to_string).collect::<Vec<_>>().join(","))
+    }
+}
+
+impl Listen {
+    /// Returns the address to listen on, using `port` unless the address has its own one.
+    pub fn address(&self, port: u16) -> ListenAddress {
+        match self {
+            Self::Ip(address) => ListenAddress::Tcp(SocketAddr::new(*address, port)),
+            Self::Socket(address) => ListenAddress::Tcp(*address),
+            Self::Unix(path) => ListenAddress::Unix(path.clone()),
+        }
+    }
+}
+
+fn parse_listen(address: &str) -> Result<Listen, String> {
+    if let Some(path) = address.strip_prefix("unix:") {
+        return Ok(Listen::Unix(PathBuf::from(path)));
+    }
+    if let Ok(address) = address.parse::<IpAddr>() {
+        return Ok(Listen::Ip(address));
+    }
+
+    address
+        .parse::<SocketAddr>()
+        .map(Listen::Socket)
+        .map_err(|_| "must be an IP address, an IP address with a port, or unix: followed by a path".to_string())
+}
+
+fn parse_collector_timeout(timeout: &str) -> Result<(String, Duration), String> {
+    let (name, duration) = timeout.split_once('=').ok_or("must be a collector name and a duration joined with =")?;
+    let duration = humantime::parse_duration(duration).map_err(|err| err.to_string())?;
+
+    Ok((name.to_string(), duration))
+}
+
+fn parse_fan_point(point: &str) -> Result<(f64, f64), String> {
+    let (temperature, duty) = point.split_once(':').ok_or("must be a temperature and a duty joined with :")?;
+    let temperature = temperature.parse::<f64>().map_err(|err| format!("invalid temperature: {err}"))?;
+    let duty = duty.parse::<f64>().map_err(|err| format!("invalid duty: {err}"))?;
+    if !(0.0..=100.0).contains(&duty) {
+        return Err(format!("duty must be from 0 to 100: {duty}"));
+    }
+
+    Ok((temperature, duty))
+}
+
+fn parse_label(label: &str) -> Result<(String, String), String> {
+    let (name, value) = label.split_once('=').ok_or("must be a label name and a value joined with =")?;
+    let valid = name.chars().enumerate().all(|(i, c)| c == '_' || c.is_ascii_alphabetic() || i > 0 && c.is_ascii_digit());
+    if name.is_empty() || !valid {
+        return Err(format!("invalid label name: {name}"));
+    }
+
+    Ok((name.to_string(), value.to_string()))
+}
+
+fn parse_path(path: &str) -> Result<String, String> {
+    match path.starts_with('/') && path.len() > 1 {
+        true => Ok(path.to_string()),
+        false => Err("must start with / and not be the root".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::{Parser, ValueEnum};
+
+    use crate::cli::{parse_fan_point, parse_label, Cli, Command, Metric, MetricSelection, Metrics, Setting, Source};
+
+    #[test]
+    fn enabled() {
+        let metrics = Metrics {
+            enable_metrics: vec![MetricSelection::Metric(Metric::Temperature), MetricSelection::Metric(Metric::Throttled)],
+            disable_metrics: vec![Metric::Throttled],
+        };
+        assert_eq!(metrics.enabled(), [Metric::Temperature]);
+
+        let metrics = Metrics {
+            enable_metrics: vec![MetricSelection::Metric(Metric::Temperature), MetricSelection::All],
+            disable_metrics: vec![Metric::Kmsg, Metric::Container],
+        };
+        let enabled = metrics.enabled();
+        assert_eq!(enabled.first(), Some(&Metric::Temperature));
+        assert_eq!(enabled.len(), Metric::value_variants().len() - 2);
+        assert!(!metrics.has(Metric::Kmsg));
+        assert!(metrics.has(Metric::Snmp));
+    }
+
+    #[test]
+    fn label() {
+        assert_eq!(parse_label("site=home=1"), Ok(("site".to_string(), "home=1".to_string())));
+        assert_eq!(parse_label("_rack2="), Ok(("_rack2".to_string(), String::new())));
+        assert!(parse_label("site").is_err());
+        assert!(parse_label("2site=home").is_err());
+        assert!(parse_label("site-name=home").is_err());
+    }
+
+    #[test]
+    fn fan_point() {
+        assert_eq!(parse_fan_point("62.5:40"), Ok((62.5, 40.0)));
+        assert!(parse_fan_point("60").is_err());
+        assert!(parse_fan_point("60:120").is_err());
+    }
+
+    #[test]
+    fn layered() {
+        let path = std::env::temp_dir().join(format!("raspi-exporter-layered-{}.toml", std::process::id()));
+        std::fs::write(&path, "port = 9100\nmetric-prefix = \"file\"\nhostname-label = true\n").unwrap();
+        let args = ["raspi_exporter", "--metric-prefix", "cli"].map(Into::into).to_vec();
+        let vars = [
+            ("RASPI_EXPORTER_CONFIG", path.to_str().unwrap()),
+            ("RASPI_EXPORTER_PORT", "9200"),
+            ("RASPI_EXPORTER_HOSTNAME_LABEL", "false"),
+        ].map(|(name, value)| (name.into(), value.into()));
+        let cli = Cli::try_parse_layered(args, vars).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!((cli.port, cli.metric_prefix.as_str(), cli.hostname_label), (9200, "cli", false));
+        let setting = |name: &str| cli.settings.iter().find(|setting| setting.name == name).cloned();
+        assert_eq!(
+            setting("port"),
+            Some(Setting { name: "port".to_string(), values: vec!["9200".to_string()], source: Source::Environment }),
+        );
+        assert_eq!(setting("metric-prefix").unwrap().source, Source::CommandLine);
+        assert_eq!(setting("hostname-label").unwrap().source, Source::Environment);
+        assert_eq!(setting("sampling-interval").unwrap().source, Source::Default);
+    }
+
+    #[test]
+    fn subcommands() {
+        let before = Cli::try_parse_from(["raspi_exporter", "--port", "9100", "serve"]).unwrap();
+        let after = Cli::try_parse_from(["raspi_exporter", "collect", "--format", "json", "--port", "9100"]).unwrap();
+
+        assert!(matches!(before.command, Some(Command::Serve)));
+        assert!(matches!(after.command, Some(Command::Collect { .. })));
+        assert_eq!((before.port, after.port), (9100, 9100));
+        assert!(Cli::try_parse_from(["raspi_exporter", "--format", "json", "collect"]).is_err());
+    }
+
+    #[test]
+    fn changed() {
+        let before = Cli::try_parse_layered(["raspi_exporter", "--port", "9100"].map(Into::into).to_vec(), []).unwrap();
+        let args = ["raspi_exporter", "--port", "9200", "--hook", "/bin/true"].map(Into::into).to_vec();
+        let after = Cli::try_parse_layered(args, []).unwrap();
+
+        assert_eq!(after.changed(&before.settings), ["port", "hook"]);
+        assert_eq!(before.changed(&after.settings), ["port", "hook"]);
+        assert!(after.changed(&after.settings).is_empty());
     }
 }
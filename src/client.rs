@@ -0,0 +1,121 @@
+use std::{path::PathBuf, sync::Arc};
+
+use anyhow::Context;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use http_body_util::{BodyExt, Full};
+use hyper::{body::Bytes, header::HOST, Request, Response};
+use hyper_util::rt::TokioIo;
+use rustls::{
+    crypto::ring,
+    pki_types::{pem::PemObject, CertificateDer, ServerName},
+    ClientConfig,
+    RootCertStore,
+};
+use tokio::{io::{AsyncRead, AsyncWrite}, net::TcpStream};
+use tokio_rustls::TlsConnector;
+
+/// HTTP client of the push modes, which verifies the servers of https URLs against the CA certificates in a PEM file.
+#[derive(Clone, Debug)]
+pub struct Client {
+    ca_file: PathBuf,
+}
+
+/// Credentials sent in the `Authorization` header.
+#[derive(Clone, Debug)]
+pub enum Credentials {
+    Basic {
+        username: String,
+        password: String,
+    },
+    Bearer(String),
+    // Token of the InfluxDB v2 API
+    Token(String),
+}
+
+impl Client {
+    pub fn new(ca_file: impl Into<PathBuf>) -> Self {
+        Self {
+            ca_file: ca_file.into(),
+        }
+    }
+
+    /// Sends a request to its absolute URI on a new connection.
+    pub async fn send(&self, mut request: Request<Full<Bytes>>) -> anyhow::Result<Response<Bytes>> {
+        let uri = request.uri().clone();
+        let host = uri.host().context("URL without a host")?;
+        let https = match uri.scheme_str() {
+            Some("https") => true,
+            Some("http") => false,
+            _ => anyhow::bail!("URL must be http or https: {uri}"),
+        };
+        let port = uri.port_u16().unwrap_or(if https { 443 } else { 80 });
+
+        if let Some(authority) = uri.authority() {
+            request.headers_mut().entry(HOST).or_insert(authority.as_str().parse()?);
+        }
+        *request.uri_mut() = uri.path_and_query().map_or("/", |path| path.as_str()).parse()?;
+
+        let stream = TcpStream::connect((host, port))
+            .await
+            .with_context(|| format!("connection error: {uri}"))?;
+        if !https {
+            return send(stream, request).await;
+        }
+
+        // Read on every request, so that updates of the CA certificates apply without a restart
+        let mut roots = RootCertStore::empty();
+        for cert in CertificateDer::pem_file_iter(&self.ca_file).with_context(|| format!("CA read error: {:?}", self.ca_file))? {
+            roots.add(cert.with_context(|| format!("CA read error: {:?}", self.ca_file))?)?;
+        }
+        let config = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+            .with_safe_default_protocol_versions()?
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        let stream = TlsConnector::from(Arc::new(config))
+            .connect(ServerName::try_from(host.to_string())?, stream)
+            .await
+            .with_context(|| format!("TLS handshake error: {uri}"))?;
+
+        send(stream, request).await
+    }
+}
+
+impl Credentials {
+    pub fn header(&self) -> String {
+        match self {
+            Self::Basic { username, password } => format!("Basic {}", STANDARD.encode(format!("{username}:{password}"))),
+            Self::Bearer(token) => format!("Bearer {token}"),
+            Self::Token(token) => format!("Token {token}"),
+        }
+    }
+}
+
+async fn send<S>(stream: S, request: Request<Full<Bytes>>) -> anyhow::Result<Response<Bytes>>
+where
+    S: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+{
+    let (mut sender, connection) = hyper::client::conn::http1::handshake(TokioIo::new(stream)).await?;
+    tokio::spawn(async move {
+        if let Err(err) = connection.await {
+            tracing::debug!("push connection error\nError: {err:?}");
+        }
+    });
+
+    let response = sender.send_request(request).await?;
+    let (parts, body) = response.into_parts();
+
+    Ok(Response::from_parts(parts, body.collect().await?.to_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::Credentials;
+
+    #[test]
+    fn header() {
+        let credentials = Credentials::Basic { username: "user".to_string(), password: "pass".to_string() };
+        assert_eq!(credentials.header(), "Basic dXNlcjpwYXNz");
+        assert_eq!(Credentials::Bearer("token".to_string()).header(), "Bearer token");
+        assert_eq!(Credentials::Token("token".to_string()).header(), "Token token");
+    }
+}
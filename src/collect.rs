@@ -0,0 +1,79 @@
+use std::collections::BTreeMap;
+
+use serde_json::json;
+
+use crate::{
+    cli::CollectFormat,
+    format::{samples, Format},
+    metrics::{Filter, Handler},
+};
+
+/// Metrics collected once, and the collectors that failed.
+#[derive(Debug)]
+pub struct Collection {
+    pub output: String,
+    pub failed: Vec<String>,
+}
+
+/// Collects the metrics once in `format`, telling failed collectors by `collector_success`.
+pub async fn collect(handler: &impl Handler, format: CollectFormat) -> anyhow::Result<Collection> {
+    let metrics = handler.handle(&Filter::default(), None).await?;
+    let samples = samples(&metrics).collect::<anyhow::Result<Vec<_>>>()?;
+    let failed = samples
+        .iter()
+        // Whatever the prefix of the metric names is
+        .filter(|sample| sample.name.ends_with("collector_success") && sample.value == 0.0)
+        .filter_map(|sample| sample.labels.iter().find(|(name, _)| name == "collector").map(|(_, collector)| collector.clone()))
+        .collect();
+
+    let output = match format {
+        CollectFormat::Text => String::from_utf8(Format::Text.encode(metrics)?)?,
+        CollectFormat::Json => {
+            let samples = samples
+                .iter()
+                .map(|sample| json!({
+                    "name": sample.name,
+                    "labels": sample.labels.iter().map(|(name, value)| (name, value)).collect::<BTreeMap<_, _>>(),
+                    "value": sample.value,
+                }))
+                .collect::<Vec<_>>();
+            serde_json::to_string_pretty(&samples)? + "\n"
+        },
+    };
+
+    Ok(Collection {
+        output,
+        failed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use prometheus_client::registry::Registry;
+
+    use crate::{cli::CollectFormat, collect::collect, metrics::{MetricsHandler, MockCollector}};
+
+    #[tokio::test]
+    async fn collect_failed() {
+        let mut mock_collector = MockCollector::new();
+        mock_collector
+            .expect_collect()
+            .times(1)
+            .returning(|| Err(anyhow::anyhow!("failed")));
+        mock_collector
+            .expect_name()
+            .return_const("throttled");
+
+        let metrics_handler = MetricsHandler::new(vec![Box::new(mock_collector)], Arc::new(Mutex::new(Registry::default())));
+        let collection = collect(&metrics_handler, CollectFormat::Json).await.unwrap();
+        let output = serde_json::from_str::<serde_json::Value>(&collection.output).unwrap();
+
+        assert_eq!(collection.failed, ["throttled"]);
+        assert_eq!(output[0]["name"], "raspi_exporter_build_info");
+        assert_eq!(output[1]["name"], "raspi_collector_success");
+        assert_eq!(output[1]["labels"]["collector"], "throttled");
+        assert_eq!(output[1]["value"], 0.0);
+    }
+}
@@ -0,0 +1,114 @@
+use async_trait::async_trait;
+
+use crate::{
+    executor::Executor,
+    metrics::{Collector, Registerer},
+    parser::{access_point::AccessPointState, Parser},
+};
+
+#[derive(Clone, Debug)]
+pub struct AccessPoint<E, P, R> {
+    executor: E,
+    parser: P,
+    registerer: R,
+}
+
+impl<E, P, R> AccessPoint<E, P, R> {
+    pub fn new(executor: E, parser: P, registerer: R) -> Self {
+        Self {
+            executor,
+            parser,
+            registerer,
+        }
+    }
+}
+
+#[async_trait]
+impl<E, P, R> Collector for AccessPoint<E, P, R>
+where
+    E: Executor + Send + Sync,
+    P: Parser<Item = AccessPointState> + Send + Sync,
+    R: Registerer<Item = AccessPointState> + Send + Sync,
+{
+    fn name(&self) -> &'static str {
+        "access_point"
+    }
+
+    #[tracing::instrument(skip_all, fields(collector = %std::any::type_name::<Self>()))]
+    async fn collect(&self) -> anyhow::Result<()> {
+        tracing::debug!("collecting access_point");
+
+        let output = self.executor.execute().await?;
+        let state = self.parser.parse(&output)?;
+
+        self.registerer.update(state).await?;
+
+        tracing::debug!("succeeded collecting access_point");
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::future::ok;
+
+    use crate::{
+        collector::access_point::AccessPoint,
+        executor::MockExecutor,
+        metrics::{Collector, Registerer},
+        parser::{access_point::AccessPointState, Parser},
+    };
+
+    mockall::mock! {
+        Registerer {}
+
+        impl Registerer for Registerer {
+            type Item = AccessPointState;
+
+            fn update(&self, state: <Self as Registerer>::Item) -> impl Future<Output = anyhow::Result<()>> + Send;
+        }
+    }
+
+    mockall::mock! {
+        Parser {}
+
+        impl Parser for Parser {
+            type Item = AccessPointState;
+
+            fn parse(&self, input: &str) -> anyhow::Result<<Self as Parser>::Item>;
+        }
+    }
+
+    #[tokio::test]
+    async fn collect() {
+        let mut mock_executor = MockExecutor::new();
+        mock_executor
+            .expect_execute()
+            .times(1)
+            .returning(|| Box::pin(ok("".to_string())));
+
+        let mut mock_parser = MockParser::new();
+        mock_parser
+            .expect_parse()
+            .times(1)
+            .withf(|x| x.is_empty())
+            .returning(|_| Ok(AccessPointState {
+                stations: vec![],
+            }));
+
+        let mut mock_registerer = MockRegisterer::new();
+        mock_registerer
+            .expect_update()
+            .times(1)
+            .withf(|x| *x == AccessPointState {
+                stations: vec![],
+            })
+            .returning(|_| Box::pin(ok(())));
+
+        let access_point = AccessPoint::new(mock_executor, mock_parser, mock_registerer);
+        let result = access_point.collect().await;
+
+        assert!(result.is_ok())
+    }
+}
@@ -0,0 +1,114 @@
+use async_trait::async_trait;
+
+use crate::{
+    executor::Executor,
+    metrics::{Collector, Registerer},
+    parser::{backlight::BacklightState, Parser},
+};
+
+#[derive(Clone, Debug)]
+pub struct Backlight<E, P, R> {
+    executor: E,
+    parser: P,
+    registerer: R,
+}
+
+impl<E, P, R> Backlight<E, P, R> {
+    pub fn new(executor: E, parser: P, registerer: R) -> Self {
+        Self {
+            executor,
+            parser,
+            registerer,
+        }
+    }
+}
+
+#[async_trait]
+impl<E, P, R> Collector for Backlight<E, P, R>
+where
+    E: Executor + Send + Sync,
+    P: Parser<Item = BacklightState> + Send + Sync,
+    R: Registerer<Item = BacklightState> + Send + Sync,
+{
+    fn name(&self) -> &'static str {
+        "backlight"
+    }
+
+    #[tracing::instrument(skip_all, fields(collector = %std::any::type_name::<Self>()))]
+    async fn collect(&self) -> anyhow::Result<()> {
+        tracing::debug!("collecting backlight");
+
+        let output = self.executor.execute().await?;
+        let state = self.parser.parse(&output)?;
+
+        self.registerer.update(state).await?;
+
+        tracing::debug!("succeeded collecting backlight");
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::future::ok;
+
+    use crate::{
+        collector::backlight::Backlight,
+        executor::MockExecutor,
+        metrics::{Collector, Registerer},
+        parser::{backlight::BacklightState, Parser},
+    };
+
+    mockall::mock! {
+        Registerer {}
+
+        impl Registerer for Registerer {
+            type Item = BacklightState;
+
+            fn update(&self, state: <Self as Registerer>::Item) -> impl Future<Output = anyhow::Result<()>> + Send;
+        }
+    }
+
+    mockall::mock! {
+        Parser {}
+
+        impl Parser for Parser {
+            type Item = BacklightState;
+
+            fn parse(&self, input: &str) -> anyhow::Result<<Self as Parser>::Item>;
+        }
+    }
+
+    #[tokio::test]
+    async fn collect() {
+        let mut mock_executor = MockExecutor::new();
+        mock_executor
+            .expect_execute()
+            .times(1)
+            .returning(|| Box::pin(ok("".to_string())));
+
+        let mut mock_parser = MockParser::new();
+        mock_parser
+            .expect_parse()
+            .times(1)
+            .withf(|x| x.is_empty())
+            .returning(|_| Ok(BacklightState {
+                devices: vec![],
+            }));
+
+        let mut mock_registerer = MockRegisterer::new();
+        mock_registerer
+            .expect_update()
+            .times(1)
+            .withf(|x| *x == BacklightState {
+                devices: vec![],
+            })
+            .returning(|_| Box::pin(ok(())));
+
+        let backlight = Backlight::new(mock_executor, mock_parser, mock_registerer);
+        let result = backlight.collect().await;
+
+        assert!(result.is_ok())
+    }
+}
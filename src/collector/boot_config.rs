@@ -0,0 +1,114 @@
+use async_trait::async_trait;
+
+use crate::{
+    executor::Executor,
+    metrics::{Collector, Registerer},
+    parser::{boot_config::BootConfigState, Parser},
+};
+
+#[derive(Clone, Debug)]
+pub struct BootConfig<E, P, R> {
+    executor: E,
+    parser: P,
+    registerer: R,
+}
+
+impl<E, P, R> BootConfig<E, P, R> {
+    pub fn new(executor: E, parser: P, registerer: R) -> Self {
+        Self {
+            executor,
+            parser,
+            registerer,
+        }
+    }
+}
+
+#[async_trait]
+impl<E, P, R> Collector for BootConfig<E, P, R>
+where
+    E: Executor + Send + Sync,
+    P: Parser<Item = BootConfigState> + Send + Sync,
+    R: Registerer<Item = BootConfigState> + Send + Sync,
+{
+    fn name(&self) -> &'static str {
+        "boot_config"
+    }
+
+    #[tracing::instrument(skip_all, fields(collector = %std::any::type_name::<Self>()))]
+    async fn collect(&self) -> anyhow::Result<()> {
+        tracing::debug!("collecting boot_config");
+
+        let output = self.executor.execute().await?;
+        let state = self.parser.parse(&output)?;
+
+        self.registerer.update(state).await?;
+
+        tracing::debug!("succeeded collecting boot_config");
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::future::ok;
+
+    use crate::{
+        collector::boot_config::BootConfig,
+        executor::MockExecutor,
+        metrics::{Collector, Registerer},
+        parser::{boot_config::BootConfigState, Parser},
+    };
+
+    mockall::mock! {
+        Registerer {}
+
+        impl Registerer for Registerer {
+            type Item = BootConfigState;
+
+            fn update(&self, state: <Self as Registerer>::Item) -> impl Future<Output = anyhow::Result<()>> + Send;
+        }
+    }
+
+    mockall::mock! {
+        Parser {}
+
+        impl Parser for Parser {
+            type Item = BootConfigState;
+
+            fn parse(&self, input: &str) -> anyhow::Result<<Self as Parser>::Item>;
+        }
+    }
+
+    #[tokio::test]
+    async fn collect() {
+        let mut mock_executor = MockExecutor::new();
+        mock_executor
+            .expect_execute()
+            .times(1)
+            .returning(|| Box::pin(ok("arm_boost=1\n".to_string())));
+
+        let mut mock_parser = MockParser::new();
+        mock_parser
+            .expect_parse()
+            .times(1)
+            .withf(|x| x == "arm_boost=1\n")
+            .returning(|_| Ok(BootConfigState {
+                sha256: "dd69b293d672d974542f4d40199619691cb734c7bb4070ae1dcb5b890a885465".to_string(),
+            }));
+
+        let mut mock_registerer = MockRegisterer::new();
+        mock_registerer
+            .expect_update()
+            .times(1)
+            .withf(|x| *x == BootConfigState {
+                sha256: "dd69b293d672d974542f4d40199619691cb734c7bb4070ae1dcb5b890a885465".to_string(),
+            })
+            .returning(|_| Box::pin(ok(())));
+
+        let boot_config = BootConfig::new(mock_executor, mock_parser, mock_registerer);
+        let result = boot_config.collect().await;
+
+        assert!(result.is_ok())
+    }
+}
@@ -0,0 +1,116 @@
+use async_trait::async_trait;
+
+use crate::{
+    executor::Executor,
+    metrics::{Collector, Registerer},
+    parser::{boot_time::BootTimeState, Parser},
+};
+
+#[derive(Clone, Debug)]
+pub struct BootTime<E, P, R> {
+    executor: E,
+    parser: P,
+    registerer: R,
+}
+
+impl<E, P, R> BootTime<E, P, R> {
+    pub fn new(executor: E, parser: P, registerer: R) -> Self {
+        Self {
+            executor,
+            parser,
+            registerer,
+        }
+    }
+}
+
+#[async_trait]
+impl<E, P, R> Collector for BootTime<E, P, R>
+where
+    E: Executor + Send + Sync,
+    P: Parser<Item = BootTimeState> + Send + Sync,
+    R: Registerer<Item = BootTimeState> + Send + Sync,
+{
+    fn name(&self) -> &'static str {
+        "boot_time"
+    }
+
+    #[tracing::instrument(skip_all, fields(collector = %std::any::type_name::<Self>()))]
+    async fn collect(&self) -> anyhow::Result<()> {
+        tracing::debug!("collecting boot_time");
+
+        let output = self.executor.execute().await?;
+        let state = self.parser.parse(&output)?;
+
+        self.registerer.update(state).await?;
+
+        tracing::debug!("succeeded collecting boot_time");
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::future::ok;
+
+    use crate::{
+        collector::boot_time::BootTime,
+        executor::MockExecutor,
+        metrics::{Collector, Registerer},
+        parser::{boot_time::BootTimeState, Parser},
+    };
+
+    mockall::mock! {
+        Registerer {}
+
+        impl Registerer for Registerer {
+            type Item = BootTimeState;
+
+            fn update(&self, state: <Self as Registerer>::Item) -> impl Future<Output = anyhow::Result<()>> + Send;
+        }
+    }
+
+    mockall::mock! {
+        Parser {}
+
+        impl Parser for Parser {
+            type Item = BootTimeState;
+
+            fn parse(&self, input: &str) -> anyhow::Result<<Self as Parser>::Item>;
+        }
+    }
+
+    #[tokio::test]
+    async fn collect() {
+        let mut mock_executor = MockExecutor::new();
+        mock_executor
+            .expect_execute()
+            .times(1)
+            .returning(|| Box::pin(ok("Startup finished in 2s (kernel) + 8s (userspace) = 10s".to_string())));
+
+        let mut mock_parser = MockParser::new();
+        mock_parser
+            .expect_parse()
+            .times(1)
+            .withf(|x| x == "Startup finished in 2s (kernel) + 8s (userspace) = 10s")
+            .returning(|_| Ok(BootTimeState {
+                total_seconds: 10.0,
+                phases: vec![],
+            }));
+
+        let mut mock_registerer = MockRegisterer::new();
+        mock_registerer
+            .expect_update()
+            .times(1)
+            .withf(|x| *x == BootTimeState {
+                total_seconds: 10.0,
+                phases: vec![],
+            })
+            .returning(|_| Box::pin(ok(())));
+
+        let boot_time = BootTime::new(mock_executor, mock_parser, mock_registerer);
+        let result = boot_time.collect().await;
+
+        assert!(result.is_ok())
+    }
+}
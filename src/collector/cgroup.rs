@@ -0,0 +1,124 @@
+use async_trait::async_trait;
+
+use crate::{
+    executor::Executor,
+    metrics::{Collector, Registerer},
+    parser::{cgroup::CgroupState, Parser},
+};
+
+#[derive(Clone, Debug)]
+pub struct Cgroup<E, P, R> {
+    executor: E,
+    parser: P,
+    registerer: R,
+}
+
+impl<E, P, R> Cgroup<E, P, R> {
+    pub fn new(executor: E, parser: P, registerer: R) -> Self {
+        Self {
+            executor,
+            parser,
+            registerer,
+        }
+    }
+}
+
+#[async_trait]
+impl<E, P, R> Collector for Cgroup<E, P, R>
+where
+    E: Executor + Send + Sync,
+    P: Parser<Item = CgroupState> + Send + Sync,
+    R: Registerer<Item = CgroupState> + Send + Sync,
+{
+    fn name(&self) -> &'static str {
+        "cgroup"
+    }
+
+    #[tracing::instrument(skip_all, fields(collector = %std::any::type_name::<Self>()))]
+    async fn collect(&self) -> anyhow::Result<()> {
+        tracing::debug!("collecting cgroup");
+
+        let output = self.executor.execute().await?;
+        let state = self.parser.parse(&output)?;
+
+        self.registerer.update(state).await?;
+
+        tracing::debug!("succeeded collecting cgroup");
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::future::ok;
+
+    use crate::{
+        collector::cgroup::Cgroup,
+        executor::MockExecutor,
+        metrics::{Collector, Registerer},
+        parser::{cgroup::CgroupState, Parser},
+    };
+
+    mockall::mock! {
+        Registerer {}
+
+        impl Registerer for Registerer {
+            type Item = CgroupState;
+
+            fn update(&self, state: <Self as Registerer>::Item) -> impl Future<Output = anyhow::Result<()>> + Send;
+        }
+    }
+
+    mockall::mock! {
+        Parser {}
+
+        impl Parser for Parser {
+            type Item = CgroupState;
+
+            fn parse(&self, input: &str) -> anyhow::Result<<Self as Parser>::Item>;
+        }
+    }
+
+    #[tokio::test]
+    async fn collect() {
+        let mut mock_executor = MockExecutor::new();
+        mock_executor
+            .expect_execute()
+            .times(1)
+            .returning(|| Box::pin(ok("usage_usec 2308493\nuser_usec 1683291\nsystem_usec 625202\nmemory.current 52428800\nmemory.max max\n".to_string())));
+
+        let mut mock_parser = MockParser::new();
+        mock_parser
+            .expect_parse()
+            .times(1)
+            .withf(|x| x == "usage_usec 2308493\nuser_usec 1683291\nsystem_usec 625202\nmemory.current 52428800\nmemory.max max\n")
+            .returning(|_| Ok(CgroupState {
+                cpu_usage_seconds: 2.308493,
+                cpu_user_seconds: 1.683291,
+                cpu_system_seconds: 0.625202,
+                cpu_throttling: None,
+                memory_usage_bytes: 52428800,
+                memory_limit_bytes: None,
+            }));
+
+        let mut mock_registerer = MockRegisterer::new();
+        mock_registerer
+            .expect_update()
+            .times(1)
+            .withf(|x| *x == CgroupState {
+                cpu_usage_seconds: 2.308493,
+                cpu_user_seconds: 1.683291,
+                cpu_system_seconds: 0.625202,
+                cpu_throttling: None,
+                memory_usage_bytes: 52428800,
+                memory_limit_bytes: None,
+            })
+            .returning(|_| Box::pin(ok(())));
+
+        let cgroup = Cgroup::new(mock_executor, mock_parser, mock_registerer);
+        let result = cgroup.collect().await;
+
+        assert!(result.is_ok())
+    }
+}
@@ -0,0 +1,118 @@
+use async_trait::async_trait;
+
+use crate::{
+    executor::Executor,
+    metrics::{Collector, Registerer},
+    parser::{chrony::ChronyState, Parser},
+};
+
+#[derive(Clone, Debug)]
+pub struct Chrony<E, P, R> {
+    executor: E,
+    parser: P,
+    registerer: R,
+}
+
+impl<E, P, R> Chrony<E, P, R> {
+    pub fn new(executor: E, parser: P, registerer: R) -> Self {
+        Self {
+            executor,
+            parser,
+            registerer,
+        }
+    }
+}
+
+#[async_trait]
+impl<E, P, R> Collector for Chrony<E, P, R>
+where
+    E: Executor + Send + Sync,
+    P: Parser<Item = ChronyState> + Send + Sync,
+    R: Registerer<Item = ChronyState> + Send + Sync,
+{
+    fn name(&self) -> &'static str {
+        "chrony"
+    }
+
+    #[tracing::instrument(skip_all, fields(collector = %std::any::type_name::<Self>()))]
+    async fn collect(&self) -> anyhow::Result<()> {
+        tracing::debug!("collecting chrony");
+
+        let output = self.executor.execute().await?;
+        let state = self.parser.parse(&output)?;
+
+        self.registerer.update(state).await?;
+
+        tracing::debug!("succeeded collecting chrony");
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::future::ok;
+
+    use crate::{
+        collector::chrony::Chrony,
+        executor::MockExecutor,
+        metrics::{Collector, Registerer},
+        parser::{chrony::ChronyState, Parser},
+    };
+
+    mockall::mock! {
+        Registerer {}
+
+        impl Registerer for Registerer {
+            type Item = ChronyState;
+
+            fn update(&self, state: <Self as Registerer>::Item) -> impl Future<Output = anyhow::Result<()>> + Send;
+        }
+    }
+
+    mockall::mock! {
+        Parser {}
+
+        impl Parser for Parser {
+            type Item = ChronyState;
+
+            fn parse(&self, input: &str) -> anyhow::Result<<Self as Parser>::Item>;
+        }
+    }
+
+    #[tokio::test]
+    async fn collect() {
+        let mut mock_executor = MockExecutor::new();
+        mock_executor
+            .expect_execute()
+            .times(1)
+            .returning(|| Box::pin(ok("A29FC87B,162.159.200.123,4,1760400000.1,-0.000012,0,0,0,0,0,0,0,64,Normal".to_string())));
+
+        let mut mock_parser = MockParser::new();
+        mock_parser
+            .expect_parse()
+            .times(1)
+            .withf(|x| x == "A29FC87B,162.159.200.123,4,1760400000.1,-0.000012,0,0,0,0,0,0,0,64,Normal")
+            .returning(|_| Ok(ChronyState {
+                offset_seconds: -0.000012,
+                stratum: 4,
+                synchronised: true,
+            }));
+
+        let mut mock_registerer = MockRegisterer::new();
+        mock_registerer
+            .expect_update()
+            .times(1)
+            .withf(|x| *x == ChronyState {
+                offset_seconds: -0.000012,
+                stratum: 4,
+                synchronised: true,
+            })
+            .returning(|_| Box::pin(ok(())));
+
+        let chrony = Chrony::new(mock_executor, mock_parser, mock_registerer);
+        let result = chrony.collect().await;
+
+        assert!(result.is_ok())
+    }
+}
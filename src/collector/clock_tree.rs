@@ -0,0 +1,128 @@
+use async_trait::async_trait;
+
+use crate::{
+    executor::Executor,
+    metrics::{Collector, Registerer},
+    parser::{clock_tree::ClockTreeState, Parser},
+};
+
+#[derive(Clone, Debug)]
+pub struct ClockTree<E, P, R> {
+    executor: E,
+    parser: P,
+    registerer: R,
+}
+
+impl<E, P, R> ClockTree<E, P, R> {
+    pub fn new(executor: E, parser: P, registerer: R) -> Self {
+        Self {
+            executor,
+            parser,
+            registerer,
+        }
+    }
+}
+
+#[async_trait]
+impl<E, P, R> Collector for ClockTree<E, P, R>
+where
+    E: Executor + Send + Sync,
+    P: Parser<Item = ClockTreeState> + Send + Sync,
+    R: Registerer<Item = ClockTreeState> + Send + Sync,
+{
+    fn name(&self) -> &'static str {
+        "clock_tree"
+    }
+
+    #[tracing::instrument(skip_all, fields(collector = %std::any::type_name::<Self>()))]
+    async fn collect(&self) -> anyhow::Result<()> {
+        tracing::debug!("collecting clock_tree");
+
+        let output = self.executor.execute().await?;
+        let state = self.parser.parse(&output)?;
+
+        self.registerer.update(state).await?;
+
+        tracing::debug!("succeeded collecting clock_tree");
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::future::ok;
+
+    use crate::{
+        collector::clock_tree::ClockTree,
+        executor::MockExecutor,
+        metrics::{Collector, Registerer},
+        parser::{clock_tree::{Clock, ClockTreeState}, Parser},
+    };
+
+    mockall::mock! {
+        Registerer {}
+
+        impl Registerer for Registerer {
+            type Item = ClockTreeState;
+
+            fn update(&self, state: <Self as Registerer>::Item) -> impl Future<Output = anyhow::Result<()>> + Send;
+        }
+    }
+
+    mockall::mock! {
+        Parser {}
+
+        impl Parser for Parser {
+            type Item = ClockTreeState;
+
+            fn parse(&self, input: &str) -> anyhow::Result<<Self as Parser>::Item>;
+        }
+    }
+
+    #[tokio::test]
+    async fn collect() {
+        let mut mock_executor = MockExecutor::new();
+        mock_executor
+            .expect_execute()
+            .times(1)
+            .returning(|| Box::pin(ok("osc 5 5 0 54000000 0 0 50000 Y".to_string())));
+
+        let mut mock_parser = MockParser::new();
+        mock_parser
+            .expect_parse()
+            .times(1)
+            .withf(|x| x == "osc 5 5 0 54000000 0 0 50000 Y")
+            .returning(|_| Ok(ClockTreeState {
+                clocks: vec![
+                    Clock {
+                        name: "osc".to_string(),
+                        enable_count: 5,
+                        prepare_count: 5,
+                        rate: 54000000,
+                    },
+                ],
+            }));
+
+        let mut mock_registerer = MockRegisterer::new();
+        mock_registerer
+            .expect_update()
+            .times(1)
+            .withf(|x| *x == ClockTreeState {
+                clocks: vec![
+                    Clock {
+                        name: "osc".to_string(),
+                        enable_count: 5,
+                        prepare_count: 5,
+                        rate: 54000000,
+                    },
+                ],
+            })
+            .returning(|_| Box::pin(ok(())));
+
+        let clock_tree = ClockTree::new(mock_executor, mock_parser, mock_registerer);
+        let result = clock_tree.collect().await;
+
+        assert!(result.is_ok())
+    }
+}
@@ -0,0 +1,114 @@
+use async_trait::async_trait;
+
+use crate::{
+    executor::Executor,
+    metrics::{Collector, Registerer},
+    parser::{cpu_vulnerability::CpuVulnerabilityState, Parser},
+};
+
+#[derive(Clone, Debug)]
+pub struct CpuVulnerability<E, P, R> {
+    executor: E,
+    parser: P,
+    registerer: R,
+}
+
+impl<E, P, R> CpuVulnerability<E, P, R> {
+    pub fn new(executor: E, parser: P, registerer: R) -> Self {
+        Self {
+            executor,
+            parser,
+            registerer,
+        }
+    }
+}
+
+#[async_trait]
+impl<E, P, R> Collector for CpuVulnerability<E, P, R>
+where
+    E: Executor + Send + Sync,
+    P: Parser<Item = CpuVulnerabilityState> + Send + Sync,
+    R: Registerer<Item = CpuVulnerabilityState> + Send + Sync,
+{
+    fn name(&self) -> &'static str {
+        "cpu_vulnerability"
+    }
+
+    #[tracing::instrument(skip_all, fields(collector = %std::any::type_name::<Self>()))]
+    async fn collect(&self) -> anyhow::Result<()> {
+        tracing::debug!("collecting cpu_vulnerability");
+
+        let output = self.executor.execute().await?;
+        let state = self.parser.parse(&output)?;
+
+        self.registerer.update(state).await?;
+
+        tracing::debug!("succeeded collecting cpu_vulnerability");
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::future::ok;
+
+    use crate::{
+        collector::cpu_vulnerability::CpuVulnerability,
+        executor::MockExecutor,
+        metrics::{Collector, Registerer},
+        parser::{cpu_vulnerability::CpuVulnerabilityState, Parser},
+    };
+
+    mockall::mock! {
+        Registerer {}
+
+        impl Registerer for Registerer {
+            type Item = CpuVulnerabilityState;
+
+            fn update(&self, state: <Self as Registerer>::Item) -> impl Future<Output = anyhow::Result<()>> + Send;
+        }
+    }
+
+    mockall::mock! {
+        Parser {}
+
+        impl Parser for Parser {
+            type Item = CpuVulnerabilityState;
+
+            fn parse(&self, input: &str) -> anyhow::Result<<Self as Parser>::Item>;
+        }
+    }
+
+    #[tokio::test]
+    async fn collect() {
+        let mut mock_executor = MockExecutor::new();
+        mock_executor
+            .expect_execute()
+            .times(1)
+            .returning(|| Box::pin(ok("".to_string())));
+
+        let mut mock_parser = MockParser::new();
+        mock_parser
+            .expect_parse()
+            .times(1)
+            .withf(|x| x.is_empty())
+            .returning(|_| Ok(CpuVulnerabilityState {
+                vulnerabilities: vec![],
+            }));
+
+        let mut mock_registerer = MockRegisterer::new();
+        mock_registerer
+            .expect_update()
+            .times(1)
+            .withf(|x| *x == CpuVulnerabilityState {
+                vulnerabilities: vec![],
+            })
+            .returning(|_| Box::pin(ok(())));
+
+        let cpu_vulnerability = CpuVulnerability::new(mock_executor, mock_parser, mock_registerer);
+        let result = cpu_vulnerability.collect().await;
+
+        assert!(result.is_ok())
+    }
+}
@@ -0,0 +1,207 @@
+use async_trait::async_trait;
+
+use crate::{
+    executor::Executor,
+    metrics::{Collector, Registerer},
+    parser::{
+        file_descriptor::{FileDescriptorState, ProcessFileDescriptorState},
+        Parser,
+    },
+};
+
+#[derive(Clone, Debug)]
+pub struct FileDescriptor<E, P, R> {
+    executor: E,
+    parser: P,
+    registerer: R,
+}
+
+#[derive(Clone, Debug)]
+pub struct ProcessFileDescriptor<E, P, R> {
+    executor: E,
+    parser: P,
+    registerer: R,
+}
+
+impl<E, P, R> FileDescriptor<E, P, R> {
+    pub fn new(executor: E, parser: P, registerer: R) -> Self {
+        Self {
+            executor,
+            parser,
+            registerer,
+        }
+    }
+}
+
+impl<E, P, R> ProcessFileDescriptor<E, P, R> {
+    pub fn new(executor: E, parser: P, registerer: R) -> Self {
+        Self {
+            executor,
+            parser,
+            registerer,
+        }
+    }
+}
+
+#[async_trait]
+impl<E, P, R> Collector for FileDescriptor<E, P, R>
+where
+    E: Executor + Send + Sync,
+    P: Parser<Item = FileDescriptorState> + Send + Sync,
+    R: Registerer<Item = FileDescriptorState> + Send + Sync,
+{
+    fn name(&self) -> &'static str {
+        "file_descriptor"
+    }
+
+    #[tracing::instrument(skip_all, fields(collector = %std::any::type_name::<Self>()))]
+    async fn collect(&self) -> anyhow::Result<()> {
+        tracing::debug!("collecting file_descriptor");
+
+        let output = self.executor.execute().await?;
+        let state = self.parser.parse(&output)?;
+
+        self.registerer.update(state).await?;
+
+        tracing::debug!("succeeded collecting file_descriptor");
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<E, P, R> Collector for ProcessFileDescriptor<E, P, R>
+where
+    E: Executor + Send + Sync,
+    P: Parser<Item = ProcessFileDescriptorState> + Send + Sync,
+    R: Registerer<Item = ProcessFileDescriptorState> + Send + Sync,
+{
+    fn name(&self) -> &'static str {
+        "process_file_descriptor"
+    }
+
+    #[tracing::instrument(skip_all, fields(collector = %std::any::type_name::<Self>()))]
+    async fn collect(&self) -> anyhow::Result<()> {
+        tracing::debug!("collecting process_file_descriptor");
+
+        let output = self.executor.execute().await?;
+        let state = self.parser.parse(&output)?;
+
+        self.registerer.update(state).await?;
+
+        tracing::debug!("succeeded collecting process_file_descriptor");
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::future::ok;
+
+    use crate::{
+        collector::file_descriptor::{FileDescriptor, ProcessFileDescriptor},
+        executor::MockExecutor,
+        metrics::{Collector, Registerer},
+        parser::{
+            file_descriptor::{FileDescriptorState, ProcessFileDescriptorState},
+            Parser,
+        },
+    };
+
+    mockall::mock! {
+        Registerer {}
+
+        impl Registerer for Registerer {
+            type Item = FileDescriptorState;
+
+            fn update(&self, state: <Self as Registerer>::Item) -> impl Future<Output = anyhow::Result<()>> + Send;
+        }
+    }
+
+    mockall::mock! {
+        Parser {}
+
+        impl Parser for Parser {
+            type Item = FileDescriptorState;
+
+            fn parse(&self, input: &str) -> anyhow::Result<<Self as Parser>::Item>;
+        }
+    }
+
+    mockall::mock! {
+        ProcessRegisterer {}
+
+        impl Registerer for ProcessRegisterer {
+            type Item = ProcessFileDescriptorState;
+
+            fn update(&self, state: <Self as Registerer>::Item) -> impl Future<Output = anyhow::Result<()>> + Send;
+        }
+    }
+
+    mockall::mock! {
+        ProcessParser {}
+
+        impl Parser for ProcessParser {
+            type Item = ProcessFileDescriptorState;
+
+            fn parse(&self, input: &str) -> anyhow::Result<<Self as Parser>::Item>;
+        }
+    }
+
+    #[tokio::test]
+    async fn collect() {
+        let mut mock_executor = MockExecutor::new();
+        mock_executor
+            .expect_execute()
+            .times(1)
+            .returning(|| Box::pin(ok("2304\t0\t65536".to_string())));
+
+        let mut mock_parser = MockParser::new();
+        mock_parser
+            .expect_parse()
+            .times(1)
+            .withf(|x| x == "2304\t0\t65536")
+            .returning(|_| Ok(FileDescriptorState { allocated: 2304, maximum: 65536 }));
+
+        let mut mock_registerer = MockRegisterer::new();
+        mock_registerer
+            .expect_update()
+            .times(1)
+            .withf(|x| *x == FileDescriptorState { allocated: 2304, maximum: 65536 })
+            .returning(|_| Box::pin(ok(())));
+
+        let file_descriptor = FileDescriptor::new(mock_executor, mock_parser, mock_registerer);
+        let result = file_descriptor.collect().await;
+
+        assert!(result.is_ok())
+    }
+
+    #[tokio::test]
+    async fn collect_process() {
+        let mut mock_executor = MockExecutor::new();
+        mock_executor
+            .expect_execute()
+            .times(1)
+            .returning(|| Box::pin(ok("0\n1\n2".to_string())));
+
+        let mut mock_parser = MockProcessParser::new();
+        mock_parser
+            .expect_parse()
+            .times(1)
+            .withf(|x| x == "0\n1\n2")
+            .returning(|_| Ok(ProcessFileDescriptorState { open: 3 }));
+
+        let mut mock_registerer = MockProcessRegisterer::new();
+        mock_registerer
+            .expect_update()
+            .times(1)
+            .withf(|x| *x == ProcessFileDescriptorState { open: 3 })
+            .returning(|_| Box::pin(ok(())));
+
+        let process_file_descriptor = ProcessFileDescriptor::new(mock_executor, mock_parser, mock_registerer);
+        let result = process_file_descriptor.collect().await;
+
+        assert!(result.is_ok())
+    }
+}
@@ -0,0 +1,132 @@
+use async_trait::async_trait;
+
+use crate::{
+    executor::Executor,
+    metrics::{Collector, Registerer},
+    parser::{filesystem::FilesystemState, Parser},
+};
+
+#[derive(Clone, Debug)]
+pub struct Filesystem<E, P, R> {
+    executor: E,
+    parser: P,
+    registerer: R,
+}
+
+impl<E, P, R> Filesystem<E, P, R> {
+    pub fn new(executor: E, parser: P, registerer: R) -> Self {
+        Self {
+            executor,
+            parser,
+            registerer,
+        }
+    }
+}
+
+#[async_trait]
+impl<E, P, R> Collector for Filesystem<E, P, R>
+where
+    E: Executor + Send + Sync,
+    P: Parser<Item = FilesystemState> + Send + Sync,
+    R: Registerer<Item = FilesystemState> + Send + Sync,
+{
+    fn name(&self) -> &'static str {
+        "filesystem"
+    }
+
+    #[tracing::instrument(skip_all, fields(collector = %std::any::type_name::<Self>()))]
+    async fn collect(&self) -> anyhow::Result<()> {
+        tracing::debug!("collecting filesystem");
+
+        let output = self.executor.execute().await?;
+        let state = self.parser.parse(&output)?;
+
+        self.registerer.update(state).await?;
+
+        tracing::debug!("succeeded collecting filesystem");
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::future::ok;
+
+    use crate::{
+        collector::filesystem::Filesystem,
+        executor::MockExecutor,
+        metrics::{Collector, Registerer},
+        parser::{filesystem::{FilesystemState, FilesystemStats}, Parser},
+    };
+
+    mockall::mock! {
+        Registerer {}
+
+        impl Registerer for Registerer {
+            type Item = FilesystemState;
+
+            fn update(&self, state: <Self as Registerer>::Item) -> impl Future<Output = anyhow::Result<()>> + Send;
+        }
+    }
+
+    mockall::mock! {
+        Parser {}
+
+        impl Parser for Parser {
+            type Item = FilesystemState;
+
+            fn parse(&self, input: &str) -> anyhow::Result<<Self as Parser>::Item>;
+        }
+    }
+
+    #[tokio::test]
+    async fn collect() {
+        let mut mock_executor = MockExecutor::new();
+        mock_executor
+            .expect_execute()
+            .times(1)
+            .returning(|| Box::pin(ok("Type 1B-blocks Avail Inodes IFree Mounted on\next4 100 50 10 5 /".to_string())));
+
+        let mut mock_parser = MockParser::new();
+        mock_parser
+            .expect_parse()
+            .times(1)
+            .withf(|x| x == "Type 1B-blocks Avail Inodes IFree Mounted on\next4 100 50 10 5 /")
+            .returning(|_| Ok(FilesystemState {
+                filesystems: vec![
+                    FilesystemStats {
+                        mountpoint: "/".to_string(),
+                        fstype: "ext4".to_string(),
+                        size_bytes: 100,
+                        avail_bytes: 50,
+                        inodes_total: Some(10),
+                        inodes_free: Some(5),
+                    },
+                ],
+            }));
+
+        let mut mock_registerer = MockRegisterer::new();
+        mock_registerer
+            .expect_update()
+            .times(1)
+            .withf(|x| *x == FilesystemState {
+                filesystems: vec![
+                    FilesystemStats {
+                        mountpoint: "/".to_string(),
+                        fstype: "ext4".to_string(),
+                        size_bytes: 100,
+                        avail_bytes: 50,
+                        inodes_total: Some(10),
+                        inodes_free: Some(5),
+                    },
+                ],
+            })
+            .returning(|_| Box::pin(ok(())));
+
+        let filesystem = Filesystem::new(mock_executor, mock_parser, mock_registerer);
+        let result = filesystem.collect().await;
+
+        assert!(result.is_ok())
+    }
+}
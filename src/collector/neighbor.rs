@@ -0,0 +1,209 @@
+use async_trait::async_trait;
+
+use crate::{
+    executor::Executor,
+    metrics::{Collector, Registerer},
+    parser::{
+        neighbor::{NeighborState, NeighborThresholdState},
+        Parser,
+    },
+};
+
+#[derive(Clone, Debug)]
+pub struct Neighbor<E, P, R> {
+    executor: E,
+    parser: P,
+    registerer: R,
+}
+
+#[derive(Clone, Debug)]
+pub struct NeighborThreshold<E, P, R> {
+    executor: E,
+    parser: P,
+    registerer: R,
+}
+
+impl<E, P, R> Neighbor<E, P, R> {
+    pub fn new(executor: E, parser: P, registerer: R) -> Self {
+        Self {
+            executor,
+            parser,
+            registerer,
+        }
+    }
+}
+
+impl<E, P, R> NeighborThreshold<E, P, R> {
+    pub fn new(executor: E, parser: P, registerer: R) -> Self {
+        Self {
+            executor,
+            parser,
+            registerer,
+        }
+    }
+}
+
+#[async_trait]
+impl<E, P, R> Collector for Neighbor<E, P, R>
+where
+    E: Executor + Send + Sync,
+    P: Parser<Item = NeighborState> + Send + Sync,
+    R: Registerer<Item = NeighborState> + Send + Sync,
+{
+    fn name(&self) -> &'static str {
+        "neighbor"
+    }
+
+    #[tracing::instrument(skip_all, fields(collector = %std::any::type_name::<Self>()))]
+    async fn collect(&self) -> anyhow::Result<()> {
+        tracing::debug!("collecting neighbor");
+
+        let output = self.executor.execute().await?;
+        let state = self.parser.parse(&output)?;
+
+        self.registerer.update(state).await?;
+
+        tracing::debug!("succeeded collecting neighbor");
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<E, P, R> Collector for NeighborThreshold<E, P, R>
+where
+    E: Executor + Send + Sync,
+    P: Parser<Item = NeighborThresholdState> + Send + Sync,
+    R: Registerer<Item = NeighborThresholdState> + Send + Sync,
+{
+    fn name(&self) -> &'static str {
+        "neighbor_threshold"
+    }
+
+    #[tracing::instrument(skip_all, fields(collector = %std::any::type_name::<Self>()))]
+    async fn collect(&self) -> anyhow::Result<()> {
+        tracing::debug!("collecting neighbor_threshold");
+
+        let output = self.executor.execute().await?;
+        let state = self.parser.parse(&output)?;
+
+        self.registerer.update(state).await?;
+
+        tracing::debug!("succeeded collecting neighbor_threshold");
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use futures::future::ok;
+
+    use crate::{
+        collector::neighbor::{Neighbor, NeighborThreshold},
+        executor::MockExecutor,
+        metrics::{Collector, Registerer},
+        parser::{
+            neighbor::{NeighborState, NeighborThresholdState},
+            Parser,
+        },
+    };
+
+    mockall::mock! {
+        Registerer {}
+
+        impl Registerer for Registerer {
+            type Item = NeighborState;
+
+            fn update(&self, state: <Self as Registerer>::Item) -> impl Future<Output = anyhow::Result<()>> + Send;
+        }
+    }
+
+    mockall::mock! {
+        Parser {}
+
+        impl Parser for Parser {
+            type Item = NeighborState;
+
+            fn parse(&self, input: &str) -> anyhow::Result<<Self as Parser>::Item>;
+        }
+    }
+
+    mockall::mock! {
+        ThresholdRegisterer {}
+
+        impl Registerer for ThresholdRegisterer {
+            type Item = NeighborThresholdState;
+
+            fn update(&self, state: <Self as Registerer>::Item) -> impl Future<Output = anyhow::Result<()>> + Send;
+        }
+    }
+
+    mockall::mock! {
+        ThresholdParser {}
+
+        impl Parser for ThresholdParser {
+            type Item = NeighborThresholdState;
+
+            fn parse(&self, input: &str) -> anyhow::Result<<Self as Parser>::Item>;
+        }
+    }
+
+    #[tokio::test]
+    async fn collect() {
+        let mut mock_executor = MockExecutor::new();
+        mock_executor
+            .expect_execute()
+            .times(1)
+            .returning(|| Box::pin(ok("IP address HW type Flags HW address Mask Device\n192.168.1.1 0x1 0x2 aa:bb:cc:dd:ee:01 * eth0".to_string())));
+
+        let mut mock_parser = MockParser::new();
+        mock_parser
+            .expect_parse()
+            .times(1)
+            .withf(|x| x == "IP address HW type Flags HW address Mask Device\n192.168.1.1 0x1 0x2 aa:bb:cc:dd:ee:01 * eth0")
+            .returning(|_| Ok(NeighborState { entries: BTreeMap::from([("eth0".to_string(), 1)]) }));
+
+        let mut mock_registerer = MockRegisterer::new();
+        mock_registerer
+            .expect_update()
+            .times(1)
+            .withf(|x| *x == NeighborState { entries: BTreeMap::from([("eth0".to_string(), 1)]) })
+            .returning(|_| Box::pin(ok(())));
+
+        let neighbor = Neighbor::new(mock_executor, mock_parser, mock_registerer);
+        let result = neighbor.collect().await;
+
+        assert!(result.is_ok())
+    }
+
+    #[tokio::test]
+    async fn collect_threshold() {
+        let mut mock_executor = MockExecutor::new();
+        mock_executor
+            .expect_execute()
+            .times(1)
+            .returning(|| Box::pin(ok("net.ipv4.neigh.default.gc_thresh1 = 128".to_string())));
+
+        let mut mock_parser = MockThresholdParser::new();
+        mock_parser
+            .expect_parse()
+            .times(1)
+            .withf(|x| x == "net.ipv4.neigh.default.gc_thresh1 = 128")
+            .returning(|_| Ok(NeighborThresholdState { thresholds: BTreeMap::from([("gc_thresh1".to_string(), 128)]) }));
+
+        let mut mock_registerer = MockThresholdRegisterer::new();
+        mock_registerer
+            .expect_update()
+            .times(1)
+            .withf(|x| *x == NeighborThresholdState { thresholds: BTreeMap::from([("gc_thresh1".to_string(), 128)]) })
+            .returning(|_| Box::pin(ok(())));
+
+        let neighbor_threshold = NeighborThreshold::new(mock_executor, mock_parser, mock_registerer);
+        let result = neighbor_threshold.collect().await;
+
+        assert!(result.is_ok())
+    }
+}
@@ -0,0 +1,116 @@
+use async_trait::async_trait;
+
+use crate::{
+    executor::Executor,
+    metrics::{Collector, Registerer},
+    parser::{nftables::NftablesState, Parser},
+};
+
+#[derive(Clone, Debug)]
+pub struct Nftables<E, P, R> {
+    executor: E,
+    parser: P,
+    registerer: R,
+}
+
+impl<E, P, R> Nftables<E, P, R> {
+    pub fn new(executor: E, parser: P, registerer: R) -> Self {
+        Self {
+            executor,
+            parser,
+            registerer,
+        }
+    }
+}
+
+#[async_trait]
+impl<E, P, R> Collector for Nftables<E, P, R>
+where
+    E: Executor + Send + Sync,
+    P: Parser<Item = NftablesState> + Send + Sync,
+    R: Registerer<Item = NftablesState> + Send + Sync,
+{
+    fn name(&self) -> &'static str {
+        "nftables"
+    }
+
+    #[tracing::instrument(skip_all, fields(collector = %std::any::type_name::<Self>()))]
+    async fn collect(&self) -> anyhow::Result<()> {
+        tracing::debug!("collecting nftables");
+
+        let output = self.executor.execute().await?;
+        let state = self.parser.parse(&output)?;
+
+        self.registerer.update(state).await?;
+
+        tracing::debug!("succeeded collecting nftables");
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::future::ok;
+
+    use crate::{
+        collector::nftables::Nftables,
+        executor::MockExecutor,
+        metrics::{Collector, Registerer},
+        parser::{nftables::NftablesState, Parser},
+    };
+
+    mockall::mock! {
+        Registerer {}
+
+        impl Registerer for Registerer {
+            type Item = NftablesState;
+
+            fn update(&self, state: <Self as Registerer>::Item) -> impl Future<Output = anyhow::Result<()>> + Send;
+        }
+    }
+
+    mockall::mock! {
+        Parser {}
+
+        impl Parser for Parser {
+            type Item = NftablesState;
+
+            fn parse(&self, input: &str) -> anyhow::Result<<Self as Parser>::Item>;
+        }
+    }
+
+    #[tokio::test]
+    async fn collect() {
+        let mut mock_executor = MockExecutor::new();
+        mock_executor
+            .expect_execute()
+            .times(1)
+            .returning(|| Box::pin(ok("{\"nftables\": []}".to_string())));
+
+        let mut mock_parser = MockParser::new();
+        mock_parser
+            .expect_parse()
+            .times(1)
+            .withf(|x| x == "{\"nftables\": []}")
+            .returning(|_| Ok(NftablesState {
+                counters: vec![],
+                chains: vec![],
+            }));
+
+        let mut mock_registerer = MockRegisterer::new();
+        mock_registerer
+            .expect_update()
+            .times(1)
+            .withf(|x| *x == NftablesState {
+                counters: vec![],
+                chains: vec![],
+            })
+            .returning(|_| Box::pin(ok(())));
+
+        let nftables = Nftables::new(mock_executor, mock_parser, mock_registerer);
+        let result = nftables.collect().await;
+
+        assert!(result.is_ok())
+    }
+}
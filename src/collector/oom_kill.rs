@@ -0,0 +1,110 @@
+use async_trait::async_trait;
+
+use crate::{
+    executor::Executor,
+    metrics::{Collector, Registerer},
+    parser::{oom_kill::OomKillState, Parser},
+};
+
+#[derive(Clone, Debug)]
+pub struct OomKill<E, P, R> {
+    executor: E,
+    parser: P,
+    registerer: R,
+}
+
+impl<E, P, R> OomKill<E, P, R> {
+    pub fn new(executor: E, parser: P, registerer: R) -> Self {
+        Self {
+            executor,
+            parser,
+            registerer,
+        }
+    }
+}
+
+#[async_trait]
+impl<E, P, R> Collector for OomKill<E, P, R>
+where
+    E: Executor + Send + Sync,
+    P: Parser<Item = OomKillState> + Send + Sync,
+    R: Registerer<Item = OomKillState> + Send + Sync,
+{
+    fn name(&self) -> &'static str {
+        "oom_kill"
+    }
+
+    #[tracing::instrument(skip_all, fields(collector = %std::any::type_name::<Self>()))]
+    async fn collect(&self) -> anyhow::Result<()> {
+        tracing::debug!("collecting oom_kill");
+
+        let output = self.executor.execute().await?;
+        let state = self.parser.parse(&output)?;
+
+        self.registerer.update(state).await?;
+
+        tracing::debug!("succeeded collecting oom_kill");
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::future::ok;
+
+    use crate::{
+        collector::oom_kill::OomKill,
+        executor::MockExecutor,
+        metrics::{Collector, Registerer},
+        parser::{oom_kill::OomKillState, Parser},
+    };
+
+    mockall::mock! {
+        Registerer {}
+
+        impl Registerer for Registerer {
+            type Item = OomKillState;
+
+            fn update(&self, state: <Self as Registerer>::Item) -> impl Future<Output = anyhow::Result<()>> + Send;
+        }
+    }
+
+    mockall::mock! {
+        Parser {}
+
+        impl Parser for Parser {
+            type Item = OomKillState;
+
+            fn parse(&self, input: &str) -> anyhow::Result<<Self as Parser>::Item>;
+        }
+    }
+
+    #[tokio::test]
+    async fn collect() {
+        let mut mock_executor = MockExecutor::new();
+        mock_executor
+            .expect_execute()
+            .times(1)
+            .returning(|| Box::pin(ok("oom_kill 2".to_string())));
+
+        let mut mock_parser = MockParser::new();
+        mock_parser
+            .expect_parse()
+            .times(1)
+            .withf(|x| x == "oom_kill 2")
+            .returning(|_| Ok(OomKillState { oom_kills: 2 }));
+
+        let mut mock_registerer = MockRegisterer::new();
+        mock_registerer
+            .expect_update()
+            .times(1)
+            .withf(|x| *x == OomKillState { oom_kills: 2 })
+            .returning(|_| Box::pin(ok(())));
+
+        let oom_kill = OomKill::new(mock_executor, mock_parser, mock_registerer);
+        let result = oom_kill.collect().await;
+
+        assert!(result.is_ok())
+    }
+}
@@ -0,0 +1,116 @@
+use async_trait::async_trait;
+
+use crate::{
+    executor::Executor,
+    metrics::{Collector, Registerer},
+    parser::{package_update::PackageUpdateState, Parser},
+};
+
+#[derive(Clone, Debug)]
+pub struct PackageUpdate<E, P, R> {
+    executor: E,
+    parser: P,
+    registerer: R,
+}
+
+impl<E, P, R> PackageUpdate<E, P, R> {
+    pub fn new(executor: E, parser: P, registerer: R) -> Self {
+        Self {
+            executor,
+            parser,
+            registerer,
+        }
+    }
+}
+
+#[async_trait]
+impl<E, P, R> Collector for PackageUpdate<E, P, R>
+where
+    E: Executor + Send + Sync,
+    P: Parser<Item = PackageUpdateState> + Send + Sync,
+    R: Registerer<Item = PackageUpdateState> + Send + Sync,
+{
+    fn name(&self) -> &'static str {
+        "package_update"
+    }
+
+    #[tracing::instrument(skip_all, fields(collector = %std::any::type_name::<Self>()))]
+    async fn collect(&self) -> anyhow::Result<()> {
+        tracing::debug!("collecting package_update");
+
+        let output = self.executor.execute().await?;
+        let state = self.parser.parse(&output)?;
+
+        self.registerer.update(state).await?;
+
+        tracing::debug!("succeeded collecting package_update");
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::future::ok;
+
+    use crate::{
+        collector::package_update::PackageUpdate,
+        executor::MockExecutor,
+        metrics::{Collector, Registerer},
+        parser::{package_update::PackageUpdateState, Parser},
+    };
+
+    mockall::mock! {
+        Registerer {}
+
+        impl Registerer for Registerer {
+            type Item = PackageUpdateState;
+
+            fn update(&self, state: <Self as Registerer>::Item) -> impl Future<Output = anyhow::Result<()>> + Send;
+        }
+    }
+
+    mockall::mock! {
+        Parser {}
+
+        impl Parser for Parser {
+            type Item = PackageUpdateState;
+
+            fn parse(&self, input: &str) -> anyhow::Result<<Self as Parser>::Item>;
+        }
+    }
+
+    #[tokio::test]
+    async fn collect() {
+        let mut mock_executor = MockExecutor::new();
+        mock_executor
+            .expect_execute()
+            .times(1)
+            .returning(|| Box::pin(ok("Inst libssl3 [3.0.15-1~deb12u1] (3.0.17-1~deb12u2 Debian-Security:12/stable-security [arm64])".to_string())));
+
+        let mut mock_parser = MockParser::new();
+        mock_parser
+            .expect_parse()
+            .times(1)
+            .withf(|x| x == "Inst libssl3 [3.0.15-1~deb12u1] (3.0.17-1~deb12u2 Debian-Security:12/stable-security [arm64])")
+            .returning(|_| Ok(PackageUpdateState {
+                pending: 1,
+                security: 1,
+            }));
+
+        let mut mock_registerer = MockRegisterer::new();
+        mock_registerer
+            .expect_update()
+            .times(1)
+            .withf(|x| *x == PackageUpdateState {
+                pending: 1,
+                security: 1,
+            })
+            .returning(|_| Box::pin(ok(())));
+
+        let package_update = PackageUpdate::new(mock_executor, mock_parser, mock_registerer);
+        let result = package_update.collect().await;
+
+        assert!(result.is_ok())
+    }
+}
@@ -0,0 +1,114 @@
+use async_trait::async_trait;
+
+use crate::{
+    executor::Executor,
+    metrics::{Collector, Registerer},
+    parser::{reboot_required::RebootRequiredState, Parser},
+};
+
+#[derive(Clone, Debug)]
+pub struct RebootRequired<E, P, R> {
+    executor: E,
+    parser: P,
+    registerer: R,
+}
+
+impl<E, P, R> RebootRequired<E, P, R> {
+    pub fn new(executor: E, parser: P, registerer: R) -> Self {
+        Self {
+            executor,
+            parser,
+            registerer,
+        }
+    }
+}
+
+#[async_trait]
+impl<E, P, R> Collector for RebootRequired<E, P, R>
+where
+    E: Executor + Send + Sync,
+    P: Parser<Item = RebootRequiredState> + Send + Sync,
+    R: Registerer<Item = RebootRequiredState> + Send + Sync,
+{
+    fn name(&self) -> &'static str {
+        "reboot_required"
+    }
+
+    #[tracing::instrument(skip_all, fields(collector = %std::any::type_name::<Self>()))]
+    async fn collect(&self) -> anyhow::Result<()> {
+        tracing::debug!("collecting reboot_required");
+
+        let output = self.executor.execute().await?;
+        let state = self.parser.parse(&output)?;
+
+        self.registerer.update(state).await?;
+
+        tracing::debug!("succeeded collecting reboot_required");
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::future::ok;
+
+    use crate::{
+        collector::reboot_required::RebootRequired,
+        executor::MockExecutor,
+        metrics::{Collector, Registerer},
+        parser::{reboot_required::RebootRequiredState, Parser},
+    };
+
+    mockall::mock! {
+        Registerer {}
+
+        impl Registerer for Registerer {
+            type Item = RebootRequiredState;
+
+            fn update(&self, state: <Self as Registerer>::Item) -> impl Future<Output = anyhow::Result<()>> + Send;
+        }
+    }
+
+    mockall::mock! {
+        Parser {}
+
+        impl Parser for Parser {
+            type Item = RebootRequiredState;
+
+            fn parse(&self, input: &str) -> anyhow::Result<<Self as Parser>::Item>;
+        }
+    }
+
+    #[tokio::test]
+    async fn collect() {
+        let mut mock_executor = MockExecutor::new();
+        mock_executor
+            .expect_execute()
+            .times(1)
+            .returning(|| Box::pin(ok("lock\nreboot-required".to_string())));
+
+        let mut mock_parser = MockParser::new();
+        mock_parser
+            .expect_parse()
+            .times(1)
+            .withf(|x| x == "lock\nreboot-required")
+            .returning(|_| Ok(RebootRequiredState {
+                required: true,
+            }));
+
+        let mut mock_registerer = MockRegisterer::new();
+        mock_registerer
+            .expect_update()
+            .times(1)
+            .withf(|x| *x == RebootRequiredState {
+                required: true,
+            })
+            .returning(|_| Box::pin(ok(())));
+
+        let reboot_required = RebootRequired::new(mock_executor, mock_parser, mock_registerer);
+        let result = reboot_required.collect().await;
+
+        assert!(result.is_ok())
+    }
+}
@@ -0,0 +1,122 @@
+use async_trait::async_trait;
+
+use crate::{
+    executor::Executor,
+    metrics::{Collector, Registerer},
+    parser::{reset::ResetState, Parser},
+};
+
+#[derive(Clone, Debug)]
+pub struct Reset<E, P, R> {
+    executor: E,
+    parser: P,
+    registerer: R,
+}
+
+impl<E, P, R> Reset<E, P, R> {
+    pub fn new(executor: E, parser: P, registerer: R) -> Self {
+        Self {
+            executor,
+            parser,
+            registerer,
+        }
+    }
+}
+
+#[async_trait]
+impl<E, P, R> Collector for Reset<E, P, R>
+where
+    E: Executor + Send + Sync,
+    P: Parser<Item = ResetState> + Send + Sync,
+    R: Registerer<Item = ResetState> + Send + Sync,
+{
+    fn name(&self) -> &'static str {
+        "reset"
+    }
+
+    #[tracing::instrument(skip_all, fields(collector = %std::any::type_name::<Self>()))]
+    async fn collect(&self) -> anyhow::Result<()> {
+        tracing::debug!("collecting reset");
+
+        let output = self.executor.execute().await?;
+        let state = self.parser.parse(&output)?;
+
+        self.registerer.update(state).await?;
+
+        tracing::debug!("succeeded collecting reset");
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::future::ok;
+
+    use crate::{
+        collector::reset::Reset,
+        executor::MockExecutor,
+        metrics::{Collector, Registerer},
+        parser::{reset::ResetState, Parser},
+    };
+
+    mockall::mock! {
+        Registerer {}
+
+        impl Registerer for Registerer {
+            type Item = ResetState;
+
+            fn update(&self, state: <Self as Registerer>::Item) -> impl Future<Output = anyhow::Result<()>> + Send;
+        }
+    }
+
+    mockall::mock! {
+        Parser {}
+
+        impl Parser for Parser {
+            type Item = ResetState;
+
+            fn parse(&self, input: &str) -> anyhow::Result<<Self as Parser>::Item>;
+        }
+    }
+
+    #[tokio::test]
+    async fn collect() {
+        let mut mock_executor = MockExecutor::new();
+        mock_executor
+            .expect_execute()
+            .times(1)
+            .returning(|| Box::pin(ok("rsts=0x00001000".to_string())));
+
+        let mut mock_parser = MockParser::new();
+        mock_parser
+            .expect_parse()
+            .times(1)
+            .withf(|x| x == "rsts=0x00001000")
+            .returning(|_| Ok(ResetState {
+                register: 0x1000,
+                power_on: true,
+                watchdog: false,
+                software: false,
+                debugger: false,
+            }));
+
+        let mut mock_registerer = MockRegisterer::new();
+        mock_registerer
+            .expect_update()
+            .times(1)
+            .withf(|x| *x == ResetState {
+                register: 0x1000,
+                power_on: true,
+                watchdog: false,
+                software: false,
+                debugger: false,
+            })
+            .returning(|_| Box::pin(ok(())));
+
+        let reset = Reset::new(mock_executor, mock_parser, mock_registerer);
+        let result = reset.collect().await;
+
+        assert!(result.is_ok())
+    }
+}
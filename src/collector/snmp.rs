@@ -0,0 +1,120 @@
+use async_trait::async_trait;
+
+use crate::{
+    executor::Executor,
+    metrics::{Collector, Registerer},
+    parser::{snmp::SnmpState, Parser},
+};
+
+#[derive(Clone, Debug)]
+pub struct Snmp<E, P, R> {
+    executor: E,
+    parser: P,
+    registerer: R,
+}
+
+impl<E, P, R> Snmp<E, P, R> {
+    pub fn new(executor: E, parser: P, registerer: R) -> Self {
+        Self {
+            executor,
+            parser,
+            registerer,
+        }
+    }
+}
+
+#[async_trait]
+impl<E, P, R> Collector for Snmp<E, P, R>
+where
+    E: Executor + Send + Sync,
+    P: Parser<Item = SnmpState> + Send + Sync,
+    R: Registerer<Item = SnmpState> + Send + Sync,
+{
+    fn name(&self) -> &'static str {
+        "snmp"
+    }
+
+    #[tracing::instrument(skip_all, fields(collector = %std::any::type_name::<Self>()))]
+    async fn collect(&self) -> anyhow::Result<()> {
+        tracing::debug!("collecting snmp");
+
+        let output = self.executor.execute().await?;
+        let state = self.parser.parse(&output)?;
+
+        self.registerer.update(state).await?;
+
+        tracing::debug!("succeeded collecting snmp");
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::future::ok;
+
+    use crate::{
+        collector::snmp::Snmp,
+        executor::MockExecutor,
+        metrics::{Collector, Registerer},
+        parser::{snmp::SnmpState, Parser},
+    };
+
+    mockall::mock! {
+        Registerer {}
+
+        impl Registerer for Registerer {
+            type Item = SnmpState;
+
+            fn update(&self, state: <Self as Registerer>::Item) -> impl Future<Output = anyhow::Result<()>> + Send;
+        }
+    }
+
+    mockall::mock! {
+        Parser {}
+
+        impl Parser for Parser {
+            type Item = SnmpState;
+
+            fn parse(&self, input: &str) -> anyhow::Result<<Self as Parser>::Item>;
+        }
+    }
+
+    #[tokio::test]
+    async fn collect() {
+        let mut mock_executor = MockExecutor::new();
+        mock_executor
+            .expect_execute()
+            .times(1)
+            .returning(|| Box::pin(ok("Tcp: RetransSegs\nTcp: 1\n".to_string())));
+
+        let mut mock_parser = MockParser::new();
+        mock_parser
+            .expect_parse()
+            .times(1)
+            .withf(|x| x == "Tcp: RetransSegs\nTcp: 1\n")
+            .returning(|_| Ok(SnmpState {
+                protocols: vec![],
+                tcp_retransmitted_segments: 1,
+                tcp_connections_established: 0,
+                udp_no_port_datagrams: 0,
+            }));
+
+        let mut mock_registerer = MockRegisterer::new();
+        mock_registerer
+            .expect_update()
+            .times(1)
+            .withf(|x| *x == SnmpState {
+                protocols: vec![],
+                tcp_retransmitted_segments: 1,
+                tcp_connections_established: 0,
+                udp_no_port_datagrams: 0,
+            })
+            .returning(|_| Box::pin(ok(())));
+
+        let snmp = Snmp::new(mock_executor, mock_parser, mock_registerer);
+        let result = snmp.collect().await;
+
+        assert!(result.is_ok())
+    }
+}
@@ -0,0 +1,114 @@
+use async_trait::async_trait;
+
+use crate::{
+    executor::Executor,
+    metrics::{Collector, Registerer},
+    parser::{temperature::TemperatureState, Parser},
+};
+
+#[derive(Clone, Debug)]
+pub struct Temperature<E, P, R> {
+    executor: E,
+    parser: P,
+    registerer: R,
+}
+
+impl<E, P, R> Temperature<E, P, R> {
+    pub fn new(executor: E, parser: P, registerer: R) -> Self {
+        Self {
+            executor,
+            parser,
+            registerer,
+        }
+    }
+}
+
+#[async_trait]
+impl<E, P, R> Collector for Temperature<E, P, R>
+where
+    E: Executor + Send + Sync,
+    P: Parser<Item = TemperatureState> + Send + Sync,
+    R: Registerer<Item = TemperatureState> + Send + Sync,
+{
+    fn name(&self) -> &'static str {
+        "temperature"
+    }
+
+    #[tracing::instrument(skip_all, fields(collector = %std::any::type_name::<Self>()))]
+    async fn collect(&self) -> anyhow::Result<()> {
+        tracing::debug!("collecting temperature");
+
+        let output = self.executor.execute().await?;
+        let state = self.parser.parse(&output)?;
+
+        self.registerer.update(state).await?;
+
+        tracing::debug!("succeeded collecting temperature");
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::future::ok;
+
+    use crate::{
+        collector::temperature::Temperature,
+        executor::MockExecutor,
+        metrics::{Collector, Registerer},
+        parser::{temperature::TemperatureState, Parser},
+    };
+
+    mockall::mock! {
+        Registerer {}
+
+        impl Registerer for Registerer {
+            type Item = TemperatureState;
+
+            fn update(&self, state: <Self as Registerer>::Item) -> impl Future<Output = anyhow::Result<()>> + Send;
+        }
+    }
+
+    mockall::mock! {
+        Parser {}
+
+        impl Parser for Parser {
+            type Item = TemperatureState;
+
+            fn parse(&self, input: &str) -> anyhow::Result<<Self as Parser>::Item>;
+        }
+    }
+
+    #[tokio::test]
+    async fn collect() {
+        let mut mock_executor = MockExecutor::new();
+        mock_executor
+            .expect_execute()
+            .times(1)
+            .returning(|| Box::pin(ok("48312".to_string())));
+
+        let mut mock_parser = MockParser::new();
+        mock_parser
+            .expect_parse()
+            .times(1)
+            .withf(|x| x == "48312")
+            .returning(|_| Ok(TemperatureState {
+                celsius: 48.312,
+            }));
+
+        let mut mock_registerer = MockRegisterer::new();
+        mock_registerer
+            .expect_update()
+            .times(1)
+            .withf(|x| *x == TemperatureState {
+                celsius: 48.312,
+            })
+            .returning(|_| Box::pin(ok(())));
+
+        let temperature = Temperature::new(mock_executor, mock_parser, mock_registerer);
+        let result = temperature.collect().await;
+
+        assert!(result.is_ok())
+    }
+}
@@ -1,3 +1,5 @@
+use async_trait::async_trait;
+
 use crate::{
     executor::Executor,
     metrics::{Collector, Registerer},
@@ -21,6 +23,7 @@ impl<E, P, R> Throttled<E, P, R> {
     }
 }
 
+#[async_trait]
 impl<E, P, R> Collector for Throttled<E, P, R>
 where
     E: Executor + Send + Sync,
@@ -38,7 +41,7 @@ where
         let output = self.executor.execute().await?;
         let state = self.parser.parse(&output)?;
 
-        self.registerer.register(state).await?;
+        self.registerer.update(state).await?;
 
         tracing::debug!("succeeded collecting throttled");
 
@@ -63,7 +66,7 @@ mod tests {
         impl Registerer for Registerer {
             type Item = ThrottledState;
 
-            fn register(&self, state: <Self as Registerer>::Item) -> impl Future<Output = anyhow::Result<()>> + Send;
+            fn update(&self, state: <Self as Registerer>::Item) -> impl Future<Output = anyhow::Result<()>> + Send;
         }
     }
 
@@ -103,7 +106,7 @@ mod tests {
 
         let mut mock_registerer = MockRegisterer::new();
         mock_registerer
-            .expect_register()
+            .expect_update()
             .times(1)
             .withf(|x| *x == ThrottledState {
                 undervoltage_detected: true,
@@ -0,0 +1,116 @@
+use async_trait::async_trait;
+
+use crate::{
+    executor::Executor,
+    metrics::{Collector, Registerer},
+    parser::{vl805::Vl805State, Parser},
+};
+
+#[derive(Clone, Debug)]
+pub struct Vl805<E, P, R> {
+    executor: E,
+    parser: P,
+    registerer: R,
+}
+
+impl<E, P, R> Vl805<E, P, R> {
+    pub fn new(executor: E, parser: P, registerer: R) -> Self {
+        Self {
+            executor,
+            parser,
+            registerer,
+        }
+    }
+}
+
+#[async_trait]
+impl<E, P, R> Collector for Vl805<E, P, R>
+where
+    E: Executor + Send + Sync,
+    P: Parser<Item = Vl805State> + Send + Sync,
+    R: Registerer<Item = Vl805State> + Send + Sync,
+{
+    fn name(&self) -> &'static str {
+        "vl805"
+    }
+
+    #[tracing::instrument(skip_all, fields(collector = %std::any::type_name::<Self>()))]
+    async fn collect(&self) -> anyhow::Result<()> {
+        tracing::debug!("collecting vl805");
+
+        let output = self.executor.execute().await?;
+        let state = self.parser.parse(&output)?;
+
+        self.registerer.update(state).await?;
+
+        tracing::debug!("succeeded collecting vl805");
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::future::ok;
+
+    use crate::{
+        collector::vl805::Vl805,
+        executor::MockExecutor,
+        metrics::{Collector, Registerer},
+        parser::{vl805::Vl805State, Parser},
+    };
+
+    mockall::mock! {
+        Registerer {}
+
+        impl Registerer for Registerer {
+            type Item = Vl805State;
+
+            fn update(&self, state: <Self as Registerer>::Item) -> impl Future<Output = anyhow::Result<()>> + Send;
+        }
+    }
+
+    mockall::mock! {
+        Parser {}
+
+        impl Parser for Parser {
+            type Item = Vl805State;
+
+            fn parse(&self, input: &str) -> anyhow::Result<<Self as Parser>::Item>;
+        }
+    }
+
+    #[tokio::test]
+    async fn collect() {
+        let mut mock_executor = MockExecutor::new();
+        mock_executor
+            .expect_execute()
+            .times(1)
+            .returning(|| Box::pin(ok("     VL805: up to date\n   CURRENT: 000138c0\n    LATEST: 000138c0".to_string())));
+
+        let mut mock_parser = MockParser::new();
+        mock_parser
+            .expect_parse()
+            .times(1)
+            .withf(|x| x == "     VL805: up to date\n   CURRENT: 000138c0\n    LATEST: 000138c0")
+            .returning(|_| Ok(Vl805State {
+                current: "000138c0".to_string(),
+                latest: "000138c0".to_string(),
+            }));
+
+        let mut mock_registerer = MockRegisterer::new();
+        mock_registerer
+            .expect_update()
+            .times(1)
+            .withf(|x| *x == Vl805State {
+                current: "000138c0".to_string(),
+                latest: "000138c0".to_string(),
+            })
+            .returning(|_| Box::pin(ok(())));
+
+        let vl805 = Vl805::new(mock_executor, mock_parser, mock_registerer);
+        let result = vl805.collect().await;
+
+        assert!(result.is_ok())
+    }
+}
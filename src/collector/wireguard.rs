@@ -0,0 +1,130 @@
+use async_trait::async_trait;
+
+use crate::{
+    executor::Executor,
+    metrics::{Collector, Registerer},
+    parser::{wireguard::WireguardState, Parser},
+};
+
+#[derive(Clone, Debug)]
+pub struct Wireguard<E, P, R> {
+    executor: E,
+    parser: P,
+    registerer: R,
+}
+
+impl<E, P, R> Wireguard<E, P, R> {
+    pub fn new(executor: E, parser: P, registerer: R) -> Self {
+        Self {
+            executor,
+            parser,
+            registerer,
+        }
+    }
+}
+
+#[async_trait]
+impl<E, P, R> Collector for Wireguard<E, P, R>
+where
+    E: Executor + Send + Sync,
+    P: Parser<Item = WireguardState> + Send + Sync,
+    R: Registerer<Item = WireguardState> + Send + Sync,
+{
+    fn name(&self) -> &'static str {
+        "wireguard"
+    }
+
+    #[tracing::instrument(skip_all, fields(collector = %std::any::type_name::<Self>()))]
+    async fn collect(&self) -> anyhow::Result<()> {
+        tracing::debug!("collecting wireguard");
+
+        let output = self.executor.execute().await?;
+        let state = self.parser.parse(&output)?;
+
+        self.registerer.update(state).await?;
+
+        tracing::debug!("succeeded collecting wireguard");
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::future::ok;
+
+    use crate::{
+        collector::wireguard::Wireguard,
+        executor::MockExecutor,
+        metrics::{Collector, Registerer},
+        parser::{wireguard::{WireguardPeer, WireguardState}, Parser},
+    };
+
+    mockall::mock! {
+        Registerer {}
+
+        impl Registerer for Registerer {
+            type Item = WireguardState;
+
+            fn update(&self, state: <Self as Registerer>::Item) -> impl Future<Output = anyhow::Result<()>> + Send;
+        }
+    }
+
+    mockall::mock! {
+        Parser {}
+
+        impl Parser for Parser {
+            type Item = WireguardState;
+
+            fn parse(&self, input: &str) -> anyhow::Result<<Self as Parser>::Item>;
+        }
+    }
+
+    #[tokio::test]
+    async fn collect() {
+        let mut mock_executor = MockExecutor::new();
+        mock_executor
+            .expect_execute()
+            .times(1)
+            .returning(|| Box::pin(ok("wg0\tUGVlcktleTE=\t(none)\t(none)\t10.0.0.2/32\t1760400000\t1024\t2048\toff".to_string())));
+
+        let mut mock_parser = MockParser::new();
+        mock_parser
+            .expect_parse()
+            .times(1)
+            .withf(|x| x == "wg0\tUGVlcktleTE=\t(none)\t(none)\t10.0.0.2/32\t1760400000\t1024\t2048\toff")
+            .returning(|_| Ok(WireguardState {
+                peers: vec![
+                    WireguardPeer {
+                        interface: "wg0".to_string(),
+                        public_key: "UGVlcktleTE=".to_string(),
+                        latest_handshake: Some(1760400000),
+                        transfer_rx: 1024,
+                        transfer_tx: 2048,
+                    },
+                ],
+            }));
+
+        let mut mock_registerer = MockRegisterer::new();
+        mock_registerer
+            .expect_update()
+            .times(1)
+            .withf(|x| *x == WireguardState {
+                peers: vec![
+                    WireguardPeer {
+                        interface: "wg0".to_string(),
+                        public_key: "UGVlcktleTE=".to_string(),
+                        latest_handshake: Some(1760400000),
+                        transfer_rx: 1024,
+                        transfer_tx: 2048,
+                    },
+                ],
+            })
+            .returning(|_| Box::pin(ok(())));
+
+        let wireguard = Wireguard::new(mock_executor, mock_parser, mock_registerer);
+        let result = wireguard.collect().await;
+
+        assert!(result.is_ok())
+    }
+}
@@ -4,7 +4,7 @@ use anyhow::Context;
 use tokio::process::Command;
 use tracing::Level;
 
-use crate::executor::Executor;
+use crate::{cache::ScrapeCache, executor::Executor};
 
 #[derive(Debug)]
 pub struct CommandExecutor<S, I> {
@@ -23,24 +23,26 @@ impl<S, I> CommandExecutor<S, I> {
 
 impl<S, I> Executor for CommandExecutor<S, I>
 where
-    S: AsRef<OsStr> + Debug + Clone + Copy + Send + Sync,
-    I: IntoIterator<Item = S> + Debug + Clone + Copy + Send + Sync,
+    S: AsRef<OsStr> + Debug + Send + Sync,
+    I: IntoIterator<Item = S> + Debug + Clone + Send + Sync,
 {
     #[tracing::instrument(skip_all, fields(command = ?self.command, args = ?self.args), ret(level = Level::DEBUG))]
     async fn execute(&self) -> anyhow::Result<String> {
-        let output = Command::new(self.command)
-            .args(self.args)
-            .output()
-            .await
-            .with_context(|| format!("command execution error: {self:?}"))?;
-        if !output.status.success() {
-            match output.status.code() {
-                Some(code) => anyhow::bail!(format!("process exited with status code {code}: {self:?}")),
-                None => anyhow::bail!(format!("process terminated by signal: {self:?}")),
+        ScrapeCache::execute(format!("{self:?}"), async {
+            let output = Command::new(&self.command)
+                .args(self.args.clone())
+                .output()
+                .await
+                .with_context(|| format!("command execution error: {self:?}"))?;
+            if !output.status.success() {
+                match output.status.code() {
+                    Some(code) => anyhow::bail!(format!("process exited with status code {code}: {self:?}")),
+                    None => anyhow::bail!(format!("process terminated by signal: {self:?}")),
+                }
             }
-        }
 
-        let result = String::from_utf8(output.stdout)?;
-        Ok(result)
+            let result = String::from_utf8(output.stdout)?;
+            Ok(result)
+        }).await
     }
 }
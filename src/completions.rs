@@ -0,0 +1,323 @@
+//! Completion scripts of the command line, generated from its definition so that they follow the options.
+//!
+//! The global options of the exporter are given before or after a subcommand, and the ones of a subcommand after it.
+
+use clap::{Arg, Command, ValueEnum};
+
+/// Shell to complete the command line in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+// Option or positional argument of a command as the scripts complete it
+struct Completion {
+    long: Option<String>,
+    short: Option<char>,
+    help: String,
+    takes_value: bool,
+    repeated: bool,
+    // Given to the subcommands as well
+    global: bool,
+    values: Vec<String>,
+}
+
+impl Shell {
+    /// Completion script of `command`, which is built so that it has its help and version flags.
+    pub fn generate(self, command: Command) -> String {
+        let mut command = command;
+        command.build();
+
+        match self {
+            Self::Bash => bash(&command),
+            Self::Zsh => zsh(&command),
+            Self::Fish => fish(&command),
+        }
+    }
+}
+
+impl Completion {
+    fn new(arg: &Arg) -> Self {
+        Self {
+            long: arg.get_long().map(ToString::to_string),
+            short: arg.get_short(),
+            help: arg.get_help().map(|help| help.to_string().lines().next().unwrap_or_default().to_string()).unwrap_or_default(),
+            takes_value: arg.get_action().takes_values(),
+            repeated: matches!(arg.get_action(), clap::ArgAction::Append | clap::ArgAction::Count),
+            global: arg.is_global_set(),
+            values: arg
+                .get_possible_values()
+                .iter()
+                .filter(|value| !value.is_hide_set())
+                .map(|value| value.get_name().to_string())
+                .collect(),
+        }
+    }
+
+    // Names the option is given by, e.g. `-p` and `--port`
+    fn flags(&self) -> Vec<String> {
+        self.short.map(|short| format!("-{short}")).into_iter().chain(self.long.as_ref().map(|long| format!("--{long}"))).collect()
+    }
+}
+
+fn options(command: &Command) -> Vec<Completion> {
+    command.get_arguments().filter(|arg| !arg.is_hide_set() && !arg.is_positional()).map(Completion::new).collect()
+}
+
+fn positionals(command: &Command) -> Vec<Completion> {
+    command.get_positionals().filter(|arg| !arg.is_hide_set()).map(Completion::new).collect()
+}
+
+fn subcommands(command: &Command) -> Vec<&Command> {
+    command.get_subcommands().filter(|subcommand| !subcommand.is_hide_set() && subcommand.get_name() != "help").collect()
+}
+
+fn about(command: &Command) -> String {
+    command.get_about().map(|about| about.to_string().lines().next().unwrap_or_default().to_string()).unwrap_or_default()
+}
+
+fn bash(command: &Command) -> String {
+    let name = command.get_name();
+    let function = format!("_{}", name.replace('-', "_"));
+    let subcommands = subcommands(command);
+    let names = subcommands.iter().map(|subcommand| subcommand.get_name()).collect::<Vec<_>>();
+
+    let mut values = Vec::new();
+    let mut words = Vec::new();
+    let targets = subcommands.iter().map(|subcommand| (subcommand.get_name(), *subcommand));
+    for (subcommand, target) in [("", command)].into_iter().chain(targets) {
+        let options = options(target);
+        // The global options once for every subcommand
+        for option in options.iter().filter(|option| option.takes_value && (subcommand.is_empty() || !option.global)) {
+            let command = if option.global { "*" } else { subcommand };
+            let patterns = option.flags().iter().map(|flag| format!("{command}:{flag}")).collect::<Vec<_>>().join("|");
+            let reply = match option.values.is_empty() {
+                true => "compgen -f -- \"$cur\"".to_string(),
+                false => format!("compgen -W \"{}\" -- \"$cur\"", option.values.join(" ")),
+            };
+            values.push(format!("        {patterns}) COMPREPLY=($({reply})); return ;;\n"));
+        }
+
+        let mut candidates = options.iter().flat_map(Completion::flags).collect::<Vec<_>>();
+        candidates.extend(positionals(target).into_iter().flat_map(|positional| positional.values));
+        if subcommand.is_empty() {
+            candidates.extend(names.iter().map(ToString::to_string));
+        }
+        let pattern = match subcommand {
+            "" => "\"\"",
+            subcommand => subcommand,
+        };
+        words.push(format!("        {pattern}) COMPREPLY=($(compgen -W \"{}\" -- \"$cur\")) ;;\n", candidates.join(" ")));
+    }
+
+    [
+        &format!("{function}() {{\n"),
+        "    local cur=\"${COMP_WORDS[COMP_CWORD]}\" prev=\"${COMP_WORDS[COMP_CWORD-1]}\" command=\"\" word\n",
+        "    for word in \"${COMP_WORDS[@]:1:COMP_CWORD-1}\"; do\n",
+        "        case \"$word\" in\n",
+        &format!("            {}) command=\"$word\" ;;\n", names.join("|")),
+        "        esac\n",
+        "    done\n",
+        "\n",
+        "    case \"$command:$prev\" in\n",
+        &values.concat(),
+        "    esac\n",
+        "    case \"$command\" in\n",
+        &words.concat(),
+        "    esac\n",
+        "}\n",
+        "\n",
+        &format!("complete -F {function} {name}\n"),
+    ].concat()
+}
+
+fn zsh(command: &Command) -> String {
+    let name = command.get_name();
+    let function = format!("_{}", name.replace('-', "_"));
+    let subcommands = subcommands(command);
+
+    let mut script = format!("#compdef {name}\n\n{function}() {{\n    local line state\n\n    _arguments -C \\\n");
+    for spec in zsh_specs(command) {
+        script.push_str(&format!("        {spec} \\\n"));
+    }
+    script.push_str("        '1: :->command' \\\n        '*:: :->args'\n\n    case $state in\n        command)\n");
+    let commands = subcommands
+        .iter()
+        .map(|subcommand| zsh_quote(&format!("{}:{}", subcommand.get_name(), about(subcommand).replace(':', "\\:"))))
+        .collect::<Vec<_>>();
+    script.push_str(&format!("            local -a commands=({})\n", commands.join(" ")));
+    script.push_str("            _describe command commands\n            ;;\n        args)\n            case $line[1] in\n");
+    for subcommand in subcommands {
+        let mut specs = zsh_specs(subcommand);
+        specs.extend(positionals(subcommand).iter().enumerate().map(|(index, positional)| {
+            zsh_quote(&format!("{}: :{}", index + 1, zsh_action(positional)))
+        }));
+        script.push_str(&format!("                {})\n", subcommand.get_name()));
+        match specs.is_empty() {
+            true => script.push_str("                    ;;\n"),
+            false => script.push_str(&format!("                    _arguments {}\n                    ;;\n", specs.join(" "))),
+        }
+    }
+    script.push_str(&format!("            esac\n            ;;\n    esac\n}}\n\n{function} \"$@\"\n"));
+
+    script
+}
+
+// Specs of `_arguments` of the options of `command`
+fn zsh_specs(command: &Command) -> Vec<String> {
+    options(command)
+        .into_iter()
+        .map(|option| {
+            let flags = option.flags();
+            let help = option.help.replace('\\', "\\\\").replace('[', "\\[").replace(']', "\\]");
+            let suffix = match option.takes_value {
+                true => format!("[{help}]: :{}", zsh_action(&option)),
+                false => format!("[{help}]"),
+            };
+            // Values follow the short name directly or as the next word, and the long name after `=` or as the next word
+            let names = flags
+                .iter()
+                .map(|flag| match (option.takes_value, flag.starts_with("--")) {
+                    (true, true) => format!("{flag}="),
+                    (true, false) => format!("{flag}+"),
+                    (false, _) => flag.clone(),
+                })
+                .collect::<Vec<_>>();
+            let exclusion = match option.repeated {
+                true => "*".to_string(),
+                false if flags.len() > 1 => format!("({})", flags.join(" ")),
+                false => String::new(),
+            };
+            match names.as_slice() {
+                [name] => zsh_quote(&format!("{exclusion}{name}{suffix}")),
+                names => format!("{}{{{}}}{}", zsh_quote(&exclusion), names.join(","), zsh_quote(&suffix)),
+            }
+        })
+        .collect()
+}
+
+fn zsh_action(completion: &Completion) -> String {
+    match completion.values.is_empty() {
+        true => "_files".to_string(),
+        false => format!("({})", completion.values.join(" ")),
+    }
+}
+
+fn zsh_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+fn fish(command: &Command) -> String {
+    let name = command.get_name();
+    let subcommands = subcommands(command);
+    let names = subcommands.iter().map(|subcommand| subcommand.get_name()).collect::<Vec<_>>();
+
+    let mut lines = Vec::new();
+    let top = "__fish_use_subcommand".to_string();
+    for option in options(command) {
+        let condition = if option.global { "true" } else { &top };
+        lines.push(fish_option(name, condition, &option));
+    }
+    for subcommand in &subcommands {
+        lines.push(format!(
+            "complete -c {name} -n {} -f -a {} -d {}",
+            fish_quote(&top),
+            subcommand.get_name(),
+            fish_quote(&about(subcommand)),
+        ));
+    }
+    for subcommand in &subcommands {
+        let condition = format!("__fish_seen_subcommand_from {}", subcommand.get_name());
+        lines.extend(options(subcommand).iter().filter(|option| !option.global).map(|option| fish_option(name, &condition, option)));
+        for positional in positionals(subcommand).iter().filter(|positional| !positional.values.is_empty()) {
+            // Not once the value is given
+            let condition = format!("{condition}; and not __fish_seen_subcommand_from {}", positional.values.join(" "));
+            lines.push(format!("complete -c {name} -n {} -f -a {}", fish_quote(&condition), fish_quote(&positional.values.join(" "))));
+        }
+    }
+    // Nothing but the subcommands as arguments of the exporter
+    lines.push(format!("complete -c {name} -n {} -f", fish_quote(&format!("not __fish_seen_subcommand_from {}", names.join(" ")))));
+
+    lines.join("\n") + "\n"
+}
+
+fn fish_option(name: &str, condition: &str, option: &Completion) -> String {
+    let mut line = format!("complete -c {name} -n {}", fish_quote(condition));
+    if let Some(long) = &option.long {
+        line.push_str(&format!(" -l {long}"));
+    }
+    if let Some(short) = option.short {
+        line.push_str(&format!(" -s {short}"));
+    }
+    match (option.takes_value, option.values.is_empty()) {
+        (true, true) => line.push_str(" -r -F"),
+        (true, false) => line.push_str(&format!(" -r -f -a {}", fish_quote(&option.values.join(" ")))),
+        (false, _) => {},
+    }
+    if !option.help.is_empty() {
+        line.push_str(&format!(" -d {}", fish_quote(&option.help)));
+    }
+
+    line
+}
+
+fn fish_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::{Arg, ArgAction, Command};
+
+    use crate::completions::Shell;
+
+    fn command() -> Command {
+        Command::new("raspi-exporter")
+            .arg(Arg::new("port").short('p').long("port").help("Port to listen on"))
+            .arg(Arg::new("log").long("log").value_parser(["plain", "json"]).global(true))
+            .arg(Arg::new("mdns").long("mdns").action(ArgAction::SetTrue).help("Advertises the exporter's [service]"))
+            .subcommand(Command::new("check").about("Validates the options"))
+            .subcommand(
+                Command::new("completions").about("Prints a completion script").arg(Arg::new("shell").value_parser(["bash", "zsh"])),
+            )
+    }
+
+    #[test]
+    fn bash() {
+        let script = Shell::Bash.generate(command());
+
+        assert!(script.contains("            check|completions) command=\"$word\" ;;\n"));
+        assert!(script.contains("        :-p|:--port) COMPREPLY=($(compgen -f -- \"$cur\")); return ;;\n"));
+        assert!(script.contains("        *:--log) COMPREPLY=($(compgen -W \"plain json\" -- \"$cur\")); return ;;\n"));
+        assert_eq!(script.matches(":--log)").count(), 1);
+        assert!(script.contains("        completions) COMPREPLY=($(compgen -W \"--log -h --help bash zsh\" -- \"$cur\")) ;;\n"));
+        assert!(script.ends_with("complete -F _raspi_exporter raspi-exporter\n"));
+    }
+
+    #[test]
+    fn zsh() {
+        let script = Shell::Zsh.generate(command());
+
+        assert!(script.starts_with("#compdef raspi-exporter\n"));
+        assert!(script.contains("        '(-p --port)'{-p+,--port=}'[Port to listen on]: :_files' \\\n"));
+        assert!(script.contains("        '--mdns[Advertises the exporter'\\''s \\[service\\]]' \\\n"));
+        // With the global options after the subcommand
+        let completions = script.lines().find(|line| line.ends_with("'1: :(bash zsh)'")).unwrap();
+        assert!(completions.contains(" '--log=[]: :(plain json)' "));
+    }
+
+    #[test]
+    fn fish() {
+        let script = Shell::Fish.generate(command());
+
+        assert!(script.contains("complete -c raspi-exporter -n 'true' -l log -r -f -a 'plain json'\n"));
+        assert_eq!(script.matches("-l log").count(), 1);
+        assert!(script.contains("complete -c raspi-exporter -n '__fish_use_subcommand' -l mdns -d 'Advertises the exporter\\'s [service]'\n"));
+        assert!(script.contains("complete -c raspi-exporter -n '__fish_use_subcommand' -f -a check -d 'Validates the options'\n"));
+        assert!(script.contains(
+            "complete -c raspi-exporter -n '__fish_seen_subcommand_from completions; and not __fish_seen_subcommand_from bash zsh' -f -a 'bash zsh'\n",
+        ));
+    }
+}
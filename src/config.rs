@@ -0,0 +1,386 @@
+pub mod toml;
+
+use std::{
+    collections::HashMap,
+    ffi::{OsStr, OsString},
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::Context;
+use clap::{Command, ValueEnum};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer};
+
+use crate::{cli::Metric, config::toml::Value, relabel::Rule, threshold::Threshold};
+
+/// Prefix of the environment variables of the options.
+pub const ENV_PREFIX: &str = "RASPI_EXPORTER_";
+
+/// Options read from a TOML file, keyed by the long names of the command line options.
+///
+/// ```toml
+/// listen = ["192.168.1.1", "127.0.0.1"]
+/// port = 9100
+/// enable-metrics = ["throttled", "filesystem"]
+/// disable-http2 = true
+///
+/// [web]
+/// telemetry-path = "/raspi/metrics"
+///
+/// [[relabel]]
+/// action = "drop"
+/// metric = "raspi_vl805"
+///
+/// [collectors.temperature]
+/// path = "/sys/class/thermal/thermal_zone1/temp"
+///
+/// [collectors.filesystem]
+/// mount-points = ["/", "/boot/firmware"]
+///
+/// [[thresholds]]
+/// name = "soc_hot"
+/// metric = "raspi_soc_temperature_celsius"
+/// above = 75
+/// ```
+///
+/// Options with dots in their names such as `web.telemetry-path` are keys of tables, or quoted keys. `relabel` is an
+/// array of [`Rule`]s, `collectors` is a table of [`CollectorConfig`]s by metric and `thresholds` is an array of
+/// [`Threshold`]s, rather than options.
+///
+/// The options can also be read from environment variables with [`Config::from_env`], which only have options.
+#[derive(Debug, Default)]
+pub struct Config {
+    options: Vec<(String, Vec<OsString>)>,
+    relabel: Vec<Rule>,
+    collectors: HashMap<Metric, CollectorConfig>,
+    thresholds: Vec<Threshold>,
+}
+
+/// Settings of the collectors of a metric replacing their defaults, each of which applies to some of the metrics only.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct CollectorConfig {
+    /// Command run with its arguments instead of the default one, e.g. `[vcgencmd, get_throttled]` of throttled.
+    pub command: Option<Vec<String>>,
+    /// File or directory read instead of the default one, e.g. another thermal zone of temperature.
+    pub path: Option<PathBuf>,
+    /// Mount points whose filesystems filesystem collects, all the local ones when empty.
+    pub mount_points: Vec<PathBuf>,
+    /// Clocks that clock-tree collects, all of them when empty.
+    pub clocks: Vec<String>,
+    /// Interval of the background sampling of throttled-history and temperature instead of `--sampling-interval`.
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub sampling_interval: Option<Duration>,
+}
+
+impl CollectorConfig {
+    /// Command to run, `command` with `args` unless another one is configured.
+    pub fn command(&self, command: &str, args: &[&str]) -> (String, Vec<String>) {
+        match self.command.as_deref() {
+            Some([command, args @ ..]) => (command.clone(), args.to_vec()),
+            _ => (command.to_string(), args.iter().map(ToString::to_string).collect()),
+        }
+    }
+
+    /// Path to read, `path` unless another one is configured.
+    pub fn path(&self, path: impl AsRef<Path>) -> PathBuf {
+        self.path.clone().unwrap_or_else(|| path.as_ref().to_path_buf())
+    }
+}
+
+fn deserialize_duration<'de, D>(deserializer: D) -> Result<Option<Duration>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let Some(duration) = Option::<String>::deserialize(deserializer)? else {
+        return Ok(None);
+    };
+
+    humantime::parse_duration(&duration).map(Some).map_err(serde::de::Error::custom)
+}
+
+impl Config {
+    pub fn from_file(path: impl AsRef<Path>, command: &Command) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path).with_context(|| format!("config read error: {path:?}"))?;
+
+        Self::parse(&content, command).with_context(|| format!("config parse error: {path:?}"))
+    }
+
+    pub fn parse(content: &str, command: &Command) -> anyhow::Result<Self> {
+        let mut options = Vec::new();
+        let mut relabel = Vec::new();
+        let mut collectors = HashMap::new();
+        let mut thresholds = Vec::<Threshold>::new();
+        for (key, value) in toml::parse(content)? {
+            if key == "relabel" {
+                relabel = from_value(value).context("invalid relabel rules")?;
+                continue;
+            }
+            if key == "collectors" {
+                collectors = from_value::<HashMap<String, _>>(value)
+                    .context("invalid collector configs")?
+                    .into_iter()
+                    .map(|(name, config)| match Metric::from_str(&name, false) {
+                        Ok(metric) => Ok((metric, config)),
+                        Err(_) => Err(anyhow::anyhow!("unknown collector: {name}")),
+                    })
+                    .collect::<anyhow::Result<_>>()?;
+                continue;
+            }
+            if key == "thresholds" {
+                thresholds = from_value(value).context("invalid thresholds")?;
+                if let Some(threshold) = thresholds.iter().find(|threshold| threshold.above.is_none() && threshold.below.is_none()) {
+                    anyhow::bail!("threshold without above or below: {}", threshold.name);
+                }
+                continue;
+            }
+
+            for (key, value) in flatten(key, value) {
+                let arg = command
+                    .get_arguments()
+                    .find(|arg| arg.get_long() == Some(&key) || arg.get_all_aliases().is_some_and(|aliases| aliases.contains(&key.as_str())))
+                    .filter(|arg| arg.get_id() != "config")
+                    .with_context(|| format!("unknown option: {key}"))?;
+                let long = arg.get_long().context("option without a long name")?;
+
+                let values = match value {
+                    Value::Array(values) => values,
+                    value => vec![value],
+                };
+                let args = values
+                    .into_iter()
+                    .filter_map(|value| match value {
+                        Value::Boolean(false) => None,
+                        Value::Boolean(true) if !arg.get_action().takes_values() => Some(Ok(format!("--{long}"))),
+                        Value::Boolean(value) => Some(Ok(format!("--{long}={value}"))),
+                        Value::Integer(value) => Some(Ok(format!("--{long}={value}"))),
+                        Value::Float(value) => Some(Ok(format!("--{long}={value}"))),
+                        Value::String(value) | Value::Datetime(value) => Some(Ok(format!("--{long}={value}"))),
+                        _ => Some(Err(anyhow::anyhow!("invalid value of {key}"))),
+                    })
+                    .map(|arg| arg.map(OsString::from))
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                options.push((arg.get_id().to_string(), args));
+            }
+        }
+
+        Ok(Self {
+            options,
+            relabel,
+            collectors,
+            thresholds,
+        })
+    }
+
+    /// Reads the options from the variables named [`ENV_PREFIX`] followed by their long names in upper case with
+    /// underscores, e.g. `RASPI_EXPORTER_WEB_TELEMETRY_PATH=/raspi/metrics`, ignoring the others.
+    ///
+    /// The value of a flag is true or 1 to set it, and false, 0 or empty otherwise.
+    pub fn from_env(vars: impl IntoIterator<Item = (OsString, OsString)>, command: &Command) -> anyhow::Result<Self> {
+        let vars = vars.into_iter().collect::<HashMap<_, _>>();
+
+        let mut options = Vec::new();
+        for arg in command.get_arguments() {
+            let Some(long) = arg.get_long() else {
+                continue;
+            };
+            let name = format!("{ENV_PREFIX}{}", long.replace(['-', '.'], "_").to_uppercase());
+            let Some(value) = vars.get(OsStr::new(&name)) else {
+                continue;
+            };
+            let value = value.to_str().with_context(|| format!("{name} is not valid UTF-8"))?;
+
+            let args = match value {
+                value if arg.get_action().takes_values() => vec![OsString::from(format!("--{long}={value}"))],
+                "true" | "1" => vec![OsString::from(format!("--{long}"))],
+                "false" | "0" | "" => Vec::new(),
+                _ => anyhow::bail!("invalid value of {name}: {value}"),
+            };
+            options.push((arg.get_id().to_string(), args));
+        }
+
+        Ok(Self {
+            options,
+            ..Default::default()
+        })
+    }
+
+    /// Returns whether the option of `id` is given, even as a flag turned off.
+    pub fn contains(&self, id: &str) -> bool {
+        self.options.iter().any(|(option, _)| option == id)
+    }
+
+    /// Returns the options as command line arguments, except ones for which `given` returns true.
+    pub fn args(&self, given: impl Fn(&str) -> bool) -> Vec<OsString> {
+        self.options
+            .iter()
+            .filter(|(id, _)| !given(id))
+            .flat_map(|(_, args)| args.iter().cloned())
+            .collect()
+    }
+
+    pub fn relabel(&self) -> &[Rule] {
+        &self.relabel
+    }
+
+    pub fn collectors(&self) -> &HashMap<Metric, CollectorConfig> {
+        &self.collectors
+    }
+
+    pub fn thresholds(&self) -> &[Threshold] {
+        &self.thresholds
+    }
+}
+
+// Options of `value` named `key`, joining the keys of tables with dots, e.g. telemetry-path of a web table into
+// web.telemetry-path
+fn flatten(key: String, value: Value) -> Vec<(String, Value)> {
+    match value {
+        Value::Table(table) => table.into_iter().flat_map(|(name, value)| flatten(format!("{key}.{name}"), value)).collect(),
+        value => vec![(key, value)],
+    }
+}
+
+fn from_value<T>(value: Value) -> anyhow::Result<T>
+where
+    T: DeserializeOwned,
+{
+    Ok(serde_json::from_value(value.into())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::PathBuf, time::Duration};
+
+    use clap::{Arg, ArgAction, Command};
+
+    use crate::{cli::Metric, config::{CollectorConfig, Config}, relabel::Rule};
+
+    fn command() -> Command {
+        Command::new("test")
+            .arg(Arg::new("address").long("address").visible_alias("listen").action(ArgAction::Append))
+            .arg(Arg::new("port").long("port"))
+            .arg(Arg::new("ipv6_only").long("ipv6-only").action(ArgAction::SetTrue))
+            .arg(Arg::new("config").long("config"))
+            .arg(Arg::new("telemetry_path").long("web.telemetry-path"))
+    }
+
+    #[test]
+    fn parse() {
+        let content = [
+            "listen = [\"192.168.1.1\", \"127.0.0.1\"]",
+            "port = 9100",
+            "ipv6-only = true",
+            "[web]",
+            "telemetry-path = \"/raspi/metrics\"",
+        ].join("\n");
+        let config = Config::parse(&content, &command()).unwrap();
+
+        assert_eq!(
+            config.args(|_| false),
+            ["--address=192.168.1.1", "--address=127.0.0.1", "--port=9100", "--ipv6-only", "--web.telemetry-path=/raspi/metrics"],
+        );
+        // Given on the command line
+        assert_eq!(
+            config.args(|id| id == "port"),
+            ["--address=192.168.1.1", "--address=127.0.0.1", "--ipv6-only", "--web.telemetry-path=/raspi/metrics"],
+        );
+    }
+
+    #[test]
+    fn from_env() {
+        let vars = [
+            ("RASPI_EXPORTER_LISTEN", "127.0.0.1"),
+            ("RASPI_EXPORTER_PORT", "9100"),
+            ("RASPI_EXPORTER_IPV6_ONLY", "false"),
+            ("RASPI_EXPORTER_REVISION", "0123456789ab"),
+            ("PORT", "9200"),
+        ].map(|(name, value)| (name.into(), value.into()));
+        let config = Config::from_env(vars, &command()).unwrap();
+
+        assert_eq!(config.args(|_| false), ["--port=9100"]);
+        // Turned off rather than left to the config file
+        assert!(config.contains("ipv6_only"));
+        assert!(!config.contains("address"));
+        assert!(Config::from_env([("RASPI_EXPORTER_IPV6_ONLY".into(), "yes".into())], &command()).is_err());
+    }
+
+    #[test]
+    fn parse_relabel() {
+        let content = [
+            "port = 9100",
+            "[[relabel]]",
+            "action = \"drop\"",
+            "metric = \"raspi_vl805\"",
+        ].join("\n");
+        let config = Config::parse(&content, &command()).unwrap();
+
+        assert_eq!(config.args(|_| false), ["--port=9100"]);
+        assert_eq!(config.relabel(), [Rule::Drop { metric: "raspi_vl805".to_string(), labels: Default::default() }]);
+        assert!(Config::parse("relabel = [{ action = \"keep\" }]", &command()).is_err());
+    }
+
+    #[test]
+    fn parse_collectors() {
+        let content = [
+            "[collectors.clock-tree]",
+            "clocks = [\"arm\", \"core\"]",
+            "[collectors.temperature]",
+            "path = \"/sys/class/thermal/thermal_zone1/temp\"",
+            "sampling-interval = \"500ms\"",
+        ].join("\n");
+        let config = Config::parse(&content, &command()).unwrap();
+
+        assert_eq!(config.collectors()[&Metric::ClockTree].clocks, ["arm", "core"]);
+        assert_eq!(
+            config.collectors()[&Metric::Temperature],
+            CollectorConfig {
+                path: Some(PathBuf::from("/sys/class/thermal/thermal_zone1/temp")),
+                sampling_interval: Some(Duration::from_millis(500)),
+                ..Default::default()
+            },
+        );
+        assert!(Config::parse("[collectors.fan]", &command()).is_err());
+        assert!(Config::parse("[collectors.temperature]\ndevice = \"/dev/null\"", &command()).is_err());
+        assert!(Config::parse("[collectors.temperature]\nsampling-interval = \"soon\"", &command()).is_err());
+        assert!(Config::parse("[collectors.temperature]\nsampling_interval = \"500ms\"", &command()).is_err());
+    }
+
+    #[test]
+    fn parse_thresholds() {
+        let content = [
+            "[[thresholds]]",
+            "name = \"soc_hot\"",
+            "metric = \"raspi_soc_temperature_celsius\"",
+            "above = 75",
+        ].join("\n");
+        let config = Config::parse(&content, &command()).unwrap();
+
+        assert_eq!(config.thresholds()[0].name, "soc_hot");
+        assert_eq!(config.thresholds()[0].above, Some(75.0));
+        assert!(Config::parse("[[thresholds]]\nname = \"soc_hot\"\nmetric = \"raspi_soc_temperature_celsius\"", &command()).is_err());
+    }
+
+    #[test]
+    fn collector_command() {
+        let config = CollectorConfig {
+            command: Some(vec!["/opt/vc/bin/vcgencmd".to_string(), "get_throttled".to_string()]),
+            ..Default::default()
+        };
+
+        assert_eq!(config.command("vcgencmd", &["get_throttled"]), ("/opt/vc/bin/vcgencmd".to_string(), vec!["get_throttled".to_string()]));
+        assert_eq!(CollectorConfig::default().command("wg", &["show"]), ("wg".to_string(), vec!["show".to_string()]));
+        assert_eq!(CollectorConfig::default().path("/proc/net/snmp"), PathBuf::from("/proc/net/snmp"));
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert!(Config::parse("unknown = 1", &command()).is_err());
+        assert!(Config::parse("config = \"other.toml\"", &command()).is_err());
+        assert!(Config::parse("port = { a = 1 }", &command()).is_err());
+        assert!(Config::parse("port = [[9100]]", &command()).is_err());
+        assert!(Config::parse("port: 9100", &command()).is_err());
+    }
+}
@@ -0,0 +1,452 @@
+//! TOML documents of the config file, parsed into values keeping the order of the keys.
+
+use anyhow::Context;
+
+/// Value of a TOML document, with date-times kept as their text.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    Datetime(String),
+    Array(Vec<Value>),
+    Table(Table),
+}
+
+/// Keys and values of a table in the order they appear.
+pub type Table = Vec<(String, Value)>;
+
+struct Parser<'a> {
+    content: &'a str,
+    position: usize,
+}
+
+/// Parses `content` into its root table.
+pub fn parse(content: &str) -> anyhow::Result<Table> {
+    let mut parser = Parser {
+        content,
+        position: 0,
+    };
+    parser.document().with_context(|| format!("line {}", parser.line()))
+}
+
+impl From<Value> for serde_json::Value {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::String(value) | Value::Datetime(value) => Self::String(value),
+            Value::Integer(value) => Self::from(value),
+            Value::Float(value) => serde_json::Number::from_f64(value).map(Self::Number).unwrap_or(Self::Null),
+            Value::Boolean(value) => Self::Bool(value),
+            Value::Array(values) => Self::Array(values.into_iter().map(Into::into).collect()),
+            Value::Table(table) => Self::Object(table.into_iter().map(|(key, value)| (key, value.into())).collect()),
+        }
+    }
+}
+
+impl<'a> Parser<'a> {
+    fn document(&mut self) -> anyhow::Result<Table> {
+        let mut root = Table::new();
+        // Table that key/value pairs go to, the last one of an array of tables
+        let mut current = Vec::new();
+        let mut headers = Vec::<Vec<String>>::new();
+        loop {
+            self.skip_blank();
+            if self.rest().is_empty() {
+                break;
+            }
+
+            if self.eat("[[") {
+                let path = self.key()?;
+                self.expect("]]")?;
+                let (last, parents) = path.split_last().context("empty table name")?;
+                let parent = table_mut(&mut root, parents)?;
+                match get_mut(parent, last) {
+                    Some(Value::Array(tables)) if tables.iter().all(|value| matches!(value, Value::Table(_))) => tables.push(Value::Table(Table::new())),
+                    Some(_) => anyhow::bail!("{} is not an array of tables", path.join(".")),
+                    None => parent.push((last.clone(), Value::Array(vec![Value::Table(Table::new())]))),
+                }
+                current = path;
+            } else if self.eat("[") {
+                let path = self.key()?;
+                self.expect("]")?;
+                if headers.contains(&path) {
+                    anyhow::bail!("table {} is defined twice", path.join("."));
+                }
+                table_mut(&mut root, &path)?;
+                headers.push(path.clone());
+                current = path;
+            } else {
+                let path = self.key()?;
+                self.expect("=")?;
+                let value = self.value()?;
+                insert(table_mut(&mut root, &current)?, &path, value)?;
+            }
+            self.end_of_line()?;
+        }
+
+        Ok(root)
+    }
+
+    // Dotted key, each part of which is bare or quoted
+    fn key(&mut self) -> anyhow::Result<Vec<String>> {
+        let mut path = Vec::new();
+        loop {
+            self.skip_spaces();
+            let part = match self.peek() {
+                Some('"') => self.basic_string()?,
+                Some('\'') => self.literal_string()?,
+                _ => {
+                    let part = self.take_while(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+                    if part.is_empty() {
+                        anyhow::bail!("expected a key");
+                    }
+                    part.to_string()
+                },
+            };
+            path.push(part);
+            self.skip_spaces();
+            if !self.eat(".") {
+                return Ok(path);
+            }
+        }
+    }
+
+    fn value(&mut self) -> anyhow::Result<Value> {
+        self.skip_spaces();
+        let value = match self.peek().context("expected a value")? {
+            '"' if self.rest().starts_with("\"\"\"") => Value::String(self.multiline_string('"')?),
+            '\'' if self.rest().starts_with("'''") => Value::String(self.multiline_string('\'')?),
+            '"' => Value::String(self.basic_string()?),
+            '\'' => Value::String(self.literal_string()?),
+            '[' => self.array()?,
+            '{' => self.inline_table()?,
+            _ => self.scalar()?,
+        };
+
+        Ok(value)
+    }
+
+    fn array(&mut self) -> anyhow::Result<Value> {
+        self.expect("[")?;
+        let mut values = Vec::new();
+        loop {
+            self.skip_blank();
+            if self.eat("]") {
+                return Ok(Value::Array(values));
+            }
+            values.push(self.value()?);
+            self.skip_blank();
+            if !self.eat(",") {
+                self.expect("]")?;
+                return Ok(Value::Array(values));
+            }
+        }
+    }
+
+    fn inline_table(&mut self) -> anyhow::Result<Value> {
+        self.expect("{")?;
+        let mut table = Table::new();
+        self.skip_spaces();
+        if self.eat("}") {
+            return Ok(Value::Table(table));
+        }
+        loop {
+            let path = self.key()?;
+            self.expect("=")?;
+            let value = self.value()?;
+            insert(&mut table, &path, value)?;
+            self.skip_spaces();
+            if !self.eat(",") {
+                self.expect("}")?;
+                return Ok(Value::Table(table));
+            }
+        }
+    }
+
+    // Boolean, number or date-time
+    fn scalar(&mut self) -> anyhow::Result<Value> {
+        let start = self.position;
+        self.take_while(|c| c.is_ascii_alphanumeric() || "+-_.:".contains(c));
+        // Date and time separated by a space rather than T
+        let date = &self.content[start..self.position];
+        if date.len() == 10 && date.as_bytes()[4] == b'-' && self.rest().starts_with(' ') && self.rest()[1..].starts_with(|c: char| c.is_ascii_digit()) {
+            self.position += 1;
+            self.take_while(|c| c.is_ascii_alphanumeric() || "+-_.:".contains(c));
+        }
+        let token = &self.content[start..self.position];
+
+        let value = match token {
+            "true" => Value::Boolean(true),
+            "false" => Value::Boolean(false),
+            "inf" | "+inf" => Value::Float(f64::INFINITY),
+            "-inf" => Value::Float(f64::NEG_INFINITY),
+            "nan" | "+nan" | "-nan" => Value::Float(f64::NAN),
+            _ if token.contains(':') || token.len() >= 10 && token.as_bytes()[4] == b'-' && token[..4].bytes().all(|b| b.is_ascii_digit()) => {
+                Value::Datetime(token.to_string())
+            },
+            _ => {
+                if token.starts_with('_') || token.ends_with('_') || token.contains("__") {
+                    anyhow::bail!("invalid number: {token}");
+                }
+                let number = token.replace('_', "");
+                let radix = [("0x", 16), ("0o", 8), ("0b", 2)].into_iter().find_map(|(prefix, radix)| Some((number.strip_prefix(prefix)?, radix)));
+                match radix {
+                    Some((digits, radix)) => Value::Integer(i64::from_str_radix(digits, radix).with_context(|| format!("invalid number: {token}"))?),
+                    None if number.contains(['.', 'e', 'E']) => Value::Float(number.parse().with_context(|| format!("invalid value: {token}"))?),
+                    None => Value::Integer(number.parse().with_context(|| format!("invalid value: {token}"))?),
+                }
+            },
+        };
+
+        Ok(value)
+    }
+
+    fn basic_string(&mut self) -> anyhow::Result<String> {
+        self.expect("\"")?;
+        let mut string = String::new();
+        loop {
+            match self.next().context("unterminated string")? {
+                '"' => return Ok(string),
+                '\\' => string.push(self.escape()?),
+                '\n' => anyhow::bail!("unterminated string"),
+                c => string.push(c),
+            }
+        }
+    }
+
+    fn literal_string(&mut self) -> anyhow::Result<String> {
+        self.expect("'")?;
+        let string = self.take_while(|c| c != '\'' && c != '\n').to_string();
+        self.expect("'").context("unterminated string")?;
+
+        Ok(string)
+    }
+
+    // Basic one with `"""` or literal one with `'''`, without the newline right after the opening quotes
+    fn multiline_string(&mut self, quote: char) -> anyhow::Result<String> {
+        self.position += 3;
+        self.eat("\r");
+        self.eat("\n");
+        let mut string = String::new();
+        loop {
+            let quotes = self.take_while(|c| c == quote).len();
+            if quotes >= 3 {
+                // Up to two quotes can precede the closing ones
+                string.extend(std::iter::repeat_n(quote, (quotes - 3).min(2)));
+                if quotes > 5 {
+                    anyhow::bail!("too many quotes closing a string");
+                }
+                return Ok(string);
+            }
+            string.extend(std::iter::repeat_n(quote, quotes));
+
+            match self.next().context("unterminated string")? {
+                '\\' if quote == '"' => {
+                    // Backslash ending a line trims the whitespace up to the next text
+                    if self.rest().trim_start_matches([' ', '\t']).starts_with(['\n', '\r']) {
+                        self.take_while(char::is_whitespace);
+                    } else {
+                        string.push(self.escape()?);
+                    }
+                },
+                c => string.push(c),
+            }
+        }
+    }
+
+    fn escape(&mut self) -> anyhow::Result<char> {
+        let c = match self.next().context("unterminated string")? {
+            'b' => '\u{8}',
+            't' => '\t',
+            'n' => '\n',
+            'f' => '\u{c}',
+            'r' => '\r',
+            '"' => '"',
+            '\\' => '\\',
+            u @ ('u' | 'U') => {
+                let length = if u == 'u' { 4 } else { 8 };
+                let hex = self.rest().get(..length).context("invalid unicode escape")?;
+                self.position += length;
+                u32::from_str_radix(hex, 16).ok().and_then(char::from_u32).with_context(|| format!("invalid unicode escape: {hex}"))?
+            },
+            c => anyhow::bail!("invalid escape: \\{c}"),
+        };
+
+        Ok(c)
+    }
+
+    fn end_of_line(&mut self) -> anyhow::Result<()> {
+        self.skip_spaces();
+        if self.peek() == Some('#') {
+            self.take_while(|c| c != '\n');
+        }
+        self.eat("\r");
+        if !self.eat("\n") && !self.rest().is_empty() {
+            anyhow::bail!("expected a newline");
+        }
+
+        Ok(())
+    }
+
+    // Whitespace, newlines and comments
+    fn skip_blank(&mut self) {
+        loop {
+            self.take_while(char::is_whitespace);
+            if self.peek() != Some('#') {
+                return;
+            }
+            self.take_while(|c| c != '\n');
+        }
+    }
+
+    fn skip_spaces(&mut self) {
+        self.take_while(|c| c == ' ' || c == '\t');
+    }
+
+    fn expect(&mut self, token: &str) -> anyhow::Result<()> {
+        self.skip_spaces();
+        if !self.eat(token) {
+            anyhow::bail!("expected {token}");
+        }
+
+        Ok(())
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        let eaten = self.rest().starts_with(token);
+        if eaten {
+            self.position += token.len();
+        }
+        eaten
+    }
+
+    fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> &'a str {
+        let start = self.position;
+        let length = self.rest().find(|c| !predicate(c)).unwrap_or(self.rest().len());
+        self.position += length;
+        &self.content[start..self.position]
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += c.len_utf8();
+        Some(c)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.content[self.position..]
+    }
+
+    fn line(&self) -> usize {
+        self.content[..self.position].matches('\n').count() + 1
+    }
+}
+
+fn get_mut<'a>(table: &'a mut Table, key: &str) -> Option<&'a mut Value> {
+    table.iter_mut().find(|(name, _)| name == key).map(|(_, value)| value)
+}
+
+// Table at `path`, created with the ones leading to it when missing, or the last one of an array of tables on the path
+fn table_mut<'a>(table: &'a mut Table, path: &[String]) -> anyhow::Result<&'a mut Table> {
+    let Some((first, rest)) = path.split_first() else {
+        return Ok(table);
+    };
+
+    if get_mut(table, first).is_none() {
+        table.push((first.clone(), Value::Table(Table::new())));
+    }
+    let child = match get_mut(table, first) {
+        Some(Value::Table(child)) => child,
+        Some(Value::Array(values)) => match values.last_mut() {
+            Some(Value::Table(child)) => child,
+            _ => anyhow::bail!("{first} is not a table"),
+        },
+        _ => anyhow::bail!("{first} is not a table"),
+    };
+
+    table_mut(child, rest)
+}
+
+fn insert(table: &mut Table, path: &[String], value: Value) -> anyhow::Result<()> {
+    let (last, parents) = path.split_last().context("empty key")?;
+    let table = table_mut(table, parents)?;
+    if get_mut(table, last).is_some() {
+        anyhow::bail!("{} is defined twice", path.join("."));
+    }
+    table.push((last.clone(), value));
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::toml::{parse, Value};
+
+    #[test]
+    fn parse_document() {
+        let content = r#"
+# Options
+port = 9100
+listen = ["192.168.1.1", '127.0.0.1'] # trailing comment
+"web.telemetry-path" = "/raspi/A\tmetrics"
+ratio = 0.1
+disable-http2 = true
+
+[collectors.temperature]
+sampling-interval = "500ms"
+device = { path = """
+/dev/null""" }
+
+[[thresholds]]
+name = "soc_hot"
+above = 7_5
+
+[[thresholds]]
+name = "soc_cold"
+below = -1e1
+"#;
+        let table = parse(content).unwrap();
+        let string = |value: &str| Value::String(value.to_string());
+
+        assert_eq!(table[0], ("port".to_string(), Value::Integer(9100)));
+        assert_eq!(table[1].1, Value::Array(vec![string("192.168.1.1"), string("127.0.0.1")]));
+        assert_eq!(table[2], ("web.telemetry-path".to_string(), string("/raspi/A\tmetrics")));
+        assert_eq!(table[3].1, Value::Float(0.1));
+        assert_eq!(table[4].1, Value::Boolean(true));
+        assert_eq!(
+            table[5],
+            (
+                "collectors".to_string(),
+                Value::Table(vec![(
+                    "temperature".to_string(),
+                    Value::Table(vec![
+                        ("sampling-interval".to_string(), string("500ms")),
+                        ("device".to_string(), Value::Table(vec![("path".to_string(), string("/dev/null"))])),
+                    ]),
+                )]),
+            ),
+        );
+        assert_eq!(
+            table[6].1,
+            Value::Array(vec![
+                Value::Table(vec![("name".to_string(), string("soc_hot")), ("above".to_string(), Value::Integer(75))]),
+                Value::Table(vec![("name".to_string(), string("soc_cold")), ("below".to_string(), Value::Float(-10.0))]),
+            ]),
+        );
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert_eq!(parse("port = 9100\nport = 9200").unwrap_err().to_string(), "line 2");
+        assert!(parse("[a]\n[a]").is_err());
+        assert!(parse("port = ").is_err());
+        assert!(parse("port = 9100 9200").is_err());
+        assert!(parse("path = \"/dev").is_err());
+        assert!(parse("- port").is_err());
+    }
+}
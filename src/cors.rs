@@ -0,0 +1,41 @@
+/// Origins of browser-based dashboards allowed to fetch from the exporter.
+#[derive(Clone, Debug)]
+pub struct Cors {
+    origins: Vec<String>,
+}
+
+impl Cors {
+    /// `*` in `origins` allows any origin.
+    pub fn new(origins: Vec<String>) -> Self {
+        Self {
+            origins,
+        }
+    }
+
+    /// Returns the value of `Access-Control-Allow-Origin` for a request from `origin`, if it is allowed.
+    pub fn allow_origin<'a>(&self, origin: &'a str) -> Option<&'a str> {
+        if self.origins.iter().any(|v| v == "*") {
+            return Some("*");
+        }
+
+        // Compared without a trailing slash, which browsers never send
+        self.origins.iter().any(|v| v.trim_end_matches('/') == origin).then_some(origin)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cors::Cors;
+
+    #[test]
+    fn allow_origin() {
+        let cors = Cors::new(vec!["http://dashboard.local:8080/".to_string()]);
+
+        assert_eq!(cors.allow_origin("http://dashboard.local:8080"), Some("http://dashboard.local:8080"));
+        assert_eq!(cors.allow_origin("http://evil.example"), None);
+
+        let cors = Cors::new(vec!["*".to_string()]);
+
+        assert_eq!(cors.allow_origin("http://evil.example"), Some("*"));
+    }
+}
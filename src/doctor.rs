@@ -0,0 +1,116 @@
+//! Probes of the board capabilities that collectors depend on, for the `doctor` subcommand.
+
+use std::{
+    fs::{self, OpenOptions},
+    io::ErrorKind,
+    path::Path,
+};
+
+use crate::backend;
+
+/// Capability of the board, found with what or missing with why.
+#[derive(Debug)]
+pub struct Probe {
+    pub name: &'static str,
+    pub result: Result<String, String>,
+}
+
+pub fn probe() -> Vec<Probe> {
+    vec![
+        Probe {
+            name: "vcgencmd",
+            result: find_command("vcgencmd").ok_or_else(|| "not found in PATH, install libraspberrypi-bin or raspi-utils".to_string()),
+        },
+        Probe {
+            name: "/dev/vchiq",
+            result: vchiq(Path::new("/dev/vchiq")),
+        },
+        Probe {
+            name: "thermal zones",
+            result: list(Path::new("/sys/class/thermal"), |name| name.starts_with("thermal_zone"), "type")
+                .ok_or_else(|| "none in /sys/class/thermal".to_string()),
+        },
+        Probe {
+            name: "hwmon sensors",
+            result: list(Path::new("/sys/class/hwmon"), |_| true, "name").ok_or_else(|| "none in /sys/class/hwmon".to_string()),
+        },
+        Probe {
+            name: "I2C buses",
+            result: list(Path::new("/dev"), |name| name.starts_with("i2c-"), "")
+                .ok_or_else(|| "none, enable them with dtparam=i2c_arm=on in config.txt".to_string()),
+        },
+        Probe {
+            name: "1-Wire devices",
+            result: list(Path::new("/sys/bus/w1/devices"), |name| !name.starts_with("w1_bus_master"), "")
+                .ok_or_else(|| "none, enable the bus with dtoverlay=w1-gpio in config.txt".to_string()),
+        },
+        Probe {
+            name: "sqlite3",
+            result: find_command("sqlite3").ok_or_else(|| "not found in PATH, install sqlite3 to record the history".to_string()),
+        },
+    ]
+}
+
+fn find_command(command: &str) -> Option<String> {
+    backend::find_command(command).map(|path| format!("found at {}", path.display()))
+}
+
+// vcgencmd talks to the firmware through it, which takes the video group
+fn vchiq(path: &Path) -> Result<String, String> {
+    match OpenOptions::new().read(true).write(true).open(path) {
+        Ok(_) => Ok("accessible".to_string()),
+        Err(err) if err.kind() == ErrorKind::NotFound => Err("missing, the board may not be a Raspberry Pi".to_string()),
+        Err(err) if err.kind() == ErrorKind::PermissionDenied => Err("permission denied, add the user to the video group".to_string()),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+// Names of the entries in `dir` matching `filter`, followed by the content of their `attribute` file if any
+fn list(dir: &Path, filter: impl Fn(&str) -> bool, attribute: &str) -> Option<String> {
+    let mut entries = fs::read_dir(dir)
+        .ok()?
+        .filter_map(Result::ok)
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| filter(name))
+        .map(|name| {
+            let attribute = (!attribute.is_empty())
+                .then(|| fs::read_to_string(dir.join(&name).join(attribute)).ok())
+                .flatten();
+            match attribute {
+                Some(attribute) => format!("{name} ({})", attribute.trim()),
+                None => name,
+            }
+        })
+        .collect::<Vec<_>>();
+    entries.sort();
+
+    (!entries.is_empty()).then(|| entries.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path, process};
+
+    use crate::doctor::{list, vchiq};
+
+    #[test]
+    fn list_entries() {
+        let dir = std::env::temp_dir().join(format!("raspi-exporter-doctor-{}", process::id()));
+        for (name, kind) in [("thermal_zone1", "gpu-thermal"), ("thermal_zone0", "cpu-thermal"), ("cooling_device0", "")] {
+            fs::create_dir_all(dir.join(name)).unwrap();
+            fs::write(dir.join(name).join("type"), format!("{kind}\n")).unwrap();
+        }
+
+        let zones = list(&dir, |name| name.starts_with("thermal_zone"), "type");
+        let missing = list(&dir, |name| name.starts_with("i2c-"), "");
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(zones.as_deref(), Some("thermal_zone0 (cpu-thermal), thermal_zone1 (gpu-thermal)"));
+        assert_eq!(missing, None);
+    }
+
+    #[test]
+    fn vchiq_missing() {
+        assert!(vchiq(Path::new("/nonexistent/vchiq")).unwrap_err().starts_with("missing"));
+    }
+}
@@ -0,0 +1,150 @@
+use std::{collections::VecDeque, sync::{Arc, Mutex}, time::SystemTime};
+
+use serde::Serialize;
+
+use crate::{
+    metrics::{throttled::ThrottlingKind, Registerer},
+    parser::throttled::ThrottledState,
+};
+
+/// Log of the latest notable events, such as throttling starting or a collector failing, dropping the oldest once
+/// `capacity` is reached, so that they can be looked into on the device without a metrics backend.
+#[derive(Debug, Default)]
+pub struct Events {
+    capacity: usize,
+    entries: Mutex<VecDeque<Event>>,
+}
+
+/// Event of the log, whose `kind` is one of `throttling_started`, `throttling_cleared`, `collector_failed`,
+/// `collector_recovered`, `threshold_exceeded` and `threshold_cleared`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Event {
+    /// RFC 3339 time in UTC
+    pub time: String,
+    pub kind: &'static str,
+    pub message: String,
+}
+
+/// Logs throttling kinds becoming active or clearing, meant to be fed by a sampler.
+///
+/// A kind already active at the first sample starts too, as with [`ThrottlingHook`](crate::hook::ThrottlingHook).
+#[derive(Debug)]
+pub struct ThrottlingEvents {
+    events: Arc<Events>,
+    // Whether each kind was active at the previous sample
+    previous: Mutex<Option<[bool; 4]>>,
+}
+
+impl Events {
+    /// Creates a log keeping `capacity` events, which keeps none with 0.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    pub fn record(&self, kind: &'static str, message: impl Into<String>) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let event = Event {
+            time: humantime::format_rfc3339_seconds(SystemTime::now()).to_string(),
+            kind,
+            message: message.into(),
+        };
+        let mut entries = self.entries.lock().expect("failed to lock entries mutex");
+        if entries.len() == self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(event);
+    }
+
+    /// Returns the events kept, the oldest first.
+    pub fn list(&self) -> Vec<Event> {
+        self.entries.lock().expect("failed to lock entries mutex").iter().cloned().collect()
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.capacity > 0
+    }
+}
+
+impl ThrottlingEvents {
+    pub fn new(events: Arc<Events>) -> Self {
+        Self {
+            events,
+            previous: Mutex::new(None),
+        }
+    }
+}
+
+impl Registerer for ThrottlingEvents {
+    type Item = ThrottledState;
+
+    async fn update(&self, state: Self::Item) -> anyhow::Result<()> {
+        let current = [
+            state.undervoltage_detected,
+            state.arm_frequency_capped,
+            state.currently_throttled,
+            state.soft_temperature_limit_active,
+        ];
+        let previous = self.previous.lock().expect("failed to lock previous mutex").replace(current).unwrap_or_default();
+
+        let kinds = [ThrottlingKind::Undervoltage, ThrottlingKind::ArmFrequency, ThrottlingKind::Throttled, ThrottlingKind::SoftTemperatureLimit];
+        for ((kind, was_active), is_active) in kinds.into_iter().zip(previous).zip(current) {
+            match (was_active, is_active) {
+                (false, true) => self.events.record("throttling_started", kind.to_string()),
+                (true, false) => self.events.record("throttling_cleared", kind.to_string()),
+                _ => {},
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        events::{Events, ThrottlingEvents},
+        metrics::Registerer,
+        parser::throttled::ThrottledState,
+    };
+
+    #[test]
+    fn record() {
+        let events = Events::new(2);
+        for message in ["first", "second", "third"] {
+            events.record("collector_failed", message);
+        }
+
+        // The oldest is dropped
+        let messages = events.list().into_iter().map(|event| event.message).collect::<Vec<_>>();
+        assert_eq!(messages, ["second", "third"]);
+
+        let disabled = Events::new(0);
+        disabled.record("collector_failed", "first");
+        assert!(disabled.list().is_empty());
+    }
+
+    #[tokio::test]
+    async fn throttling() {
+        let events = Arc::new(Events::new(10));
+        let registerer = ThrottlingEvents::new(events.clone());
+        let undervoltage = ThrottledState { undervoltage_detected: true, ..Default::default() };
+
+        registerer.update(undervoltage.clone()).await.unwrap();
+        registerer.update(undervoltage).await.unwrap();
+        registerer.update(ThrottledState::default()).await.unwrap();
+
+        let kinds = events.list().into_iter().map(|event| (event.kind, event.message)).collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            [("throttling_started", "undervoltage".to_string()), ("throttling_cleared", "undervoltage".to_string())],
+        );
+    }
+}
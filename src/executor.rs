@@ -1,5 +1,27 @@
+pub mod access_point;
+pub mod backlight;
+pub mod boot_config;
+pub mod boot_time;
+pub mod cgroup;
+pub mod chrony;
+pub mod clock_tree;
+pub mod container;
+pub mod cpu_vulnerability;
+pub mod file_descriptor;
+pub mod filesystem;
+pub mod neighbor;
+pub mod nftables;
+pub mod oom_kill;
+pub mod package_update;
+pub mod reboot_required;
+pub mod reset;
+pub mod snmp;
+pub mod temperature;
 pub mod throttled;
+pub mod vl805;
+pub mod wireguard;
 
+/// Reads the raw output of a collection, such as of a command or a file.
 #[cfg_attr(test, mockall::automock)]
 pub trait Executor {
     fn execute(&self) -> impl Future<Output = anyhow::Result<String>> + Send;
@@ -0,0 +1,41 @@
+use std::{fmt::Debug, path::Path};
+
+use anyhow::Context;
+use tracing::Level;
+
+use crate::executor::Executor;
+
+/// Reads the brightness of every device in a backlight class directory as lines of
+/// `<device> <brightness> <max_brightness>`.
+#[derive(Debug)]
+pub struct BacklightExecutor<P> {
+    path: P,
+}
+
+impl<P> BacklightExecutor<P> {
+    pub fn new(path: P) -> Self {
+        Self {
+            path,
+        }
+    }
+}
+
+impl<P> Executor for BacklightExecutor<P>
+where
+    P: AsRef<Path> + Debug + Send + Sync,
+{
+    #[tracing::instrument(skip_all, fields(path = ?self.path), ret(level = Level::DEBUG))]
+    async fn execute(&self) -> anyhow::Result<String> {
+        let read_error = || format!("backlight read error: {self:?}");
+
+        let mut entries = tokio::fs::read_dir(&self.path).await.with_context(read_error)?;
+        let mut lines = Vec::new();
+        while let Some(entry) = entries.next_entry().await.with_context(read_error)? {
+            let brightness = tokio::fs::read_to_string(entry.path().join("brightness")).await.with_context(read_error)?;
+            let max_brightness = tokio::fs::read_to_string(entry.path().join("max_brightness")).await.with_context(read_error)?;
+            lines.push(format!("{} {} {}", entry.file_name().to_string_lossy(), brightness.trim(), max_brightness.trim()));
+        }
+
+        Ok(lines.join("\n"))
+    }
+}
@@ -0,0 +1,40 @@
+use std::{fmt::Debug, path::Path};
+
+use anyhow::Context;
+use tracing::Level;
+
+use crate::executor::Executor;
+
+/// Reads `cpu.stat` of a cgroup followed by its single-value memory files as flat-keyed lines,
+/// e.g. `memory.current 1024`.
+#[derive(Debug)]
+pub struct CgroupExecutor<P> {
+    path: P,
+}
+
+impl<P> CgroupExecutor<P> {
+    pub fn new(path: P) -> Self {
+        Self {
+            path,
+        }
+    }
+}
+
+impl<P> Executor for CgroupExecutor<P>
+where
+    P: AsRef<Path> + Debug + Send + Sync,
+{
+    #[tracing::instrument(skip_all, fields(path = ?self.path), ret(level = Level::DEBUG))]
+    async fn execute(&self) -> anyhow::Result<String> {
+        let path = self.path.as_ref();
+        let read_error = || format!("file read error: {self:?}");
+
+        let mut result = tokio::fs::read_to_string(path.join("cpu.stat")).await.with_context(read_error)?;
+        for name in ["memory.current", "memory.max"] {
+            let value = tokio::fs::read_to_string(path.join(name)).await.with_context(read_error)?;
+            result.push_str(&format!("{name} {}\n", value.trim()));
+        }
+
+        Ok(result)
+    }
+}
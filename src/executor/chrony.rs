@@ -0,0 +1,3 @@
+use crate::command::CommandExecutor;
+
+pub type ChronyExecutor<S, I> = CommandExecutor<S, I>;
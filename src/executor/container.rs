@@ -0,0 +1,87 @@
+use std::{fmt::Debug, path::Path};
+
+use anyhow::Context;
+use http_body_util::{BodyExt, Empty};
+use hyper::{body::Bytes, client::conn::http1::SendRequest, header::HOST, Request};
+use hyper_util::rt::TokioIo;
+use serde::Deserialize;
+use serde_json::json;
+use tokio::net::UnixStream;
+use tracing::Level;
+
+use crate::executor::Executor;
+
+/// Queries the Docker Engine API, which Podman also serves, through its Unix socket.
+///
+/// Outputs a line of `{"inspect": ..., "stats": ...}` per container, where `stats` is null unless it is running.
+#[derive(Debug)]
+pub struct ContainerExecutor<P> {
+    socket: P,
+}
+
+#[derive(Deserialize)]
+struct ContainerSummary {
+    #[serde(rename = "Id")]
+    id: String,
+    #[serde(rename = "State")]
+    state: String,
+}
+
+impl<P> ContainerExecutor<P> {
+    pub fn new(socket: P) -> Self {
+        Self {
+            socket,
+        }
+    }
+}
+
+impl<P> Executor for ContainerExecutor<P>
+where
+    P: AsRef<Path> + Debug + Send + Sync,
+{
+    #[tracing::instrument(skip_all, fields(socket = ?self.socket), ret(level = Level::DEBUG))]
+    async fn execute(&self) -> anyhow::Result<String> {
+        let stream = UnixStream::connect(&self.socket)
+            .await
+            .with_context(|| format!("socket connection error: {self:?}"))?;
+        let (mut sender, connection) = hyper::client::conn::http1::handshake(TokioIo::new(stream)).await?;
+        tokio::spawn(async move {
+            if let Err(err) = connection.await {
+                tracing::debug!("container runtime connection error\nError: {err:?}");
+            }
+        });
+
+        let containers: Vec<ContainerSummary> = serde_json::from_slice(&get(&mut sender, "/containers/json?all=true").await?)?;
+        let mut lines = Vec::new();
+        for container in containers {
+            let inspect: serde_json::Value = serde_json::from_slice(&get(&mut sender, &format!("/containers/{}/json", container.id)).await?)?;
+            let stats: serde_json::Value = if container.state == "running" {
+                // one-shot skips waiting for a second sample to fill precpu_stats
+                serde_json::from_slice(&get(&mut sender, &format!("/containers/{}/stats?stream=false&one-shot=true", container.id)).await?)?
+            } else {
+                serde_json::Value::Null
+            };
+
+            lines.push(json!({ "inspect": inspect, "stats": stats }).to_string());
+        }
+
+        Ok(lines.join("\n"))
+    }
+}
+
+async fn get(sender: &mut SendRequest<Empty<Bytes>>, path: &str) -> anyhow::Result<Bytes> {
+    sender.ready().await?;
+
+    let request = Request::get(path)
+        .header(HOST, "localhost")
+        .body(Empty::new())?;
+    let response = sender
+        .send_request(request)
+        .await
+        .with_context(|| format!("request error: {path}"))?;
+    if !response.status().is_success() {
+        anyhow::bail!(format!("request failed with status {}: {path}", response.status()));
+    }
+
+    Ok(response.into_body().collect().await?.to_bytes())
+}
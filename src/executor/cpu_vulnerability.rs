@@ -0,0 +1,3 @@
+use crate::file::DirectoryFilesExecutor;
+
+pub type CpuVulnerabilityExecutor<P> = DirectoryFilesExecutor<P>;
@@ -0,0 +1,5 @@
+use crate::file::{DirectoryExecutor, FileExecutor};
+
+pub type FileDescriptorExecutor<P> = FileExecutor<P>;
+
+pub type ProcessFileDescriptorExecutor<P> = DirectoryExecutor<P>;
@@ -0,0 +1,5 @@
+use crate::{command::CommandExecutor, file::FileExecutor};
+
+pub type NeighborExecutor<P> = FileExecutor<P>;
+
+pub type NeighborThresholdExecutor<S, I> = CommandExecutor<S, I>;
@@ -0,0 +1,3 @@
+use crate::command::CommandExecutor;
+
+pub type NftablesExecutor<S, I> = CommandExecutor<S, I>;
@@ -0,0 +1,3 @@
+use crate::{cache::CachedExecutor, command::CommandExecutor};
+
+pub type PackageUpdateExecutor<S, I> = CachedExecutor<CommandExecutor<S, I>>;
@@ -0,0 +1,3 @@
+use crate::file::DirectoryExecutor;
+
+pub type RebootRequiredExecutor<P> = DirectoryExecutor<P>;
@@ -0,0 +1,3 @@
+use crate::file::FileExecutor;
+
+pub type SnmpExecutor<P> = FileExecutor<P>;
@@ -0,0 +1,3 @@
+use crate::{cache::CachedExecutor, command::CommandExecutor};
+
+pub type Vl805Executor<S, I> = CachedExecutor<CommandExecutor<S, I>>;
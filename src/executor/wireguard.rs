@@ -0,0 +1,3 @@
+use crate::command::CommandExecutor;
+
+pub type WireguardExecutor<S, I> = CommandExecutor<S, I>;
@@ -0,0 +1,714 @@
+//! Library API to embed the collectors in another daemon, serving them from its own router instead of a second process.
+//!
+//! ```no_run
+//! use raspi_exporter::{cli::Metric, exporter::RaspiExporter};
+//!
+//! # async fn run() -> anyhow::Result<()> {
+//! let exporter = RaspiExporter::builder().enable(Metric::Throttled).port(9090).build().await;
+//! // Either serves on its own port, or merges the metrics route into an axum router of the daemon
+//! let router = exporter.router();
+//! exporter.serve().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{borrow::Cow, fs, net::IpAddr, path::Path, sync::Arc, time::Duration};
+
+use axum::Router;
+use clap::Parser;
+use prometheus_client::{metrics::gauge::Gauge, registry::Registry};
+
+use crate::{
+    cli::{Cli, Listen, Metric, MetricSelection},
+    client::Client,
+    collector::{
+        access_point::AccessPoint,
+        backlight::Backlight,
+        boot_config::BootConfig,
+        boot_time::BootTime,
+        cgroup::Cgroup,
+        chrony::Chrony,
+        clock_tree::ClockTree,
+        container::Container,
+        cpu_vulnerability::CpuVulnerability,
+        file_descriptor::{FileDescriptor, ProcessFileDescriptor},
+        filesystem::Filesystem,
+        neighbor::{Neighbor, NeighborThreshold},
+        nftables::Nftables,
+        oom_kill::OomKill,
+        package_update::PackageUpdate,
+        reboot_required::RebootRequired,
+        reset::Reset,
+        snmp::Snmp,
+        temperature::Temperature,
+        throttled::Throttled,
+        vl805::Vl805,
+        wireguard::Wireguard,
+    },
+    command::CommandExecutor,
+    events::{Events, ThrottlingEvents},
+    executor::{
+        access_point::AccessPointExecutor,
+        backlight::BacklightExecutor,
+        boot_config::BootConfigExecutor,
+        boot_time::BootTimeExecutor,
+        cgroup::CgroupExecutor,
+        chrony::ChronyExecutor,
+        clock_tree::ClockTreeExecutor,
+        container::ContainerExecutor,
+        cpu_vulnerability::CpuVulnerabilityExecutor,
+        file_descriptor::{FileDescriptorExecutor, ProcessFileDescriptorExecutor},
+        filesystem::FilesystemExecutor,
+        neighbor::{NeighborExecutor, NeighborThresholdExecutor},
+        nftables::NftablesExecutor,
+        oom_kill::OomKillExecutor,
+        package_update::PackageUpdateExecutor,
+        reboot_required::RebootRequiredExecutor,
+        reset::ResetExecutor,
+        snmp::SnmpExecutor,
+        vl805::Vl805Executor,
+        wireguard::WireguardExecutor,
+    },
+    fan::{FanController, FanOutput},
+    follower::{CommandLineSource, FileLineSource, Follower},
+    hook::{Hook, ThrottlingHook},
+    metrics::{reboot_required::RebootRequiredReason, Collector, MetricGroup, MetricsHandler},
+    notifier::ThrottlingNotifier,
+    parser::{
+        access_point::AccessPointParser,
+        backlight::BacklightParser,
+        boot_config::BootConfigParser,
+        boot_time::BootTimeParser,
+        cgroup::CgroupParser,
+        chrony::ChronyParser,
+        clock_tree::ClockTreeParser,
+        container::ContainerParser,
+        cpu_vulnerability::CpuVulnerabilityParser,
+        file_descriptor::{FileDescriptorParser, ProcessFileDescriptorParser},
+        filesystem::FilesystemParser,
+        kmsg::KmsgParser,
+        neighbor::{NeighborParser, NeighborThresholdParser},
+        nftables::NftablesParser,
+        oom_kill::OomKillParser,
+        package_update::PackageUpdateParser,
+        reboot_required::{RebootRequiredFlagParser, RebootRequiredKernelParser},
+        reset::ResetParser,
+        snmp::SnmpParser,
+        ssh::SshAuthFailureParser,
+        temperature::TemperatureParser,
+        throttled::ThrottledParser,
+        vl805::Vl805Parser,
+        wireguard::WireguardParser,
+    },
+    registerer::{
+        access_point::AccessPointRegisterer,
+        backlight::BacklightRegisterer,
+        boot_config::BootConfigRegisterer,
+        boot_time::BootTimeRegisterer,
+        cgroup::CgroupRegisterer,
+        chrony::ChronyRegisterer,
+        clock_tree::ClockTreeRegisterer,
+        container::ContainerRegisterer,
+        cpu_vulnerability::CpuVulnerabilityRegisterer,
+        file_descriptor::{FileDescriptorRegisterer, ProcessFileDescriptorRegisterer},
+        filesystem::FilesystemRegisterer,
+        kmsg::KmsgRegisterer,
+        neighbor::{NeighborRegisterer, NeighborThresholdRegisterer},
+        nftables::NftablesRegisterer,
+        oom_kill::OomKillRegisterer,
+        package_update::PackageUpdateRegisterer,
+        reboot_required::RebootRequiredRegisterer,
+        reset::ResetRegisterer,
+        snmp::SnmpRegisterer,
+        ssh::SshAuthFailureRegisterer,
+        temperature::TemperatureRegisterer,
+        throttled::{ThrottledDurationRegisterer, ThrottledLastOccurrenceRegisterer, ThrottledRegisterer, ThrottledTransitionRegisterer},
+        vl805::Vl805Registerer,
+        wireguard::WireguardRegisterer,
+    },
+    sampler::Sampler,
+    server::{self, Server},
+};
+#[cfg(feature = "ssh")]
+use crate::{config::CollectorConfig, remote::RemoteExecutor};
+
+/// Collectors of the enabled metrics and how to serve them.
+pub struct RaspiExporter {
+    args: Cli,
+    metrics_handler: Arc<MetricsHandler>,
+}
+
+/// Builder of [`RaspiExporter`], which takes the defaults of the command line options except that no metric is enabled.
+pub struct Builder {
+    args: Cli,
+    metrics: Vec<Metric>,
+}
+
+impl RaspiExporter {
+    pub fn builder() -> Builder {
+        Builder {
+            args: Cli::parse_from(["raspi_exporter"]),
+            metrics: Vec::new(),
+        }
+    }
+
+    /// Handler collecting the metrics, e.g. for the push modes.
+    pub fn metrics_handler(&self) -> Arc<MetricsHandler> {
+        self.metrics_handler.clone()
+    }
+
+    /// Router serving the metrics on the metrics path, to merge into a router of the embedding daemon.
+    pub fn router(&self) -> Router {
+        server::router(&self.args.metrics_path, self.metrics_handler.clone())
+    }
+
+    /// Serves the metrics on the address and the port until SIGINT or SIGTERM.
+    pub async fn serve(self) -> anyhow::Result<()> {
+        let addresses = self.args.address.iter().map(|address| address.address(self.args.port)).collect();
+        Server::new(addresses, self.metrics_handler)
+            .metrics_path(self.args.metrics_path)
+            .start()
+            .await
+    }
+}
+
+impl Builder {
+    /// Enables `metric`, along with the ones enabled already.
+    pub fn enable(mut self, metric: Metric) -> Self {
+        if !self.metrics.contains(&metric) {
+            self.metrics.push(metric);
+        }
+        self
+    }
+
+    /// Port to serve on, 8021 by default.
+    pub fn port(mut self, port: u16) -> Self {
+        self.args.port = port;
+        self
+    }
+
+    /// Address to serve on, 0.0.0.0 by default.
+    pub fn address(mut self, address: IpAddr) -> Self {
+        self.args.address = vec![Listen::Ip(address)];
+        self
+    }
+
+    /// Path to serve the metrics on, /metrics by default.
+    pub fn metrics_path(mut self, metrics_path: impl Into<String>) -> Self {
+        self.args.metrics_path = metrics_path.into();
+        self
+    }
+
+    /// Sets the other options, such as the cgroups to collect or the metric prefix, as on the command line.
+    pub fn options(mut self, options: impl FnOnce(&mut Cli)) -> Self {
+        options(&mut self.args);
+        self
+    }
+
+    /// Registers the collectors of the enabled metrics, some of which take their first readings.
+    pub async fn build(mut self) -> RaspiExporter {
+        self.args.metrics.enable_metrics = self.metrics.iter().copied().map(MetricSelection::Metric).collect();
+        self.args.metrics.disable_metrics = Vec::new();
+
+        let metrics_handler = Arc::new(metrics_handler(&self.args, None));
+        for metric in &self.metrics {
+            metrics_handler.insert(metric_group(&self.args, metric).await);
+        }
+        metrics_handler.insert(fallback_group(&self.args));
+
+        RaspiExporter {
+            args: self.args,
+            metrics_handler,
+        }
+    }
+}
+
+/// Handler without collectors, with the timeouts, the relabel rules, the thresholds and the hook of `args`, labeling the
+/// metrics with `target` if any.
+pub fn metrics_handler(args: &Cli, target: Option<&str>) -> MetricsHandler {
+    MetricsHandler::default()
+        .registry(registry(args, target))
+        .collector_timeout(Some(args.collector_timeout))
+        .collector_timeouts(args.collector_timeouts.iter().cloned().collect())
+        .fail_on_collector_error(args.fail_on_collector_error)
+        .relabel(args.relabel.clone())
+        .thresholds(args.thresholds.clone())
+        .hook(args.hook.clone().map(Hook::new))
+}
+
+/// Registry giving the metrics registered in it the prefix and the extra labels, and the target label of a probe target.
+pub fn registry(args: &Cli, target: Option<&str>) -> Registry {
+    let mut labels = args.labels.clone();
+    match target {
+        Some(target) => labels.push(("target".to_string(), target.to_string())),
+        // Of the host running the exporter rather than of the target
+        None if args.hostname_label => {
+            let hostname = fs::read_to_string("/proc/sys/kernel/hostname").unwrap_or_else(|_| "localhost".to_string());
+            labels.push(("hostname".to_string(), hostname.trim().to_string()));
+        },
+        None => {},
+    }
+    let labels = labels.into_iter().map(|(name, value)| (Cow::from(name), Cow::from(value)));
+
+    match args.metric_prefix.as_str() {
+        "" => Registry::with_labels(labels),
+        prefix => Registry::with_prefix_and_labels(prefix, labels),
+    }
+}
+
+/// Group of the collectors of `metric` configured by `args`, started along with their background tasks.
+pub async fn metric_group(args: &Cli, metric: &Metric) -> MetricGroup {
+    let mut group = MetricGroup::with_registry(metric, registry(args, None));
+    // Empty rather than failing every scrape
+    if metric.needs_videocore() && args.fallback() {
+        tracing::warn!("leaving {metric} out in the fallback mode");
+        return group;
+    }
+    let config = args.collector(*metric);
+    match metric {
+        Metric::Throttled => {
+            let registry = group.registry();
+            let registerer = ThrottledRegisterer::new(&mut registry.lock().expect("failed to lock registry mutex"));
+            let registerer = match &args.throttled_state_file {
+                Some(state_file) => registerer.with_state_file(state_file),
+                None => registerer,
+            };
+            group.push(Box::new(Throttled::new(
+                args.backend.throttled(&config),
+                ThrottledParser,
+                registerer,
+            )));
+        },
+        Metric::OomKill => {
+            let registry = group.registry();
+            let registerer = OomKillRegisterer::new(&mut registry.lock().expect("failed to lock registry mutex"));
+            for cgroup in &args.oom_kill_cgroups {
+                group.push(Box::new(OomKill::new(
+                    OomKillExecutor::new(Path::new("/sys/fs/cgroup").join(cgroup).join("memory.events")),
+                    OomKillParser,
+                    registerer.with_cgroup(cgroup),
+                )));
+            }
+            group.push(Box::new(OomKill::new(
+                OomKillExecutor::new(config.path("/proc/vmstat")),
+                OomKillParser,
+                registerer,
+            )));
+        },
+        Metric::FileDescriptor => {
+            let registry = group.registry();
+            let mut registry = registry.lock().expect("failed to lock registry mutex");
+            group.push(Box::new(FileDescriptor::new(
+                FileDescriptorExecutor::new(config.path("/proc/sys/fs/file-nr")),
+                FileDescriptorParser,
+                FileDescriptorRegisterer::new(&mut registry),
+            )));
+            group.push(Box::new(ProcessFileDescriptor::new(
+                ProcessFileDescriptorExecutor::new("/proc/self/fd"),
+                ProcessFileDescriptorParser,
+                ProcessFileDescriptorRegisterer::new(&mut registry),
+            )));
+        },
+        Metric::Filesystem => {
+            let registry = group.registry();
+            let (command, mut arguments) = config.command("df", &[
+                "--block-size=1",
+                "--local",
+                "--exclude-type=tmpfs",
+                "--exclude-type=devtmpfs",
+                "--exclude-type=squashfs",
+                "--exclude-type=overlay",
+                "--output=fstype,size,avail,itotal,iavail,target",
+            ]);
+            // df reports the filesystems of the files given only
+            arguments.extend(config.mount_points.iter().map(|mount_point| mount_point.to_string_lossy().into_owned()));
+            group.push(Box::new(Filesystem::new(
+                FilesystemExecutor::new(command, arguments),
+                FilesystemParser,
+                FilesystemRegisterer::new(&mut registry.lock().expect("failed to lock registry mutex")),
+            )));
+        },
+        Metric::Chrony => {
+            let registry = group.registry();
+            let (command, arguments) = config.command("chronyc", &["-c", "tracking"]);
+            group.push(Box::new(Chrony::new(
+                ChronyExecutor::new(command, arguments),
+                ChronyParser,
+                ChronyRegisterer::new(&mut registry.lock().expect("failed to lock registry mutex")),
+            )));
+        },
+        Metric::Neighbor => {
+            let registry = group.registry();
+            let mut registry = registry.lock().expect("failed to lock registry mutex");
+            let (command, arguments) = config.command("sysctl", &[
+                "net.ipv4.neigh.default.gc_thresh1",
+                "net.ipv4.neigh.default.gc_thresh2",
+                "net.ipv4.neigh.default.gc_thresh3",
+            ]);
+            group.push(Box::new(Neighbor::new(
+                NeighborExecutor::new(config.path("/proc/net/arp")),
+                NeighborParser,
+                NeighborRegisterer::new(&mut registry),
+            )));
+            group.push(Box::new(NeighborThreshold::new(
+                NeighborThresholdExecutor::new(command, arguments),
+                NeighborThresholdParser,
+                NeighborThresholdRegisterer::new(&mut registry),
+            )));
+        },
+        Metric::Wireguard => {
+            let registry = group.registry();
+            let (command, arguments) = config.command("wg", &["show", "all", "dump"]);
+            group.push(Box::new(Wireguard::new(
+                WireguardExecutor::new(command, arguments),
+                WireguardParser,
+                WireguardRegisterer::new(&mut registry.lock().expect("failed to lock registry mutex")),
+            )));
+        },
+        Metric::PackageUpdate => {
+            let registry = group.registry();
+            let (command, arguments) = config.command("apt-get", &["--simulate", "--quiet", "upgrade"]);
+            group.push(Box::new(PackageUpdate::new(
+                PackageUpdateExecutor::new(
+                    CommandExecutor::new(command, arguments),
+                    args.package_update_interval,
+                ),
+                PackageUpdateParser,
+                PackageUpdateRegisterer::new(&mut registry.lock().expect("failed to lock registry mutex")),
+            )));
+        },
+        Metric::RebootRequired => {
+            let registry = group.registry();
+            let registerer = RebootRequiredRegisterer::new(&mut registry.lock().expect("failed to lock registry mutex"));
+            group.push(Box::new(RebootRequired::new(
+                RebootRequiredExecutor::new("/run"),
+                RebootRequiredFlagParser,
+                registerer.with_reason(RebootRequiredReason::FlagFile),
+            )));
+            // The running kernel can't change without restarting the exporter
+            match fs::read_to_string("/proc/sys/kernel/osrelease") {
+                Ok(running) => group.push(Box::new(RebootRequired::new(
+                    RebootRequiredExecutor::new("/lib/modules"),
+                    RebootRequiredKernelParser::new(running),
+                    registerer.with_reason(RebootRequiredReason::Kernel),
+                ))),
+                Err(err) => tracing::warn!("failed to read running kernel release\nError: {err:?}"),
+            }
+        },
+        Metric::ClockTree => {
+            let registry = group.registry();
+            group.push(Box::new(ClockTree::new(
+                ClockTreeExecutor::new(config.path("/sys/kernel/debug/clk/clk_summary")),
+                ClockTreeParser::new(config.clocks.clone()),
+                ClockTreeRegisterer::new(&mut registry.lock().expect("failed to lock registry mutex")),
+            )));
+        },
+        Metric::ThrottledHistory => {
+            let registry = group.registry();
+            let mut registry = registry.lock().expect("failed to lock registry mutex");
+            let sampler = Sampler::new(
+                Box::new(Throttled::new(
+                    args.backend.throttled(&config),
+                    ThrottledParser,
+                    (
+                        ThrottledDurationRegisterer::new(&mut registry),
+                        (ThrottledLastOccurrenceRegisterer::new(&mut registry), ThrottledTransitionRegisterer::new(&mut registry)),
+                    ),
+                )),
+                config.sampling_interval.unwrap_or(args.sampling_interval),
+            );
+            group.task(sampler.spawn());
+        },
+        Metric::Temperature => {
+            let registry = group.registry();
+            let registerer = TemperatureRegisterer::new(&mut registry.lock().expect("failed to lock registry mutex"));
+            let sampler = Sampler::new(
+                Box::new(Temperature::new(
+                    args.backend.temperature(&config),
+                    TemperatureParser,
+                    registerer.extrema(),
+                )),
+                config.sampling_interval.unwrap_or(args.sampling_interval),
+            );
+            group.task(sampler.spawn());
+            group.push(Box::new(Temperature::new(
+                args.backend.temperature(&config),
+                TemperatureParser,
+                registerer,
+            )));
+        },
+        Metric::Vl805 => {
+            let registry = group.registry();
+            let (command, arguments) = config.command("rpi-eeprom-update", &[]);
+            group.push(Box::new(Vl805::new(
+                // The firmware only changes through an update followed by a reboot
+                Vl805Executor::new(CommandExecutor::new(command, arguments), Duration::from_secs(60 * 60)),
+                Vl805Parser,
+                Vl805Registerer::new(&mut registry.lock().expect("failed to lock registry mutex")),
+            )));
+        },
+        Metric::Reset => {
+            let registry = group.registry();
+            let (command, arguments) = config.command("vcgencmd", &["get_rsts"]);
+            group.push(Box::new(Reset::new(
+                ResetExecutor::new(command, arguments),
+                ResetParser,
+                ResetRegisterer::new(&mut registry.lock().expect("failed to lock registry mutex")),
+            )));
+        },
+        Metric::Kmsg => {
+            let registry = group.registry();
+            // Starts from the oldest record left in the ring buffer, which counts messages since boot unless it wrapped
+            let follower = Follower::new(
+                "kmsg",
+                FileLineSource::new(config.path("/dev/kmsg")),
+                KmsgParser::new(args.kmsg_subsystems.clone()),
+                KmsgRegisterer::new(&mut registry.lock().expect("failed to lock registry mutex")),
+            );
+            group.task(follower.spawn());
+        },
+        Metric::Cgroup => {
+            let registry = group.registry();
+            let registerer = CgroupRegisterer::new(&mut registry.lock().expect("failed to lock registry mutex"));
+            for cgroup in &args.cgroups {
+                group.push(Box::new(Cgroup::new(
+                    CgroupExecutor::new(Path::new("/sys/fs/cgroup").join(cgroup)),
+                    CgroupParser,
+                    registerer.with_cgroup(cgroup),
+                )));
+            }
+        },
+        Metric::Container => {
+            let registry = group.registry();
+            group.push(Box::new(Container::new(
+                ContainerExecutor::new(args.container_socket.clone()),
+                ContainerParser,
+                ContainerRegisterer::new(&mut registry.lock().expect("failed to lock registry mutex")),
+            )));
+        },
+        Metric::AccessPoint => {
+            let registry = group.registry();
+            let registerer = AccessPointRegisterer::new(&mut registry.lock().expect("failed to lock registry mutex"));
+            for interface in &args.access_point_interfaces {
+                group.push(Box::new(AccessPoint::new(
+                    AccessPointExecutor::new("iw".to_string(), ["dev", interface, "station", "dump"].map(String::from)),
+                    AccessPointParser,
+                    registerer.with_interface(interface),
+                )));
+            }
+        },
+        Metric::Backlight => {
+            let registry = group.registry();
+            group.push(Box::new(Backlight::new(
+                BacklightExecutor::new(config.path("/sys/class/backlight")),
+                BacklightParser,
+                BacklightRegisterer::new(&mut registry.lock().expect("failed to lock registry mutex")),
+            )));
+        },
+        Metric::Nftables => {
+            let registry = group.registry();
+            let (command, arguments) = config.command("nft", &["--json", "list", "ruleset"]);
+            group.push(Box::new(Nftables::new(
+                // Rules added with iptables-nft are also listed
+                NftablesExecutor::new(command, arguments),
+                NftablesParser,
+                NftablesRegisterer::new(&mut registry.lock().expect("failed to lock registry mutex")),
+            )));
+        },
+        Metric::BootTime => {
+            let registry = group.registry();
+            let (command, arguments) = config.command("systemd-analyze", &["time"]);
+            group.push(Box::new(BootTime::new(
+                // Doesn't change until the next boot once it finished, and failures of an unfinished boot are retried
+                BootTimeExecutor::new(CommandExecutor::new(command, arguments), Duration::MAX),
+                BootTimeParser,
+                BootTimeRegisterer::new(&mut registry.lock().expect("failed to lock registry mutex")),
+            )));
+        },
+        Metric::SshAuthFailure => {
+            let registry = group.registry();
+            // Starts from new entries, so that reopening doesn't count entries again
+            // (newer OpenSSH logs authentication as sshd-session)
+            let follower = Follower::new(
+                "ssh_auth_failure",
+                CommandLineSource::new("journalctl", [
+                    "--follow",
+                    "--lines=0",
+                    "--output=cat",
+                    "--identifier=sshd",
+                    "--identifier=sshd-session",
+                ]),
+                SshAuthFailureParser,
+                SshAuthFailureRegisterer::new(&mut registry.lock().expect("failed to lock registry mutex"), args.ssh_auth_failures_by_method),
+            );
+            group.task(follower.spawn());
+        },
+        Metric::CpuVulnerability => {
+            let registry = group.registry();
+            group.push(Box::new(CpuVulnerability::new(
+                CpuVulnerabilityExecutor::new(config.path("/sys/devices/system/cpu/vulnerabilities")),
+                CpuVulnerabilityParser,
+                CpuVulnerabilityRegisterer::new(&mut registry.lock().expect("failed to lock registry mutex")),
+            )));
+        },
+        Metric::BootConfig => {
+            let registry = group.registry();
+            let registerer = BootConfigRegisterer::new(&mut registry.lock().expect("failed to lock registry mutex"));
+            for file in &args.boot_config_files {
+                let collector = BootConfig::new(
+                    BootConfigExecutor::new(file.clone()),
+                    BootConfigParser,
+                    registerer.with_file(file.to_string_lossy()),
+                );
+                // Takes the hashes to compare with before the files can be changed further
+                if let Err(err) = collector.collect().await {
+                    tracing::warn!("failed to read boot configuration\nError: {err:?}");
+                }
+                group.push(Box::new(collector));
+            }
+        },
+        Metric::Snmp => {
+            let registry = group.registry();
+            group.push(Box::new(Snmp::new(
+                SnmpExecutor::new(config.path("/proc/net/snmp")),
+                SnmpParser,
+                SnmpRegisterer::new(&mut registry.lock().expect("failed to lock registry mutex")),
+            )));
+        },
+    }
+
+    group
+}
+
+/// Group of a metric collected from `target` over SSH, none for the metrics that need more than a command or a file.
+#[cfg(feature = "ssh")]
+pub fn remote_metric_group(args: &Cli, target: &str, metric: &Metric) -> Option<MetricGroup> {
+    let mut group = MetricGroup::with_registry(metric, registry(args, Some(target)));
+    let registry = group.registry();
+    let mut registry = registry.lock().expect("failed to lock registry mutex");
+    // Configured as for the exporter host, which the Pis probed are usually alike
+    let config = args.collector(*metric);
+    let collector: Box<dyn Collector> = match metric {
+        Metric::Throttled => Box::new(Throttled::new(
+            remote_command(target, &config, "vcgencmd", &["get_throttled"]),
+            ThrottledParser,
+            ThrottledRegisterer::new(&mut registry),
+        )),
+        Metric::ClockTree => Box::new(ClockTree::new(
+            RemoteExecutor::file(target, config.path("/sys/kernel/debug/clk/clk_summary")),
+            ClockTreeParser::new(config.clocks.clone()),
+            ClockTreeRegisterer::new(&mut registry),
+        )),
+        Metric::Temperature => Box::new(Temperature::new(
+            RemoteExecutor::file(target, config.path("/sys/class/thermal/thermal_zone0/temp")),
+            TemperatureParser,
+            TemperatureRegisterer::new(&mut registry),
+        )),
+        Metric::Reset => Box::new(Reset::new(
+            remote_command(target, &config, "vcgencmd", &["get_rsts"]),
+            ResetParser,
+            ResetRegisterer::new(&mut registry),
+        )),
+        Metric::Snmp => Box::new(Snmp::new(
+            RemoteExecutor::file(target, config.path("/proc/net/snmp")),
+            SnmpParser,
+            SnmpRegisterer::new(&mut registry),
+        )),
+        _ => return None,
+    };
+    group.push(collector);
+
+    Some(group)
+}
+
+/// Sampler of the throttled command notifying the webhook and running the hook on throttling, independently of the
+/// throttled metric, and logging throttling in `events` when the throttled metric is enabled, none when none of them
+/// applies or in the fallback mode.
+pub fn throttling_notifier(args: &Cli, events: Arc<Events>) -> Option<Sampler> {
+    if args.fallback() {
+        return None;
+    }
+    let webhook = args
+        .webhook_url
+        .clone()
+        .map(|url| ThrottlingNotifier::new(url, Client::new(&args.push_ca_file), args.webhook_debounce));
+    let hook = args.hook.clone().map(|path| ThrottlingHook::new(Hook::new(path)));
+    let events = (events.is_enabled() && args.metrics.has(Metric::Throttled)).then(|| ThrottlingEvents::new(events));
+    if webhook.is_none() && hook.is_none() && events.is_none() {
+        return None;
+    }
+
+    let executor = args.backend.throttled(&args.collector(Metric::Throttled));
+    let collector = Throttled::new(executor, ThrottledParser, ((webhook, hook), events));
+    Some(Sampler::new(Box::new(collector), args.sampling_interval))
+}
+
+/// Unnamed group of the fan driven by --fan-pwm or --fan-gpio along --fan-curve at the SoC temperature, none when neither
+/// is given.
+pub fn fan_group(args: &Cli) -> Option<MetricGroup> {
+    let output = match (&args.fan_pwm, &args.fan_gpio) {
+        (Some(path), _) => FanOutput::Pwm { path: path.clone(), period: args.fan_pwm_period },
+        (None, Some(path)) => FanOutput::Gpio(path.clone()),
+        (None, None) => return None,
+    };
+
+    let mut group = MetricGroup::unnamed(registry(args, None));
+    let registry = group.registry();
+    let controller = FanController::new(output, args.fan_curve.clone(), &mut registry.lock().expect("failed to lock registry mutex"))
+        .hysteresis(args.fan_hysteresis);
+    let executor = args.backend.temperature(&args.collector(Metric::Temperature));
+    let sampler = Sampler::new(Box::new(Temperature::new(executor, TemperatureParser, controller)), args.sampling_interval);
+    group.task(sampler.spawn());
+
+    Some(group)
+}
+
+/// Unnamed group of the gauge telling whether the exporter runs in the fallback mode, without the metrics needing the
+/// VideoCore firmware.
+pub fn fallback_group(args: &Cli) -> MetricGroup {
+    let group = MetricGroup::unnamed(registry(args, None));
+    let fallback = Gauge::<i64>::default();
+    fallback.set(args.fallback().into());
+    group.registry().lock().expect("failed to lock registry mutex").register(
+        "fallback_mode",
+        "Whether the metrics needing the VideoCore firmware are left out for the board lacking it",
+        fallback,
+    );
+
+    group
+}
+
+#[cfg(feature = "ssh")]
+fn remote_command(target: &str, config: &CollectorConfig, command: &str, args: &[&str]) -> RemoteExecutor {
+    let (command, args) = config.command(command, args);
+    RemoteExecutor::command(target, &command, args.iter().map(String::as_str))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{backend::has_videocore, cli::Metric, exporter::RaspiExporter, metrics::Handler};
+
+    #[tokio::test]
+    async fn build() {
+        let exporter = RaspiExporter::builder()
+            .enable(Metric::FileDescriptor)
+            .enable(Metric::FileDescriptor)
+            .port(9090)
+            .options(|args| args.metric_prefix = "pi".to_string())
+            .build()
+            .await;
+
+        assert_eq!(exporter.args.port, 9090);
+        assert_eq!(exporter.metrics_handler().names(), ["file_descriptor"]);
+        assert!(exporter.metrics_handler().handle(&Default::default(), None).await.unwrap().contains("\npi_file_descriptors_allocated "));
+    }
+
+    #[tokio::test]
+    async fn fallback() {
+        let exporter = RaspiExporter::builder().enable(Metric::Reset).build().await;
+        let metrics = exporter.metrics_handler().handle(&Default::default(), None).await.unwrap();
+
+        assert!(metrics.contains(&format!("\nraspi_fallback_mode {}\n", u8::from(!has_videocore()))));
+        assert_eq!(metrics.contains("\nraspi_reset"), has_videocore());
+    }
+}
@@ -0,0 +1,216 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{atomic::AtomicU64, Mutex},
+    time::Duration,
+};
+
+use anyhow::Context;
+use prometheus_client::{metrics::gauge::Gauge, registry::Registry};
+
+use crate::{metrics::Registerer, parser::temperature::TemperatureState};
+
+/// Fan driven through sysfs, either with PWM or by switching a GPIO.
+#[derive(Clone, Debug)]
+pub enum FanOutput {
+    /// Channel of a PWM chip such as /sys/class/pwm/pwmchip0/pwm0, with the period of its signal
+    Pwm {
+        path: PathBuf,
+        period: Duration,
+    },
+    /// GPIO such as /sys/class/gpio/gpio14, on whenever the duty is above zero
+    Gpio(PathBuf),
+}
+
+/// Drives a fan with the duty of a curve at the temperature, meant to be fed by a sampler of the temperature.
+///
+/// The duty lowers only once the temperature has dropped `hysteresis` degrees below the one it was raised at, so that the
+/// fan doesn't keep speeding up and slowing down around a point of the curve. It stays as it is while the temperature can't
+/// be read.
+#[derive(Debug)]
+pub struct FanController {
+    output: FanOutput,
+    // Duties in percent at temperatures in Celsius, by the temperature
+    curve: Vec<(f64, f64)>,
+    hysteresis: f64,
+    // Duty last commanded, none until the output is set up
+    current: Mutex<Option<f64>>,
+    duty: Gauge<f64, AtomicU64>,
+}
+
+impl FanController {
+    pub fn new(output: FanOutput, mut curve: Vec<(f64, f64)>, registry: &mut Registry) -> Self {
+        let duty = Gauge::<f64, AtomicU64>::default();
+        registry.register(
+            "fan_duty_ratio",
+            "Duty cycle commanded to the fan, from 0 to 1",
+            duty.clone(),
+        );
+        curve.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+
+        Self {
+            output,
+            curve,
+            hysteresis: 0.0,
+            current: Mutex::new(None),
+            duty,
+        }
+    }
+
+    pub fn hysteresis(self, hysteresis: f64) -> Self {
+        Self {
+            hysteresis,
+            ..self
+        }
+    }
+
+    // Duty in percent to command at `celsius`, given the current one
+    fn next(&self, current: Option<f64>, celsius: f64) -> f64 {
+        let duty = interpolate(&self.curve, celsius);
+        match current {
+            Some(current) if duty < current => interpolate(&self.curve, celsius + self.hysteresis).min(current),
+            _ => duty,
+        }
+    }
+
+    async fn set_up(&self) -> anyhow::Result<()> {
+        match &self.output {
+            FanOutput::Pwm { path, period } => {
+                export(path, "pwm").await?;
+                write(&path.join("period"), &period.as_nanos().to_string()).await?;
+                write(&path.join("enable"), "1").await
+            },
+            FanOutput::Gpio(path) => {
+                export(path, "gpio").await?;
+                write(&path.join("direction"), "out").await
+            },
+        }
+    }
+
+    async fn command(&self, duty: f64) -> anyhow::Result<f64> {
+        match &self.output {
+            FanOutput::Pwm { path, period } => {
+                let duty_cycle = (period.as_nanos() as f64 * duty / 100.0).round() as u128;
+                write(&path.join("duty_cycle"), &duty_cycle.to_string()).await?;
+                Ok(duty)
+            },
+            FanOutput::Gpio(path) => {
+                let on = duty > 0.0;
+                write(&path.join("value"), if on { "1" } else { "0" }).await?;
+                Ok(if on { 100.0 } else { 0.0 })
+            },
+        }
+    }
+}
+
+impl Registerer for FanController {
+    type Item = TemperatureState;
+
+    async fn update(&self, state: Self::Item) -> anyhow::Result<()> {
+        let current = *self.current.lock().expect("failed to lock current mutex");
+        if current.is_none() {
+            self.set_up().await.context("fan setup error")?;
+        }
+
+        let duty = self.next(current, state.celsius);
+        if current == Some(duty) {
+            return Ok(());
+        }
+        let duty = self.command(duty).await.context("fan control error")?;
+        tracing::debug!("commanded fan duty of {duty}% at {}°C", state.celsius);
+        *self.current.lock().expect("failed to lock current mutex") = Some(duty);
+        self.duty.set(duty / 100.0);
+
+        Ok(())
+    }
+}
+
+// Duty of the curve at `celsius`, linear between its points and flat beyond them
+fn interpolate(curve: &[(f64, f64)], celsius: f64) -> f64 {
+    let Some(index) = curve.iter().position(|(temperature, _)| celsius < *temperature) else {
+        return curve.last().map_or(100.0, |(_, duty)| *duty);
+    };
+    if index == 0 {
+        return curve[0].1;
+    }
+
+    let ((t0, d0), (t1, d1)) = (curve[index - 1], curve[index]);
+    d0 + (d1 - d0) * (celsius - t0) / (t1 - t0)
+}
+
+// Exports the channel or the GPIO of `path` named like pwm0 or gpio14 to userspace unless it is already
+async fn export(path: &Path, prefix: &str) -> anyhow::Result<()> {
+    if tokio::fs::try_exists(path).await? {
+        return Ok(());
+    }
+
+    let number = path
+        .file_name()
+        .and_then(|name| name.to_str()?.strip_prefix(prefix))
+        .with_context(|| format!("{prefix} path must end with {prefix} followed by a number: {path:?}"))?;
+    let parent = path.parent().with_context(|| format!("{prefix} path without a parent: {path:?}"))?;
+    write(&parent.join("export"), number).await
+}
+
+async fn write(path: &Path, value: &str) -> anyhow::Result<()> {
+    tokio::fs::write(path, value).await.with_context(|| format!("file write error: {path:?}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process, time::Duration};
+
+    use prometheus_client::{encoding::text, registry::Registry};
+
+    use crate::{
+        fan::{interpolate, FanController, FanOutput},
+        metrics::Registerer,
+        parser::temperature::TemperatureState,
+    };
+
+    #[test]
+    fn curve() {
+        let curve = [(50.0, 0.0), (60.0, 40.0), (70.0, 100.0)];
+
+        assert_eq!(interpolate(&curve, 45.0), 0.0);
+        assert_eq!(interpolate(&curve, 55.0), 20.0);
+        assert_eq!(interpolate(&curve, 65.0), 70.0);
+        assert_eq!(interpolate(&curve, 80.0), 100.0);
+    }
+
+    #[test]
+    fn hysteresis() {
+        let controller = FanController::new(FanOutput::Gpio("/sys/class/gpio/gpio14".into()), vec![(60.0, 40.0), (50.0, 0.0)], &mut Registry::default())
+            .hysteresis(2.0);
+
+        assert_eq!(controller.next(None, 55.0), 20.0);
+        // Rising follows the curve
+        assert_eq!(controller.next(Some(20.0), 57.5), 30.0);
+        // Falling less than the hysteresis keeps the duty
+        assert_eq!(controller.next(Some(30.0), 56.0), 30.0);
+        assert_eq!(controller.next(Some(30.0), 54.0), 24.0);
+    }
+
+    #[tokio::test]
+    async fn pwm() {
+        let chip = env::temp_dir().join(format!("raspi_exporter_fan_{}", process::id()));
+        let channel = chip.join("pwm0");
+        fs::create_dir_all(&channel).unwrap();
+
+        let mut registry = Registry::default();
+        let output = FanOutput::Pwm {
+            path: channel.clone(),
+            period: Duration::from_micros(40),
+        };
+        let controller = FanController::new(output, vec![(50.0, 0.0), (70.0, 100.0)], &mut registry);
+        controller.update(TemperatureState { celsius: 65.0 }).await.unwrap();
+
+        let read = |name: &str| fs::read_to_string(channel.join(name)).unwrap();
+        let (period, duty_cycle, enable) = (read("period"), read("duty_cycle"), read("enable"));
+        let mut buffer = String::new();
+        text::encode_registry(&mut buffer, &registry).unwrap();
+        fs::remove_dir_all(&chip).unwrap();
+
+        assert_eq!((period.as_str(), duty_cycle.as_str(), enable.as_str()), ("40000", "30000", "1"));
+        assert!(buffer.contains("\nfan_duty_ratio 0.75\n"));
+    }
+}
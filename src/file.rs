@@ -0,0 +1,100 @@
+use std::{fmt::Debug, path::Path};
+
+use anyhow::Context;
+use tracing::Level;
+
+use crate::{cache::ScrapeCache, executor::Executor};
+
+#[derive(Debug)]
+pub struct FileExecutor<P> {
+    path: P,
+}
+
+impl<P> FileExecutor<P> {
+    pub fn new(path: P) -> Self {
+        Self {
+            path,
+        }
+    }
+}
+
+impl<P> Executor for FileExecutor<P>
+where
+    P: AsRef<Path> + Debug + Send + Sync,
+{
+    #[tracing::instrument(skip_all, fields(path = ?self.path), ret(level = Level::DEBUG))]
+    async fn execute(&self) -> anyhow::Result<String> {
+        ScrapeCache::execute(format!("{self:?}"), async {
+            let result = tokio::fs::read_to_string(&self.path)
+                .await
+                .with_context(|| format!("file read error: {self:?}"))?;
+
+            Ok(result)
+        }).await
+    }
+}
+
+/// Lists the entry names of a directory, one per line.
+#[derive(Debug)]
+pub struct DirectoryExecutor<P> {
+    path: P,
+}
+
+impl<P> DirectoryExecutor<P> {
+    pub fn new(path: P) -> Self {
+        Self {
+            path,
+        }
+    }
+}
+
+impl<P> Executor for DirectoryExecutor<P>
+where
+    P: AsRef<Path> + Debug + Send + Sync,
+{
+    #[tracing::instrument(skip_all, fields(path = ?self.path), ret(level = Level::DEBUG))]
+    async fn execute(&self) -> anyhow::Result<String> {
+        let read_dir_error = || format!("directory read error: {self:?}");
+
+        let mut entries = tokio::fs::read_dir(&self.path).await.with_context(read_dir_error)?;
+        let mut names = Vec::new();
+        while let Some(entry) = entries.next_entry().await.with_context(read_dir_error)? {
+            names.push(entry.file_name().to_string_lossy().into_owned());
+        }
+
+        Ok(names.join("\n"))
+    }
+}
+
+/// Reads every single-line file in a directory as lines of `<name> <content>`.
+#[derive(Debug)]
+pub struct DirectoryFilesExecutor<P> {
+    path: P,
+}
+
+impl<P> DirectoryFilesExecutor<P> {
+    pub fn new(path: P) -> Self {
+        Self {
+            path,
+        }
+    }
+}
+
+impl<P> Executor for DirectoryFilesExecutor<P>
+where
+    P: AsRef<Path> + Debug + Send + Sync,
+{
+    #[tracing::instrument(skip_all, fields(path = ?self.path), ret(level = Level::DEBUG))]
+    async fn execute(&self) -> anyhow::Result<String> {
+        let read_dir_error = || format!("directory read error: {self:?}");
+
+        let mut entries = tokio::fs::read_dir(&self.path).await.with_context(read_dir_error)?;
+        let mut lines = Vec::new();
+        while let Some(entry) = entries.next_entry().await.with_context(read_dir_error)? {
+            let content = tokio::fs::read_to_string(entry.path()).await.with_context(read_dir_error)?;
+            lines.push(format!("{} {}", entry.file_name().to_string_lossy(), content.trim()));
+        }
+
+        Ok(lines.join("\n"))
+    }
+}
@@ -0,0 +1,246 @@
+use std::{collections::HashMap, time::Duration};
+
+use anyhow::Context;
+use clap::ValueEnum;
+use futures::future;
+use http_body_util::Full;
+use hyper::{header::{ACCEPT, USER_AGENT}, Request, Uri};
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use prometheus_client::{
+    encoding::{text, EncodeLabelSet},
+    metrics::{family::Family, gauge::Gauge},
+    registry::Registry,
+};
+use tokio::time;
+
+use crate::{
+    cli::Metric,
+    client::Client,
+    format::{join_sample, split_sample, Format},
+    metrics::{Filter, Handler},
+};
+
+/// Scrapes other instances of the exporter and merges their metrics, labeling them with the `instance` they come from.
+///
+/// Each of `instances` is the URL of a metrics endpoint such as `http://pi-01:9100/metrics`, and its instance is the host
+/// and port of the URL like the `instance` label of Prometheus.
+pub struct Fleet {
+    instances: Vec<Uri>,
+    client: Client,
+    timeout: Duration,
+    registry: Registry,
+    up: Family<InstanceLabels, Gauge>,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct InstanceLabels {
+    instance: String,
+}
+
+impl Fleet {
+    /// Registers whether each instance was scraped in `registry`, which gives it its prefix.
+    pub fn new(instances: Vec<Uri>, client: Client, mut registry: Registry) -> Self {
+        let up = Family::<InstanceLabels, Gauge>::default();
+        registry.register("fleet_up", "Whether the last scrape of the instance succeeded (1) or failed (0)", up.clone());
+
+        Self {
+            instances,
+            client,
+            timeout: Duration::from_secs(10),
+            registry,
+            up,
+        }
+    }
+
+    /// Gives up scraping an instance taking longer than `timeout`, unless the scrape of the fleet has a shorter one.
+    pub fn timeout(self, timeout: Duration) -> Self {
+        Self {
+            timeout,
+            ..self
+        }
+    }
+
+    pub fn instances(&self) -> &[Uri] {
+        &self.instances
+    }
+
+    async fn scrape(&self, url: &Uri, filter: &Filter) -> anyhow::Result<String> {
+        // Lets the instances select the metrics rather than collecting all of them
+        let params = filter
+            .collect
+            .iter()
+            .map(|name| ("collect[]", name))
+            .chain(filter.exclude.iter().map(|name| ("exclude[]", name)))
+            .map(|(key, name)| format!("{key}={}", utf8_percent_encode(name, NON_ALPHANUMERIC)))
+            .collect::<Vec<_>>();
+        let url = match (params.is_empty(), url.query()) {
+            (true, _) => url.to_string(),
+            (false, Some(_)) => format!("{url}&{}", params.join("&")),
+            (false, None) => format!("{url}?{}", params.join("&")),
+        };
+
+        let request = Request::get(&url)
+            .header(ACCEPT, Format::OpenMetrics.content_type())
+            .header(USER_AGENT, concat!("raspi_exporter/", env!("CARGO_PKG_VERSION")))
+            .body(Full::default())?;
+        let response = self.client.send(request).await?;
+        if !response.status().is_success() {
+            anyhow::bail!("scrape failed with status {}: {url}", response.status());
+        }
+
+        String::from_utf8(response.body().to_vec()).with_context(|| format!("invalid response: {url}"))
+    }
+}
+
+impl Handler for Fleet {
+    // The instances are exporters too, so that they take the same names
+    fn names(&self) -> Vec<String> {
+        Metric::value_variants().iter().map(ToString::to_string).collect()
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn handle(&self, filter: &Filter, timeout: Option<Duration>) -> anyhow::Result<String> {
+        let timeout = timeout.map_or(self.timeout, |timeout| timeout.min(self.timeout));
+        let scrapes = future::join_all(self.instances.iter().map(|url| async move {
+            let instance = instance(url);
+            let result = time::timeout(timeout, self.scrape(url, filter))
+                .await
+                .unwrap_or_else(|_| Err(anyhow::anyhow!("scrape timed out: {url}")));
+            let scrape = match result {
+                Ok(scrape) => Some((instance.clone(), scrape)),
+                Err(err) => {
+                    tracing::error!("failed to scrape {instance}\nError: {err:?}");
+                    None
+                },
+            };
+            self.up.get_or_create(&InstanceLabels { instance }).set(i64::from(scrape.is_some()));
+            scrape
+        }))
+        .await;
+
+        let mut buffer = String::new();
+        text::encode_registry(&mut buffer, &self.registry)?;
+        let scrapes = scrapes.into_iter().flatten().chain([(String::new(), buffer)]).collect::<Vec<_>>();
+
+        merge(&scrapes)
+    }
+
+    async fn readiness(&self) -> Vec<(&'static str, bool)> {
+        Vec::new()
+    }
+}
+
+// Host and port of a URL, with the default port of its scheme when it has none
+fn instance(url: &Uri) -> String {
+    let host = url.host().unwrap_or_default();
+    let port = url.port_u16().unwrap_or(if url.scheme_str() == Some("https") { 443 } else { 80 });
+
+    format!("{host}:{port}")
+}
+
+// Merges the metrics of the instances by family, since OpenMetrics takes the samples of a family in one place. The metrics
+// of an empty instance are left unlabeled
+fn merge(scrapes: &[(String, String)]) -> anyhow::Result<String> {
+    // Metadata and samples by family in the order the families first appear
+    let mut families = Vec::<(&str, Vec<&str>, Vec<String>)>::new();
+    let mut indices = HashMap::new();
+    for (instance, openmetrics) in scrapes {
+        let mut index = None;
+        // Families whose metadata this instance has given, so that the first instance giving them wins
+        let mut seen = Vec::new();
+        for line in openmetrics.lines().filter(|line| *line != "# EOF") {
+            if let Some(metadata) = line.strip_prefix("# ") {
+                let name = metadata.split(' ').nth(1).with_context(|| format!("invalid metadata: {line}"))?;
+                let family = *indices.entry(name).or_insert_with(|| {
+                    families.push((name, Vec::new(), Vec::new()));
+                    seen.push(name);
+                    families.len() - 1
+                });
+                if seen.contains(&name) {
+                    families[family].1.push(line);
+                }
+                index = Some(family);
+                continue;
+            }
+
+            let family = index.with_context(|| format!("sample without metadata: {line}"))?;
+            let (name, mut labels, rest) = split_sample(line).with_context(|| format!("invalid sample: {line}"))?;
+            if !instance.is_empty() {
+                labels.retain(|(label, _)| label != "instance");
+                labels.insert(0, ("instance".to_string(), instance.clone()));
+            }
+            families[family].2.push(join_sample(name, &labels, rest));
+        }
+    }
+
+    let mut buffer = String::new();
+    for (_, metadata, samples) in families {
+        for line in metadata.into_iter().map(str::to_string).chain(samples) {
+            buffer.push_str(&line);
+            buffer.push('\n');
+        }
+    }
+    buffer.push_str("# EOF\n");
+
+    Ok(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::Uri;
+
+    use crate::fleet::{instance, merge};
+
+    #[test]
+    fn instances() {
+        assert_eq!(instance(&Uri::from_static("http://pi-01:9100/metrics")), "pi-01:9100");
+        assert_eq!(instance(&Uri::from_static("https://pi-02/metrics")), "pi-02:443");
+    }
+
+    #[test]
+    fn merge_instances() {
+        let pi01 = [
+            "# HELP raspi_oom_kills Number of processes killed by the OOM killer.",
+            "# TYPE raspi_oom_kills counter",
+            "raspi_oom_kills_total 3",
+            "# HELP raspi_soc_temperature_celsius Temperature of the SoC.",
+            "# TYPE raspi_soc_temperature_celsius gauge",
+            "raspi_soc_temperature_celsius 48.5",
+            "# EOF",
+        ].join("\n") + "\n";
+        let pi02 = [
+            "# HELP raspi_soc_temperature_celsius Temperature of the SoC.",
+            "# TYPE raspi_soc_temperature_celsius gauge",
+            "raspi_soc_temperature_celsius{instance=\"local\"} 51.0",
+            "# EOF",
+        ].join("\n") + "\n";
+        let fleet = [
+            "# HELP raspi_fleet_up Whether the last scrape of the instance succeeded (1) or failed (0).",
+            "# TYPE raspi_fleet_up gauge",
+            "raspi_fleet_up{instance=\"pi-01:9100\"} 1",
+            "# EOF",
+        ].join("\n") + "\n";
+        let scrapes = [
+            ("pi-01:9100".to_string(), pi01),
+            ("pi-02:9100".to_string(), pi02),
+            (String::new(), fleet),
+        ];
+
+        assert_eq!(
+            merge(&scrapes).unwrap(),
+            [
+                "# HELP raspi_oom_kills Number of processes killed by the OOM killer.",
+                "# TYPE raspi_oom_kills counter",
+                "raspi_oom_kills_total{instance=\"pi-01:9100\"} 3",
+                "# HELP raspi_soc_temperature_celsius Temperature of the SoC.",
+                "# TYPE raspi_soc_temperature_celsius gauge",
+                "raspi_soc_temperature_celsius{instance=\"pi-01:9100\"} 48.5",
+                "raspi_soc_temperature_celsius{instance=\"pi-02:9100\"} 51.0",
+                "# HELP raspi_fleet_up Whether the last scrape of the instance succeeded (1) or failed (0).",
+                "# TYPE raspi_fleet_up gauge",
+                "raspi_fleet_up{instance=\"pi-01:9100\"} 1",
+                "# EOF",
+            ].join("\n") + "\n",
+        );
+    }
+}
@@ -0,0 +1,213 @@
+use std::{ffi::OsStr, fmt::Debug, io::ErrorKind, path::Path, pin::Pin, process::Stdio, time::Duration};
+
+use anyhow::Context;
+use tokio::{io::{AsyncBufRead, AsyncBufReadExt, BufReader}, process::Command, task::JoinHandle, time};
+use tracing::Level;
+
+use crate::{metrics::Registerer, parser::Parser};
+
+/// Interval between reopening a source that ended or failed.
+const REOPEN_INTERVAL: Duration = Duration::from_secs(5);
+
+pub trait LineSource {
+    fn open(&self) -> impl Future<Output = anyhow::Result<Pin<Box<dyn AsyncBufRead + Send>>>> + Send;
+}
+
+/// Reads a file that blocks until new lines are written, such as `/dev/kmsg`.
+#[derive(Debug)]
+pub struct FileLineSource<P> {
+    path: P,
+}
+
+impl<P> FileLineSource<P> {
+    pub fn new(path: P) -> Self {
+        Self {
+            path,
+        }
+    }
+}
+
+impl<P> LineSource for FileLineSource<P>
+where
+    P: AsRef<Path> + Debug + Send + Sync,
+{
+    #[tracing::instrument(skip_all, fields(path = ?self.path), level = Level::DEBUG)]
+    async fn open(&self) -> anyhow::Result<Pin<Box<dyn AsyncBufRead + Send>>> {
+        let file = tokio::fs::File::open(&self.path)
+            .await
+            .with_context(|| format!("file open error: {self:?}"))?;
+
+        Ok(Box::pin(BufReader::new(file)))
+    }
+}
+
+/// Reads the output of a command that keeps running, such as `journalctl --follow`.
+#[derive(Debug)]
+pub struct CommandLineSource<S, I> {
+    command: S,
+    args: I,
+}
+
+impl<S, I> CommandLineSource<S, I> {
+    pub fn new(command: S, args: I) -> Self {
+        Self {
+            command,
+            args,
+        }
+    }
+}
+
+impl<S, I> LineSource for CommandLineSource<S, I>
+where
+    S: AsRef<OsStr> + Debug + Send + Sync,
+    I: IntoIterator<Item = S> + Debug + Clone + Send + Sync,
+{
+    #[tracing::instrument(skip_all, fields(command = ?self.command, args = ?self.args), level = Level::DEBUG)]
+    async fn open(&self) -> anyhow::Result<Pin<Box<dyn AsyncBufRead + Send>>> {
+        // The command is left running when the output is dropped, and exits by writing to the closed pipe
+        let mut child = Command::new(&self.command)
+            .args(self.args.clone())
+            .stdout(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("command execution error: {self:?}"))?;
+        let stdout = child.stdout.take().with_context(|| format!("command output error: {self:?}"))?;
+
+        Ok(Box::pin(BufReader::new(stdout)))
+    }
+}
+
+/// Parses every line of a source in the background as it arrives, independently of scrapes.
+///
+/// Used for metrics that count events, which would be missed by reading a state on scrapes.
+/// Lines parsed into `None` are skipped.
+pub struct Follower<S, P, R> {
+    name: &'static str,
+    source: S,
+    parser: P,
+    registerer: R,
+}
+
+impl<S, P, R> Follower<S, P, R>
+where
+    S: LineSource + Send + Sync + 'static,
+    P: Parser<Item = Option<R::Item>> + Send + Sync + 'static,
+    R: Registerer + Send + Sync + 'static,
+    R::Item: Send,
+{
+    pub fn new(name: &'static str, source: S, parser: P, registerer: R) -> Self {
+        Self {
+            name,
+            source,
+            parser,
+            registerer,
+        }
+    }
+
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                if let Err(err) = self.follow().await.with_context(|| follower_error(self.name)) {
+                    tracing::error!("{err:?}");
+                }
+
+                time::sleep(REOPEN_INTERVAL).await;
+            }
+        })
+    }
+
+    async fn follow(&self) -> anyhow::Result<()> {
+        let mut lines = self.source.open().await?.lines();
+
+        loop {
+            let line = match lines.next_line().await {
+                Ok(Some(line)) => line,
+                Ok(None) => return Ok(()),
+                // /dev/kmsg reports records overwritten before being read, and the next read continues after them
+                Err(err) if err.kind() == ErrorKind::BrokenPipe => {
+                    tracing::warn!("{} missed lines", self.name);
+                    continue;
+                },
+                Err(err) => return Err(err.into()),
+            };
+
+            match self.parser.parse(&line) {
+                Ok(Some(state)) => self.registerer.update(state).await?,
+                Ok(None) => {},
+                Err(err) => tracing::warn!("{err:?}"),
+            }
+        }
+    }
+}
+
+fn follower_error(name: &str) -> String {
+    format!("{name} follower error")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::Cursor,
+        pin::Pin,
+        sync::{atomic::{AtomicUsize, Ordering}, Arc},
+        time::Duration,
+    };
+
+    use tokio::io::AsyncBufRead;
+
+    use crate::{
+        follower::{Follower, LineSource},
+        metrics::Registerer,
+        parser::Parser,
+    };
+
+    struct StaticLineSource(&'static str);
+
+    impl LineSource for StaticLineSource {
+        async fn open(&self) -> anyhow::Result<Pin<Box<dyn AsyncBufRead + Send>>> {
+            Ok(Box::pin(Cursor::new(self.0)))
+        }
+    }
+
+    struct LengthParser;
+
+    impl Parser for LengthParser {
+        type Item = Option<usize>;
+
+        fn parse(&self, input: &str) -> anyhow::Result<Self::Item> {
+            match input {
+                "invalid" => anyhow::bail!("invalid input: {input}"),
+                "skip" => Ok(None),
+                _ => Ok(Some(input.len())),
+            }
+        }
+    }
+
+    struct SumRegisterer(Arc<AtomicUsize>);
+
+    impl Registerer for SumRegisterer {
+        type Item = usize;
+
+        async fn update(&self, state: Self::Item) -> anyhow::Result<()> {
+            self.0.fetch_add(state, Ordering::SeqCst);
+
+            Ok(())
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn spawn() {
+        let sum = Arc::new(AtomicUsize::new(0));
+
+        let handle = Follower::new(
+            "mock",
+            StaticLineSource("a\nskip\ninvalid\nbcd\n"),
+            LengthParser,
+            SumRegisterer(sum.clone()),
+        ).spawn();
+        // Reads the source immediately, then reopens it after it ended
+        tokio::time::sleep(Duration::from_secs(7)).await;
+        handle.abort();
+
+        assert_eq!(sum.load(Ordering::SeqCst), 8);
+    }
+}
@@ -0,0 +1,355 @@
+use std::collections::BTreeMap;
+
+use anyhow::Context;
+
+pub mod csv;
+pub mod graphite;
+pub mod influx;
+pub mod parquet;
+pub mod protobuf;
+pub mod remote_write;
+pub mod statsd;
+
+/// Exposition format of the metrics endpoint negotiated with the `Accept` header.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    OpenMetrics,
+    // Prometheus text format 0.0.4, for scrapers that don't understand OpenMetrics
+    Text,
+    // Delimited protobuf MetricFamily messages
+    Protobuf,
+}
+
+impl Format {
+    /// Picks the format with the highest quality in an `Accept` header, OpenMetrics when neither is accepted.
+    pub fn negotiate(accept: Option<&str>) -> Self {
+        let Some(accept) = accept else {
+            return Self::OpenMetrics;
+        };
+
+        let mut best = None::<(Self, f32)>;
+        for media_range in accept.split(',') {
+            let mut params = media_range.split(';').map(str::trim);
+            let format = match params.next() {
+                Some("application/openmetrics-text") => Self::OpenMetrics,
+                Some("text/plain") => Self::Text,
+                Some("application/vnd.google.protobuf") => Self::Protobuf,
+                _ => continue,
+            };
+            let params = params.collect::<Vec<_>>();
+            if format == Self::Protobuf
+                && !(params.contains(&"proto=io.prometheus.client.MetricFamily") && params.contains(&"encoding=delimited"))
+            {
+                continue;
+            }
+            let quality = params
+                .iter()
+                .filter_map(|param| param.strip_prefix("q="))
+                .find_map(|q| q.parse::<f32>().ok())
+                .unwrap_or(1.0);
+
+            if quality > 0.0 && best.is_none_or(|(_, best)| quality > best) {
+                best = Some((format, quality));
+            }
+        }
+
+        best.map_or(Self::OpenMetrics, |(format, _)| format)
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::OpenMetrics => "application/openmetrics-text; version=1.0.0; charset=utf-8",
+            Self::Text => "text/plain; version=0.0.4; charset=utf-8",
+            Self::Protobuf => "application/vnd.google.protobuf; proto=io.prometheus.client.MetricFamily; encoding=delimited",
+        }
+    }
+
+    /// Converts metrics encoded in OpenMetrics into this format.
+    pub fn encode(&self, openmetrics: String) -> anyhow::Result<Vec<u8>> {
+        let encoded = match self {
+            Self::OpenMetrics => state_sets(openmetrics, false)?.into_bytes(),
+            Self::Text => to_text(&state_sets(openmetrics, true)?).into_bytes(),
+            Self::Protobuf => protobuf::encode(&state_sets(openmetrics, true)?)?,
+        };
+
+        Ok(encoded)
+    }
+}
+
+// The text format names counters and infos by their samples, and has neither units nor # EOF
+fn to_text(openmetrics: &str) -> String {
+    let mut buffer = String::with_capacity(openmetrics.len());
+    let mut help = None;
+    for line in openmetrics.lines() {
+        if line == "# EOF" || line.starts_with("# UNIT ") {
+            continue;
+        }
+        if let Some(rest) = line.strip_prefix("# HELP ") {
+            help = Some(rest);
+            continue;
+        }
+
+        let Some(rest) = line.strip_prefix("# TYPE ") else {
+            buffer.push_str(line);
+            buffer.push('\n');
+            continue;
+        };
+        let Some((name, metric_type)) = rest.split_once(' ') else {
+            continue;
+        };
+        let (name, metric_type) = match metric_type {
+            "counter" => (format!("{name}_total"), "counter"),
+            "info" => (format!("{name}_info"), "gauge"),
+            "stateset" => (name.to_string(), "gauge"),
+            "gauge" | "histogram" | "summary" => (name.to_string(), metric_type),
+            _ => (name.to_string(), "untyped"),
+        };
+
+        if let Some(help) = help.take().and_then(|help| help.split_once(' ')) {
+            buffer.push_str(&format!("# HELP {name} {}\n", help.1));
+        }
+        buffer.push_str(&format!("# TYPE {name} {metric_type}\n"));
+    }
+
+    buffer
+}
+
+/// Sample of OpenMetrics text, named by itself rather than by its family.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Sample {
+    pub(crate) name: String,
+    pub(crate) labels: Vec<(String, String)>,
+    pub(crate) value: f64,
+}
+
+pub(crate) type Labels = Vec<(String, String)>;
+
+/// JSON object of `labels`, sorted by name.
+pub(crate) fn labels_json(labels: &Labels) -> anyhow::Result<String> {
+    Ok(serde_json::to_string(&labels.iter().map(|(name, value)| (name, value)).collect::<BTreeMap<_, _>>())?)
+}
+
+/// Parses the samples of OpenMetrics text, skipping the metadata.
+pub(crate) fn samples(openmetrics: &str) -> impl Iterator<Item = anyhow::Result<Sample>> + '_ {
+    openmetrics
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .map(|line| parse_sample(line).with_context(|| format!("invalid sample: {line}")))
+}
+
+fn parse_sample(line: &str) -> anyhow::Result<Sample> {
+    let (name, labels, rest) = split_sample(line)?;
+    let value = rest.split_whitespace().next().context("missing value")?.parse::<f64>()?;
+
+    Ok(Sample {
+        name: name.to_string(),
+        labels,
+        value,
+    })
+}
+
+/// Turns the families of [`StateSet`](crate::metrics::state_set::StateSet)s, told by the unknown type they are encoded
+/// with, into StateSets labeling their states by the names of the families, or into gauges keeping the labels when `gauges`.
+pub(crate) fn state_sets(openmetrics: String, gauges: bool) -> anyhow::Result<String> {
+    if !openmetrics.lines().any(|line| line.starts_with("# TYPE ") && line.ends_with(" unknown")) {
+        return Ok(openmetrics);
+    }
+
+    let mut buffer = String::with_capacity(openmetrics.len());
+    let mut state_set = None;
+    for line in openmetrics.lines() {
+        if let Some(rest) = line.strip_prefix("# TYPE ") {
+            state_set = rest.strip_suffix(" unknown");
+            match (state_set, gauges) {
+                (Some(family), false) => buffer.push_str(&format!("# TYPE {family} stateset\n")),
+                (Some(family), true) => buffer.push_str(&format!("# TYPE {family} gauge\n")),
+                (None, _) => buffer.push_str(&format!("{line}\n")),
+            }
+            continue;
+        }
+
+        match state_set {
+            Some(family) if !gauges && !line.starts_with('#') => {
+                let (name, mut labels, rest) = split_sample(line).with_context(|| format!("invalid sample: {line}"))?;
+                // Encoded after the labels of the registry, and kept there by relabeling
+                if let Some((label, _)) = labels.last_mut() {
+                    *label = family.to_string();
+                }
+                buffer.push_str(&join_sample(name, &labels, rest));
+            },
+            _ => buffer.push_str(line),
+        }
+        buffer.push('\n');
+    }
+
+    Ok(buffer)
+}
+
+/// Splits a sample line into its name, its unescaped labels and the rest starting with the space before its value.
+pub(crate) fn split_sample(line: &str) -> anyhow::Result<(&str, Labels, &str)> {
+    let index = line.find(['{', ' ']).context("missing value")?;
+    let mut labels = Vec::new();
+    let rest = match line[index..].strip_prefix('{') {
+        Some(mut rest) => {
+            while !rest.starts_with('}') {
+                let (name, after) = rest.split_once("=\"").context("invalid label")?;
+                let (value, after) = split_label_value(after)?;
+                labels.push((name.to_string(), value));
+                rest = after.strip_prefix(',').unwrap_or(after);
+            }
+            &rest[1..]
+        },
+        None => &line[index..],
+    };
+
+    Ok((&line[..index], labels, rest))
+}
+
+/// Joins the parts of a sample line split by [`split_sample`], escaping the label values.
+pub(crate) fn join_sample(name: &str, labels: &[(String, String)], rest: &str) -> String {
+    if labels.is_empty() {
+        return format!("{name}{rest}");
+    }
+
+    let labels = labels
+        .iter()
+        .map(|(name, value)| format!("{name}=\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")))
+        .collect::<Vec<_>>();
+    format!("{name}{{{}}}{rest}", labels.join(","))
+}
+
+// Splits an escaped label value at its closing quote
+fn split_label_value(input: &str) -> anyhow::Result<(String, &str)> {
+    let mut value = String::new();
+    let mut chars = input.char_indices();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '"' => return Ok((value, &input[index + 1..])),
+            '\\' => match chars.next() {
+                Some((_, 'n')) => value.push('\n'),
+                Some((_, c)) => value.push(c),
+                None => break,
+            },
+            c => value.push(c),
+        }
+    }
+
+    anyhow::bail!("unterminated label value")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::format::{samples, Format, Sample};
+
+    #[test]
+    fn negotiate() {
+        assert_eq!(Format::negotiate(None), Format::OpenMetrics);
+        assert_eq!(Format::negotiate(Some("*/*")), Format::OpenMetrics);
+        assert_eq!(Format::negotiate(Some("text/plain;version=0.0.4;q=0.5,*/*;q=0.1")), Format::Text);
+        assert_eq!(
+            Format::negotiate(Some("application/openmetrics-text;version=1.0.0;q=0.5,text/plain;version=0.0.4;q=0.4")),
+            Format::OpenMetrics,
+        );
+        assert_eq!(Format::negotiate(Some("application/openmetrics-text;q=0,text/plain")), Format::Text);
+        assert_eq!(
+            Format::negotiate(Some("application/vnd.google.protobuf;proto=io.prometheus.client.MetricFamily;encoding=delimited;q=0.7,text/plain;q=0.3")),
+            Format::Protobuf,
+        );
+        assert_eq!(Format::negotiate(Some("application/vnd.google.protobuf;encoding=text")), Format::OpenMetrics);
+    }
+
+    #[test]
+    fn encode_text() {
+        let openmetrics = [
+            "# HELP raspi_oom_kills Number of processes killed by the OOM killer.",
+            "# TYPE raspi_oom_kills counter",
+            "raspi_oom_kills_total{} 3",
+            "# HELP raspi_soc_temperature_celsius SoC temperature.",
+            "# TYPE raspi_soc_temperature_celsius gauge",
+            "# UNIT raspi_soc_temperature_celsius celsius",
+            "raspi_soc_temperature_celsius 48.3",
+            "# HELP raspi_vl805 VL805 firmware.",
+            "# TYPE raspi_vl805 info",
+            "raspi_vl805_info{version=\"000138c0\"} 1",
+            "# EOF",
+        ].join("\n") + "\n";
+
+        assert_eq!(
+            String::from_utf8(Format::Text.encode(openmetrics).unwrap()).unwrap(),
+            [
+                "# HELP raspi_oom_kills_total Number of processes killed by the OOM killer.",
+                "# TYPE raspi_oom_kills_total counter",
+                "raspi_oom_kills_total{} 3",
+                "# HELP raspi_soc_temperature_celsius SoC temperature.",
+                "# TYPE raspi_soc_temperature_celsius gauge",
+                "raspi_soc_temperature_celsius 48.3",
+                "# HELP raspi_vl805_info VL805 firmware.",
+                "# TYPE raspi_vl805_info gauge",
+                "raspi_vl805_info{version=\"000138c0\"} 1",
+            ].join("\n") + "\n",
+        );
+    }
+
+    #[test]
+    fn encode_state_sets() {
+        let openmetrics = [
+            "# HELP raspi_throttling_active State about throttling active currently.",
+            "# TYPE raspi_throttling_active unknown",
+            "raspi_throttling_active{site=\"home\",kind=\"undervoltage\"} 1",
+            "raspi_throttling_active{site=\"home\",kind=\"throttled\"} 0",
+            "# HELP raspi_neighbor_gc_threshold Garbage collection thresholds of the neighbor table.",
+            "# TYPE raspi_neighbor_gc_threshold gauge",
+            "raspi_neighbor_gc_threshold{raspi_neighbor_gc_threshold=\"gc_thresh1\"} 128",
+            "# HELP raspi_throttling_active_seconds Time spent with throttling active.",
+            "# TYPE raspi_throttling_active_seconds counter",
+            "raspi_throttling_active_seconds_total{site=\"home\",kind=\"throttled\"} 0.0",
+            "# HELP raspi_file_descriptors_allocated Number of file descriptors allocated by the system.",
+            "# TYPE raspi_file_descriptors_allocated gauge",
+            "raspi_file_descriptors_allocated{site=\"home\"} 306",
+            "# EOF",
+        ].join("\n") + "\n";
+        let text = String::from_utf8(Format::Text.encode(openmetrics.clone()).unwrap()).unwrap();
+        let openmetrics = String::from_utf8(Format::OpenMetrics.encode(openmetrics).unwrap()).unwrap();
+
+        assert_eq!(
+            openmetrics,
+            [
+                "# HELP raspi_throttling_active State about throttling active currently.",
+                "# TYPE raspi_throttling_active stateset",
+                "raspi_throttling_active{site=\"home\",raspi_throttling_active=\"undervoltage\"} 1",
+                "raspi_throttling_active{site=\"home\",raspi_throttling_active=\"throttled\"} 0",
+                "# HELP raspi_neighbor_gc_threshold Garbage collection thresholds of the neighbor table.",
+                "# TYPE raspi_neighbor_gc_threshold gauge",
+                "raspi_neighbor_gc_threshold{raspi_neighbor_gc_threshold=\"gc_thresh1\"} 128",
+                "# HELP raspi_throttling_active_seconds Time spent with throttling active.",
+                "# TYPE raspi_throttling_active_seconds counter",
+                "raspi_throttling_active_seconds_total{site=\"home\",kind=\"throttled\"} 0.0",
+                "# HELP raspi_file_descriptors_allocated Number of file descriptors allocated by the system.",
+                "# TYPE raspi_file_descriptors_allocated gauge",
+                "raspi_file_descriptors_allocated{site=\"home\"} 306",
+                "# EOF",
+            ].join("\n") + "\n",
+        );
+        // Falls back to gauges keeping their labels
+        assert!(text.contains("# TYPE raspi_throttling_active gauge\nraspi_throttling_active{site=\"home\",kind=\"undervoltage\"} 1\n"));
+    }
+
+    #[test]
+    fn parse_samples() {
+        let openmetrics = [
+            "# TYPE a counter",
+            "a_total{k=\"v\\\"\",l=\"\"} 3",
+            "b 2.5 1700000000",
+            "# EOF",
+        ].join("\n");
+
+        assert_eq!(
+            samples(&openmetrics).collect::<anyhow::Result<Vec<_>>>().unwrap(),
+            [
+                Sample { name: "a_total".to_string(), labels: vec![("k".to_string(), "v\"".to_string()), ("l".to_string(), String::new())], value: 3.0 },
+                Sample { name: "b".to_string(), labels: Vec::new(), value: 2.5 },
+            ],
+        );
+        assert!(samples("a{k=\"v} 1").next().unwrap().is_err());
+    }
+}
@@ -0,0 +1,51 @@
+//! Encoder of CSV rows from OpenMetrics text.
+
+use crate::format::{labels_json, samples};
+
+/// Header of the rows of [`encode`].
+pub(crate) const HEADER: &str = "time,name,labels,value\n";
+
+/// Encodes every sample as a row of `time`, its name, its labels as a JSON object and its value.
+pub(crate) fn encode(openmetrics: &str, time: &str) -> anyhow::Result<String> {
+    let mut rows = String::new();
+    for sample in samples(openmetrics) {
+        let sample = sample?;
+        let row = [time, &sample.name, &labels_json(&sample.labels)?, &sample.value.to_string()].map(escape).join(",");
+        rows.push_str(&row);
+        rows.push('\n');
+    }
+
+    Ok(rows)
+}
+
+// Quotes a field having a separator, a quote or a line break in it, doubling the quotes
+fn escape(field: &str) -> String {
+    match field.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        false => field.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::format::csv::encode;
+
+    #[test]
+    fn encode_samples() {
+        let openmetrics = [
+            "# TYPE raspi_soc_temperature_celsius gauge",
+            "raspi_soc_temperature_celsius 48.5",
+            "# TYPE raspi_filesystem_avail_bytes gauge",
+            "raspi_filesystem_avail_bytes{mountpoint=\"/\",fstype=\"ext4\"} 1000",
+            "# EOF",
+        ].join("\n");
+
+        assert_eq!(
+            encode(&openmetrics, "2024-01-01T00:00:00Z").unwrap(),
+            [
+                "2024-01-01T00:00:00Z,raspi_soc_temperature_celsius,{},48.5\n",
+                "2024-01-01T00:00:00Z,raspi_filesystem_avail_bytes,\"{\"\"fstype\"\":\"\"ext4\"\",\"\"mountpoint\"\":\"\"/\"\"}\",1000\n",
+            ].concat(),
+        );
+    }
+}
@@ -0,0 +1,58 @@
+//! Encoder of the Graphite plaintext protocol from OpenMetrics text.
+
+use crate::format::samples;
+
+/// Encodes every sample as a dotted path of `prefix`, its name and its label values, at `timestamp` in seconds.
+pub(crate) fn encode(openmetrics: &str, prefix: &str, timestamp: u64) -> anyhow::Result<String> {
+    let mut buffer = String::new();
+    for sample in samples(openmetrics) {
+        let sample = sample?;
+        // Carbon drops values that aren't numbers
+        if !sample.value.is_finite() {
+            continue;
+        }
+
+        let nodes = [prefix, &sample.name]
+            .into_iter()
+            .filter(|node| !node.is_empty())
+            .map(ToString::to_string)
+            .chain(sample.labels.iter().map(|(_, value)| escape_node(value)));
+        buffer.push_str(&format!("{} {} {timestamp}\n", nodes.collect::<Vec<_>>().join("."), sample.value));
+    }
+
+    Ok(buffer)
+}
+
+// Dots separate nodes and whitespace separates fields, nor can a node be empty
+fn escape_node(value: &str) -> String {
+    match value {
+        "" => "_".to_string(),
+        value => value.replace(|c: char| !c.is_ascii_alphanumeric() && !matches!(c, '_' | '-' | ':'), "_"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::format::graphite::encode;
+
+    #[test]
+    fn encode_paths() {
+        let openmetrics = [
+            "# HELP raspi_filesystem_size_bytes Size of the filesystem.",
+            "# TYPE raspi_filesystem_size_bytes gauge",
+            "raspi_filesystem_size_bytes{mount_point=\"/boot/firmware\",type=\"\"} 1000",
+            "raspi_soc_temperature_celsius NaN",
+            "raspi_soc_temperature_max_celsius 48.3",
+            "# EOF",
+        ].join("\n");
+
+        assert_eq!(
+            encode(&openmetrics, "home.raspberrypi", 1).unwrap(),
+            [
+                "home.raspberrypi.raspi_filesystem_size_bytes._boot_firmware._ 1000 1",
+                "home.raspberrypi.raspi_soc_temperature_max_celsius 48.3 1",
+            ].join("\n") + "\n",
+        );
+        assert_eq!(encode("a 1", "", 1).unwrap(), "a 1 1\n");
+    }
+}
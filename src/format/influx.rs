@@ -0,0 +1,64 @@
+//! Encoder of the InfluxDB line protocol from OpenMetrics text.
+
+use crate::format::samples;
+
+/// Encodes every sample as a point of the measurement named by it, with its labels as tags and a `value` field at
+/// `timestamp` in nanoseconds.
+pub(crate) fn encode(openmetrics: &str, timestamp: i64) -> anyhow::Result<String> {
+    let mut buffer = String::new();
+    for sample in samples(openmetrics) {
+        let sample = sample?;
+        // Fields can't be NaN nor infinite
+        if !sample.value.is_finite() {
+            continue;
+        }
+
+        buffer.push_str(&escape(&sample.name, &[',', ' ']));
+        // Tags can't be empty
+        for (name, value) in sample.labels.iter().filter(|(_, value)| !value.is_empty()) {
+            buffer.push_str(&format!(",{}={}", escape(name, &[',', '=', ' ']), escape(value, &[',', '=', ' '])));
+        }
+        buffer.push_str(&format!(" value={:?} {timestamp}\n", sample.value));
+    }
+
+    Ok(buffer)
+}
+
+// Lines can't contain newlines even if escaped
+fn escape(input: &str, special: &[char]) -> String {
+    input
+        .chars()
+        .map(|c| if c == '\n' { ' ' } else { c })
+        .fold(String::with_capacity(input.len()), |mut buffer, c| {
+            if special.contains(&c) {
+                buffer.push('\\');
+            }
+            buffer.push(c);
+            buffer
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::format::influx::encode;
+
+    #[test]
+    fn encode_points() {
+        let openmetrics = [
+            "# HELP raspi_filesystem_size_bytes Size of the filesystem.",
+            "# TYPE raspi_filesystem_size_bytes gauge",
+            "raspi_filesystem_size_bytes{mount_point=\"/boot/firm ware\",type=\"\"} 1000",
+            "raspi_soc_temperature_celsius NaN",
+            "raspi_oom_kills_total{cgroup=\"a,b=c\"} 3",
+            "# EOF",
+        ].join("\n");
+
+        assert_eq!(
+            encode(&openmetrics, 1).unwrap(),
+            [
+                "raspi_filesystem_size_bytes,mount_point=/boot/firm\\ ware value=1000.0 1",
+                "raspi_oom_kills_total,cgroup=a\\,b\\=c value=3.0 1",
+            ].join("\n") + "\n",
+        );
+    }
+}
@@ -0,0 +1,286 @@
+//! Writer of Parquet files of the samples of OpenMetrics text, uncompressed in the plain encoding with a row group per
+//! collection, whose metadata is encoded with the Thrift compact protocol as parquet.thrift of apache/parquet-format says.
+
+use std::io::{self, Write};
+
+use crate::format::{labels_json, samples};
+
+const MAGIC: &[u8] = b"PAR1";
+
+// Types of the Thrift compact protocol
+const COMPACT_I32: u8 = 5;
+const COMPACT_I64: u8 = 6;
+const COMPACT_BINARY: u8 = 8;
+const COMPACT_LIST: u8 = 9;
+const COMPACT_STRUCT: u8 = 12;
+
+// Enums of parquet.thrift
+const TYPE_INT64: i32 = 2;
+const TYPE_DOUBLE: i32 = 5;
+const TYPE_BYTE_ARRAY: i32 = 6;
+const CONVERTED_UTF8: i32 = 0;
+const CONVERTED_TIMESTAMP_MILLIS: i32 = 9;
+const REPETITION_REQUIRED: i32 = 0;
+const ENCODING_PLAIN: i32 = 0;
+const ENCODING_RLE: i32 = 3;
+const CODEC_UNCOMPRESSED: i32 = 0;
+const PAGE_DATA: i32 = 0;
+
+// Name, physical type and converted type of each column
+const COLUMNS: [(&str, i32, Option<i32>); 4] = [
+    ("time", TYPE_INT64, Some(CONVERTED_TIMESTAMP_MILLIS)),
+    ("name", TYPE_BYTE_ARRAY, Some(CONVERTED_UTF8)),
+    ("labels", TYPE_BYTE_ARRAY, Some(CONVERTED_UTF8)),
+    ("value", TYPE_DOUBLE, None),
+];
+
+/// Writes the samples of collections as rows of their time, name, labels as a JSON object and value.
+///
+/// The file is readable only once [`ParquetWriter::finish`] has written the footer.
+pub(crate) struct ParquetWriter<W> {
+    writer: W,
+    offset: i64,
+    // Encoded RowGroup structs
+    row_groups: Vec<Vec<u8>>,
+    rows: i64,
+}
+
+// Struct of the Thrift compact protocol, whose fields are added in the order of their ids
+#[derive(Default)]
+struct Struct {
+    buffer: Vec<u8>,
+    last: i16,
+}
+
+impl<W> ParquetWriter<W>
+where
+    W: Write,
+{
+    pub(crate) fn new(mut writer: W) -> io::Result<Self> {
+        writer.write_all(MAGIC)?;
+
+        Ok(Self {
+            writer,
+            offset: MAGIC.len() as i64,
+            row_groups: Vec::new(),
+            rows: 0,
+        })
+    }
+
+    /// Writes the samples of `openmetrics` as a row group at `time` in milliseconds since the Unix epoch.
+    pub(crate) fn write(&mut self, openmetrics: &str, time: i64) -> anyhow::Result<()> {
+        let samples = samples(openmetrics).collect::<anyhow::Result<Vec<_>>>()?;
+        if samples.is_empty() {
+            return Ok(());
+        }
+
+        let mut values = [Vec::new(), Vec::new(), Vec::new(), Vec::new()];
+        for sample in &samples {
+            values[0].extend(time.to_le_bytes());
+            for (column, value) in [(1, sample.name.clone()), (2, labels_json(&sample.labels)?)] {
+                values[column].extend((value.len() as u32).to_le_bytes());
+                values[column].extend(value.as_bytes());
+            }
+            values[3].extend(sample.value.to_le_bytes());
+        }
+
+        let mut columns = Vec::new();
+        let mut total_size = 0;
+        for ((name, physical_type, _), data) in COLUMNS.into_iter().zip(values) {
+            let data_page_header = Struct::default()
+                .i32(1, samples.len() as i32)
+                .i32(2, ENCODING_PLAIN)
+                .i32(3, ENCODING_RLE)
+                .i32(4, ENCODING_RLE);
+            let page_header = Struct::default()
+                .i32(1, PAGE_DATA)
+                .i32(2, data.len() as i32)
+                .i32(3, data.len() as i32)
+                .structure(5, data_page_header)
+                .finish();
+            let size = (page_header.len() + data.len()) as i64;
+
+            let metadata = Struct::default()
+                .i32(1, physical_type)
+                .i32_list(2, &[ENCODING_PLAIN, ENCODING_RLE])
+                .binary_list(3, &[name.as_bytes()])
+                .i32(4, CODEC_UNCOMPRESSED)
+                .i64(5, samples.len() as i64)
+                .i64(6, size)
+                .i64(7, size)
+                .i64(9, self.offset);
+            columns.push(Struct::default().i64(2, self.offset).structure(3, metadata).finish());
+
+            self.writer.write_all(&page_header)?;
+            self.writer.write_all(&data)?;
+            self.offset += size;
+            total_size += size;
+        }
+
+        let row_group = Struct::default()
+            .struct_list(1, columns)
+            .i64(2, total_size)
+            .i64(3, samples.len() as i64)
+            .finish();
+        self.row_groups.push(row_group);
+        self.rows += samples.len() as i64;
+        self.writer.flush()?;
+
+        Ok(())
+    }
+
+    /// Writes the footer with the schema and the row groups, returning the underlying writer.
+    pub(crate) fn finish(mut self) -> io::Result<W> {
+        let root = Struct::default().binary(4, b"schema").i32(5, COLUMNS.len() as i32).finish();
+        let schema = [root].into_iter().chain(COLUMNS.iter().map(|(name, physical_type, converted_type)| {
+            let element = Struct::default().i32(1, *physical_type).i32(3, REPETITION_REQUIRED).binary(4, name.as_bytes());
+            match converted_type {
+                Some(converted_type) => element.i32(6, *converted_type),
+                None => element,
+            }.finish()
+        }));
+        let metadata = Struct::default()
+            .i32(1, 1)
+            .struct_list(2, schema.collect())
+            .i64(3, self.rows)
+            .struct_list(4, self.row_groups)
+            .binary(6, concat!("raspi_exporter version ", env!("CARGO_PKG_VERSION")).as_bytes())
+            .finish();
+
+        self.writer.write_all(&metadata)?;
+        self.writer.write_all(&(metadata.len() as u32).to_le_bytes())?;
+        self.writer.write_all(MAGIC)?;
+        self.writer.flush()?;
+
+        Ok(self.writer)
+    }
+}
+
+impl Struct {
+    fn i32(self, id: i16, value: i32) -> Self {
+        self.i64_as(id, COMPACT_I32, value.into())
+    }
+
+    fn i64(self, id: i16, value: i64) -> Self {
+        self.i64_as(id, COMPACT_I64, value)
+    }
+
+    fn binary(mut self, id: i16, value: &[u8]) -> Self {
+        self.field(id, COMPACT_BINARY);
+        varint(&mut self.buffer, value.len() as u64);
+        self.buffer.extend(value);
+        self
+    }
+
+    fn structure(mut self, id: i16, value: Struct) -> Self {
+        self.field(id, COMPACT_STRUCT);
+        self.buffer.extend(value.finish());
+        self
+    }
+
+    fn i32_list(mut self, id: i16, values: &[i32]) -> Self {
+        self.list(id, COMPACT_I32, values.len());
+        for value in values {
+            varint(&mut self.buffer, zigzag((*value).into()));
+        }
+        self
+    }
+
+    fn binary_list(mut self, id: i16, values: &[&[u8]]) -> Self {
+        self.list(id, COMPACT_BINARY, values.len());
+        for value in values {
+            varint(&mut self.buffer, value.len() as u64);
+            self.buffer.extend(*value);
+        }
+        self
+    }
+
+    // `values` are encoded structs
+    fn struct_list(mut self, id: i16, values: Vec<Vec<u8>>) -> Self {
+        self.list(id, COMPACT_STRUCT, values.len());
+        for value in values {
+            self.buffer.extend(value);
+        }
+        self
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        self.buffer.push(0);
+        self.buffer
+    }
+
+    fn i64_as(mut self, id: i16, kind: u8, value: i64) -> Self {
+        self.field(id, kind);
+        varint(&mut self.buffer, zigzag(value));
+        self
+    }
+
+    fn list(&mut self, id: i16, kind: u8, size: usize) {
+        self.field(id, COMPACT_LIST);
+        match size {
+            0..15 => self.buffer.push((size as u8) << 4 | kind),
+            _ => {
+                self.buffer.push(0xf0 | kind);
+                varint(&mut self.buffer, size as u64);
+            },
+        }
+    }
+
+    // Field header with the delta from the id of the previous field when it fits in 4 bits
+    fn field(&mut self, id: i16, kind: u8) {
+        match id - self.last {
+            delta @ 1..=15 => self.buffer.push((delta as u8) << 4 | kind),
+            _ => {
+                self.buffer.push(kind);
+                varint(&mut self.buffer, zigzag(id.into()));
+            },
+        }
+        self.last = id;
+    }
+}
+
+fn varint(buffer: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buffer.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    buffer.push(value as u8);
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::format::parquet::{zigzag, ParquetWriter, Struct};
+
+    #[test]
+    fn compact_struct() {
+        let nested = Struct::default().i32(1, -1);
+        let encoded = Struct::default().i32(1, 3).i64(3, 300).structure(20, nested).i32_list(21, &[0, 3]).finish();
+
+        assert_eq!(encoded, [0x15, 0x06, 0x26, 0xd8, 0x04, 0x0c, 0x28, 0x15, 0x01, 0x00, 0x19, 0x25, 0x00, 0x06, 0x00]);
+        assert_eq!(zigzag(-2), 3);
+    }
+
+    #[test]
+    fn write() {
+        let openmetrics = "# TYPE raspi_soc_temperature_celsius gauge\nraspi_soc_temperature_celsius 48.5\n# EOF\n";
+        let mut writer = ParquetWriter::new(Vec::new()).unwrap();
+        writer.write(openmetrics, 1_700_000_000_000).unwrap();
+        writer.write("# EOF\n", 1_700_000_010_000).unwrap();
+        let file = writer.finish().unwrap();
+
+        assert_eq!(&file[..4], b"PAR1");
+        assert_eq!(&file[file.len() - 4..], b"PAR1");
+        // Time of the only row right after the header of its page
+        let time = 1_700_000_000_000i64.to_le_bytes();
+        assert!(file.windows(time.len()).any(|window| window == time));
+        let footer = u32::from_le_bytes(file[file.len() - 8..file.len() - 4].try_into().unwrap()) as usize;
+        let metadata = &file[file.len() - 8 - footer..file.len() - 8];
+        // Version 1 followed by the list of the root and the four columns
+        assert_eq!(metadata[..3], [0x15, 0x02, 0x19]);
+        assert_eq!(metadata[3], 0x5c);
+    }
+}
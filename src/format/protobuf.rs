@@ -0,0 +1,215 @@
+//! Encoder of the delimited `io.prometheus.client.MetricFamily` protobuf format from OpenMetrics text.
+
+use anyhow::Context;
+
+use crate::format::parse_sample;
+
+// Field numbers and wire types of metrics.proto in prometheus/client_model
+pub(super) const WIRE_VARINT: u64 = 0;
+pub(super) const WIRE_FIXED64: u64 = 1;
+pub(super) const WIRE_LENGTH_DELIMITED: u64 = 2;
+
+const FAMILY_NAME: u64 = 1;
+const FAMILY_HELP: u64 = 2;
+const FAMILY_TYPE: u64 = 3;
+const FAMILY_METRIC: u64 = 4;
+
+const METRIC_LABEL: u64 = 1;
+const METRIC_GAUGE: u64 = 2;
+const METRIC_COUNTER: u64 = 3;
+const METRIC_UNTYPED: u64 = 5;
+
+const LABEL_NAME: u64 = 1;
+const LABEL_VALUE: u64 = 2;
+
+const VALUE: u64 = 1;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum MetricType {
+    Counter = 0,
+    Gauge = 1,
+    Untyped = 3,
+}
+
+#[derive(Debug)]
+struct Family {
+    name: String,
+    help: Option<String>,
+    metric_type: MetricType,
+    metrics: Vec<Vec<u8>>,
+}
+
+pub(crate) fn encode(openmetrics: &str) -> anyhow::Result<Vec<u8>> {
+    let mut buffer = Vec::new();
+    let mut family = None::<Family>;
+    for line in openmetrics.lines() {
+        if line == "# EOF" || line.starts_with("# UNIT ") {
+            continue;
+        }
+        if let Some(rest) = line.strip_prefix("# HELP ") {
+            let (name, help) = rest.split_once(' ').unwrap_or((rest, ""));
+            if let Some(family) = family.replace(Family::new(name)) {
+                family.encode(&mut buffer);
+            }
+            family.as_mut().expect("family was just set").help = Some(unescape(help));
+            continue;
+        }
+        if let Some(rest) = line.strip_prefix("# TYPE ") {
+            let (name, metric_type) = rest.split_once(' ').context("invalid TYPE line")?;
+            let family = match &mut family {
+                Some(family) if family.name == name => family,
+                _ => {
+                    if let Some(family) = family.replace(Family::new(name)) {
+                        family.encode(&mut buffer);
+                    }
+                    family.as_mut().expect("family was just set")
+                },
+            };
+            // Families are named by their samples like the text format
+            (family.name, family.metric_type) = match metric_type {
+                "counter" => (format!("{name}_total"), MetricType::Counter),
+                "info" => (format!("{name}_info"), MetricType::Gauge),
+                // Like Prometheus ingests StateSets
+                "gauge" | "stateset" => (name.to_string(), MetricType::Gauge),
+                _ => (name.to_string(), MetricType::Untyped),
+            };
+            continue;
+        }
+
+        let family = family.as_mut().with_context(|| format!("sample without metadata: {line}"))?;
+        family.metrics.push(encode_sample(line, family.metric_type).with_context(|| format!("invalid sample: {line}"))?);
+    }
+    if let Some(family) = family {
+        family.encode(&mut buffer);
+    }
+
+    Ok(buffer)
+}
+
+impl Family {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            help: None,
+            metric_type: MetricType::Untyped,
+            metrics: Vec::new(),
+        }
+    }
+
+    fn encode(self, buffer: &mut Vec<u8>) {
+        let mut family = Vec::new();
+        encode_bytes(&mut family, FAMILY_NAME, self.name.as_bytes());
+        if let Some(help) = &self.help {
+            encode_bytes(&mut family, FAMILY_HELP, help.as_bytes());
+        }
+        encode_key(&mut family, FAMILY_TYPE, WIRE_VARINT);
+        encode_varint(&mut family, self.metric_type as u64);
+        for metric in &self.metrics {
+            encode_bytes(&mut family, FAMILY_METRIC, metric);
+        }
+
+        encode_varint(buffer, family.len() as u64);
+        buffer.extend(family);
+    }
+}
+
+fn encode_sample(line: &str, metric_type: MetricType) -> anyhow::Result<Vec<u8>> {
+    let sample = parse_sample(line)?;
+    let mut metric = Vec::new();
+    for (name, value) in &sample.labels {
+        let mut label = Vec::new();
+        encode_bytes(&mut label, LABEL_NAME, name.as_bytes());
+        encode_bytes(&mut label, LABEL_VALUE, value.as_bytes());
+        encode_bytes(&mut metric, METRIC_LABEL, &label);
+    }
+
+    let mut inner = Vec::new();
+    encode_key(&mut inner, VALUE, WIRE_FIXED64);
+    inner.extend(sample.value.to_le_bytes());
+    let field = match metric_type {
+        MetricType::Counter => METRIC_COUNTER,
+        MetricType::Gauge => METRIC_GAUGE,
+        MetricType::Untyped => METRIC_UNTYPED,
+    };
+    encode_bytes(&mut metric, field, &inner);
+
+    Ok(metric)
+}
+
+fn unescape(input: &str) -> String {
+    input.replace("\\n", "\n").replace("\\\"", "\"").replace("\\\\", "\\")
+}
+
+pub(super) fn encode_key(buffer: &mut Vec<u8>, field: u64, wire_type: u64) {
+    encode_varint(buffer, (field << 3) | wire_type);
+}
+
+pub(super) fn encode_bytes(buffer: &mut Vec<u8>, field: u64, bytes: &[u8]) {
+    encode_key(buffer, field, WIRE_LENGTH_DELIMITED);
+    encode_varint(buffer, bytes.len() as u64);
+    buffer.extend_from_slice(bytes);
+}
+
+pub(super) fn encode_varint(buffer: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buffer.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buffer.push(value as u8);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::format::protobuf::encode;
+
+    #[test]
+    fn encode_counter() {
+        let openmetrics = [
+            "# HELP a b.",
+            "# TYPE a counter",
+            "a_total{k=\"v\\\"\"} 3",
+            "# EOF",
+        ].join("\n");
+
+        let family_name = [0x0a, 0x07, b'a', b'_', b't', b'o', b't', b'a', b'l'];
+        let family_help = [0x12, 0x02, b'b', b'.'];
+        let family_type = [0x18, 0x00];
+        let label = [0x0a, 0x07, 0x0a, 0x01, b'k', 0x12, 0x02, b'v', b'"'];
+        let counter = [[0x1a, 0x09, 0x09].as_slice(), &3.0_f64.to_le_bytes()].concat();
+        let metric = [label.as_slice(), &counter].concat();
+        let family = [
+            family_name.as_slice(),
+            &family_help,
+            &family_type,
+            &[0x22, metric.len() as u8],
+            &metric,
+        ].concat();
+
+        assert_eq!(encode(&openmetrics).unwrap(), [[family.len() as u8].as_slice(), &family].concat());
+    }
+
+    #[test]
+    fn encode_gauges() {
+        let openmetrics = [
+            "# HELP a .",
+            "# TYPE a gauge",
+            "a 1",
+            "# HELP b .",
+            "# TYPE b gauge",
+            "b{} 2.5",
+            "# EOF",
+        ].join("\n");
+        let result = encode(&openmetrics).unwrap();
+
+        // Two length-delimited families
+        let first = result[0] as usize;
+        assert_eq!(result.len(), 1 + first + 1 + result[1 + first] as usize);
+        assert!(result.ends_with(&2.5_f64.to_le_bytes()));
+    }
+
+    #[test]
+    fn encode_invalid() {
+        assert!(encode("a 1\n").is_err());
+        assert!(encode("# HELP a .\n# TYPE a gauge\na{k=\"v} 1\n").is_err());
+    }
+}
@@ -0,0 +1,112 @@
+//! Encoder of the `prometheus.WriteRequest` protobuf message of the remote write protocol from OpenMetrics text.
+
+use crate::format::{
+    protobuf::{encode_bytes, encode_key, encode_varint, WIRE_FIXED64, WIRE_VARINT},
+    samples,
+    Sample,
+};
+
+// Field numbers of remote.proto and types.proto in prometheus/prometheus
+const REQUEST_TIMESERIES: u64 = 1;
+
+const TIMESERIES_LABEL: u64 = 1;
+const TIMESERIES_SAMPLE: u64 = 2;
+
+const LABEL_NAME: u64 = 1;
+const LABEL_VALUE: u64 = 2;
+
+const SAMPLE_VALUE: u64 = 1;
+const SAMPLE_TIMESTAMP: u64 = 2;
+
+// Longest literal whose length fits in the two bytes after a tag
+const MAX_LITERAL_LENGTH: usize = 0x10000;
+
+/// Encodes every sample as a time series of a single sample at `timestamp` in milliseconds.
+pub(crate) fn encode(openmetrics: &str, timestamp: i64) -> anyhow::Result<Vec<u8>> {
+    let mut buffer = Vec::new();
+    for sample in samples(openmetrics) {
+        encode_bytes(&mut buffer, REQUEST_TIMESERIES, &encode_series(sample?, timestamp));
+    }
+
+    Ok(buffer)
+}
+
+/// Compresses in the snappy block format, which remote write requires, only with literals.
+///
+/// Metrics are small enough not to be worth the size of a real compressor.
+pub(crate) fn compress(input: &[u8]) -> Vec<u8> {
+    let mut buffer = Vec::with_capacity(input.len() + input.len() / MAX_LITERAL_LENGTH * 3 + 13);
+    encode_varint(&mut buffer, input.len() as u64);
+    for literal in input.chunks(MAX_LITERAL_LENGTH) {
+        let length = literal.len() - 1;
+        match length {
+            0..60 => buffer.push((length as u8) << 2),
+            60..0x100 => buffer.extend([60 << 2, length as u8]),
+            _ => buffer.extend([61 << 2, length as u8, (length >> 8) as u8]),
+        }
+        buffer.extend_from_slice(literal);
+    }
+
+    buffer
+}
+
+fn encode_series(sample: Sample, timestamp: i64) -> Vec<u8> {
+    let mut labels = sample.labels;
+    labels.push(("__name__".to_string(), sample.name));
+    // Receivers require labels sorted by name
+    labels.sort();
+
+    let mut series = Vec::new();
+    for (name, value) in labels {
+        let mut label = Vec::new();
+        encode_bytes(&mut label, LABEL_NAME, name.as_bytes());
+        encode_bytes(&mut label, LABEL_VALUE, value.as_bytes());
+        encode_bytes(&mut series, TIMESERIES_LABEL, &label);
+    }
+
+    let mut value = Vec::new();
+    encode_key(&mut value, SAMPLE_VALUE, WIRE_FIXED64);
+    value.extend(sample.value.to_le_bytes());
+    encode_key(&mut value, SAMPLE_TIMESTAMP, WIRE_VARINT);
+    encode_varint(&mut value, timestamp as u64);
+    encode_bytes(&mut series, TIMESERIES_SAMPLE, &value);
+
+    series
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::format::remote_write::{compress, encode};
+
+    #[test]
+    fn encode_sample() {
+        let openmetrics = [
+            "# HELP a b.",
+            "# TYPE a counter",
+            "a_total{z=\"1\",k=\"v\"} 3",
+            "# EOF",
+        ].join("\n");
+
+        let name = [[0x0a, 0x13, 0x0a, 0x08].as_slice(), b"__name__", &[0x12, 0x07], b"a_total"].concat();
+        let k = [0x0a, 0x06, 0x0a, 0x01, b'k', 0x12, 0x01, b'v'];
+        let z = [0x0a, 0x06, 0x0a, 0x01, b'z', 0x12, 0x01, b'1'];
+        let sample = [[0x12, 0x0b, 0x09].as_slice(), &3.0_f64.to_le_bytes(), &[0x10, 0x01]].concat();
+        let series = [name.as_slice(), &k, &z, &sample].concat();
+
+        assert_eq!(encode(&openmetrics, 1).unwrap(), [[0x0a, series.len() as u8].as_slice(), &series].concat());
+        assert!(encode("a{k=\"v} 1\n", 1).is_err());
+    }
+
+    #[test]
+    fn compress_literals() {
+        assert_eq!(compress(b"abc"), [0x03, 0x08, b'a', b'b', b'c']);
+
+        let input = vec![0; 0x10000 + 0x100];
+        let compressed = compress(&input);
+        // Varint of the length, then a literal of 0x10000 bytes and one of 0x100 bytes
+        assert_eq!(compressed[..3], [0x80, 0x82, 0x04]);
+        assert_eq!(compressed[3..6], [61 << 2, 0xff, 0xff]);
+        assert_eq!(compressed[0x10006..0x10008], [60 << 2, 0xff]);
+        assert_eq!(compressed.len(), 3 + 3 + 0x10000 + 2 + 0x100);
+    }
+}
@@ -0,0 +1,81 @@
+//! Encoder of StatsD gauges from OpenMetrics text.
+
+use crate::format::samples;
+
+/// Encodes every sample as a gauge named by `prefix` and its name, with its labels as DogStatsD tags if `tags` is true
+/// or as its label values appended to the name otherwise.
+///
+/// Counters are also sent as gauges, since StatsD counters are increments rather than totals.
+pub(crate) fn encode(openmetrics: &str, prefix: &str, tags: bool) -> anyhow::Result<Vec<String>> {
+    let mut lines = Vec::new();
+    for sample in samples(openmetrics) {
+        let sample = sample?;
+        if !sample.value.is_finite() {
+            continue;
+        }
+
+        let mut name = [prefix, &sample.name]
+            .into_iter()
+            .filter(|node| !node.is_empty())
+            .collect::<Vec<_>>()
+            .join(".");
+        let mut suffix = String::new();
+        if tags {
+            let tags = sample.labels
+                .iter()
+                .map(|(name, value)| format!("{}:{}", escape(name), escape(value)))
+                .collect::<Vec<_>>();
+            if !tags.is_empty() {
+                suffix = format!("|#{}", tags.join(","));
+            }
+        } else {
+            for (_, value) in &sample.labels {
+                name.push('.');
+                name.push_str(&escape(value));
+            }
+        }
+        let name = name.replace([':', '|', '@'], "_");
+
+        // A signed value changes a gauge by itself, so negative ones are set from zero
+        if sample.value < 0.0 {
+            lines.push(format!("{name}:0|g{suffix}"));
+        }
+        lines.push(format!("{name}:{}|g{suffix}", sample.value));
+    }
+
+    Ok(lines)
+}
+
+fn escape(value: &str) -> String {
+    match value {
+        "" => "_".to_string(),
+        value => value.replace([':', '|', '@', ',', '#', '.', ' ', '\n'], "_"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::format::statsd::encode;
+
+    #[test]
+    fn encode_gauges() {
+        let openmetrics = [
+            "# HELP raspi_throttling_active State about throttling active currently.",
+            "# TYPE raspi_throttling_active gauge",
+            "raspi_throttling_active{kind=\"arm frequency\"} 1",
+            "raspi_time_offset_seconds -0.5",
+            "raspi_soc_temperature_celsius NaN",
+            "# EOF",
+        ].join("\n");
+
+        assert_eq!(
+            encode(&openmetrics, "pi", true).unwrap(),
+            [
+                "pi.raspi_throttling_active:1|g|#kind:arm_frequency",
+                "pi.raspi_time_offset_seconds:0|g",
+                "pi.raspi_time_offset_seconds:-0.5|g",
+            ],
+        );
+        assert_eq!(encode(&openmetrics, "", false).unwrap()[0], "raspi_throttling_active.arm_frequency:1|g");
+    }
+}
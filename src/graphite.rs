@@ -0,0 +1,75 @@
+use std::time::{Duration, SystemTime};
+
+use anyhow::Context;
+use tokio::{
+    io::AsyncWriteExt,
+    net::TcpStream,
+    time::{self, MissedTickBehavior},
+};
+
+use crate::{format::graphite, metrics::{Filter, Handler}};
+
+/// Sends the metrics to Carbon with the Graphite plaintext protocol on an interval.
+pub struct Graphite<H> {
+    carbon: String,
+    interval: Duration,
+    handler: H,
+    prefix: String,
+}
+
+impl<H> Graphite<H>
+where
+    H: Handler,
+{
+    /// `carbon` is a host with an optional port, 2003 by default.
+    pub fn new(carbon: impl ToString, interval: Duration, handler: H) -> Self {
+        Self {
+            carbon: carbon.to_string(),
+            interval,
+            handler,
+            prefix: String::new(),
+        }
+    }
+
+    /// Dotted path prepended to the metric paths, such as `home.raspberrypi`.
+    pub fn prefix(self, prefix: impl ToString) -> Self {
+        Self {
+            prefix: prefix.to_string(),
+            ..self
+        }
+    }
+
+    pub async fn start(self) {
+        let mut interval = time::interval(self.interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+
+            match time::timeout(self.interval, self.send()).await {
+                Ok(Ok(())) => {},
+                Ok(Err(err)) => tracing::error!("failed to send metrics to Carbon\nError: {err:?}"),
+                Err(_) => tracing::error!("sending metrics to Carbon timed out"),
+            }
+        }
+    }
+
+    /// Sends the metrics on a connection of its own, which is closed afterwards.
+    pub async fn send(&self) -> anyhow::Result<()> {
+        let timestamp = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)?.as_secs();
+        let metrics = self.handler.handle(&Filter::default(), Some(self.interval)).await?;
+        let lines = graphite::encode(&metrics, &self.prefix, timestamp)?;
+
+        let address = match self.carbon.contains(':') {
+            true => self.carbon.clone(),
+            false => format!("{}:2003", self.carbon),
+        };
+        let mut stream = TcpStream::connect(&address)
+            .await
+            .with_context(|| format!("connection error: {address}"))?;
+        stream.write_all(lines.as_bytes()).await?;
+        stream.shutdown().await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,216 @@
+//! gzip compression of responses, with LZ77 and the fixed Huffman codes of deflate.
+
+const WINDOW: usize = 32768;
+const HASH_SIZE: usize = 1 << 15;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+// Candidates tried for a match, trading ratio for time
+const MAX_CHAIN: usize = 64;
+
+// Base lengths and extra bits of the length codes from 257
+const LENGTH_BASES: [usize; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258,
+];
+const LENGTH_EXTRA_BITS: [u32; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+// Base distances and extra bits of the distance codes
+const DISTANCE_BASES: [usize; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145, 8193,
+    12289, 16385, 24577,
+];
+const DISTANCE_EXTRA_BITS: [u32; 30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+
+#[derive(Default)]
+struct BitWriter {
+    buffer: Vec<u8>,
+    bits: u64,
+    count: u32,
+}
+
+impl BitWriter {
+    // Least significant bit first as deflate packs them
+    fn write(&mut self, value: u32, count: u32) {
+        self.bits |= u64::from(value) << self.count;
+        self.count += count;
+        while self.count >= 8 {
+            self.buffer.push(self.bits as u8);
+            self.bits >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    // Huffman codes go most significant bit first
+    fn write_code(&mut self, code: u32, count: u32) {
+        self.write(code.reverse_bits() >> (32 - count), count);
+    }
+
+    fn write_literal(&mut self, literal: usize) {
+        let literal = literal as u32;
+        match literal {
+            0..=143 => self.write_code(0x30 + literal, 8),
+            144..=255 => self.write_code(0x190 + literal - 144, 9),
+            256..=279 => self.write_code(literal - 256, 7),
+            _ => self.write_code(0xc0 + literal - 280, 8),
+        }
+    }
+
+    fn write_match(&mut self, length: usize, distance: usize) {
+        let code = LENGTH_BASES.iter().rposition(|base| *base <= length).unwrap_or_default();
+        self.write_literal(257 + code);
+        self.write((length - LENGTH_BASES[code]) as u32, LENGTH_EXTRA_BITS[code]);
+
+        let code = DISTANCE_BASES.iter().rposition(|base| *base <= distance).unwrap_or_default();
+        self.write_code(code as u32, 5);
+        self.write((distance - DISTANCE_BASES[code]) as u32, DISTANCE_EXTRA_BITS[code]);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.buffer.push(self.bits as u8);
+        }
+        self.buffer
+    }
+}
+
+/// Compresses `input` into a gzip member.
+pub fn compress(input: &[u8]) -> Vec<u8> {
+    // No file name and no modification time
+    let mut buffer = vec![0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 0, 0xff];
+    buffer.extend(deflate(input));
+    buffer.extend(crc32(input).to_le_bytes());
+    buffer.extend((input.len() as u32).to_le_bytes());
+
+    buffer
+}
+
+/// Whether `accept_encoding`, the value of an `Accept-Encoding` header, accepts gzip.
+pub fn accepts(accept_encoding: &str) -> bool {
+    let mut gzip = None;
+    let mut any = None;
+    for encoding in accept_encoding.split(',') {
+        let mut params = encoding.split(';');
+        let name = params.next().unwrap_or_default().trim().to_ascii_lowercase();
+        let accepted = params
+            .filter_map(|param| param.trim().strip_prefix("q="))
+            .all(|quality| quality.trim().parse::<f64>().is_ok_and(|quality| quality > 0.0));
+        match name.as_str() {
+            "gzip" | "x-gzip" => gzip = Some(accepted),
+            "*" => any = Some(accepted),
+            _ => {},
+        }
+    }
+
+    gzip.or(any).unwrap_or(false)
+}
+
+// Single final block with the fixed Huffman codes, which are good enough for text and need no tables in the output
+fn deflate(input: &[u8]) -> Vec<u8> {
+    let mut writer = BitWriter::default();
+    writer.write(1, 1);
+    writer.write(1, 2);
+
+    let mut head = vec![usize::MAX; HASH_SIZE];
+    let mut prev = vec![usize::MAX; WINDOW];
+    let mut position = 0;
+    while position < input.len() {
+        let (mut length, mut distance) = (0, 0);
+        if position + MIN_MATCH <= input.len() {
+            let mut candidate = head[hash(&input[position..])];
+            let mut chain = 0;
+            while candidate != usize::MAX && position - candidate <= WINDOW && chain < MAX_CHAIN {
+                let matched = input[candidate..]
+                    .iter()
+                    .zip(&input[position..])
+                    .take(MAX_MATCH)
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                if matched > length {
+                    (length, distance) = (matched, position - candidate);
+                    if length == MAX_MATCH {
+                        break;
+                    }
+                }
+                candidate = prev[candidate % WINDOW];
+                chain += 1;
+            }
+        }
+
+        let advance = match length >= MIN_MATCH {
+            true => {
+                writer.write_match(length, distance);
+                length
+            },
+            false => {
+                writer.write_literal(usize::from(input[position]));
+                1
+            },
+        };
+        for inserted in position..(position + advance).min(input.len().saturating_sub(MIN_MATCH - 1)) {
+            let hash = hash(&input[inserted..]);
+            prev[inserted % WINDOW] = head[hash];
+            head[hash] = inserted;
+        }
+        position += advance;
+    }
+
+    writer.write_literal(256);
+    writer.finish()
+}
+
+fn hash(bytes: &[u8]) -> usize {
+    ((usize::from(bytes[0]) << 10) ^ (usize::from(bytes[1]) << 5) ^ usize::from(bytes[2])) & (HASH_SIZE - 1)
+}
+
+fn crc32(input: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in input {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            crc = match crc & 1 {
+                1 => (crc >> 1) ^ 0xedb8_8320,
+                _ => crc >> 1,
+            };
+        }
+    }
+
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gzip::{accepts, compress, crc32};
+
+    #[test]
+    fn compress_repeated() {
+        let input = "raspi_throttling_active{kind=\"undervoltage\"} 0\n".repeat(100);
+        let compressed = compress(input.as_bytes());
+
+        assert_eq!(compressed[..10], [0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 0, 0xff]);
+        assert_eq!(compressed[compressed.len() - 8..compressed.len() - 4], crc32(input.as_bytes()).to_le_bytes());
+        assert_eq!(compressed[compressed.len() - 4..], (input.len() as u32).to_le_bytes());
+        assert!(compressed.len() < input.len() / 20);
+    }
+
+    #[test]
+    fn compress_bytes() {
+        assert_eq!(compress(b""), [0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 0, 0xff, 3, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(
+            compress(b"aaaaaaaaaa"),
+            [0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 0, 0xff, 0x4b, 0x84, 0x03, 0, 0xf0, 0xcd, 0x11, 0x4c, 0x0a, 0, 0, 0],
+        );
+    }
+
+    #[test]
+    fn crc() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+    }
+
+    #[test]
+    fn accept_encoding() {
+        assert!(accepts("gzip, deflate, br"));
+        assert!(accepts("br;q=1.0, gzip;q=0.8"));
+        assert!(accepts("*"));
+        assert!(!accepts("gzip;q=0, *"));
+        assert!(!accepts("identity"));
+        assert!(!accepts(""));
+    }
+}
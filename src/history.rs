@@ -0,0 +1,242 @@
+use std::{
+    collections::BTreeMap,
+    path::PathBuf,
+    process::Stdio,
+    time::{Duration, SystemTime},
+};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use tokio::{
+    io::AsyncWriteExt,
+    process::Command,
+    time::{self, MissedTickBehavior},
+};
+
+use crate::{format::{labels_json, samples}, metrics::{Filter, Handler}};
+
+/// History of the metrics in a SQLite database, written and read with the sqlite3 command, so that a Pi out of reach of
+/// Prometheus keeps its own.
+#[derive(Clone, Debug)]
+pub struct History {
+    path: PathBuf,
+}
+
+/// Records the metrics into a [`History`] on an interval, deleting the samples older than the retention.
+pub struct HistoryRecorder<H> {
+    history: History,
+    interval: Duration,
+    retention: Duration,
+    handler: H,
+}
+
+/// Sample of a metric in the history.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct HistorySample {
+    /// Unix timestamp when the sample was recorded
+    pub time: u64,
+    pub labels: BTreeMap<String, String>,
+    pub value: f64,
+}
+
+// Row of the JSON output of sqlite3, with the labels in JSON text
+#[derive(Deserialize)]
+struct Row {
+    time: u64,
+    labels: String,
+    value: f64,
+}
+
+const SCHEMA: &str = "CREATE TABLE IF NOT EXISTS samples (time INTEGER NOT NULL, name TEXT NOT NULL, labels TEXT NOT NULL, value REAL NOT NULL);
+CREATE INDEX IF NOT EXISTS samples_name_time ON samples (name, time);
+";
+
+impl History {
+    /// `path` is the database file, created on the first recording.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+        }
+    }
+
+    /// Inserts the samples of `openmetrics` at `time` and deletes the ones older than `retention` in a transaction.
+    pub async fn record(&self, openmetrics: &str, time: u64, retention: Duration) -> anyhow::Result<()> {
+        let sql = [
+            SCHEMA,
+            "BEGIN;\n",
+            &inserts(openmetrics, time)?,
+            &format!("DELETE FROM samples WHERE time < {};\n", time.saturating_sub(retention.as_secs())),
+            "COMMIT;\n",
+        ].concat();
+        self.execute(&sql, false).await?;
+
+        Ok(())
+    }
+
+    /// Returns the samples of `metric`, such as `raspi_soc_temperature_celsius`, recorded since `since`, the oldest first.
+    pub async fn query(&self, metric: &str, since: u64) -> anyhow::Result<Vec<HistorySample>> {
+        let sql = format!(
+            "{SCHEMA}SELECT time, labels, value FROM samples WHERE name = {} AND time >= {since} ORDER BY time;\n",
+            quote(metric),
+        );
+        let output = self.execute(&sql, true).await?;
+        // Prints nothing rather than an empty array when no row matches
+        if output.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+
+        serde_json::from_str::<Vec<Row>>(&output)
+            .context("invalid output of sqlite3")?
+            .into_iter()
+            .map(|row| {
+                Ok(HistorySample {
+                    time: row.time,
+                    labels: serde_json::from_str(&row.labels).context("invalid labels in the history")?,
+                    value: row.value,
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the version of sqlite3, failing when it isn't installed, so that the history can be told unavailable at
+    /// startup rather than on every recording.
+    pub async fn check(&self) -> anyhow::Result<String> {
+        let output = Command::new("sqlite3")
+            .arg("-version")
+            .stdin(Stdio::null())
+            .output()
+            .await
+            .context("failed to run sqlite3, install it to record the history")?;
+        if !output.status.success() {
+            anyhow::bail!("sqlite3 exited with {}: {}", output.status, String::from_utf8_lossy(&output.stderr).trim());
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    async fn execute(&self, sql: &str, json: bool) -> anyhow::Result<String> {
+        let mut command = Command::new("sqlite3");
+        command.arg("-bail");
+        if json {
+            command.arg("-json");
+        }
+        let mut child = command
+            .arg(&self.path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("failed to run sqlite3")?;
+        let mut stdin = child.stdin.take().context("stdin of sqlite3 is missing")?;
+        stdin.write_all(sql.as_bytes()).await?;
+        drop(stdin);
+
+        let output = child.wait_with_output().await?;
+        if !output.status.success() {
+            anyhow::bail!("sqlite3 exited with {}: {}", output.status, String::from_utf8_lossy(&output.stderr).trim());
+        }
+
+        Ok(String::from_utf8(output.stdout)?)
+    }
+}
+
+impl<H> HistoryRecorder<H>
+where
+    H: Handler,
+{
+    pub fn new(history: History, interval: Duration, handler: H) -> Self {
+        Self {
+            history,
+            interval,
+            retention: Duration::from_secs(7 * 24 * 60 * 60),
+            handler,
+        }
+    }
+
+    /// Keeps the samples for `retention`, a week by default.
+    pub fn retention(self, retention: Duration) -> Self {
+        Self {
+            retention,
+            ..self
+        }
+    }
+
+    pub async fn start(self) {
+        let mut interval = time::interval(self.interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+
+            match time::timeout(self.interval, self.record()).await {
+                Ok(Ok(())) => {},
+                Ok(Err(err)) => tracing::error!("failed to record metrics into the history\nError: {err:?}"),
+                Err(_) => tracing::error!("recording metrics into the history timed out"),
+            }
+        }
+    }
+
+    pub async fn record(&self) -> anyhow::Result<()> {
+        let time = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)?.as_secs();
+        let metrics = self.handler.handle(&Filter::default(), Some(self.interval)).await?;
+
+        self.history.record(&metrics, time, self.retention).await
+    }
+}
+
+// INSERT statements of the samples, skipping NaN and infinite values that SQLite can't store
+fn inserts(openmetrics: &str, time: u64) -> anyhow::Result<String> {
+    let mut sql = String::new();
+    for sample in samples(openmetrics) {
+        let sample = sample?;
+        if !sample.value.is_finite() {
+            continue;
+        }
+
+        let labels = labels_json(&sample.labels)?;
+        sql.push_str(&format!(
+            "INSERT INTO samples VALUES ({time}, {}, {}, {:?});\n",
+            quote(&sample.name),
+            quote(&labels),
+            sample.value,
+        ));
+    }
+
+    Ok(sql)
+}
+
+// SQL string literal of `value`
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::history::{inserts, quote};
+
+    #[test]
+    fn insert_samples() {
+        let openmetrics = [
+            "# TYPE raspi_soc_temperature_celsius gauge",
+            "raspi_soc_temperature_celsius 48.5",
+            "# TYPE raspi_filesystem_avail_bytes gauge",
+            "raspi_filesystem_avail_bytes{mountpoint=\"/\",fstype=\"ext4\"} 1000",
+            "# TYPE raspi_collector_duration_seconds gauge",
+            "raspi_collector_duration_seconds NaN",
+            "# EOF",
+        ].join("\n");
+
+        assert_eq!(
+            inserts(&openmetrics, 1700000000).unwrap(),
+            [
+                "INSERT INTO samples VALUES (1700000000, 'raspi_soc_temperature_celsius', '{}', 48.5);\n",
+                "INSERT INTO samples VALUES (1700000000, 'raspi_filesystem_avail_bytes', '{\"fstype\":\"ext4\",\"mountpoint\":\"/\"}', 1000.0);\n",
+            ].concat(),
+        );
+    }
+
+    #[test]
+    fn quote_string() {
+        assert_eq!(quote("it's"), "'it''s'");
+    }
+}
@@ -0,0 +1,152 @@
+use std::{path::PathBuf, sync::Mutex};
+
+use tokio::process::Command;
+
+use crate::{
+    format::Labels,
+    metrics::{throttled::ThrottlingKind, Registerer},
+    parser::throttled::ThrottledState,
+};
+
+/// Executable run on events such as throttling starting, with the details of the event in environment variables.
+///
+/// `RASPI_EVENT` is the name of the event, and the other variables depend on it:
+///
+/// - `throttling_started` and `throttling_cleared`: `RASPI_THROTTLING_KIND` such as `undervoltage`
+/// - `threshold_exceeded` and `threshold_cleared`: `RASPI_THRESHOLD` of the name of the threshold, and `RASPI_LABEL_<NAME>`
+///   of each label of the sample in uppercase
+///
+/// The exporter doesn't wait for it, so that a slow one doesn't hold collections.
+#[derive(Clone, Debug)]
+pub struct Hook {
+    path: PathBuf,
+}
+
+/// Runs a hook when a throttling kind becomes active or clears, meant to be fed by a sampler.
+///
+/// A kind already active at the first sample starts too, so that the hook can tell the state without waiting for a change.
+#[derive(Debug)]
+pub struct ThrottlingHook {
+    hook: Hook,
+    // Whether each kind was active at the previous sample
+    previous: Mutex<Option<[bool; 4]>>,
+}
+
+impl Hook {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+        }
+    }
+
+    /// Runs the hook for `event` in the background, logging its failure.
+    pub fn run(&self, event: &str, vars: Vec<(String, String)>) {
+        tracing::info!("running hook for {event}");
+        let child = Command::new(&self.path).env("RASPI_EVENT", event).envs(vars).spawn();
+        let mut child = match child {
+            Ok(child) => child,
+            Err(err) => {
+                tracing::error!("failed to run hook {:?}\nError: {err:?}", self.path);
+                return;
+            },
+        };
+
+        let path = self.path.clone();
+        let event = event.to_string();
+        tokio::spawn(async move {
+            match child.wait().await {
+                Ok(status) if status.success() => {},
+                Ok(status) => tracing::error!("hook {path:?} for {event} exited with {status}"),
+                Err(err) => tracing::error!("failed to wait for hook {path:?}\nError: {err:?}"),
+            }
+        });
+    }
+
+    /// Runs the hook for a threshold having been exceeded or cleared by the sample with `labels`.
+    pub fn threshold(&self, name: &str, labels: &Labels, exceeded: bool) {
+        let event = if exceeded { "threshold_exceeded" } else { "threshold_cleared" };
+        self.run(event, threshold_vars(name, labels));
+    }
+}
+
+impl ThrottlingHook {
+    pub fn new(hook: Hook) -> Self {
+        Self {
+            hook,
+            previous: Mutex::new(None),
+        }
+    }
+
+    // Kinds that have started (true) or cleared (false) since the previous sample
+    fn transitions(&self, state: &ThrottledState) -> Vec<(ThrottlingKind, bool)> {
+        let current = [
+            state.undervoltage_detected,
+            state.arm_frequency_capped,
+            state.currently_throttled,
+            state.soft_temperature_limit_active,
+        ];
+        let previous = self.previous.lock().expect("failed to lock previous mutex").replace(current).unwrap_or_default();
+
+        let kinds = [ThrottlingKind::Undervoltage, ThrottlingKind::ArmFrequency, ThrottlingKind::Throttled, ThrottlingKind::SoftTemperatureLimit];
+        kinds
+            .into_iter()
+            .zip(previous)
+            .zip(current)
+            .filter(|((_, was_active), is_active)| was_active != is_active)
+            .map(|((kind, _), is_active)| (kind, is_active))
+            .collect()
+    }
+}
+
+impl Registerer for ThrottlingHook {
+    type Item = ThrottledState;
+
+    async fn update(&self, state: Self::Item) -> anyhow::Result<()> {
+        for (kind, active) in self.transitions(&state) {
+            let event = if active { "throttling_started" } else { "throttling_cleared" };
+            self.hook.run(event, vec![("RASPI_THROTTLING_KIND".to_string(), kind.to_string())]);
+        }
+
+        Ok(())
+    }
+}
+
+fn threshold_vars(name: &str, labels: &Labels) -> Vec<(String, String)> {
+    // The name of the threshold comes first
+    let labels = labels.iter().skip(1).map(|(label, value)| (format!("RASPI_LABEL_{}", label.to_uppercase()), value.clone()));
+
+    [("RASPI_THRESHOLD".to_string(), name.to_string())].into_iter().chain(labels).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        hook::{threshold_vars, Hook, ThrottlingHook},
+        metrics::throttled::ThrottlingKind,
+        parser::throttled::ThrottledState,
+    };
+
+    #[test]
+    fn transitions() {
+        let hook = ThrottlingHook::new(Hook::new("/bin/true"));
+        let undervoltage = ThrottledState { undervoltage_detected: true, ..Default::default() };
+
+        // Started at the first sample
+        assert_eq!(hook.transitions(&undervoltage), [(ThrottlingKind::Undervoltage, true)]);
+        assert!(hook.transitions(&undervoltage).is_empty());
+        assert_eq!(
+            hook.transitions(&ThrottledState { currently_throttled: true, ..Default::default() }),
+            [(ThrottlingKind::Undervoltage, false), (ThrottlingKind::Throttled, true)],
+        );
+    }
+
+    #[test]
+    fn vars() {
+        let labels = [("name", "filesystem_full"), ("mountpoint", "/")].map(|(name, value)| (name.to_string(), value.to_string())).to_vec();
+
+        assert_eq!(
+            threshold_vars("filesystem_full", &labels),
+            [("RASPI_THRESHOLD", "filesystem_full"), ("RASPI_LABEL_MOUNTPOINT", "/")].map(|(name, value)| (name.to_string(), value.to_string())),
+        );
+    }
+}
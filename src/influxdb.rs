@@ -0,0 +1,82 @@
+use std::time::{Duration, SystemTime};
+
+use http_body_util::Full;
+use hyper::{header::{AUTHORIZATION, CONTENT_TYPE, USER_AGENT}, Request, Uri};
+use tokio::time::{self, MissedTickBehavior};
+
+use crate::{
+    client::{Client, Credentials},
+    format::influx,
+    metrics::{Filter, Handler},
+};
+
+/// Writes the metrics to InfluxDB with the line protocol on an interval.
+///
+/// `url` is the write endpoint with its parameters, such as `http://influxdb:8086/write?db=raspi` of the v1 API or
+/// `http://influxdb:8086/api/v2/write?org=home&bucket=raspi` of the v2 API.
+pub struct InfluxDb<H> {
+    url: Uri,
+    interval: Duration,
+    handler: H,
+    client: Client,
+    credentials: Option<Credentials>,
+}
+
+impl<H> InfluxDb<H>
+where
+    H: Handler,
+{
+    pub fn new(url: Uri, interval: Duration, handler: H, client: Client) -> Self {
+        Self {
+            url,
+            interval,
+            handler,
+            client,
+            credentials: None,
+        }
+    }
+
+    /// Basic authentication for the v1 API, or a token for the v2 API.
+    pub fn credentials(self, credentials: Option<Credentials>) -> Self {
+        Self {
+            credentials,
+            ..self
+        }
+    }
+
+    pub async fn start(self) {
+        let mut interval = time::interval(self.interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+
+            match time::timeout(self.interval, self.write()).await {
+                Ok(Ok(())) => {},
+                Ok(Err(err)) => tracing::error!("failed to write metrics to InfluxDB\nError: {err:?}"),
+                Err(_) => tracing::error!("writing metrics to InfluxDB timed out"),
+            }
+        }
+    }
+
+    pub async fn write(&self) -> anyhow::Result<()> {
+        // In nanoseconds, the default precision of both APIs
+        let timestamp = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)?.as_nanos() as i64;
+        let metrics = self.handler.handle(&Filter::default(), Some(self.interval)).await?;
+        let body = influx::encode(&metrics, timestamp)?;
+
+        let mut request = Request::post(&self.url)
+            .header(CONTENT_TYPE, "text/plain; charset=utf-8")
+            .header(USER_AGENT, concat!("raspi_exporter/", env!("CARGO_PKG_VERSION")));
+        if let Some(credentials) = &self.credentials {
+            request = request.header(AUTHORIZATION, credentials.header());
+        }
+
+        let response = self.client.send(request.body(Full::from(body))?).await?;
+        if !response.status().is_success() {
+            anyhow::bail!("InfluxDB write failed with status {}: {}", response.status(), String::from_utf8_lossy(response.body()));
+        }
+
+        Ok(())
+    }
+}
@@ -1,8 +1,49 @@
+pub mod allowlist;
+pub mod backend;
+pub mod basic_auth;
+pub mod cache;
 pub mod cli;
+pub mod client;
+pub mod collect;
 pub mod collector;
 pub mod command;
+pub mod completions;
+pub mod config;
+pub mod cors;
+pub mod doctor;
+pub mod events;
 pub mod executor;
+pub mod exporter;
+pub mod fan;
+pub mod file;
+pub mod fleet;
+pub mod follower;
+pub mod format;
+pub mod graphite;
+pub mod gzip;
+pub mod history;
+pub mod hook;
+pub mod influxdb;
+pub mod limit;
+pub mod man;
+pub mod mdns;
 pub mod metrics;
+#[cfg(feature = "mqtt")]
+pub mod mqtt;
+pub mod nagios;
+pub mod notifier;
 pub mod parser;
+pub mod record;
 pub mod registerer;
+pub mod relabel;
+#[cfg(feature = "ssh")]
+pub mod remote;
+pub mod remote_write;
+pub mod sampler;
+pub mod sd;
 pub mod server;
+pub mod statsd;
+pub mod textfile;
+pub mod threshold;
+pub mod tls;
+pub mod web_config;
@@ -0,0 +1,145 @@
+use std::{
+    future::{ready, Ready},
+    io,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use axum::serve::Listener;
+use axum_server::accept::Accept;
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    net::TcpStream,
+    sync::{OwnedSemaphorePermit, Semaphore},
+};
+
+/// Closes connections accepted beyond `max_connections` open ones instead of queuing them.
+#[derive(Clone, Debug)]
+pub struct ConnectionLimit {
+    semaphore: Arc<Semaphore>,
+}
+
+/// Listener of plain HTTP connections limited by a [`ConnectionLimit`].
+#[derive(Debug)]
+pub struct LimitedListener<L> {
+    listener: L,
+    limit: ConnectionLimit,
+}
+
+/// Connection holding its share of a [`ConnectionLimit`] until it is closed.
+#[derive(Debug)]
+pub struct LimitedStream<S> {
+    stream: S,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl ConnectionLimit {
+    pub fn new(max_connections: usize) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(max_connections)),
+        }
+    }
+
+    pub fn listener<L>(self, listener: L) -> LimitedListener<L> {
+        LimitedListener {
+            listener,
+            limit: self,
+        }
+    }
+
+    fn limit<S>(&self, stream: S) -> Option<LimitedStream<S>> {
+        let Ok(permit) = self.semaphore.clone().try_acquire_owned() else {
+            tracing::warn!("closing a connection beyond the connection limit");
+            return None;
+        };
+
+        Some(LimitedStream {
+            stream,
+            _permit: permit,
+        })
+    }
+}
+
+impl<L> Listener for LimitedListener<L>
+where
+    L: Listener,
+{
+    type Io = LimitedStream<L::Io>;
+    type Addr = L::Addr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        loop {
+            let (stream, address) = Listener::accept(&mut self.listener).await;
+            if let Some(stream) = self.limit.limit(stream) {
+                return (stream, address);
+            }
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<Self::Addr> {
+        Listener::local_addr(&self.listener)
+    }
+}
+
+// Runs before the TLS handshake, so that excess connections don't cost a handshake
+impl<S> Accept<TcpStream, S> for ConnectionLimit {
+    type Stream = LimitedStream<TcpStream>;
+    type Service = S;
+    type Future = Ready<io::Result<(Self::Stream, Self::Service)>>;
+
+    fn accept(&self, stream: TcpStream, service: S) -> Self::Future {
+        ready(self.limit(stream).map(|stream| (stream, service)).ok_or_else(|| io::Error::other("too many connections")))
+    }
+}
+
+impl<S> AsyncRead for LimitedStream<S>
+where
+    S: AsyncRead + Unpin,
+{
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_read(cx, buf)
+    }
+}
+
+impl<S> AsyncWrite for LimitedStream<S>
+where
+    S: AsyncWrite + Unpin,
+{
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.stream).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_shutdown(cx)
+    }
+
+    fn poll_write_vectored(mut self: Pin<&mut Self>, cx: &mut Context<'_>, bufs: &[io::IoSlice<'_>]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.stream).poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.stream.is_write_vectored()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::limit::ConnectionLimit;
+
+    #[test]
+    fn limit() {
+        let limit = ConnectionLimit::new(1);
+
+        let first = limit.limit(());
+        assert!(first.is_some());
+        assert!(limit.limit(()).is_none());
+
+        drop(first);
+        assert!(limit.limit(()).is_some());
+    }
+}
@@ -1,20 +1,44 @@
-use std::sync::{Arc, Mutex};
+use std::{collections::HashMap, fs, io, path::Path, process, sync::Arc, time::Duration};
 
-use clap::Parser;
-use prometheus_client::registry::Registry;
+use anyhow::Context;
+use clap::{CommandFactory, ValueEnum};
 
 use raspi_exporter::{
-    cli::{ Cli, Log },
-    collector::throttled::Throttled,
-    executor::throttled::ThrottledExecutor,
-    metrics::MetricsHandler,
-    parser::throttled::ThrottledParser,
-    registerer::throttled::ThrottledRegisterer,
-    server::Server,
+    allowlist::Allowlist,
+    basic_auth::BasicAuth,
+    cli::{ Cli, CollectFormat, Command, Listen, Log, Metric, RecordFormat, Setting },
+    client::{Client, Credentials},
+    doctor,
+    man,
+    collect::collect,
+    config::CollectorConfig,
+    events::Events,
+    exporter::{self, fallback_group, fan_group, metric_group, metrics_handler, registry, throttling_notifier},
+    fleet::Fleet,
+    mdns::Mdns,
+    cors::Cors,
+    graphite::Graphite,
+    history::{History, HistoryRecorder},
+    influxdb::InfluxDb,
+    metrics::{Handler, MetricsHandler},
+    nagios::{HealthCheck, Readings},
+    record::record,
+    remote_write::RemoteWrite,
+    sampler::Sampler,
+    server::{ListenAddress, Server},
+    statsd::StatsD,
+    textfile::Textfile,
+    tls::{self_signed, TlsConfig},
+    web_config::WebConfig,
 };
+#[cfg(feature = "mqtt")]
+use raspi_exporter::mqtt::Mqtt;
+#[cfg(feature = "ssh")]
+use raspi_exporter::exporter::remote_metric_group;
+use tokio::{signal::unix::{self, SignalKind}, sync::watch, task::JoinHandle};
 use tracing::level_filters::LevelFilter;
 use tracing_subscriber::{
-    fmt,
+    fmt::{self, writer::BoxMakeWriter},
     layer::SubscriberExt,
     util::SubscriberInitExt,
     EnvFilter,
@@ -23,32 +47,520 @@ use tracing_subscriber::{
 
 #[tokio::main]
 async fn main() {
-    let args = Cli::parse();
+    let args = Cli::parse_with_config();
 
-    setup_logging(args.log);
+    if args.list_collectors {
+        let width = Metric::value_variants().iter().map(|metric| metric.name().len()).max().unwrap_or_default();
+        for metric in Metric::value_variants() {
+            let state = if metric.is_default() { "enabled" } else { "disabled" };
+            println!("{:width$}  {state:8}  {}", metric.name(), metric.description());
+        }
+        return;
+    }
+
+    // Keeps the output of subcommands apart from logs
+    setup_logging(args.log.clone(), !matches!(args.command, None | Some(Command::Serve)));
+
+    match &args.command {
+        Some(Command::Collect { format }) => process::exit(collect_once(&args, *format).await),
+        Some(Command::Check) => process::exit(check(&args)),
+        Some(Command::CheckHealth { warn_temp, crit_temp }) => process::exit(check_health(&args, *warn_temp, *crit_temp).await),
+        Some(Command::Record { interval, out, format }) => {
+            let format = format.unwrap_or_else(|| RecordFormat::from_path(out));
+            process::exit(record_metrics(&args, *interval, out, format).await);
+        },
+        Some(Command::Completions { shell }) => {
+            print!("{}", shell.generate(Cli::command()));
+            return;
+        },
+        Some(Command::Man) => {
+            print!("{}", man::render(Cli::command()));
+            return;
+        },
+        // Exits without waiting for the blocking reads of followers
+        Some(Command::Doctor) => {
+            doctor(&args).await;
+            process::exit(0);
+        },
+        None | Some(Command::Serve) => {},
+    }
 
     tracing::info!("starting raspi_exporter");
     tracing::info!("enabled metrics: {}", args.metrics);
 
-    let registry = Arc::new(Mutex::new(Registry::default()));
-    let throttled = args
-        .metrics
-        .has_throttled()
-        .then(|| Throttled::new(
-            ThrottledExecutor::new("vcgencmd", ["get_throttled"]),
-            ThrottledParser,
-            ThrottledRegisterer { registry: registry.clone() }
-        ));
-    let metrics_handler = MetricsHandler::new(throttled, registry.clone());
-
-    let server = Server::new(args.port, metrics_handler);
+    let web_config = match args.web_config_file.as_ref().map(WebConfig::from_file).transpose() {
+        Ok(web_config) => web_config.unwrap_or_default(),
+        Err(err) => {
+            tracing::error!("failed to load web config\nError: {err:?}");
+            return;
+        },
+    };
+    let tls = match tls_config(&args, &web_config) {
+        Ok(tls) => tls,
+        Err(err) => {
+            tracing::error!("invalid TLS config\nError: {err:?}");
+            return;
+        },
+    };
+    let (tls_updates, tls) = tls.map(watch::channel).unzip();
+
+    let probe_targets = probe_targets(&args);
+    let events = Arc::new(Events::new(args.events_capacity));
+    let metrics_handler = Arc::new(metrics_handler(&args, None).events(events.clone()));
+    if args.fallback() {
+        tracing::warn!("no VideoCore firmware found, falling back to the metrics of sysfs and procfs");
+    }
+    metrics_handler.insert(fallback_group(&args));
+    for metric in &args.metrics.enabled() {
+        metrics_handler.insert(metric_group(&args, metric).await);
+    }
+    if let Some(group) = fan_group(&args) {
+        tracing::info!("controlling the fan");
+        metrics_handler.insert(group);
+    }
+    let notifier = throttling_notifier(&args, events.clone()).map(|sampler| {
+        tracing::info!("sampling throttling for notifications and events");
+        sampler.spawn()
+    });
+    let basic_auth = web_config.basic_auth().map(Arc::new);
+    tokio::spawn(reload(args.settings.clone(), args.collectors.clone(), metrics_handler.clone(), tls_updates, basic_auth.clone(), events, notifier));
+
+    if let Some(url) = args.remote_write_url.clone() {
+        let credentials = match (&args.remote_write_username, &args.remote_write_password, &args.remote_write_bearer_token) {
+            (Some(username), Some(password), _) => Some(Credentials::Basic { username: username.clone(), password: password.clone() }),
+            (_, _, Some(token)) => Some(Credentials::Bearer(token.clone())),
+            _ => None,
+        };
+        tracing::info!("pushing metrics to {url}");
+        let remote_write = RemoteWrite::new(url, args.remote_write_interval, metrics_handler.clone(), Client::new(&args.push_ca_file))
+            .credentials(credentials);
+        tokio::spawn(remote_write.start());
+    }
+
+    if let Some(url) = args.influxdb_url.clone() {
+        let credentials = match (&args.influxdb_username, &args.influxdb_password, &args.influxdb_token) {
+            (Some(username), Some(password), _) => Some(Credentials::Basic { username: username.clone(), password: password.clone() }),
+            (_, _, Some(token)) => Some(Credentials::Token(token.clone())),
+            _ => None,
+        };
+        tracing::info!("writing metrics to {url}");
+        let influxdb = InfluxDb::new(url, args.influxdb_interval, metrics_handler.clone(), Client::new(&args.push_ca_file))
+            .credentials(credentials);
+        tokio::spawn(influxdb.start());
+    }
+
+    if let Some(carbon) = &args.graphite_carbon {
+        tracing::info!("sending metrics to {carbon}");
+        let graphite = Graphite::new(carbon, args.graphite_interval, metrics_handler.clone()).prefix(&args.graphite_prefix);
+        tokio::spawn(graphite.start());
+    }
+
+    if let Some(address) = &args.statsd_address {
+        tracing::info!("sending metrics to {address}");
+        let statsd = StatsD::new(address, args.statsd_interval, metrics_handler.clone())
+            .prefix(&args.statsd_prefix)
+            .tags(args.statsd_tags);
+        tokio::spawn(statsd.start());
+    }
+
+    #[cfg(feature = "mqtt")]
+    if let Some(broker) = &args.mqtt_broker {
+        tracing::info!("publishing metrics to {broker}");
+        let mqtt = Mqtt::new(broker, args.mqtt_interval, metrics_handler.clone())
+            .prefix(&args.mqtt_topic_prefix)
+            .client_id(&args.mqtt_client_id)
+            .credentials(args.mqtt_username.clone().zip(args.mqtt_password.clone()))
+            .qos(args.mqtt_qos)
+            .retain(args.mqtt_retain)
+            .discovery_prefix(args.mqtt_discovery_prefix.clone());
+        tokio::spawn(mqtt.start());
+    }
+
+    let history = args.history_file.clone().map(History::new);
+    if let Some(history) = history.clone() {
+        let version = match history.check().await {
+            Ok(version) => version,
+            Err(err) => {
+                tracing::error!("failed to record metrics into the history\nError: {err:?}");
+                return;
+            },
+        };
+        tracing::info!("recording metrics into {:?} with sqlite3 {version}", args.history_file);
+        let recorder = HistoryRecorder::new(history, args.history_interval, metrics_handler.clone()).retention(args.history_retention);
+        tokio::spawn(recorder.start());
+    }
+
+    if let Some(path) = args.textfile_output {
+        tracing::info!("writing metrics into {path:?}");
+        Textfile::new(path, args.textfile_interval, metrics_handler).start().await;
+        return;
+    }
+
+    if args.mdns {
+        match mdns(&args, tls.is_some()) {
+            Ok(mdns) => {
+                tokio::spawn(mdns.start());
+            },
+            Err(err) => tracing::error!("failed to advertise over mDNS\nError: {err:?}"),
+        }
+    }
+
+    let fleet = (!args.fleet_instances.is_empty()).then(|| {
+        Fleet::new(args.fleet_instances.clone(), Client::new(&args.push_ca_file), registry(&args, None)).timeout(args.fleet_timeout)
+    });
+    let server = Server::new(args.address.iter().map(|address| address.address(args.port)).collect(), metrics_handler)
+        .metrics_path(args.metrics_path)
+        .admin_address(args.admin_address)
+        .allowlist((!args.allowed_networks.is_empty()).then(|| Allowlist::new(args.allowed_networks)))
+        .cors((!args.cors_origins.is_empty()).then(|| Cors::new(args.cors_origins)))
+        .ipv6_only(args.ipv6_only)
+        .tls(tls)
+        .http2(!args.disable_http2 && web_config.http2())
+        .basic_auth(basic_auth)
+        .request_timeout(args.request_timeout)
+        .max_connections(args.max_connections)
+        .max_in_flight_requests(args.max_in_flight_requests)
+        .probe_targets(probe_targets)
+        .fleet(fleet)
+        .history(history);
     if let Err(err) = server.start().await {
         tracing::error!("failed to start server\nError: {err:?}");
     };
 }
 
-fn setup_logging(output_type: Log) {
-    let layer = fmt::layer();
+// Handlers of the --probe-target destinations collecting over SSH
+#[cfg(feature = "ssh")]
+fn probe_targets(args: &Cli) -> HashMap<String, Arc<MetricsHandler>> {
+    let mut probe_targets = HashMap::new();
+    for target in &args.probe_targets {
+        tracing::info!("probing {target} over SSH");
+        let handler = metrics_handler(args, Some(target));
+        for metric in &args.metrics.enabled() {
+            match remote_metric_group(args, target, metric) {
+                Some(group) => handler.insert(group),
+                None => tracing::debug!("{metric} isn't collected over SSH"),
+            }
+        }
+        probe_targets.insert(target.clone(), Arc::new(handler));
+    }
+
+    probe_targets
+}
+
+#[cfg(not(feature = "ssh"))]
+fn probe_targets(_: &Cli) -> HashMap<String, Arc<MetricsHandler>> {
+    HashMap::new()
+}
+
+async fn collect_once(args: &Cli, format: CollectFormat) -> i32 {
+    let metrics_handler = metrics_handler(args, None);
+    metrics_handler.insert(fallback_group(args));
+    for metric in &args.metrics.enabled() {
+        metrics_handler.insert(metric_group(args, metric).await);
+    }
+
+    match collect(&metrics_handler, format).await {
+        Ok(collection) => {
+            print!("{}", collection.output);
+            if collection.failed.is_empty() {
+                return 0;
+            }
+            tracing::error!("failed collectors: {}", collection.failed.join(", "));
+        },
+        Err(err) => tracing::error!("failed to collect metrics\nError: {err:?}"),
+    }
+
+    1
+}
+
+// Reads from the backend with the configured temperature and throttled sources
+async fn check_health(args: &Cli, warn_temp: f64, crit_temp: f64) -> i32 {
+    let temperature = args.backend.temperature(&args.collector(Metric::Temperature));
+    let throttled = args.backend.throttled(&args.collector(Metric::Throttled));
+
+    let (status, line) = HealthCheck::new(warn_temp, crit_temp).evaluate(Readings::read(&temperature, &throttled).await);
+    println!("{line}");
+
+    status as i32
+}
+
+// Records until SIGINT or SIGTERM
+async fn record_metrics(args: &Cli, interval: Duration, out: &Path, format: RecordFormat) -> i32 {
+    let metrics_handler = metrics_handler(args, None);
+    metrics_handler.insert(fallback_group(args));
+    for metric in &args.metrics.enabled() {
+        metrics_handler.insert(metric_group(args, metric).await);
+    }
+
+    let mut sigterm = unix::signal(SignalKind::terminate()).expect("SIGTERM error");
+    let stop = async move {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {},
+            _ = sigterm.recv() => {},
+        }
+    };
+    tracing::info!("recording metrics into {out:?} every {}", humantime::format_duration(interval));
+    match record(&metrics_handler, interval, out, format, stop).await {
+        Ok(recorded) => {
+            tracing::info!("recorded {recorded} collections into {out:?}");
+            0
+        },
+        Err(err) => {
+            tracing::error!("failed to record metrics\nError: {err:?}");
+            1
+        },
+    }
+}
+
+fn check(args: &Cli) -> i32 {
+    let mut problems = Vec::new();
+
+    let web_config = match args.web_config_file.as_ref().map(WebConfig::from_file).transpose() {
+        Ok(web_config) => web_config.unwrap_or_default(),
+        Err(err) => {
+            problems.push(format!("invalid web config: {err:#}"));
+            WebConfig::default()
+        },
+    };
+    // Not generated by a check, but on the first start
+    let self_signed_missing = args.tls_self_signed
+        .as_ref()
+        .is_some_and(|dir| !dir.join("cert.pem").exists() || !dir.join("key.pem").exists());
+    if !self_signed_missing {
+        match tls_config(args, &web_config).and_then(|tls| tls.as_ref().map(TlsConfig::validate).transpose()) {
+            Ok(Some(())) if args.address.iter().any(|address| matches!(address, Listen::Unix(_))) => {
+                problems.push("TLS can't be served on a unix socket".to_string());
+            },
+            Ok(_) => {},
+            Err(err) => problems.push(format!("invalid TLS config: {err:#}")),
+        }
+    }
+
+    for address in &args.address {
+        if let Listen::Unix(path) = address
+            && path.parent().is_some_and(|dir| !dir.as_os_str().is_empty() && !dir.is_dir())
+        {
+            problems.push(format!("directory of the unix socket doesn't exist: {path:?}"));
+        }
+    }
+
+    let cgroups = match (args.metrics.has(Metric::OomKill), args.metrics.has(Metric::Cgroup)) {
+        (true, true) => [&args.oom_kill_cgroups[..], &args.cgroups[..]].concat(),
+        (true, false) => args.oom_kill_cgroups.clone(),
+        (false, true) => args.cgroups.clone(),
+        (false, false) => Vec::new(),
+    };
+    for cgroup in cgroups {
+        if !Path::new("/sys/fs/cgroup").join(&cgroup).is_dir() {
+            problems.push(format!("cgroup doesn't exist: {cgroup}"));
+        }
+    }
+    if let Some(state_file) = args.throttled_state_file.as_ref().filter(|_| args.metrics.has(Metric::Throttled))
+        && state_file.parent().is_some_and(|dir| !dir.as_os_str().is_empty() && !dir.is_dir())
+    {
+        problems.push(format!("directory of the throttling state file doesn't exist: {state_file:?}"));
+    }
+    if let Some(history_file) = &args.history_file
+        && history_file.parent().is_some_and(|dir| !dir.as_os_str().is_empty() && !dir.is_dir())
+    {
+        problems.push(format!("directory of the history file doesn't exist: {history_file:?}"));
+    }
+    if args.metrics.has(Metric::Container) && !args.container_socket.exists() {
+        problems.push(format!("container socket doesn't exist: {:?}", args.container_socket));
+    }
+    if args.metrics.has(Metric::BootConfig) {
+        for file in args.boot_config_files.iter().filter(|file| !file.is_file()) {
+            problems.push(format!("boot config file doesn't exist: {file:?}"));
+        }
+    }
+
+    if let Some(admin_address) = args.admin_address
+        && !admin_address.ip().is_loopback()
+        && web_config.basic_auth().is_none()
+    {
+        problems.push(format!("admin address must be a loopback address unless basic auth is configured: {admin_address}"));
+    }
+
+    for url in [&args.remote_write_url, &args.influxdb_url].into_iter().flatten() {
+        if !matches!(url.scheme_str(), Some("http" | "https")) || url.host().is_none() {
+            problems.push(format!("URL must be http or https with a host: {url}"));
+        }
+    }
+
+    println!("Effective options");
+    for setting in &args.settings {
+        let values = match setting.name.contains("password") || setting.name.contains("token") {
+            true => "<hidden>".to_string(),
+            false => setting.values.join(","),
+        };
+        println!("  {}: {values} ({})", setting.name, setting.source);
+    }
+    println!();
+
+    if problems.is_empty() {
+        println!("configuration is valid");
+        return 0;
+    }
+    for problem in problems {
+        println!("{problem}");
+    }
+
+    1
+}
+
+// Tries every collector rather than only the enabled ones
+async fn doctor(args: &Cli) {
+    println!("Board");
+    for probe in doctor::probe() {
+        match probe.result {
+            Ok(found) => println!("  {}: {found}", probe.name),
+            Err(missing) => println!("  {}: {missing}", probe.name),
+        }
+    }
+
+    let metrics_handler = metrics_handler(args, None);
+    for metric in Metric::value_variants() {
+        metrics_handler.insert(metric_group(args, metric).await);
+    }
+    println!("Collectors");
+    for (name, result) in metrics_handler.diagnose().await {
+        match result {
+            Ok(()) => println!("  {name}: works"),
+            Err(err) => println!("  {name}: fails: {err:#}"),
+        }
+    }
+}
+
+// Options applied by a reload, the others being warned of as taking a restart
+const RELOADABLE: [&str; 13] = [
+    "config",
+    "enable-metrics",
+    "disable-metrics",
+    "tls-cert",
+    "tls-key",
+    "tls-self-signed",
+    "web.config.file",
+    "collector-timeout",
+    "collector-timeouts",
+    "fail-on-collector-error",
+    "hook",
+    "webhook-url",
+    "webhook-debounce",
+];
+
+// Options of the throttling notifier, which is started over when one of them changes
+const NOTIFIER: [&str; 5] = ["enable-metrics", "disable-metrics", "hook", "webhook-url", "webhook-debounce"];
+
+// Applies the config file again on SIGHUP, keeping the state of the metrics that stay enabled
+async fn reload(
+    mut settings: Vec<Setting>,
+    mut collectors: HashMap<Metric, CollectorConfig>,
+    metrics_handler: Arc<MetricsHandler>,
+    tls_updates: Option<watch::Sender<TlsConfig>>,
+    basic_auth: Option<Arc<BasicAuth>>,
+    events: Arc<Events>,
+    mut notifier: Option<JoinHandle<()>>,
+) {
+    let mut sighup = unix::signal(SignalKind::hangup()).expect("SIGHUP error");
+    while sighup.recv().await.is_some() {
+        let args = match Cli::try_parse_with_config() {
+            Ok(args) => args,
+            Err(err) => {
+                tracing::error!("failed to reload config, keeping the current one\nError: {err}");
+                continue;
+            },
+        };
+
+        match args.web_config_file.as_ref().map(WebConfig::from_file).transpose() {
+            Ok(web_config) => {
+                let web_config = web_config.unwrap_or_default();
+                match (tls_config(&args, &web_config), &tls_updates) {
+                    (Ok(Some(tls)), Some(tls_updates)) => {
+                        tls_updates.send_replace(tls);
+                    },
+                    (Ok(None), None) => {},
+                    (Ok(_), _) => tracing::warn!("turning TLS on or off takes a restart"),
+                    (Err(err), _) => tracing::error!("failed to reload TLS config, keeping the current one\nError: {err:?}"),
+                }
+                match (web_config.basic_auth(), &basic_auth) {
+                    (Some(users), Some(basic_auth)) => basic_auth.reload(users),
+                    (None, None) => {},
+                    (_, _) => tracing::warn!("turning basic authentication on or off takes a restart"),
+                }
+            },
+            Err(err) => tracing::error!("failed to reload web config, keeping the current one\nError: {err:?}"),
+        }
+
+        metrics_handler.reload(exporter::metrics_handler(&args, None));
+
+        let changed = args.changed(&settings);
+        let previous = |metric| collectors.get(&metric).cloned().unwrap_or_default();
+        let throttled = args.collector(Metric::Throttled) != previous(Metric::Throttled);
+        if throttled || changed.iter().any(|name| NOTIFIER.contains(&name.as_str())) {
+            if let Some(notifier) = notifier.take() {
+                notifier.abort();
+            }
+            notifier = throttling_notifier(&args, events.clone()).map(Sampler::spawn);
+        }
+        if (args.fan_pwm.is_some() || args.fan_gpio.is_some()) && args.collector(Metric::Temperature) != previous(Metric::Temperature) {
+            tracing::warn!("the fan keeps the collector settings of temperature until a restart");
+        }
+        let restart = changed.into_iter().filter(|name| !RELOADABLE.contains(&name.as_str())).collect::<Vec<_>>();
+        if !restart.is_empty() {
+            tracing::warn!("changing {} takes a restart, keeping the current values", restart.join(", "));
+        }
+
+        metrics_handler.retain(&args.metrics.enabled().iter().map(ToString::to_string).collect::<Vec<_>>());
+        let names = metrics_handler.names();
+        for metric in &args.metrics.enabled() {
+            if !names.contains(&metric.to_string()) {
+                metrics_handler.insert(metric_group(&args, metric).await);
+            } else if args.collector(*metric) != previous(*metric) {
+                tracing::info!("rebuilding {metric} with its new collector settings");
+                metrics_handler.replace(metric_group(&args, metric).await);
+            }
+        }
+        tracing::info!("reloaded config, enabled metrics: {}", args.metrics);
+        settings = args.settings;
+        collectors = args.collectors;
+    }
+}
+
+// Service on the first TCP address to listen on
+fn mdns(args: &Cli, https: bool) -> anyhow::Result<Mdns> {
+    let address = args.address
+        .iter()
+        .find_map(|address| match address.address(args.port) {
+            ListenAddress::Tcp(address) => Some(address),
+            ListenAddress::Unix(_) => None,
+        })
+        .context("no TCP address to advertise")?;
+    let hostname = fs::read_to_string("/proc/sys/kernel/hostname").unwrap_or_else(|_| "localhost".to_string());
+
+    Ok(Mdns::new(hostname.trim(), address.port(), &args.metrics_path).https(https))
+}
+
+// TLS settings of a self-signed certificate, generated on the first start, or of the web config or the command line
+fn tls_config(args: &Cli, web_config: &WebConfig) -> anyhow::Result<Option<TlsConfig>> {
+    let Some(dir) = &args.tls_self_signed else {
+        let tls = web_config.tls()?;
+        return Ok(tls.or_else(|| args.tls_cert.clone().zip(args.tls_key.clone()).map(|(cert, key)| TlsConfig::new(cert, key))));
+    };
+
+    let (cert_file, key_file) = (dir.join("cert.pem"), dir.join("key.pem"));
+    let hostname = fs::read_to_string("/proc/sys/kernel/hostname").unwrap_or_else(|_| "localhost".to_string());
+    if self_signed::ensure(&cert_file, &key_file, hostname.trim()).context("failed to generate a self-signed certificate")? {
+        tracing::info!("generated a self-signed certificate: {cert_file:?}");
+    }
+
+    Ok(Some(TlsConfig::new(cert_file, key_file)))
+}
+
+fn setup_logging(output_type: Log, stderr: bool) {
+    let layer = match stderr {
+        true => fmt::layer().with_writer(BoxMakeWriter::new(io::stderr)),
+        false => fmt::layer().with_writer(BoxMakeWriter::new(io::stdout)),
+    };
     let layer = match output_type {
         Log::Plain => layer.boxed(),
         Log::Json => layer.json().boxed(),
@@ -0,0 +1,147 @@
+//! Man page of the command line in roff, generated from its definition for packages to install as section 1.
+
+use clap::{Arg, Command};
+
+use crate::config::ENV_PREFIX;
+
+/// Man page of `command` with its options, its subcommands and their options.
+pub fn render(command: Command) -> String {
+    let mut command = command;
+    command.build();
+    let name = command.get_name().to_string();
+    let version = command.get_version().unwrap_or_default();
+
+    let mut page = format!(".TH {} 1 \"\" \"{name} {version}\"\n", escape(&name.to_uppercase()));
+    page.push_str(&format!(".SH NAME\n{} \\- {}\n", escape(&name), escape(&first_line(command.get_about()))));
+    page.push_str(&format!(".SH SYNOPSIS\n.B {}\n[\\fIOPTIONS\\fR] [\\fICOMMAND\\fR] [\\fICOMMAND OPTIONS\\fR]\n", escape(&name)));
+    if let Some(about) = command.get_long_about().or(command.get_about()) {
+        page.push_str(&format!(".SH DESCRIPTION\n{}\n", paragraphs(&about.to_string())));
+    }
+
+    page.push_str(".SH OPTIONS\n");
+    for arg in command.get_arguments().filter(|arg| !arg.is_hide_set()) {
+        page.push_str(&option(arg));
+    }
+
+    let subcommands = command.get_subcommands().filter(|subcommand| !subcommand.is_hide_set() && subcommand.get_name() != "help");
+    page.push_str(".SH COMMANDS\n");
+    for subcommand in subcommands {
+        let about = subcommand.get_long_about().or(subcommand.get_about()).map(ToString::to_string).unwrap_or_default();
+        page.push_str(&format!(".TP\n\\fB{}\\fR\n{}\n", escape(subcommand.get_name()), paragraphs(&about)));
+        // Without the global options listed above
+        let args = subcommand
+            .get_arguments()
+            .filter(|arg| !arg.is_hide_set() && !arg.is_global_set() && arg.get_id() != "help")
+            .collect::<Vec<_>>();
+        if !args.is_empty() {
+            page.push_str(".RS\n");
+            for arg in args {
+                page.push_str(&option(arg));
+            }
+            page.push_str(".RE\n");
+        }
+    }
+
+    page.push_str(&format!(
+        ".SH ENVIRONMENT\n.TP\n\\fB{}\\fR\\fINAME\\fR\n{}\n",
+        escape(ENV_PREFIX),
+        escape(concat!(
+            "Option of the long name NAME in upper case with underscores, e.g. RASPI_EXPORTER_PORT of --port, which overrides ",
+            "the config file and is overridden by the command line.",
+        )),
+    ));
+
+    page
+}
+
+// Tagged paragraph of an option with its values and its default
+fn option(arg: &Arg) -> String {
+    let mut names = Vec::new();
+    if let Some(short) = arg.get_short() {
+        names.push(format!("\\fB\\-{}\\fR", escape(&short.to_string())));
+    }
+    if let Some(long) = arg.get_long() {
+        names.push(format!("\\fB\\-\\-{}\\fR", escape(long)));
+    }
+    let value = arg
+        .get_value_names()
+        .and_then(|names| names.first())
+        .map(ToString::to_string)
+        .unwrap_or_else(|| arg.get_id().as_str().to_uppercase());
+    let mut tag = names.join(", ");
+    if arg.is_positional() {
+        tag = format!("\\fI{}\\fR", escape(&value));
+    } else if arg.get_action().takes_values() {
+        tag.push_str(&format!(" \\fI{}\\fR", escape(&value)));
+    }
+
+    let help = arg.get_long_help().or(arg.get_help()).map(|help| paragraphs(&help.to_string()));
+    let mut description = help.into_iter().collect::<Vec<_>>();
+    let values = arg.get_possible_values().into_iter().filter(|value| !value.is_hide_set()).collect::<Vec<_>>();
+    if arg.get_action().takes_values() && !values.is_empty() {
+        let values = values.iter().map(|value| value.get_name()).collect::<Vec<_>>().join(", ");
+        description.push(format!("Possible values: {}", escape(&values)));
+    }
+    let defaults = arg.get_default_values().iter().map(|value| value.to_string_lossy()).collect::<Vec<_>>();
+    if arg.get_action().takes_values() && !defaults.is_empty() {
+        description.push(format!("Default: {}", escape(&defaults.join(","))));
+    }
+    let description = description.join("\n.br\n");
+
+    format!(".TP\n{tag}\n{description}\n")
+}
+
+fn first_line(text: Option<&clap::builder::StyledStr>) -> String {
+    text.map(|text| text.to_string().lines().next().unwrap_or_default().to_string()).unwrap_or_default()
+}
+
+// Escaped text with a paragraph break for each blank line
+fn paragraphs(text: &str) -> String {
+    text.split("\n\n").map(|paragraph| escape(paragraph.trim())).collect::<Vec<_>>().join("\n.IP\n")
+}
+
+// Text safe from being taken for roff requests and escapes
+fn escape(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            let line = line.replace('\\', "\\e").replace('-', "\\-");
+            match line.starts_with(['.', '\'']) {
+                true => format!("\\&{line}"),
+                false => line,
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::{Arg, ArgAction, Command};
+
+    use crate::man::{escape, render};
+
+    #[test]
+    fn render_page() {
+        let command = Command::new("raspi-exporter")
+            .version("0.1.1")
+            .about("Prometheus exporter of Raspberry Pi")
+            .arg(Arg::new("port").short('p').long("port").value_name("PORT").default_value("8021").help("Port to listen on"))
+            .arg(Arg::new("log").long("log").value_parser(["plain", "json"]).global(true))
+            .arg(Arg::new("mdns").long("mdns").action(ArgAction::SetTrue).help("Advertises the exporter"))
+            .subcommand(Command::new("check").about("Validates the options"));
+        let page = render(command);
+
+        assert!(page.starts_with(".TH RASPI\\-EXPORTER 1 \"\" \"raspi-exporter 0.1.1\"\n"));
+        assert!(page.contains(".SH NAME\nraspi\\-exporter \\- Prometheus exporter of Raspberry Pi\n"));
+        assert!(page.contains(".TP\n\\fB\\-p\\fR, \\fB\\-\\-port\\fR \\fIPORT\\fR\nPort to listen on\n.br\nDefault: 8021\n"));
+        assert!(page.contains(".TP\n\\fB\\-\\-log\\fR \\fILOG\\fR\nPossible values: plain, json\n"));
+        assert!(page.contains(".TP\n\\fB\\-\\-mdns\\fR\nAdvertises the exporter\n"));
+        assert!(page.contains(".SH COMMANDS\n.TP\n\\fBcheck\\fR\nValidates the options\n"));
+        assert_eq!(page.matches("\\-\\-log").count(), 1);
+    }
+
+    #[test]
+    fn escape_text() {
+        assert_eq!(escape(".hidden\n'quoted\nC:\\path --flag"), "\\&.hidden\n\\&'quoted\nC:\\epath \\-\\-flag");
+    }
+}
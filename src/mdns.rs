@@ -0,0 +1,249 @@
+//! Advertisement of the exporter as `_prometheus-http._tcp` over multicast DNS, answering the queries of DNS-SD browsers
+//! such as `avahi-browse` alongside the responder of the system, which answers for the address of the host.
+
+use std::{
+    net::{Ipv4Addr, SocketAddr, SocketAddrV4},
+    time::Duration,
+};
+
+use anyhow::Context;
+use socket2::{Domain, Protocol, Socket, Type};
+use tokio::{net::UdpSocket, time};
+
+const GROUP: Ipv4Addr = Ipv4Addr::new(224, 0, 0, 251);
+const PORT: u16 = 5353;
+const SERVICE: &str = "_prometheus-http._tcp.local";
+const SERVICES: &str = "_services._dns-sd._udp.local";
+
+const TYPE_PTR: u16 = 12;
+const TYPE_TXT: u16 = 16;
+const TYPE_SRV: u16 = 33;
+const TYPE_ANY: u16 = 255;
+const CLASS_IN: u16 = 1;
+// Tells caches to replace the records of the name rather than add to them, for the records unique to this service
+const CACHE_FLUSH: u16 = 0x8000;
+
+/// Service of the exporter on `hostname`.local, with the port and the metrics path in its TXT record.
+///
+/// The address of `hostname`.local is left to the responder of the system, which owns the name.
+#[derive(Clone, Debug)]
+pub struct Mdns {
+    hostname: String,
+    port: u16,
+    path: String,
+    https: bool,
+}
+
+impl Mdns {
+    pub fn new(hostname: impl Into<String>, port: u16, path: impl Into<String>) -> Self {
+        Self {
+            hostname: hostname.into(),
+            port,
+            path: path.into(),
+            https: false,
+        }
+    }
+
+    /// Advertises the scheme as https in the TXT record when `https` is true.
+    pub fn https(self, https: bool) -> Self {
+        Self {
+            https,
+            ..self
+        }
+    }
+
+    /// Announces the service, and then answers the queries for it until the exporter stops.
+    pub async fn start(self) {
+        if let Err(err) = self.serve().await {
+            tracing::error!("failed to advertise over mDNS\nError: {err:?}");
+        }
+    }
+
+    async fn serve(&self) -> anyhow::Result<()> {
+        let socket = bind().context("failed to bind the mDNS socket")?;
+        let group = SocketAddr::from((GROUP, PORT));
+        let response = self.response();
+
+        // Twice a second apart, as RFC 6762 asks of announcements
+        for _ in 0..2 {
+            socket.send_to(&response, group).await?;
+            time::sleep(Duration::from_secs(1)).await;
+        }
+        tracing::info!("advertising {} over mDNS", self.instance());
+
+        let mut buffer = [0; 9000];
+        loop {
+            let (length, source) = socket.recv_from(&mut buffer).await?;
+            let questions = match questions(&buffer[..length]) {
+                Ok(questions) => questions,
+                Err(err) => {
+                    tracing::debug!("ignoring an mDNS message from {source}\nError: {err:?}");
+                    continue;
+                },
+            };
+            if questions.iter().any(|question| self.answers(question)) {
+                socket.send_to(&response, group).await?;
+            }
+        }
+    }
+
+    fn instance(&self) -> String {
+        format!("{}.{SERVICE}", self.hostname)
+    }
+
+    fn host(&self) -> String {
+        format!("{}.local", self.hostname)
+    }
+
+    fn answers(&self, (name, kind): &(String, u16)) -> bool {
+        let names = [
+            (SERVICES.to_string(), TYPE_PTR),
+            (SERVICE.to_string(), TYPE_PTR),
+            (self.instance(), TYPE_SRV),
+            (self.instance(), TYPE_TXT),
+        ];
+        names.iter().any(|(known, known_kind)| name.eq_ignore_ascii_case(known) && (kind == known_kind || *kind == TYPE_ANY))
+    }
+
+    // Every record of the service in one message, which is small enough
+    fn response(&self) -> Vec<u8> {
+        let mut txt = vec![format!("path={}", self.path)];
+        if self.https {
+            txt.push("scheme=https".to_string());
+        }
+        let txt = txt.into_iter().flat_map(|entry| [&[entry.len() as u8][..], entry.as_bytes()].concat()).collect::<Vec<_>>();
+        let srv = [&0u16.to_be_bytes()[..], &0u16.to_be_bytes(), &self.port.to_be_bytes(), &name(&self.host())].concat();
+
+        let records = [
+            record(SERVICES, TYPE_PTR, CLASS_IN, 4500, &name(SERVICE)),
+            record(SERVICE, TYPE_PTR, CLASS_IN, 4500, &name(&self.instance())),
+            record(&self.instance(), TYPE_SRV, CLASS_IN | CACHE_FLUSH, 120, &srv),
+            record(&self.instance(), TYPE_TXT, CLASS_IN | CACHE_FLUSH, 4500, &txt),
+        ];
+
+        // An authoritative response without questions
+        let mut message = [0u16, 0x8400, 0, records.len() as u16, 0, 0].iter().flat_map(|field| field.to_be_bytes()).collect::<Vec<_>>();
+        message.extend(records.concat());
+        message
+    }
+}
+
+// Shares the port with the responder of the system such as avahi-daemon
+fn bind() -> anyhow::Result<UdpSocket> {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+    socket.set_reuse_address(true)?;
+    socket.set_nonblocking(true)?;
+    socket.bind(&SocketAddr::from(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, PORT)).into())?;
+    socket.join_multicast_v4(&GROUP, &Ipv4Addr::UNSPECIFIED)?;
+    socket.set_multicast_ttl_v4(255)?;
+
+    Ok(UdpSocket::from_std(socket.into())?)
+}
+
+fn record(owner: &str, kind: u16, class: u16, ttl: u32, data: &[u8]) -> Vec<u8> {
+    [&name(owner)[..], &kind.to_be_bytes(), &class.to_be_bytes(), &ttl.to_be_bytes(), &(data.len() as u16).to_be_bytes(), data].concat()
+}
+
+// Name in labels, without compression
+fn name(name: &str) -> Vec<u8> {
+    let mut encoded = name.split('.').flat_map(|label| [&[label.len() as u8][..], label.as_bytes()].concat()).collect::<Vec<_>>();
+    encoded.push(0);
+    encoded
+}
+
+// Names and types of the questions of a query, none of a response
+fn questions(message: &[u8]) -> anyhow::Result<Vec<(String, u16)>> {
+    let field = |offset: usize| message.get(offset..offset + 2).map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]])).context("truncated message");
+    if field(2)? & 0x8000 != 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut questions = Vec::new();
+    let mut offset = 12;
+    for _ in 0..field(4)? {
+        let (name, end) = read_name(message, offset)?;
+        // The top bit of the class asks for a unicast response, which is answered by multicast anyway
+        questions.push((name, field(end)?));
+        field(end + 2)?;
+        offset = end + 4;
+    }
+
+    Ok(questions)
+}
+
+// Name at `offset` following compression pointers, and the offset after it
+fn read_name(message: &[u8], mut offset: usize) -> anyhow::Result<(String, usize)> {
+    let mut labels = Vec::new();
+    let mut end = None;
+    // Bounds the pointers followed, so that a loop of them can't hang
+    for _ in 0..128 {
+        let length = *message.get(offset).context("truncated name")? as usize;
+        match length {
+            0 => return Ok((labels.join("."), end.unwrap_or(offset + 1))),
+            length if length & 0xc0 == 0xc0 => {
+                let low = *message.get(offset + 1).context("truncated pointer")? as usize;
+                end.get_or_insert(offset + 2);
+                offset = (length & 0x3f) << 8 | low;
+            },
+            length => {
+                let label = message.get(offset + 1..offset + 1 + length).context("truncated label")?;
+                labels.push(String::from_utf8_lossy(label).into_owned());
+                offset += 1 + length;
+            },
+        }
+    }
+
+    anyhow::bail!("too many compression pointers")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mdns::{name, questions, read_name, Mdns, TYPE_ANY, TYPE_PTR, TYPE_SRV};
+
+    #[test]
+    fn encode_name() {
+        assert_eq!(name("pi.local"), b"\x02pi\x05local\x00");
+    }
+
+    #[test]
+    fn read_compressed_name() {
+        let message = b"\x05local\x00\x02pi\xc0\x00";
+        assert_eq!(read_name(message, 0).unwrap(), ("local".to_string(), 7));
+        assert_eq!(read_name(message, 7).unwrap(), ("pi.local".to_string(), 12));
+        assert!(read_name(b"\xc0\x00", 0).is_err());
+    }
+
+    #[test]
+    fn answer_questions() {
+        let mdns = Mdns::new("pi-01", 8021, "/metrics");
+        let query = [
+            &[0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0][..],
+            &name("_prometheus-http._tcp.local"),
+            &TYPE_PTR.to_be_bytes(),
+            &0x8001u16.to_be_bytes(),
+        ].concat();
+        let asked = questions(&query).unwrap();
+
+        assert_eq!(asked, [("_prometheus-http._tcp.local".to_string(), TYPE_PTR)]);
+        assert!(mdns.answers(&asked[0]));
+        assert!(mdns.answers(&("PI-01._prometheus-http._tcp.local".to_string(), TYPE_SRV)));
+        assert!(!mdns.answers(&("pi-02._prometheus-http._tcp.local".to_string(), TYPE_SRV)));
+        // The address is answered by the responder of the system
+        assert!(!mdns.answers(&("pi-01.local".to_string(), TYPE_ANY)));
+        // Responses aren't answered
+        assert!(questions(&mdns.response()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn response() {
+        let response = Mdns::new("pi-01", 8021, "/metrics").https(true).response();
+
+        // PTR, SRV and TXT answers
+        assert_eq!(response[..12], [0, 0, 0x84, 0, 0, 0, 0, 4, 0, 0, 0, 0]);
+        let contains = |bytes: &[u8]| response.windows(bytes.len()).any(|window| window == bytes);
+        assert!(contains(b"\x0dpath=/metrics\x0cscheme=https"));
+        assert!(contains(&[0, 0, 0, 0, 0x1f, 0x55]));
+        // No A record of pi-01.local
+        assert!(!contains(&[&name("pi-01.local")[..], &[0, 1]].concat()));
+    }
+}
@@ -1,59 +1,856 @@
-use std::sync::{Arc, Mutex};
+use std::{collections::{HashMap, HashSet}, sync::{atomic::{AtomicBool, Ordering}, Arc, Mutex, RwLock}, time::Duration};
 
 use anyhow::Context;
-use prometheus_client::{encoding::text, registry::Registry};
+use async_trait::async_trait;
+use futures::future;
+use percent_encoding::percent_decode_str;
+use prometheus_client::{
+    encoding::text,
+    metrics::{counter::Counter, family::Family, gauge::Gauge, info::Info},
+    registry::Registry,
+};
+use tokio::{sync::watch, task::JoinHandle, time::{self, Instant}};
+use tracing::Instrument;
 
+use crate::{
+    cache::ScrapeCache,
+    events::{Event, Events},
+    format::Labels,
+    hook::Hook,
+    metrics::collector::CollectorLabels,
+    relabel::{self, Rule},
+    threshold::{self, Threshold},
+};
+
+pub mod access_point;
+pub mod backlight;
+pub mod boot_config;
+pub mod boot_time;
+pub mod cgroup;
+pub mod clock_tree;
+pub mod collector;
+pub mod container;
+pub mod cpu_vulnerability;
+pub mod filesystem;
+pub mod kmsg;
+pub mod neighbor;
+pub mod nftables;
+pub mod oom_kill;
+pub mod reboot_required;
+pub mod reset;
+pub mod snmp;
+pub mod ssh;
+pub mod state_set;
 pub mod throttled;
+pub mod vl805;
+pub mod wireguard;
+
+pub struct MetricsHandler {
+    // Replaced on reload, while scrapes keep the groups they selected
+    groups: RwLock<Vec<Arc<MetricGroup>>>,
+    // Results of the scrapes currently running by the names of selected groups, which concurrent scrapes wait for instead of
+    // collecting again
+    in_flight: Mutex<HashMap<Vec<Option<String>>, InFlight>>,
+    // Replaced on reload like the relabel rules, the thresholds and the hook
+    collector_timeout: RwLock<Option<Duration>>,
+    collector_timeouts: RwLock<HashMap<String, Duration>>,
+    fail_on_collector_error: AtomicBool,
+    // Metrics about the collectors themselves, exposed regardless of filters
+    registry: Mutex<Registry>,
+    timeouts: Family<CollectorLabels, Counter>,
+    successes: Family<CollectorLabels, Gauge>,
+    relabel: RwLock<Vec<Rule>>,
+    thresholds: RwLock<Vec<Threshold>>,
+    exceeded: Family<Labels, Gauge>,
+    hook: RwLock<Option<Hook>>,
+    // Samples beyond their thresholds as of the last scrape selecting them, to run the hook on changes only
+    breached: Mutex<HashSet<Labels>>,
+    events: Arc<Events>,
+    // Collectors whose last collection failed, to log failures on changes only
+    failing: Mutex<HashSet<&'static str>>,
+}
 
-#[derive(Debug)]
-pub struct MetricsHandler<Throttled> {
-    throttled: Option<Throttled>,
+/// Collectors of an enabled metric and the registry they register in, so that scrapes can select metrics by name.
+pub struct MetricGroup {
+    name: Option<String>,
+    collectors: Vec<Box<dyn Collector>>,
+    // Whether each collector has succeeded at least once
+    ready: Vec<AtomicBool>,
+    // Turned off through the admin API, which keeps the group to turn it on again
+    enabled: AtomicBool,
     registry: Arc<Mutex<Registry>>,
+    // Background tasks such as samplers, stopped when the group is dropped
+    tasks: Vec<JoinHandle<()>>,
+}
+
+/// Metrics selected by `collect[]` and `exclude[]` query parameters like node_exporter, all of them when both are empty.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Filter {
+    pub collect: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+type ScrapeResult = Result<String, String>;
+type InFlight = watch::Receiver<Option<ScrapeResult>>;
+
+enum Flight {
+    Leader(watch::Sender<Option<ScrapeResult>>),
+    Follower(watch::Receiver<Option<ScrapeResult>>),
+}
+
+// Lets the next scrape collect again once the running one has finished or been cancelled
+struct InFlightGuard<'a> {
+    in_flight: &'a Mutex<HashMap<Vec<Option<String>>, InFlight>>,
+    selected: &'a [Option<String>],
 }
 
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        self.in_flight.lock().expect("failed to lock in-flight mutex").remove(self.selected);
+    }
+}
+
+// Runs one collection of the collector at a time, since scrapes selecting different groups don't share their results, so
+// that an older reading doesn't land after a newer one, which a counter would take for a reset
+struct Serialized {
+    collector: Box<dyn Collector>,
+    collecting: tokio::sync::Mutex<()>,
+}
+
+/// Sets the values of metrics from a collected state.
+///
+/// Metric families are registered once when a registerer is created, typically by a `new` taking the registry, so that
+/// each collection only updates values instead of registering again.
 pub trait Registerer {
     type Item;
 
-    fn register(&self, state: Self::Item) -> impl Future<Output = anyhow::Result<()>> + Send;
+    fn update(&self, state: Self::Item) -> impl Future<Output = anyhow::Result<()>> + Send;
+}
+
+/// Updates both registerers with the same state.
+impl<A, B> Registerer for (A, B)
+where
+    A: Registerer + Sync,
+    B: Registerer<Item = A::Item> + Sync,
+    A::Item: Clone + Send,
+{
+    type Item = A::Item;
+
+    async fn update(&self, state: Self::Item) -> anyhow::Result<()> {
+        self.0.update(state.clone()).await?;
+        self.1.update(state).await?;
+
+        Ok(())
+    }
+}
+
+/// Updates the registerer if any, e.g. one of an optional notification.
+impl<R> Registerer for Option<R>
+where
+    R: Registerer + Sync,
+    R::Item: Send,
+{
+    type Item = R::Item;
+
+    async fn update(&self, state: Self::Item) -> anyhow::Result<()> {
+        match self {
+            Some(registerer) => registerer.update(state).await,
+            None => Ok(()),
+        }
+    }
 }
 
+/// Collects a state with an [`Executor`](crate::executor::Executor), parses it with a [`Parser`](crate::parser::Parser)
+/// and updates metrics with a [`Registerer`], typically holding one of each.
+///
+/// Implementations take [`async_trait`], so that collectors of different types can be boxed together.
 #[cfg_attr(test, mockall::automock)]
-pub trait Collector {
+#[async_trait]
+pub trait Collector: Send + Sync {
     fn name(&self) -> &'static str;
-    fn collect(&self) -> impl Future<Output = anyhow::Result<()>> + Send;
+    async fn collect(&self) -> anyhow::Result<()>;
+}
+
+#[async_trait]
+impl Collector for Serialized {
+    fn name(&self) -> &'static str {
+        self.collector.name()
+    }
+
+    async fn collect(&self) -> anyhow::Result<()> {
+        let _collecting = self.collecting.lock().await;
+        self.collector.collect().await
+    }
+}
+
+impl Serialized {
+    fn boxed(collector: Box<dyn Collector>) -> Box<dyn Collector> {
+        Box::new(Self {
+            collector,
+            collecting: tokio::sync::Mutex::default(),
+        })
+    }
 }
 
 pub trait Handler {
-    fn handle(&self) -> impl Future<Output = anyhow::Result<String>> + Send;
+    /// Returns the names of the metrics that filters can select.
+    fn names(&self) -> Vec<String>;
+
+    /// Collects the metrics selected by `filter`, giving up collecting the rest once `timeout` has elapsed.
+    fn handle(&self, filter: &Filter, timeout: Option<Duration>) -> impl Future<Output = anyhow::Result<String>> + Send;
+
+    /// Returns whether each collector has succeeded at least once, by name.
+    fn readiness(&self) -> impl Future<Output = Vec<(&'static str, bool)>> + Send;
+
+    /// Returns the metrics that can be turned on and off, by name with whether each is on, none by default.
+    fn toggles(&self) -> Vec<(String, bool)> {
+        Vec::new()
+    }
+
+    /// Turns collecting the metric on or off until the exporter restarts, returning false when it is unknown.
+    fn toggle(&self, _name: &str, _enabled: bool) -> bool {
+        false
+    }
+
+    /// Collects the metric right away even if it is off, returning the result of each of its collectors or none when it is
+    /// unknown.
+    fn collect_now(&self, _name: &str) -> impl Future<Output = Option<Vec<(&'static str, anyhow::Result<()>)>>> + Send {
+        async { None }
+    }
+
+    /// Returns the latest notable events, the oldest first, none by default.
+    fn latest_events(&self) -> Vec<Event> {
+        Vec::new()
+    }
+}
+
+impl<H> Handler for Arc<H>
+where
+    H: Handler + Send + Sync,
+{
+    fn names(&self) -> Vec<String> {
+        (**self).names()
+    }
+
+    fn handle(&self, filter: &Filter, timeout: Option<Duration>) -> impl Future<Output = anyhow::Result<String>> + Send {
+        (**self).handle(filter, timeout)
+    }
+
+    fn readiness(&self) -> impl Future<Output = Vec<(&'static str, bool)>> + Send {
+        (**self).readiness()
+    }
+
+    fn toggles(&self) -> Vec<(String, bool)> {
+        (**self).toggles()
+    }
+
+    fn toggle(&self, name: &str, enabled: bool) -> bool {
+        (**self).toggle(name, enabled)
+    }
+
+    fn collect_now(&self, name: &str) -> impl Future<Output = Option<Vec<(&'static str, anyhow::Result<()>)>>> + Send {
+        (**self).collect_now(name)
+    }
+
+    fn latest_events(&self) -> Vec<Event> {
+        (**self).latest_events()
+    }
 }
 
-impl<Throttled> MetricsHandler<Throttled> {
-    pub fn new(throttled: Option<Throttled>, registry: Arc<Mutex<Registry>>) -> Self {
+impl Default for MetricsHandler {
+    fn default() -> Self {
         Self {
-            throttled,
-            registry,
+            groups: RwLock::default(),
+            in_flight: Mutex::default(),
+            collector_timeout: RwLock::default(),
+            collector_timeouts: RwLock::default(),
+            fail_on_collector_error: AtomicBool::new(false),
+            registry: Mutex::default(),
+            timeouts: Family::default(),
+            successes: Family::default(),
+            relabel: RwLock::default(),
+            thresholds: RwLock::default(),
+            exceeded: Family::default(),
+            hook: RwLock::default(),
+            breached: Mutex::default(),
+            events: Arc::default(),
+            failing: Mutex::default(),
         }
+        .registry(Registry::with_prefix("raspi"))
     }
 }
 
-impl<Throttled> Handler for MetricsHandler<Throttled>
-where
-    Throttled: Collector + Send + Sync + 'static,
-{
-    #[tracing::instrument(skip_all)]
-    async fn handle(&self) -> anyhow::Result<String> {
-        if let Some(collector) = &self.throttled
-            && let Err(err) = collector.collect().await.with_context(|| collector_error(collector.name()))
-        {
-            tracing::error!("{err:?}");
+impl MetricsHandler {
+    /// Creates a handler with collectors that are scraped regardless of filters.
+    pub fn new(collectors: Vec<Box<dyn Collector>>, registry: Arc<Mutex<Registry>>) -> Self {
+        let ready = collectors.iter().map(|_| AtomicBool::new(false)).collect();
+
+        Self {
+            groups: RwLock::new(vec![Arc::new(MetricGroup {
+                name: None,
+                collectors: collectors.into_iter().map(Serialized::boxed).collect(),
+                ready,
+                registry,
+                enabled: AtomicBool::new(true),
+                tasks: Vec::new(),
+            })]),
+            ..Self::default()
+        }
+    }
+
+    /// Registers the metrics about the exporter and the collectors themselves in `registry`, which gives them its prefix.
+    pub fn registry(self, mut registry: Registry) -> Self {
+        registry.register(
+            "exporter_build",
+            "Version, revision and Rust compiler version the exporter is built from",
+            Info::new([
+                ("version", env!("CARGO_PKG_VERSION")),
+                ("revision", env!("RASPI_EXPORTER_REVISION")),
+                ("rustc", env!("RASPI_EXPORTER_RUSTC")),
+            ]),
+        );
+        registry.register(
+            "collector_timeouts",
+            "Number of collections given up for exceeding the collector or scrape timeout",
+            self.timeouts.clone(),
+        );
+        registry.register(
+            "collector_success",
+            "Whether the last collection succeeded (1) or failed (0)",
+            self.successes.clone(),
+        );
+
+        Self {
+            registry: Mutex::new(registry),
+            ..self
+        }
+    }
+
+    /// Gives up a collection taking longer than `collector_timeout`, so that a hung command doesn't stall the whole scrape.
+    pub fn collector_timeout(self, collector_timeout: Option<Duration>) -> Self {
+        Self {
+            collector_timeout: RwLock::new(collector_timeout),
+            ..self
+        }
+    }
+
+    /// Overrides the collector timeout by collector name.
+    pub fn collector_timeouts(self, collector_timeouts: HashMap<String, Duration>) -> Self {
+        Self {
+            collector_timeouts: RwLock::new(collector_timeouts),
+            ..self
+        }
+    }
+
+    /// Fails the whole scrape when any collector fails, rather than exposing what the others have collected.
+    pub fn fail_on_collector_error(self, fail_on_collector_error: bool) -> Self {
+        Self {
+            fail_on_collector_error: AtomicBool::new(fail_on_collector_error),
+            ..self
+        }
+    }
+
+    /// Rewrites the metrics with `relabel` rules before encoding.
+    pub fn relabel(self, relabel: Vec<Rule>) -> Self {
+        Self {
+            relabel: RwLock::new(relabel),
+            ..self
+        }
+    }
+
+    /// Exposes whether the samples of the metrics are beyond `thresholds`, in the registry given before.
+    pub fn thresholds(self, thresholds: Vec<Threshold>) -> Self {
+        if !thresholds.is_empty() {
+            self.register_exceeded();
+        }
+
+        Self {
+            thresholds: RwLock::new(thresholds),
+            ..self
+        }
+    }
+
+    /// Runs `hook` when a sample exceeds its threshold or stops exceeding it.
+    pub fn hook(self, hook: Option<Hook>) -> Self {
+        Self {
+            hook: RwLock::new(hook),
+            ..self
+        }
+    }
+
+    /// Logs collectors starting to fail or recovering and samples exceeding their thresholds or clearing in `events`.
+    pub fn events(self, events: Arc<Events>) -> Self {
+        Self {
+            events,
+            ..self
+        }
+    }
+
+    /// Adds a collector of another crate, such as one of a HAT vendor, which is collected on every scrape regardless of filters.
+    ///
+    /// `register` registers the metric families of the collector in the registry of the handler, which gives them its prefix
+    /// and labels, and returns the collector updating them.
+    ///
+    /// ```
+    /// use async_trait::async_trait;
+    /// use prometheus_client::metrics::gauge::Gauge;
+    /// use raspi_exporter::{
+    ///     executor::Executor,
+    ///     file::FileExecutor,
+    ///     metrics::{Collector, MetricsHandler, Registerer},
+    ///     parser::Parser,
+    /// };
+    ///
+    /// struct FanParser;
+    ///
+    /// impl Parser for FanParser {
+    ///     type Item = i64;
+    ///
+    ///     fn parse(&self, input: &str) -> anyhow::Result<i64> {
+    ///         Ok(input.trim().parse()?)
+    ///     }
+    /// }
+    ///
+    /// struct FanRegisterer {
+    ///     rpm: Gauge,
+    /// }
+    ///
+    /// impl Registerer for FanRegisterer {
+    ///     type Item = i64;
+    ///
+    ///     async fn update(&self, rpm: i64) -> anyhow::Result<()> {
+    ///         self.rpm.set(rpm);
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// struct Fan<E> {
+    ///     executor: E,
+    ///     registerer: FanRegisterer,
+    /// }
+    ///
+    /// #[async_trait]
+    /// impl<E> Collector for Fan<E>
+    /// where
+    ///     E: Executor + Send + Sync,
+    /// {
+    ///     fn name(&self) -> &'static str {
+    ///         "fan"
+    ///     }
+    ///
+    ///     async fn collect(&self) -> anyhow::Result<()> {
+    ///         let output = self.executor.execute().await?;
+    ///         self.registerer.update(FanParser.parse(&output)?).await
+    ///     }
+    /// }
+    ///
+    /// let handler = MetricsHandler::default();
+    /// handler.register_collector(|registry| {
+    ///     let rpm = Gauge::default();
+    ///     registry.register("fan_speed_rpm", "Speed of the fan", rpm.clone());
+    ///     Box::new(Fan {
+    ///         executor: FileExecutor::new("/sys/devices/platform/cooling_fan/hwmon/hwmon2/fan1_input"),
+    ///         registerer: FanRegisterer { rpm },
+    ///     })
+    /// });
+    /// ```
+    pub fn register_collector(&self, register: impl FnOnce(&mut Registry) -> Box<dyn Collector>) {
+        let collector = register(&mut self.registry.lock().expect("failed to lock registry mutex"));
+        self.groups.write().expect("failed to lock groups").push(Arc::new(MetricGroup {
+            name: None,
+            collectors: vec![Serialized::boxed(collector)],
+            ready: vec![AtomicBool::new(false)],
+            registry: Arc::default(),
+            enabled: AtomicBool::new(true),
+            tasks: Vec::new(),
+        }));
+    }
+
+    /// Adds a group of collectors selectable by its name.
+    pub fn insert(&self, group: MetricGroup) {
+        self.groups.write().expect("failed to lock groups").push(Arc::new(group));
+    }
+
+    /// Replaces the group of the same name, e.g. rebuilt with new settings, dropping the background tasks of the old one, or
+    /// adds it if there is none.
+    ///
+    /// The group stays disabled if the old one was.
+    pub fn replace(&self, group: MetricGroup) {
+        let mut groups = self.groups.write().expect("failed to lock groups");
+        match groups.iter_mut().find(|current| current.name.is_some() && current.name == group.name) {
+            Some(current) => {
+                group.enabled.store(current.enabled.load(Ordering::Relaxed), Ordering::Relaxed);
+                *current = Arc::new(group);
+            },
+            None => groups.push(Arc::new(group)),
+        }
+    }
+
+    /// Drops the named groups other than the ones of `names` along with their background tasks.
+    ///
+    /// Groups kept keep their state, such as values accumulated by samplers.
+    pub fn retain(&self, names: &[String]) {
+        self.groups
+            .write()
+            .expect("failed to lock groups")
+            .retain(|group| group.name.as_ref().is_none_or(|name| names.contains(name)));
+    }
+
+    fn select(&self, filter: &Filter) -> Vec<Arc<MetricGroup>> {
+        self.groups
+            .read()
+            .expect("failed to lock groups")
+            .iter()
+            .filter(|group| group.enabled.load(Ordering::Relaxed) && group.name.as_deref().is_none_or(|name| filter.matches(name)))
+            .cloned()
+            .collect()
+    }
+
+    fn named(&self, name: &str) -> Vec<Arc<MetricGroup>> {
+        self.groups
+            .read()
+            .expect("failed to lock groups")
+            .iter()
+            .filter(|group| group.name.as_deref() == Some(name))
+            .cloned()
+            .collect()
+    }
+
+    /// Collects with every collector once, returning their results in order, e.g. to tell why some of them fail.
+    pub async fn diagnose(&self) -> Vec<(&'static str, anyhow::Result<()>)> {
+        let groups = self.groups.read().expect("failed to lock groups").clone();
+        let mut results = Vec::new();
+        for collector in groups.iter().flat_map(|group| &group.collectors) {
+            results.push((collector.name(), self.collect_once(collector.as_ref()).await));
         }
 
+        results
+    }
+
+    /// Takes the timeouts, the relabel rules, the thresholds, the hook and the strictness of `handler`, e.g. built from a
+    /// reloaded config, keeping the groups and the state of their metrics.
+    pub fn reload(&self, handler: Self) {
+        *self.collector_timeout.write().expect("failed to lock collector timeout") = handler.collector_timeout.into_inner().expect("failed to lock collector timeout");
+        *self.collector_timeouts.write().expect("failed to lock collector timeouts") = handler.collector_timeouts.into_inner().expect("failed to lock collector timeouts");
+        self.fail_on_collector_error.store(handler.fail_on_collector_error.into_inner(), Ordering::Relaxed);
+        *self.relabel.write().expect("failed to lock relabel rules") = handler.relabel.into_inner().expect("failed to lock relabel rules");
+        *self.hook.write().expect("failed to lock hook") = handler.hook.into_inner().expect("failed to lock hook");
+
+        let thresholds = handler.thresholds.into_inner().expect("failed to lock thresholds");
+        let mut current = self.thresholds.write().expect("failed to lock thresholds");
+        if current.is_empty() && !thresholds.is_empty() {
+            self.register_exceeded();
+        }
+        // Of the thresholds gone, which the next scrape wouldn't clear
+        self.exceeded.clear();
+        self.breached
+            .lock()
+            .expect("failed to lock breached mutex")
+            .retain(|labels| thresholds.iter().any(|threshold| threshold.name == labels[0].1));
+        *current = thresholds;
+    }
+
+    fn register_exceeded(&self) {
+        self.registry.lock().expect("failed to lock registry mutex").register(
+            "threshold_exceeded",
+            "Whether the sample is beyond the threshold (1) or not (0)",
+            self.exceeded.clone(),
+        );
+    }
+
+    fn timeout(&self, name: &str) -> Option<Duration> {
+        let collector_timeouts = self.collector_timeouts.read().expect("failed to lock collector timeouts");
+        collector_timeouts.get(name).copied().or(*self.collector_timeout.read().expect("failed to lock collector timeout"))
+    }
+
+    async fn collect_once(&self, collector: &dyn Collector) -> anyhow::Result<()> {
+        match self.timeout(collector.name()) {
+            Some(timeout) => time::timeout(timeout, collector.collect()).await.unwrap_or_else(|_| Err(anyhow::anyhow!("timed out"))),
+            None => collector.collect().await,
+        }
+    }
+
+    fn join(&self, selected: &[Option<String>]) -> Flight {
+        let mut in_flight = self.in_flight.lock().expect("failed to lock in-flight mutex");
+        match in_flight.get(selected) {
+            Some(receiver) => Flight::Follower(receiver.clone()),
+            None => {
+                let (sender, receiver) = watch::channel(None);
+                in_flight.insert(selected.to_vec(), receiver);
+                Flight::Leader(sender)
+            },
+        }
+    }
+
+    async fn scrape(&self, groups: &[Arc<MetricGroup>], timeout: Option<Duration>) -> anyhow::Result<String> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        // Collects concurrently, so that a scrape takes as long as the slowest collector rather than all of them
+        let collections = groups
+            .iter()
+            .flat_map(|group| group.collectors.iter().zip(&group.ready))
+            .map(|(collector, ready)| async move {
+                let collector_deadline = self.timeout(collector.name()).map(|timeout| Instant::now() + timeout);
+                let result = match deadline.into_iter().chain(collector_deadline).min() {
+                    Some(deadline) => time::timeout_at(deadline, collector.collect()).await,
+                    None => Ok(collector.collect().await),
+                };
+                // Error of the failure if any
+                let failure = match result {
+                    Ok(result) => match result.with_context(|| collector_error(collector.name())) {
+                        Ok(()) => {
+                            ready.store(true, Ordering::Relaxed);
+                            None
+                        },
+                        Err(err) => {
+                            tracing::error!("{err:?}");
+                            Some(format!("{err:#}"))
+                        },
+                    },
+                    // Exposes what the others have collected rather than letting the whole scrape time out
+                    Err(_) => {
+                        tracing::warn!("{} collector timed out", collector.name());
+                        self.timeouts.get_or_create(&CollectorLabels { collector: collector.name().to_string() }).inc();
+                        Some(format!("{} collector timed out", collector.name()))
+                    },
+                };
+                (collector.name(), failure)
+            }.instrument(tracing::info_span!("collect", collector = collector.name())));
+
+        // Collectors sharing a name succeed when all of them do
+        let mut failures = HashMap::<&'static str, Option<String>>::new();
+        // Collectors reading the same source in the scrape share the read
+        for (name, failure) in ScrapeCache::scope(future::join_all(collections)).await {
+            let first = failures.entry(name).or_default();
+            *first = first.take().or(failure);
+        }
+        let mut failed = failures.iter().filter(|(_, failure)| failure.is_some()).map(|(name, _)| *name).collect::<Vec<_>>();
+        let mut failing = self.failing.lock().expect("failed to lock failing mutex");
+        for (name, failure) in failures {
+            self.successes.get_or_create(&CollectorLabels { collector: name.to_string() }).set(i64::from(failure.is_none()));
+            match failure {
+                Some(err) if failing.insert(name) => self.events.record("collector_failed", err),
+                None if failing.remove(name) => self.events.record("collector_recovered", format!("{name} collector")),
+                _ => {},
+            }
+        }
+        drop(failing);
+        if self.fail_on_collector_error.load(Ordering::Relaxed) && !failed.is_empty() {
+            failed.sort_unstable();
+            anyhow::bail!("{} collector failed", failed.join(", "));
+        }
 
         let mut buffer = String::new();
         tracing::debug!("encoding metrics");
-        text::encode(&mut buffer, &self.registry.lock().expect("failed to lock registry mutex"))?;
+        for group in groups {
+            text::encode_registry(&mut buffer, &group.registry.lock().expect("failed to lock registry mutex"))?;
+        }
+        let thresholds = self.thresholds.read().expect("failed to lock thresholds").clone();
+        if !thresholds.is_empty() {
+            // Of the metrics just collected, so that series of the metrics gone or not selected go too
+            self.exceeded.clear();
+            let mut breached = self.breached.lock().expect("failed to lock breached mutex");
+            for (labels, exceeded) in threshold::evaluate(&thresholds, &buffer)? {
+                self.exceeded.get_or_create(&labels).set(i64::from(exceeded));
+                let changed = match exceeded {
+                    true => breached.insert(labels.clone()),
+                    false => breached.remove(&labels),
+                };
+                if !changed {
+                    continue;
+                }
+                let event = if exceeded { "threshold_exceeded" } else { "threshold_cleared" };
+                self.events.record(event, threshold_message(&labels));
+                if let Some(hook) = &*self.hook.read().expect("failed to lock hook") {
+                    hook.threshold(&labels[0].1, &labels, exceeded);
+                }
+            }
+        }
+        text::encode_registry(&mut buffer, &self.registry.lock().expect("failed to lock registry mutex"))?;
+        text::encode_eof(&mut buffer)?;
+
+        relabel::apply(&self.relabel.read().expect("failed to lock relabel rules"), buffer)
+    }
+}
+
+impl MetricGroup {
+    /// Creates a group of collectors with its own registry, selectable by `name`.
+    pub fn new(name: impl ToString) -> Self {
+        Self::with_registry(name, Registry::default())
+    }
+
+    /// Creates a group of collectors registering in `registry`, which gives their metrics its prefix.
+    pub fn with_registry(name: impl ToString, registry: Registry) -> Self {
+        Self {
+            name: Some(name.to_string()),
+            collectors: Vec::new(),
+            ready: Vec::new(),
+            registry: Arc::new(Mutex::new(registry)),
+            enabled: AtomicBool::new(true),
+            tasks: Vec::new(),
+        }
+    }
+
+    /// Creates a group collected on every scrape regardless of the filters, which reloads keep.
+    pub fn unnamed(registry: Registry) -> Self {
+        Self {
+            name: None,
+            collectors: Vec::new(),
+            ready: Vec::new(),
+            registry: Arc::new(Mutex::new(registry)),
+            enabled: AtomicBool::new(true),
+            tasks: Vec::new(),
+        }
+    }
+
+    pub fn registry(&self) -> Arc<Mutex<Registry>> {
+        self.registry.clone()
+    }
+
+    pub fn push(&mut self, collector: Box<dyn Collector>) {
+        self.collectors.push(Serialized::boxed(collector));
+        self.ready.push(AtomicBool::new(false));
+    }
+
+    /// Ties a background task collecting into the registry to this group.
+    pub fn task(&mut self, task: JoinHandle<()>) {
+        self.tasks.push(task);
+    }
+}
+
+impl Drop for MetricGroup {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+impl Filter {
+    /// Parses `collect[]` and `exclude[]` out of a query string, ignoring the other parameters.
+    pub fn from_query(query: &str) -> Self {
+        let mut filter = Self::default();
+        for (key, value) in query.split('&').filter_map(|pair| pair.split_once('=')) {
+            let value = percent_decode_str(&value.replace('+', " ")).decode_utf8_lossy().into_owned();
+            match &*percent_decode_str(key).decode_utf8_lossy() {
+                "collect[]" => filter.collect.push(value),
+                "exclude[]" => filter.exclude.push(value),
+                _ => {},
+            }
+        }
+
+        filter
+    }
+
+    /// Returns the names in the filter that aren't any of `names`.
+    pub fn unknown<'a>(&'a self, names: &[String]) -> Vec<&'a str> {
+        self.collect
+            .iter()
+            .chain(&self.exclude)
+            .filter(|name| !names.contains(name))
+            .map(String::as_str)
+            .collect()
+    }
+
+    fn matches(&self, name: &str) -> bool {
+        (self.collect.is_empty() || self.collect.iter().any(|v| v == name)) && !self.exclude.iter().any(|v| v == name)
+    }
+}
+
+impl Handler for MetricsHandler {
+    fn names(&self) -> Vec<String> {
+        self.groups.read().expect("failed to lock groups").iter().filter_map(|group| group.name.clone()).collect()
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn handle(&self, filter: &Filter, timeout: Option<Duration>) -> anyhow::Result<String> {
+        let groups = self.select(filter);
+        let selected = groups.iter().map(|group| group.name.clone()).collect::<Vec<_>>();
+        let sender = match self.join(&selected) {
+            Flight::Leader(sender) => sender,
+            Flight::Follower(mut receiver) => {
+                tracing::debug!("waiting for the in-flight scrape");
+                let result = receiver
+                    .wait_for(Option::is_some)
+                    .await
+                    .map_err(|_| anyhow::anyhow!("in-flight scrape was cancelled"))?;
+
+                return result.clone().expect("in-flight scrape result is missing").map_err(anyhow::Error::msg);
+            },
+        };
+        let _guard = InFlightGuard {
+            in_flight: &self.in_flight,
+            selected: &selected,
+        };
+
+        let result = self.scrape(&groups, timeout).await;
+        sender.send_replace(Some(result.as_ref().map(Clone::clone).map_err(|err| format!("{err:?}"))));
+
+        result
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn readiness(&self) -> Vec<(&'static str, bool)> {
+        let mut statuses = Vec::<(&'static str, bool)>::new();
+        let groups = self.groups.read().expect("failed to lock groups").clone();
+        // A metric turned off doesn't hold the exporter unready
+        let collectors = groups
+            .iter()
+            .filter(|group| group.enabled.load(Ordering::Relaxed))
+            .flat_map(|group| group.collectors.iter().zip(&group.ready));
+        for (collector, ready) in collectors {
+            // Preflights collectors that haven't succeeded yet, so that readiness doesn't wait for the first scrape
+            if !ready.load(Ordering::Relaxed) {
+                match self.collect_once(collector.as_ref()).await.with_context(|| collector_error(collector.name())) {
+                    Ok(()) => ready.store(true, Ordering::Relaxed),
+                    Err(err) => tracing::warn!("{err:?}"),
+                }
+            }
+
+            // Collectors sharing a name are ready when all of them are
+            let ready = ready.load(Ordering::Relaxed);
+            match statuses.iter_mut().find(|(name, _)| *name == collector.name()) {
+                Some((_, status)) => *status &= ready,
+                None => statuses.push((collector.name(), ready)),
+            }
+        }
+
+        statuses
+    }
+
+    fn toggles(&self) -> Vec<(String, bool)> {
+        self.groups
+            .read()
+            .expect("failed to lock groups")
+            .iter()
+            .filter_map(|group| Some((group.name.clone()?, group.enabled.load(Ordering::Relaxed))))
+            .collect()
+    }
+
+    fn toggle(&self, name: &str, enabled: bool) -> bool {
+        let groups = self.named(name);
+        for group in &groups {
+            group.enabled.store(enabled, Ordering::Relaxed);
+        }
+
+        !groups.is_empty()
+    }
+
+    async fn collect_now(&self, name: &str) -> Option<Vec<(&'static str, anyhow::Result<()>)>> {
+        let groups = self.named(name);
+        if groups.is_empty() {
+            return None;
+        }
+
+        let mut results = Vec::new();
+        for collector in groups.iter().flat_map(|group| &group.collectors) {
+            results.push((collector.name(), self.collect_once(collector.as_ref()).await));
+        }
+
+        Some(results)
+    }
 
-        Ok(buffer)
+    fn latest_events(&self) -> Vec<Event> {
+        self.events.list()
     }
 }
 
@@ -61,30 +858,521 @@ fn collector_error(name: &str) -> String {
     format!("{name} collector error")
 }
 
+// Name of the threshold followed by the labels of the sample, such as `filesystem_full: mountpoint=/, fstype=ext4`
+fn threshold_message(labels: &Labels) -> String {
+    let (name, labels) = labels.split_first().expect("threshold name label is missing");
+    match labels {
+        [] => name.1.clone(),
+        labels => format!("{}: {}", name.1, labels.iter().map(|(label, value)| format!("{label}={value}")).collect::<Vec<_>>().join(", ")),
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use std::sync::{Arc, Mutex};
+    use std::{collections::HashMap, sync::{atomic::{AtomicUsize, Ordering}, Arc, Mutex}, time::Duration};
 
-    use futures::future::ok;
-    use prometheus_client::registry::Registry;
+    use async_trait::async_trait;
+    use prometheus_client::{metrics::gauge::Gauge, registry::Registry};
 
-    use crate::metrics::{
-        Handler,
-        MetricsHandler,
-        MockCollector,
+    use crate::{
+        metrics::{
+            Collector,
+            Filter,
+            Handler,
+            MetricGroup,
+            MetricsHandler,
+            MockCollector,
+        },
+        events::Events,
+        hook::Hook,
+        threshold::Threshold,
     };
 
+    // Without the HELP and TYPE lines, which families without samples have or not depending on the version of
+    // prometheus-client
+    fn samples(output: &str) -> Vec<&str> {
+        assert!(output.ends_with("# EOF\n"));
+        output.lines().filter(|line| !line.starts_with('#')).collect()
+    }
+
+    fn build_info() -> String {
+        format!(
+            "raspi_exporter_build_info{{version=\"{}\",revision=\"{}\",rustc=\"{}\"}} 1",
+            env!("CARGO_PKG_VERSION"),
+            env!("RASPI_EXPORTER_REVISION"),
+            env!("RASPI_EXPORTER_RUSTC"),
+        )
+    }
+
     #[tokio::test]
     async fn handle() {
         let mut mock_throttled = MockCollector::new();
         mock_throttled
             .expect_collect()
             .times(1)
-            .returning(|| Box::pin(ok(())));
+            .returning(|| Ok(()));
+        mock_throttled
+            .expect_name()
+            .return_const("throttled");
+
+        let metrics_handler = MetricsHandler::new(vec![Box::new(mock_throttled)], Arc::new(Mutex::new(Registry::default())));
+        let result = metrics_handler.handle(&Filter::default(), None).await.unwrap();
+
+        assert_eq!(samples(&result), [build_info().as_str(), "raspi_collector_success{collector=\"throttled\"} 1"]);
+    }
+
+    #[tokio::test]
+    async fn handle_failure() {
+        let events = Arc::new(Events::new(10));
+        let metrics_handler = MetricsHandler::default().events(events.clone());
+        for (name, result) in [("throttled", false), ("temperature", true)] {
+            let mut mock_collector = MockCollector::new();
+            mock_collector
+                .expect_collect()
+                .times(3)
+                .returning(move || if result { Ok(()) } else { Err(anyhow::anyhow!("failed")) });
+            mock_collector
+                .expect_name()
+                .return_const(name);
+            let mut group = MetricGroup::new(name);
+            group.push(Box::new(mock_collector));
+            group.registry().lock().unwrap().register(name, "", Gauge::<i64>::default());
+            metrics_handler.insert(group);
+        }
+
+        let result = metrics_handler.handle(&Filter::default(), None).await.unwrap();
+
+        // Exposes the others rather than failing the whole scrape
+        assert!(result.starts_with("# HELP throttled .\n# TYPE throttled gauge\nthrottled 0\n# HELP temperature .\n"));
+        assert!(result.contains("raspi_collector_success{collector=\"throttled\"} 0\n"));
+        assert!(result.contains("raspi_collector_success{collector=\"temperature\"} 1\n"));
+
+        // Logged once until it recovers
+        metrics_handler.handle(&Filter::default(), None).await.unwrap();
+        let logged = events.list().into_iter().map(|event| (event.kind, event.message)).collect::<Vec<_>>();
+        assert_eq!(logged, [("collector_failed", "throttled collector error: failed".to_string())]);
+
+        // Strict rather than partial
+        let metrics_handler = metrics_handler.fail_on_collector_error(true);
+        let err = metrics_handler.handle(&Filter::default(), None).await.unwrap_err();
+        assert_eq!(err.to_string(), "throttled collector failed");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn handle_concurrently() {
+        struct SlowCollector(Arc<AtomicUsize>);
+
+        #[async_trait]
+        impl Collector for SlowCollector {
+            fn name(&self) -> &'static str {
+                "slow"
+            }
+
+            async fn collect(&self) -> anyhow::Result<()> {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                Ok(())
+            }
+        }
+
+        let count = Arc::new(AtomicUsize::new(0));
+        let metrics_handler = MetricsHandler::new(vec![Box::new(SlowCollector(count.clone()))], Arc::new(Mutex::new(Registry::default())));
+
+        // Scrapes arriving while one is running share its result
+        let filter = Filter::default();
+        let (first, second) = tokio::join!(metrics_handler.handle(&filter, None), metrics_handler.handle(&filter, None));
+        let (first, second) = (first.unwrap(), second.unwrap());
+        assert_eq!(samples(&first), [build_info().as_str(), "raspi_collector_success{collector=\"slow\"} 1"]);
+        assert_eq!(first, second);
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+
+        metrics_handler.handle(&Filter::default(), None).await.unwrap();
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn handle_overlapping() {
+        struct SlowCollector {
+            running: AtomicUsize,
+            overlapped: Arc<AtomicUsize>,
+        }
+
+        #[async_trait]
+        impl Collector for SlowCollector {
+            fn name(&self) -> &'static str {
+                "slow"
+            }
+
+            async fn collect(&self) -> anyhow::Result<()> {
+                if self.running.fetch_add(1, Ordering::SeqCst) > 0 {
+                    self.overlapped.fetch_add(1, Ordering::SeqCst);
+                }
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                self.running.fetch_sub(1, Ordering::SeqCst);
+                Ok(())
+            }
+        }
+
+        let overlapped = Arc::new(AtomicUsize::new(0));
+        let metrics_handler = MetricsHandler::default();
+        let mut group = MetricGroup::new("slow");
+        group.push(Box::new(SlowCollector { running: AtomicUsize::new(0), overlapped: overlapped.clone() }));
+        metrics_handler.insert(group);
+        metrics_handler.insert(MetricGroup::new("temperature"));
+
+        // Selections sharing a collector without sharing their results collect one after the other
+        let (first, second) = (Filter::from_query("collect[]=slow"), Filter::from_query("collect[]=slow&collect[]=temperature"));
+        let (first, second) = tokio::join!(metrics_handler.handle(&first, None), metrics_handler.handle(&second, None));
+        first.unwrap();
+        second.unwrap();
+        assert_eq!(overlapped.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn handle_filtered() {
+        let metrics_handler = MetricsHandler::default();
+        for name in ["throttled", "temperature", "reset"] {
+            let mut mock_collector = MockCollector::new();
+            mock_collector
+                .expect_collect()
+                .times(usize::from(name == "temperature"))
+                .returning(|| Ok(()));
+            mock_collector
+                .expect_name()
+                .return_const(name);
+            let mut group = MetricGroup::new(name);
+            group.push(Box::new(mock_collector));
+            group.registry().lock().unwrap().register(name, "", Gauge::<i64>::default());
+            metrics_handler.insert(group);
+        }
+
+        let filter = Filter::from_query("collect%5B%5D=throttled&collect[]=temperature&exclude[]=throttled&debug=1");
+        let result = metrics_handler.handle(&filter, None).await.unwrap();
+
+        assert!(result.starts_with("# HELP temperature .\n# TYPE temperature gauge\ntemperature 0\n"));
+        assert_eq!(
+            samples(&result),
+            ["temperature 0", build_info().as_str(), "raspi_collector_success{collector=\"temperature\"} 1"],
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn handle_timeout() {
+        struct SlowCollector;
+
+        #[async_trait]
+        impl Collector for SlowCollector {
+            fn name(&self) -> &'static str {
+                "slow"
+            }
+
+            async fn collect(&self) -> anyhow::Result<()> {
+                tokio::time::sleep(Duration::from_secs(10)).await;
+                Ok(())
+            }
+        }
+
+        let mut mock_collector = MockCollector::new();
+        mock_collector
+            .expect_collect()
+            .times(1)
+            .returning(|| Ok(()));
+        mock_collector
+            .expect_name()
+            .return_const("mock");
+
+        let metrics_handler = MetricsHandler::new(vec![Box::new(SlowCollector), Box::new(mock_collector)], Arc::new(Mutex::new(Registry::default())));
+        let started_at = tokio::time::Instant::now();
+        let result = metrics_handler.handle(&Filter::default(), Some(Duration::from_secs(1))).await.unwrap();
+
+        assert!(result.contains("raspi_collector_timeouts_total{collector=\"slow\"} 1\n"));
+        assert_eq!(started_at.elapsed(), Duration::from_secs(1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn handle_collector_timeout() {
+        struct SlowCollector(&'static str);
+
+        #[async_trait]
+        impl Collector for SlowCollector {
+            fn name(&self) -> &'static str {
+                self.0
+            }
+
+            async fn collect(&self) -> anyhow::Result<()> {
+                tokio::time::sleep(Duration::from_secs(3)).await;
+                Ok(())
+            }
+        }
+
+        let metrics_handler = MetricsHandler::new(vec![Box::new(SlowCollector("slow")), Box::new(SlowCollector("slower"))], Arc::new(Mutex::new(Registry::default())))
+            .collector_timeout(Some(Duration::from_secs(2)))
+            .collector_timeouts(HashMap::from([("slower".to_string(), Duration::from_secs(5))]));
+        let started_at = tokio::time::Instant::now();
+        let result = metrics_handler.handle(&Filter::default(), None).await.unwrap();
+
+        assert!(result.contains("raspi_collector_timeouts_total{collector=\"slow\"} 1\n"));
+        assert!(!result.contains("raspi_collector_timeouts_total{collector=\"slower\"}"));
+        assert!(result.contains("raspi_collector_success{collector=\"slow\"} 0\n"));
+        assert!(result.contains("raspi_collector_success{collector=\"slower\"} 1\n"));
+        assert_eq!(started_at.elapsed(), Duration::from_secs(3));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn handle_parallel() {
+        struct SlowCollector;
+
+        #[async_trait]
+        impl Collector for SlowCollector {
+            fn name(&self) -> &'static str {
+                "slow"
+            }
+
+            async fn collect(&self) -> anyhow::Result<()> {
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                Ok(())
+            }
+        }
+
+        let metrics_handler = MetricsHandler::new(vec![Box::new(SlowCollector), Box::new(SlowCollector)], Arc::new(Mutex::new(Registry::default())));
+        let started_at = tokio::time::Instant::now();
+        metrics_handler.handle(&Filter::default(), None).await.unwrap();
+
+        // Takes as long as the slowest collector rather than the sum
+        assert_eq!(started_at.elapsed(), Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn retain() {
+        let metrics_handler = MetricsHandler::new(Vec::new(), Arc::new(Mutex::new(Registry::default())));
+        let mut group = MetricGroup::new("temperature");
+        let task = tokio::spawn(std::future::pending());
+        let abort_handle = task.abort_handle();
+        group.task(task);
+        metrics_handler.insert(group);
+        metrics_handler.insert(MetricGroup::new("throttled"));
+
+        metrics_handler.retain(&["throttled".to_string()]);
+        tokio::task::yield_now().await;
+
+        assert_eq!(metrics_handler.names(), ["throttled"]);
+        // The sampler of the dropped group is stopped
+        assert!(abort_handle.is_finished());
+        // Unnamed groups are always kept
+        assert_eq!(metrics_handler.groups.read().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn replace() {
+        let metrics_handler = MetricsHandler::new(Vec::new(), Arc::new(Mutex::new(Registry::default())));
+        let mut group = MetricGroup::new("temperature");
+        let task = tokio::spawn(std::future::pending());
+        let abort_handle = task.abort_handle();
+        group.task(task);
+        metrics_handler.insert(group);
+        metrics_handler.insert(MetricGroup::new("throttled"));
+        metrics_handler.toggle("temperature", false);
+
+        metrics_handler.replace(MetricGroup::new("temperature"));
+        metrics_handler.replace(MetricGroup::new("clock"));
+        tokio::task::yield_now().await;
+
+        assert_eq!(metrics_handler.names(), ["temperature", "throttled", "clock"]);
+        // The sampler of the old group is stopped
+        assert!(abort_handle.is_finished());
+        assert_eq!(metrics_handler.toggles()[0], ("temperature".to_string(), false));
+    }
+
+    #[tokio::test]
+    async fn diagnose() {
+        let metrics_handler = MetricsHandler::default();
+        for (name, result) in [("throttled", true), ("chrony", false)] {
+            let mut mock_collector = MockCollector::new();
+            mock_collector
+                .expect_collect()
+                .times(1)
+                .returning(move || if result { Ok(()) } else { Err(anyhow::anyhow!("command not found")) });
+            mock_collector
+                .expect_name()
+                .return_const(name);
+            let mut group = MetricGroup::new(name);
+            group.push(Box::new(mock_collector));
+            metrics_handler.insert(group);
+        }
+
+        let results = metrics_handler.diagnose().await;
+
+        assert_eq!(results.len(), 2);
+        assert!(matches!(results[0], ("throttled", Ok(()))));
+        assert!(matches!(&results[1], ("chrony", Err(err)) if err.to_string() == "command not found"));
+    }
+
+    #[test]
+    fn filter_unknown() {
+        let filter = Filter::from_query("collect[]=throttled&exclude[]=unknown");
+
+        assert_eq!(filter.unknown(&["throttled".to_string(), "temperature".to_string()]), ["unknown"]);
+    }
+
+    #[tokio::test]
+    async fn readiness() {
+        let mut mock_throttled = MockCollector::new();
+        mock_throttled
+            .expect_collect()
+            .times(1)
+            .returning(|| Ok(()));
+        mock_throttled
+            .expect_name()
+            .return_const("throttled");
+
+        let mut mock_chrony = MockCollector::new();
+        mock_chrony
+            .expect_collect()
+            .times(2)
+            .returning(|| Err(anyhow::anyhow!("failed")));
+        mock_chrony
+            .expect_name()
+            .return_const("chrony");
+
+        let metrics_handler = MetricsHandler::new(vec![Box::new(mock_throttled), Box::new(mock_chrony)], Arc::new(Mutex::new(Registry::default())));
+
+        // Only collectors not ready yet are collected again
+        assert_eq!(metrics_handler.readiness().await, [("throttled", true), ("chrony", false)]);
+        assert_eq!(metrics_handler.readiness().await, [("throttled", true), ("chrony", false)]);
+    }
+
+    #[tokio::test]
+    async fn register_collector() {
+        let metrics_handler = MetricsHandler::default();
+        metrics_handler.register_collector(|registry| {
+            let gauge = Gauge::<i64>::default();
+            gauge.set(1200);
+            registry.register("fan_speed_rpm", "Speed of the fan", gauge);
+
+            let mut mock_collector = MockCollector::new();
+            mock_collector
+                .expect_collect()
+                .times(1)
+                .returning(|| Ok(()));
+            mock_collector
+                .expect_name()
+                .return_const("fan");
+            Box::new(mock_collector)
+        });
+
+        // Collected regardless of filters, with the prefix of the handler
+        let result = metrics_handler.handle(&Filter::from_query("exclude[]=fan"), None).await.unwrap();
+
+        assert!(result.contains("\nraspi_fan_speed_rpm 1200\n"));
+        assert!(result.contains("raspi_collector_success{collector=\"fan\"} 1\n"));
+    }
+
+    #[tokio::test]
+    async fn toggle() {
+        let metrics_handler = MetricsHandler::default();
+        for name in ["throttled", "temperature"] {
+            let mut mock_collector = MockCollector::new();
+            // Throttled is collected only on demand once disabled
+            mock_collector
+                .expect_collect()
+                .times(1)
+                .returning(|| Ok(()));
+            mock_collector
+                .expect_name()
+                .return_const(name);
+            let mut group = MetricGroup::new(name);
+            group.push(Box::new(mock_collector));
+            metrics_handler.insert(group);
+        }
+
+        assert!(metrics_handler.toggle("throttled", false));
+        assert!(!metrics_handler.toggle("fan", false));
+        assert_eq!(metrics_handler.toggles(), [("throttled".to_string(), false), ("temperature".to_string(), true)]);
+
+        let result = metrics_handler.handle(&Filter::default(), None).await.unwrap();
+        assert!(!result.contains("collector=\"throttled\""));
+        assert!(result.contains("raspi_collector_success{collector=\"temperature\"} 1\n"));
+
+        let results = metrics_handler.collect_now("throttled").await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(matches!(results[0], ("throttled", Ok(()))));
+        assert!(metrics_handler.collect_now("fan").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn thresholds() {
+        let metrics_handler = MetricsHandler::default().thresholds(vec![Threshold {
+            name: "soc_hot".to_string(),
+            metric: "soc_temperature_celsius".to_string(),
+            labels: Default::default(),
+            ratio_of: None,
+            above: Some(75.0),
+            below: None,
+        }]).hook(Some(Hook::new("/bin/true"))).events(Arc::new(Events::new(10)));
+        let mut mock_collector = MockCollector::new();
+        mock_collector
+            .expect_collect()
+            .times(2)
+            .returning(|| Ok(()));
+        mock_collector
+            .expect_name()
+            .return_const("temperature");
+        let mut group = MetricGroup::new("temperature");
+        group.push(Box::new(mock_collector));
+        let temperature = Gauge::<i64>::default();
+        group.registry().lock().unwrap().register("soc_temperature_celsius", "", temperature.clone());
+        metrics_handler.insert(group);
+
+        temperature.set(80);
+        let result = metrics_handler.handle(&Filter::default(), None).await.unwrap();
+        assert!(result.contains("\nraspi_threshold_exceeded{name=\"soc_hot\"} 1\n"));
+        assert_eq!(metrics_handler.breached.lock().unwrap().len(), 1);
+
+        temperature.set(60);
+        let result = metrics_handler.handle(&Filter::default(), None).await.unwrap();
+        assert!(result.contains("\nraspi_threshold_exceeded{name=\"soc_hot\"} 0\n"));
+        assert!(metrics_handler.breached.lock().unwrap().is_empty());
+        let kinds = metrics_handler.latest_events().into_iter().map(|event| (event.kind, event.message)).collect::<Vec<_>>();
+        assert_eq!(kinds, [("threshold_exceeded", "soc_hot".to_string()), ("threshold_cleared", "soc_hot".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn reload() {
+        let metrics_handler = MetricsHandler::default();
+        let mut mock_collector = MockCollector::new();
+        mock_collector
+            .expect_collect()
+            .times(3)
+            .returning(|| Ok(()));
+        mock_collector
+            .expect_name()
+            .return_const("temperature");
+        let mut group = MetricGroup::new("temperature");
+        group.push(Box::new(mock_collector));
+        let temperature = Gauge::<i64>::default();
+        group.registry().lock().unwrap().register("soc_temperature_celsius", "", temperature.clone());
+        metrics_handler.insert(group);
+        temperature.set(80);
+
+        let result = metrics_handler.handle(&Filter::default(), None).await.unwrap();
+        assert!(!result.contains("threshold_exceeded"));
 
-        let metrics_handler = MetricsHandler::new(Some(mock_throttled), Arc::new(Mutex::new(Registry::default())));
-        let result = metrics_handler.handle().await.unwrap();
+        metrics_handler.reload(MetricsHandler::default().thresholds(vec![Threshold {
+            name: "soc_hot".to_string(),
+            metric: "soc_temperature_celsius".to_string(),
+            labels: Default::default(),
+            ratio_of: None,
+            above: Some(75.0),
+            below: None,
+        }]));
+        let result = metrics_handler.handle(&Filter::default(), None).await.unwrap();
+        assert!(result.contains("\nraspi_threshold_exceeded{name=\"soc_hot\"} 1\n"));
+        assert_eq!(metrics_handler.breached.lock().unwrap().len(), 1);
 
-        assert_eq!(result, "# EOF\n")
+        metrics_handler.reload(MetricsHandler::default().collector_timeout(Some(Duration::from_secs(1))));
+        let result = metrics_handler.handle(&Filter::default(), None).await.unwrap();
+        assert!(!result.contains("name=\"soc_hot\""));
+        assert!(metrics_handler.breached.lock().unwrap().is_empty());
+        assert_eq!(metrics_handler.timeout("temperature"), Some(Duration::from_secs(1)));
     }
 }
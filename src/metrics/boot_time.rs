@@ -0,0 +1,6 @@
+use prometheus_client::encoding::EncodeLabelSet;
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct BootTimePhaseLabels {
+    pub phase: String,
+}
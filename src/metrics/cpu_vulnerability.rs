@@ -0,0 +1,27 @@
+use prometheus_client::encoding::{EncodeLabelSet, EncodeLabelValue, LabelValueEncoder};
+use strum::Display as StrumDisplay;
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct CpuVulnerabilityLabels {
+    pub vulnerability: String,
+    pub status: CpuVulnerabilityStatus,
+    pub description: String,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug, StrumDisplay)]
+pub enum CpuVulnerabilityStatus {
+    #[strum(to_string = "not affected")]
+    NotAffected,
+    #[strum(to_string = "mitigated")]
+    Mitigated,
+    #[strum(to_string = "vulnerable")]
+    Vulnerable,
+    #[strum(to_string = "unknown")]
+    Unknown,
+}
+
+impl EncodeLabelValue for CpuVulnerabilityStatus {
+    fn encode(&self, encoder: &mut LabelValueEncoder) -> Result<(), std::fmt::Error> {
+        self.to_string().encode(encoder)
+    }
+}
@@ -0,0 +1,7 @@
+use prometheus_client::encoding::EncodeLabelSet;
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct FilesystemLabels {
+    pub mountpoint: String,
+    pub fstype: String,
+}
@@ -0,0 +1,28 @@
+use prometheus_client::encoding::{EncodeLabelSet, EncodeLabelValue, LabelValueEncoder};
+use strum::Display as StrumDisplay;
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct KmsgLabels {
+    pub severity: KmsgSeverity,
+    pub subsystem: String,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, StrumDisplay)]
+pub enum KmsgSeverity {
+    #[strum(to_string = "emerg")]
+    Emergency,
+    #[strum(to_string = "alert")]
+    Alert,
+    #[strum(to_string = "crit")]
+    Critical,
+    #[strum(to_string = "err")]
+    Error,
+    #[strum(to_string = "warning")]
+    Warning,
+}
+
+impl EncodeLabelValue for KmsgSeverity {
+    fn encode(&self, encoder: &mut LabelValueEncoder) -> Result<(), std::fmt::Error> {
+        self.to_string().encode(encoder)
+    }
+}
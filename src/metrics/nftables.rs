@@ -0,0 +1,15 @@
+use prometheus_client::encoding::EncodeLabelSet;
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct NftablesCounterLabels {
+    pub family: String,
+    pub table: String,
+    pub name: String,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct NftablesChainLabels {
+    pub family: String,
+    pub table: String,
+    pub chain: String,
+}
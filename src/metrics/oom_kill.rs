@@ -0,0 +1,39 @@
+use std::fmt;
+
+use prometheus_client::{
+    encoding::{EncodeLabelSet, EncodeMetric, MetricEncoder, NoLabelSet},
+    metrics::{counter::Counter, family::Family, MetricType, TypedMetric},
+};
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct OomKillLabels {
+    pub cgroup: String,
+}
+
+/// Number of OOM kills system-wide, followed by the ones of the cgroups labeled by them in the same family.
+///
+/// A [`Family`] encodes even a series without labels with braces, so that the system-wide one is encoded as a plain
+/// counter instead. It is still kept in a family keyed by nothing, so that a reset of the kernel counter can recreate it.
+#[derive(Clone, Debug, Default)]
+pub struct OomKills {
+    pub total: Family<(), Counter>,
+    pub cgroups: Family<OomKillLabels, Counter>,
+}
+
+impl TypedMetric for OomKills {
+    const TYPE: MetricType = MetricType::Counter;
+}
+
+impl EncodeMetric for OomKills {
+    fn encode(&self, mut encoder: MetricEncoder) -> Result<(), fmt::Error> {
+        if let Some(total) = self.total.get(&()) {
+            encoder.encode_counter::<NoLabelSet, _, u64>(&total.get(), None)?;
+        }
+
+        self.cgroups.encode(encoder)
+    }
+
+    fn metric_type(&self) -> MetricType {
+        Self::TYPE
+    }
+}
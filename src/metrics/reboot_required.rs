@@ -0,0 +1,21 @@
+use prometheus_client::encoding::{EncodeLabelSet, EncodeLabelValue, LabelValueEncoder};
+use strum::Display as StrumDisplay;
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct RebootRequiredLabels {
+    pub reason: RebootRequiredReason,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug, StrumDisplay)]
+pub enum RebootRequiredReason {
+    #[strum(to_string = "flag file")]
+    FlagFile,
+    #[strum(to_string = "kernel")]
+    Kernel,
+}
+
+impl EncodeLabelValue for RebootRequiredReason {
+    fn encode(&self, encoder: &mut LabelValueEncoder) -> Result<(), std::fmt::Error> {
+        self.to_string().encode(encoder)
+    }
+}
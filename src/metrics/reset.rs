@@ -0,0 +1,25 @@
+use prometheus_client::encoding::{EncodeLabelSet, EncodeLabelValue, LabelValueEncoder};
+use strum::Display as StrumDisplay;
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct ResetCauseLabels {
+    pub cause: ResetCause,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug, StrumDisplay)]
+pub enum ResetCause {
+    #[strum(to_string = "power on")]
+    PowerOn,
+    #[strum(to_string = "watchdog")]
+    Watchdog,
+    #[strum(to_string = "software")]
+    Software,
+    #[strum(to_string = "debugger")]
+    Debugger,
+}
+
+impl EncodeLabelValue for ResetCause {
+    fn encode(&self, encoder: &mut LabelValueEncoder) -> Result<(), std::fmt::Error> {
+        self.to_string().encode(encoder)
+    }
+}
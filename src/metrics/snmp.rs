@@ -0,0 +1,25 @@
+use prometheus_client::encoding::{EncodeLabelSet, EncodeLabelValue, LabelValueEncoder};
+use strum::Display as StrumDisplay;
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct SnmpLabels {
+    pub protocol: SnmpProtocol,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, StrumDisplay)]
+pub enum SnmpProtocol {
+    #[strum(to_string = "ip")]
+    Ip,
+    #[strum(to_string = "icmp")]
+    Icmp,
+    #[strum(to_string = "tcp")]
+    Tcp,
+    #[strum(to_string = "udp")]
+    Udp,
+}
+
+impl EncodeLabelValue for SnmpProtocol {
+    fn encode(&self, encoder: &mut LabelValueEncoder) -> Result<(), std::fmt::Error> {
+        self.to_string().encode(encoder)
+    }
+}
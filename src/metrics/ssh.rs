@@ -0,0 +1,16 @@
+use prometheus_client::encoding::{EncodeLabelSet, LabelSetEncoder};
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct SshAuthFailureLabels {
+    pub method: Option<String>,
+}
+
+impl EncodeLabelSet for SshAuthFailureLabels {
+    fn encode(&self, encoder: &mut LabelSetEncoder) -> Result<(), std::fmt::Error> {
+        // Failures are counted without the method label unless enabled
+        match &self.method {
+            Some(method) => [("method", method.as_str())].encode(encoder),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use std::{collections::BTreeMap, fmt, sync::{Arc, RwLock}};
+
+use prometheus_client::{
+    encoding::{EncodeLabelValue, EncodeMetric, MetricEncoder},
+    metrics::{MetricType, TypedMetric},
+};
+
+/// Set of states each of which is either true or false, exposed as a StateSet of OpenMetrics.
+///
+/// prometheus_client has no StateSet type, so that this is encoded as a sample per state labeled by `label` with the
+/// unknown type, which no other metric of the exporter has. [`crate::format::Format::encode`] then turns it into a
+/// StateSet labeled by the name of its family in OpenMetrics, and into a gauge keeping `label` in the other formats.
+#[derive(Clone, Debug)]
+pub struct StateSet<S> {
+    label: &'static str,
+    states: Arc<RwLock<BTreeMap<S, bool>>>,
+}
+
+impl<S> StateSet<S>
+where
+    S: Ord,
+{
+    pub fn new(label: &'static str) -> Self {
+        Self {
+            label,
+            states: Arc::default(),
+        }
+    }
+
+    pub fn set(&self, state: S, value: bool) {
+        self.states.write().expect("failed to lock states").insert(state, value);
+    }
+}
+
+impl<S> TypedMetric for StateSet<S> {
+    const TYPE: MetricType = MetricType::Unknown;
+}
+
+impl<S> EncodeMetric for StateSet<S>
+where
+    S: Clone + EncodeLabelValue + fmt::Debug + Send + Sync,
+{
+    fn encode(&self, mut encoder: MetricEncoder) -> Result<(), fmt::Error> {
+        for (state, value) in self.states.read().expect("failed to lock states").iter() {
+            encoder.encode_family(&[(self.label, state.clone())])?.encode_gauge(&i64::from(*value))?;
+        }
+
+        Ok(())
+    }
+
+    fn metric_type(&self) -> MetricType {
+        Self::TYPE
+    }
+}
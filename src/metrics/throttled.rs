@@ -11,7 +11,7 @@ pub struct ThrottlingOccurredLabels {
     pub kind: ThrottlingKind,
 }
 
-#[derive(Clone, PartialEq, Eq, Hash, Debug, StrumDisplay)]
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, StrumDisplay)]
 pub enum ThrottlingKind {
     #[strum(to_string = "undervoltage")]
     Undervoltage,
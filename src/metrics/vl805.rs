@@ -0,0 +1,7 @@
+use prometheus_client::encoding::EncodeLabelSet;
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct Vl805FirmwareLabels {
+    pub current: String,
+    pub latest: String,
+}
@@ -0,0 +1,7 @@
+use prometheus_client::encoding::EncodeLabelSet;
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct WireguardPeerLabels {
+    pub interface: String,
+    pub public_key: String,
+}
@@ -0,0 +1,390 @@
+//! Publisher of the metrics to an MQTT broker with a minimal MQTT 3.1.1 client.
+
+use std::time::Duration;
+
+use anyhow::Context;
+use serde_json::json;
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::TcpStream,
+    time::{self, MissedTickBehavior},
+};
+
+use crate::{format::{samples, Sample}, metrics::{Filter, Handler}};
+
+const CONNECT: u8 = 0x10;
+const CONNACK: u8 = 0x20;
+const PUBLISH: u8 = 0x30;
+const PUBACK: u8 = 0x40;
+const DISCONNECT: u8 = 0xe0;
+
+const PROTOCOL_LEVEL: u8 = 4;
+const CLEAN_SESSION: u8 = 0x02;
+const PASSWORD: u8 = 0x40;
+const USERNAME: u8 = 0x80;
+const RETAIN: u8 = 0x01;
+
+/// Publishes the value of every sample to a topic of its own on an interval, for home automation without Prometheus.
+///
+/// Topics are the name of a sample followed by its label values under the prefix, such as
+/// `raspi/raspi_throttling_active/undervoltage`.
+pub struct Mqtt<H> {
+    broker: String,
+    interval: Duration,
+    handler: H,
+    prefix: String,
+    client_id: String,
+    credentials: Option<(String, String)>,
+    qos: u8,
+    retain: bool,
+    discovery_prefix: Option<String>,
+}
+
+struct Message {
+    topic: String,
+    payload: String,
+    retain: bool,
+}
+
+impl<H> Mqtt<H>
+where
+    H: Handler,
+{
+    /// `broker` is a host with an optional port, 1883 by default.
+    pub fn new(broker: impl ToString, interval: Duration, handler: H) -> Self {
+        Self {
+            broker: broker.to_string(),
+            interval,
+            handler,
+            prefix: "raspi".to_string(),
+            client_id: "raspi_exporter".to_string(),
+            credentials: None,
+            qos: 0,
+            retain: false,
+            discovery_prefix: None,
+        }
+    }
+
+    pub fn prefix(self, prefix: impl ToString) -> Self {
+        Self {
+            prefix: prefix.to_string(),
+            ..self
+        }
+    }
+
+    pub fn client_id(self, client_id: impl ToString) -> Self {
+        Self {
+            client_id: client_id.to_string(),
+            ..self
+        }
+    }
+
+    /// Username and password to connect with.
+    pub fn credentials(self, credentials: Option<(String, String)>) -> Self {
+        Self {
+            credentials,
+            ..self
+        }
+    }
+
+    /// Quality of service of the messages, either at most once (0) or at least once (1).
+    pub fn qos(self, qos: u8) -> Self {
+        Self {
+            qos,
+            ..self
+        }
+    }
+
+    /// Makes the broker keep the last values for subscribers coming later.
+    pub fn retain(self, retain: bool) -> Self {
+        Self {
+            retain,
+            ..self
+        }
+    }
+
+    /// Also publishes Home Assistant discovery configs under `discovery_prefix`, usually `homeassistant`, so that the SoC
+    /// temperature and throttling show up as entities of a device.
+    pub fn discovery_prefix(self, discovery_prefix: Option<String>) -> Self {
+        Self {
+            discovery_prefix,
+            ..self
+        }
+    }
+
+    pub async fn start(self) {
+        let mut interval = time::interval(self.interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+
+            match time::timeout(self.interval, self.publish()).await {
+                Ok(Ok(())) => {},
+                Ok(Err(err)) => tracing::error!("failed to publish metrics\nError: {err:?}"),
+                Err(_) => tracing::error!("publishing metrics timed out"),
+            }
+        }
+    }
+
+    /// Publishes the metrics on a connection of its own, which is closed afterwards.
+    pub async fn publish(&self) -> anyhow::Result<()> {
+        let metrics = self.handler.handle(&Filter::default(), Some(self.interval)).await?;
+        let mut messages = Vec::new();
+        for sample in samples(&metrics) {
+            let sample = sample?;
+            messages.extend(self.discovery(&sample));
+            messages.push(Message {
+                topic: self.topic(&sample),
+                payload: sample.value.to_string(),
+                retain: self.retain,
+            });
+        }
+
+        let address = match self.broker.contains(':') {
+            true => self.broker.clone(),
+            false => format!("{}:1883", self.broker),
+        };
+        let mut stream = TcpStream::connect(&address)
+            .await
+            .with_context(|| format!("connection error: {address}"))?;
+        self.send(&mut stream, &messages).await
+    }
+
+    async fn send<S>(&self, stream: &mut S, messages: &[Message]) -> anyhow::Result<()>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        stream.write_all(&connect(&self.client_id, self.credentials.as_ref())).await?;
+        let (packet_type, body) = read_packet(stream).await?;
+        match (packet_type, body.get(1)) {
+            (CONNACK, Some(0)) => {},
+            (CONNACK, Some(code)) => anyhow::bail!("connection refused with return code {code}"),
+            _ => anyhow::bail!("unexpected packet in place of CONNACK: {packet_type:#x}"),
+        }
+
+        for (index, message) in messages.iter().enumerate() {
+            // Packet identifiers are from 1, and unique while messages are unacknowledged
+            let packet_id = (index % usize::from(u16::MAX)) as u16 + 1;
+            stream.write_all(&publish(&message.topic, message.payload.as_bytes(), self.qos, message.retain, packet_id)).await?;
+            if self.qos > 0 {
+                let (packet_type, body) = read_packet(stream).await?;
+                if packet_type != PUBACK || body != packet_id.to_be_bytes() {
+                    anyhow::bail!("unexpected packet in place of PUBACK: {packet_type:#x}");
+                }
+            }
+        }
+
+        stream.write_all(&[DISCONNECT, 0]).await?;
+        stream.shutdown().await?;
+
+        Ok(())
+    }
+
+    fn topic(&self, sample: &Sample) -> String {
+        let levels = [self.prefix.as_str(), &sample.name]
+            .into_iter()
+            .map(ToString::to_string)
+            .chain(sample.labels.iter().map(|(_, value)| escape_level(value)));
+
+        levels.collect::<Vec<_>>().join("/")
+    }
+
+    // Retained config of the entity of a sample, for the metrics that Home Assistant has device classes for
+    fn discovery(&self, sample: &Sample) -> Option<Message> {
+        let discovery_prefix = self.discovery_prefix.as_ref()?;
+        // State of a StateSet, labeled by the name of the metric
+        let kind = sample.labels.iter().find(|(name, _)| *name == sample.name).map(|(_, kind)| kind.as_str());
+        // Whatever the prefix of the metric names is
+        let (component, name, mut config) = match (sample.name.as_str(), kind) {
+            (name, _) if name.ends_with("soc_temperature_celsius") => (
+                "sensor",
+                "SoC temperature".to_string(),
+                json!({ "device_class": "temperature", "unit_of_measurement": "°C", "state_class": "measurement" }),
+            ),
+            (name, Some(kind)) if name.ends_with("throttling_active") => {
+                ("binary_sensor", format!("Throttling active ({kind})"), json!({ "device_class": "problem" }))
+            },
+            (name, Some(kind)) if name.ends_with("throttling_occurred") => {
+                ("binary_sensor", format!("Throttling occurred ({kind})"), json!({ "device_class": "problem" }))
+            },
+            _ => return None,
+        };
+
+        let topic = self.topic(sample);
+        let node_id = object_id(&self.client_id);
+        let object_id = object_id(&topic);
+        config["name"] = json!(name);
+        config["state_topic"] = json!(topic);
+        config["unique_id"] = json!(format!("{node_id}_{object_id}"));
+        if component == "binary_sensor" {
+            config["payload_on"] = json!("1");
+            config["payload_off"] = json!("0");
+        }
+        config["device"] = json!({ "identifiers": [node_id], "name": self.client_id });
+
+        Some(Message {
+            topic: format!("{discovery_prefix}/{component}/{node_id}/{object_id}/config"),
+            payload: config.to_string(),
+            retain: true,
+        })
+    }
+}
+
+// Home Assistant only allows letters, digits, underscores and hyphens in IDs of topics
+fn object_id(value: &str) -> String {
+    value.replace(|c: char| !c.is_ascii_alphanumeric() && c != '_' && c != '-', "_")
+}
+
+// Wildcards and separators aren't allowed in a level, nor is it empty
+fn escape_level(value: &str) -> String {
+    match value {
+        "" => "_".to_string(),
+        value => value.replace(['/', '+', '#'], "_"),
+    }
+}
+
+fn connect(client_id: &str, credentials: Option<&(String, String)>) -> Vec<u8> {
+    let mut body = Vec::new();
+    encode_string(&mut body, b"MQTT");
+    body.push(PROTOCOL_LEVEL);
+    body.push(CLEAN_SESSION | credentials.map_or(0, |_| USERNAME | PASSWORD));
+    // Without keep alive, as the connection lasts only for a publication
+    body.extend(0_u16.to_be_bytes());
+    encode_string(&mut body, client_id.as_bytes());
+    if let Some((username, password)) = credentials {
+        encode_string(&mut body, username.as_bytes());
+        encode_string(&mut body, password.as_bytes());
+    }
+
+    packet(CONNECT, &body)
+}
+
+fn publish(topic: &str, payload: &[u8], qos: u8, retain: bool, packet_id: u16) -> Vec<u8> {
+    let mut body = Vec::new();
+    encode_string(&mut body, topic.as_bytes());
+    if qos > 0 {
+        body.extend(packet_id.to_be_bytes());
+    }
+    body.extend_from_slice(payload);
+
+    packet(PUBLISH | qos << 1 | if retain { RETAIN } else { 0 }, &body)
+}
+
+fn packet(header: u8, body: &[u8]) -> Vec<u8> {
+    let mut buffer = vec![header];
+    let mut length = body.len();
+    loop {
+        let byte = (length % 0x80) as u8;
+        length /= 0x80;
+        match length {
+            0 => {
+                buffer.push(byte);
+                break;
+            },
+            _ => buffer.push(byte | 0x80),
+        }
+    }
+    buffer.extend_from_slice(body);
+
+    buffer
+}
+
+fn encode_string(buffer: &mut Vec<u8>, string: &[u8]) {
+    buffer.extend((string.len() as u16).to_be_bytes());
+    buffer.extend_from_slice(string);
+}
+
+// Returns the type of a packet without its flags, and its body
+async fn read_packet<S>(stream: &mut S) -> anyhow::Result<(u8, Vec<u8>)>
+where
+    S: AsyncRead + Unpin,
+{
+    let header = stream.read_u8().await.context("connection closed by the broker")?;
+    let mut length = 0;
+    for shift in (0..4).map(|index| index * 7) {
+        let byte = stream.read_u8().await?;
+        length |= usize::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            let mut body = vec![0; length];
+            stream.read_exact(&mut body).await?;
+            return Ok((header & 0xf0, body));
+        }
+    }
+
+    anyhow::bail!("malformed remaining length")
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use crate::{
+        format::Sample,
+        metrics::MetricsHandler,
+        mqtt::{connect, packet, publish, read_packet, Message, Mqtt},
+    };
+
+    #[test]
+    fn encode() {
+        assert_eq!(
+            connect("id", Some(&("u".to_string(), "p".to_string()))),
+            [[0x10, 0x14, 0x00, 0x04].as_slice(), b"MQTT", &[0x04, 0xc2, 0x00, 0x00, 0x00, 0x02], b"id", &[0x00, 0x01, b'u', 0x00, 0x01, b'p']].concat(),
+        );
+        assert_eq!(publish("a/b", b"1", 1, true, 7), [[0x33, 0x08, 0x00, 0x03].as_slice(), b"a/b", &[0x00, 0x07], b"1"].concat());
+        assert_eq!(packet(0x30, &[0; 200])[..3], [0x30, 0xc8, 0x01]);
+    }
+
+    #[test]
+    fn topic() {
+        let mqtt = Mqtt::new("localhost", std::time::Duration::from_secs(15), MetricsHandler::default()).prefix("home/pi");
+        let sample = Sample {
+            name: "raspi_filesystem_size_bytes".to_string(),
+            labels: vec![("mount_point".to_string(), "/boot/firmware".to_string()), ("type".to_string(), String::new())],
+            value: 1.0,
+        };
+
+        assert_eq!(mqtt.topic(&sample), "home/pi/raspi_filesystem_size_bytes/_boot_firmware/_");
+    }
+
+    #[test]
+    fn discovery() {
+        let mqtt = Mqtt::new("localhost", std::time::Duration::from_secs(15), MetricsHandler::default())
+            .client_id("raspberrypi")
+            .discovery_prefix(Some("homeassistant".to_string()));
+        let sample = Sample {
+            name: "raspi_throttling_active".to_string(),
+            labels: vec![("raspi_throttling_active".to_string(), "soft temperature limit".to_string())],
+            value: 1.0,
+        };
+        let message = mqtt.discovery(&sample).unwrap();
+        let config = serde_json::from_str::<serde_json::Value>(&message.payload).unwrap();
+
+        assert_eq!(message.topic, "homeassistant/binary_sensor/raspberrypi/raspi_raspi_throttling_active_soft_temperature_limit/config");
+        assert!(message.retain);
+        assert_eq!(config["device_class"], "problem");
+        assert_eq!(config["state_topic"], "raspi/raspi_throttling_active/soft temperature limit");
+        assert_eq!(config["unique_id"], "raspberrypi_raspi_raspi_throttling_active_soft_temperature_limit");
+
+        let sample = Sample { name: "raspi_oom_kills_total".to_string(), labels: Vec::new(), value: 1.0 };
+        assert!(mqtt.discovery(&sample).is_none());
+    }
+
+    #[tokio::test]
+    async fn send() {
+        let (mut client, mut broker) = tokio::io::duplex(1024);
+        let mqtt = Mqtt::new("localhost", std::time::Duration::from_secs(15), MetricsHandler::default()).qos(1);
+
+        let messages = [Message { topic: "raspi/a".to_string(), payload: "1".to_string(), retain: false }];
+        let (result, ()) = tokio::join!(mqtt.send(&mut client, &messages), async {
+            assert_eq!(read_packet(&mut broker).await.unwrap().0, 0x10);
+            broker.write_all(&[0x20, 0x02, 0x00, 0x00]).await.unwrap();
+            let (packet_type, body) = read_packet(&mut broker).await.unwrap();
+            assert_eq!((packet_type, body), (0x30, [[0x00, 0x07].as_slice(), b"raspi/a", &[0x00, 0x01], b"1"].concat()));
+            broker.write_all(&[0x40, 0x02, 0x00, 0x01]).await.unwrap();
+            assert_eq!(read_packet(&mut broker).await.unwrap(), (0xe0, Vec::new()));
+            assert_eq!(broker.read_u8().await.ok(), None);
+        });
+        result.unwrap();
+    }
+}
@@ -0,0 +1,151 @@
+//! Health check following the plugin API of Nagios and Icinga, which tells the state by the exit code and prints a line
+//! of text followed by performance data.
+
+use std::fmt::{self, Display};
+
+use crate::{
+    executor::Executor,
+    parser::{temperature::TemperatureParser, throttled::{ThrottledParser, ThrottledState}, Parser},
+};
+
+/// State of a check by its exit code.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Status {
+    Ok = 0,
+    Warning = 1,
+    Critical = 2,
+    Unknown = 3,
+}
+
+/// Current readings of the board that the check is made of.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Readings {
+    pub celsius: f64,
+    pub throttled: ThrottledState,
+}
+
+/// Check warning from `warn_temp` and critical from `crit_temp` in Celsius, and warning while undervoltage or throttling is
+/// active.
+#[derive(Clone, Copy, Debug)]
+pub struct HealthCheck {
+    warn_temp: f64,
+    crit_temp: f64,
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = match self {
+            Self::Ok => "OK",
+            Self::Warning => "WARNING",
+            Self::Critical => "CRITICAL",
+            Self::Unknown => "UNKNOWN",
+        };
+        f.write_str(status)
+    }
+}
+
+impl Readings {
+    /// Reads the SoC temperature and the throttled state with the executors of the temperature and throttled collectors.
+    pub async fn read(temperature: &impl Executor, throttled: &impl Executor) -> anyhow::Result<Self> {
+        let celsius = TemperatureParser.parse(&temperature.execute().await?)?.celsius;
+        let throttled = ThrottledParser.parse(&throttled.execute().await?)?;
+
+        Ok(Self {
+            celsius,
+            throttled,
+        })
+    }
+}
+
+impl HealthCheck {
+    pub fn new(warn_temp: f64, crit_temp: f64) -> Self {
+        Self {
+            warn_temp,
+            crit_temp,
+        }
+    }
+
+    /// Status of `readings` and the line to print, unknown when they couldn't be read.
+    pub fn evaluate(&self, readings: anyhow::Result<Readings>) -> (Status, String) {
+        let readings = match readings {
+            Ok(readings) => readings,
+            Err(err) => return (Status::Unknown, format!("RASPI UNKNOWN - {err:#}")),
+        };
+
+        let mut problems = Vec::new();
+        let temperature = match readings.celsius {
+            celsius if celsius >= self.crit_temp => {
+                problems.push(format!("SoC temperature {celsius}°C is at or above {}°C", self.crit_temp));
+                Status::Critical
+            },
+            celsius if celsius >= self.warn_temp => {
+                problems.push(format!("SoC temperature {celsius}°C is at or above {}°C", self.warn_temp));
+                Status::Warning
+            },
+            _ => Status::Ok,
+        };
+        let throttled = &readings.throttled;
+        let throttling = [(throttled.undervoltage_detected, "undervoltage"), (throttled.currently_throttled, "throttling")]
+            .into_iter()
+            .filter(|(active, _)| *active)
+            .map(|(_, kind)| kind)
+            .collect::<Vec<_>>();
+        if !throttling.is_empty() {
+            problems.push(format!("{} active", throttling.join(" and ")));
+        }
+
+        let status = match throttling.is_empty() {
+            true => temperature,
+            false => temperature.max(Status::Warning),
+        };
+        let text = match problems.is_empty() {
+            true => format!("SoC temperature {}°C", readings.celsius),
+            false => problems.join(", "),
+        };
+        let performance = format!(
+            "soc_temperature={};{};{} undervoltage={} throttled={}",
+            readings.celsius,
+            self.warn_temp,
+            self.crit_temp,
+            u8::from(throttled.undervoltage_detected),
+            u8::from(throttled.currently_throttled),
+        );
+
+        (status, format!("RASPI {status} - {text} | {performance}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        nagios::{HealthCheck, Readings, Status},
+        parser::throttled::ThrottledState,
+    };
+
+    #[test]
+    fn evaluate() {
+        let check = HealthCheck::new(70.0, 80.0);
+        let readings = |celsius, undervoltage_detected| Ok(Readings {
+            celsius,
+            throttled: ThrottledState { undervoltage_detected, ..Default::default() },
+        });
+
+        assert_eq!(
+            check.evaluate(readings(48.3, false)),
+            (Status::Ok, "RASPI OK - SoC temperature 48.3°C | soc_temperature=48.3;70;80 undervoltage=0 throttled=0".to_string()),
+        );
+        assert_eq!(
+            check.evaluate(readings(72.0, false)).1,
+            "RASPI WARNING - SoC temperature 72°C is at or above 70°C | soc_temperature=72;70;80 undervoltage=0 throttled=0",
+        );
+        assert_eq!(check.evaluate(readings(50.0, true)).0, Status::Warning);
+        assert_eq!(
+            check.evaluate(readings(85.5, true)).1,
+            "RASPI CRITICAL - SoC temperature 85.5°C is at or above 80°C, undervoltage active | soc_temperature=85.5;70;80 undervoltage=1 throttled=0",
+        );
+        assert_eq!(
+            check.evaluate(Err(anyhow::anyhow!("vcgencmd not found"))),
+            (Status::Unknown, "RASPI UNKNOWN - vcgencmd not found".to_string()),
+        );
+    }
+}
@@ -0,0 +1,167 @@
+use std::{
+    fs,
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Context;
+use http_body_util::Full;
+use hyper::{header::{CONTENT_TYPE, USER_AGENT}, Request, Uri};
+use serde::Serialize;
+use tokio::time::{self, Instant};
+
+use crate::{client::Client, metrics::{throttled::ThrottlingKind, Registerer}, parser::throttled::ThrottledState};
+
+/// POSTs a JSON payload to a webhook when a throttling kind becomes active, meant to be fed by a sampler.
+///
+/// A kind must stay active for the debounce duration before it is notified, and is notified again only after it has
+/// cleared, so that a flapping supply doesn't flood the webhook. A kind already active at the first sample is notified too.
+#[derive(Debug)]
+pub struct ThrottlingNotifier {
+    url: Uri,
+    client: Client,
+    debounce: Duration,
+    hostname: String,
+    // Since when each kind has been active and whether it has been notified
+    states: Mutex<[(Option<Instant>, bool); 4]>,
+}
+
+/// Payload of the webhook, whose `text` is also what chat webhooks such as the ones of Slack and Mattermost show.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ThrottlingEvent {
+    pub hostname: String,
+    pub kind: String,
+    pub active: bool,
+    /// Unix timestamp when the kind became active
+    pub since: u64,
+    pub text: String,
+}
+
+const KINDS: [ThrottlingKind; 4] = [
+    ThrottlingKind::Undervoltage,
+    ThrottlingKind::ArmFrequency,
+    ThrottlingKind::Throttled,
+    ThrottlingKind::SoftTemperatureLimit,
+];
+
+impl ThrottlingNotifier {
+    pub fn new(url: Uri, client: Client, debounce: Duration) -> Self {
+        let hostname = fs::read_to_string("/proc/sys/kernel/hostname").unwrap_or_else(|_| "localhost".to_string());
+
+        Self {
+            url,
+            client,
+            debounce,
+            hostname: hostname.trim().to_string(),
+            states: Mutex::new([(None, false); 4]),
+        }
+    }
+
+    // Kinds active for the debounce duration and not notified yet, with how long they have been active
+    fn activated(&self, state: &ThrottledState) -> Vec<(ThrottlingKind, Duration)> {
+        let current = [
+            state.undervoltage_detected,
+            state.arm_frequency_capped,
+            state.currently_throttled,
+            state.soft_temperature_limit_active,
+        ];
+        let now = Instant::now();
+
+        let mut states = self.states.lock().expect("failed to lock states mutex");
+        let mut activated = Vec::new();
+        for ((kind, (since, notified)), active) in KINDS.into_iter().zip(states.iter_mut()).zip(current) {
+            if !active {
+                *since = None;
+                *notified = false;
+                continue;
+            }
+
+            let elapsed = now - *since.get_or_insert(now);
+            if !*notified && elapsed >= self.debounce {
+                *notified = true;
+                activated.push((kind, elapsed));
+            }
+        }
+
+        activated
+    }
+
+    async fn send(&self, event: &ThrottlingEvent) -> anyhow::Result<()> {
+        let request = Request::post(&self.url)
+            .header(CONTENT_TYPE, "application/json")
+            .header(USER_AGENT, concat!("raspi_exporter/", env!("CARGO_PKG_VERSION")))
+            .body(Full::from(serde_json::to_vec(event)?))?;
+        let response = time::timeout(Duration::from_secs(10), self.client.send(request))
+            .await
+            .with_context(|| format!("webhook timed out: {}", self.url))??;
+        if !response.status().is_success() {
+            anyhow::bail!("webhook failed with status {}: {}", response.status(), self.url);
+        }
+
+        Ok(())
+    }
+}
+
+impl Registerer for ThrottlingNotifier {
+    type Item = ThrottledState;
+
+    async fn update(&self, state: Self::Item) -> anyhow::Result<()> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?;
+        for (kind, elapsed) in self.activated(&state) {
+            let event = ThrottlingEvent {
+                hostname: self.hostname.clone(),
+                kind: kind.to_string(),
+                active: true,
+                since: now.saturating_sub(elapsed).as_secs(),
+                text: format!("{kind} active on {} for {}", self.hostname, humantime::format_duration(Duration::from_secs(elapsed.as_secs()))),
+            };
+            // Doesn't stop the other kinds from being notified
+            match self.send(&event).await {
+                Ok(()) => tracing::info!("notified {kind} becoming active"),
+                Err(err) => tracing::error!("failed to notify {kind} becoming active\nError: {err:?}"),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use hyper::Uri;
+    use tokio::time;
+
+    use crate::{client::Client, metrics::throttled::ThrottlingKind, notifier::ThrottlingNotifier, parser::throttled::ThrottledState};
+
+    #[tokio::test(start_paused = true)]
+    async fn activated() {
+        let notifier = ThrottlingNotifier::new(Uri::from_static("http://localhost/hook"), Client::new("/dev/null"), Duration::from_secs(10));
+        let undervoltage = ThrottledState { undervoltage_detected: true, ..Default::default() };
+
+        assert!(notifier.activated(&undervoltage).is_empty());
+        time::advance(Duration::from_secs(5)).await;
+        // Cleared before the debounce duration
+        assert!(notifier.activated(&ThrottledState::default()).is_empty());
+        assert!(notifier.activated(&undervoltage).is_empty());
+        time::advance(Duration::from_secs(10)).await;
+        assert_eq!(notifier.activated(&undervoltage), [(ThrottlingKind::Undervoltage, Duration::from_secs(10))]);
+        // Notified once until cleared
+        time::advance(Duration::from_secs(5)).await;
+        assert!(notifier.activated(&undervoltage).is_empty());
+        assert!(notifier.activated(&ThrottledState::default()).is_empty());
+        assert!(notifier.activated(&undervoltage).is_empty());
+    }
+
+    #[tokio::test]
+    async fn without_debounce() {
+        let notifier = ThrottlingNotifier::new(Uri::from_static("http://localhost/hook"), Client::new("/dev/null"), Duration::ZERO);
+        let state = ThrottledState { currently_throttled: true, soft_temperature_limit_active: true, ..Default::default() };
+
+        assert_eq!(
+            notifier.activated(&state),
+            [(ThrottlingKind::Throttled, Duration::ZERO), (ThrottlingKind::SoftTemperatureLimit, Duration::ZERO)],
+        );
+    }
+}
@@ -1,5 +1,30 @@
+pub mod access_point;
+pub mod backlight;
+pub mod boot_config;
+pub mod boot_time;
+pub mod cgroup;
+pub mod chrony;
+pub mod clock_tree;
+pub mod container;
+pub mod cpu_vulnerability;
+pub mod file_descriptor;
+pub mod filesystem;
+pub mod kmsg;
+pub mod neighbor;
+pub mod nftables;
+pub mod oom_kill;
+pub mod package_update;
+pub mod reboot_required;
+pub mod reset;
+pub mod snmp;
+pub mod ssh;
+pub mod temperature;
 pub mod throttled;
+pub mod vl805;
+pub mod wireguard;
 
+/// Parses the output of an [`Executor`](crate::executor::Executor) into the state that a
+/// [`Registerer`](crate::metrics::Registerer) takes.
 pub trait Parser {
     type Item;
 
@@ -0,0 +1,121 @@
+use anyhow::Context as _;
+
+use crate::parser::Parser;
+
+#[derive(Debug)]
+pub struct AccessPointParser;
+
+#[derive(Debug, PartialEq)]
+pub struct AccessPointState {
+    pub stations: Vec<AccessPointStation>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct AccessPointStation {
+    pub address: String,
+    pub signal_dbm: Option<i64>,
+    pub transmit_bitrate: Option<f64>,
+}
+
+impl Parser for AccessPointParser {
+    type Item = AccessPointState;
+
+    // Parses the output of `iw dev <interface> station dump`, where a `Station <address> (on <interface>)` line
+    // is followed by tab-indented `<name>:<tabs><value>` lines
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Item> {
+        let invalid_input_error = |line: &str| format!("invalid input: {line}");
+
+        let mut stations = Vec::<AccessPointStation>::new();
+        for line in input.lines() {
+            if let Some(station) = line.strip_prefix("Station ") {
+                let address = station.split_whitespace().next().with_context(|| invalid_input_error(line))?;
+                stations.push(AccessPointStation {
+                    address: address.to_string(),
+                    signal_dbm: None,
+                    transmit_bitrate: None,
+                });
+                continue;
+            }
+
+            let Some((name, value)) = line.trim().split_once(':') else {
+                continue;
+            };
+            let station = stations.last_mut().with_context(|| invalid_input_error(line))?;
+            let value = value.split_whitespace().next();
+            match name {
+                // e.g. `-46 [-46] dBm`
+                "signal" => {
+                    station.signal_dbm = Some(value.and_then(|v| v.parse().ok()).with_context(|| invalid_input_error(line))?);
+                },
+                // e.g. `65.0 MBit/s MCS 7`
+                "tx bitrate" => {
+                    let mbits = value.and_then(|v| v.parse::<f64>().ok()).with_context(|| invalid_input_error(line))?;
+                    station.transmit_bitrate = Some(mbits * 1_000_000.0);
+                },
+                _ => {},
+            }
+        }
+
+        Ok(Self::Item {
+            stations,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{access_point::{AccessPointParser, AccessPointState, AccessPointStation}, Parser};
+
+    #[test]
+    fn parse() {
+        let access_point_parser = AccessPointParser;
+        let result = access_point_parser.parse(concat!(
+            "Station 12:34:56:78:9a:bc (on wlan0)\n",
+            "\tinactive time:\t304 ms\n",
+            "\trx bytes:\t18816\n",
+            "\tsignal:  \t-46 [-46] dBm\n",
+            "\tsignal avg:\t-47 [-47] dBm\n",
+            "\ttx bitrate:\t65.0 MBit/s MCS 7\n",
+            "\trx bitrate:\t1.0 MBit/s\n",
+            "\tconnected time:\t420 seconds\n",
+            "Station de:ad:be:ef:00:01 (on wlan0)\n",
+            "\tinactive time:\t1200 ms\n",
+            "\tsignal:  \t-71 [-71] dBm\n",
+            "\ttx bitrate:\t6.5 MBit/s MCS 0\n",
+        )).unwrap();
+
+        assert_eq!(
+            result,
+            AccessPointState {
+                stations: vec![
+                    AccessPointStation {
+                        address: "12:34:56:78:9a:bc".to_string(),
+                        signal_dbm: Some(-46),
+                        transmit_bitrate: Some(65_000_000.0),
+                    },
+                    AccessPointStation {
+                        address: "de:ad:be:ef:00:01".to_string(),
+                        signal_dbm: Some(-71),
+                        transmit_bitrate: Some(6_500_000.0),
+                    },
+                ],
+            }
+        )
+    }
+
+    #[test]
+    fn parse_empty() {
+        let access_point_parser = AccessPointParser;
+        let result = access_point_parser.parse("").unwrap();
+
+        assert_eq!(result, AccessPointState { stations: vec![] })
+    }
+
+    #[test]
+    fn parse_invalid() {
+        let access_point_parser = AccessPointParser;
+        let result = access_point_parser.parse("\tsignal:  \t-46 [-46] dBm\n");
+
+        assert!(result.is_err())
+    }
+}
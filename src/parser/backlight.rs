@@ -0,0 +1,85 @@
+use anyhow::Context as _;
+
+use crate::parser::Parser;
+
+#[derive(Debug)]
+pub struct BacklightParser;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct BacklightState {
+    pub devices: Vec<BacklightDevice>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct BacklightDevice {
+    pub device: String,
+    pub brightness: u64,
+    pub max_brightness: u64,
+}
+
+impl Parser for BacklightParser {
+    type Item = BacklightState;
+
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Item> {
+        let devices = input
+            .lines()
+            .map(|line| {
+                let invalid_input_error = || format!("invalid input: {line}");
+
+                let mut fields = line.split(' ');
+                let device = fields.next().with_context(invalid_input_error)?;
+                let mut next_value = || fields.next().and_then(|v| v.parse::<u64>().ok()).with_context(invalid_input_error);
+
+                Ok(BacklightDevice {
+                    device: device.to_string(),
+                    brightness: next_value()?,
+                    max_brightness: next_value()?,
+                })
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(Self::Item {
+            devices,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{backlight::{BacklightDevice, BacklightParser, BacklightState}, Parser};
+
+    #[test]
+    fn parse() {
+        let backlight_parser = BacklightParser;
+        let result = backlight_parser.parse("10-0045 128 255\n").unwrap();
+
+        assert_eq!(
+            result,
+            BacklightState {
+                devices: vec![
+                    BacklightDevice {
+                        device: "10-0045".to_string(),
+                        brightness: 128,
+                        max_brightness: 255,
+                    },
+                ],
+            }
+        )
+    }
+
+    #[test]
+    fn parse_empty() {
+        let backlight_parser = BacklightParser;
+        let result = backlight_parser.parse("").unwrap();
+
+        assert_eq!(result, BacklightState { devices: vec![] })
+    }
+
+    #[test]
+    fn parse_invalid() {
+        let backlight_parser = BacklightParser;
+        let result = backlight_parser.parse("10-0045 128\n");
+
+        assert!(result.is_err())
+    }
+}
@@ -0,0 +1,41 @@
+use sha2::{Digest, Sha256};
+
+use crate::parser::Parser;
+
+#[derive(Debug)]
+pub struct BootConfigParser;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BootConfigState {
+    pub sha256: String,
+}
+
+impl Parser for BootConfigParser {
+    type Item = BootConfigState;
+
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Item> {
+        let state = Self::Item {
+            sha256: format!("{:x}", Sha256::digest(input)),
+        };
+
+        Ok(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{boot_config::{BootConfigParser, BootConfigState}, Parser};
+
+    #[test]
+    fn parse() {
+        let boot_config_parser = BootConfigParser;
+        let result = boot_config_parser.parse("arm_boost=1\n").unwrap();
+
+        assert_eq!(
+            result,
+            BootConfigState {
+                sha256: "dd69b293d672d974542f4d40199619691cb734c7bb4070ae1dcb5b890a885465".to_string(),
+            }
+        )
+    }
+}
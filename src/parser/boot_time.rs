@@ -0,0 +1,149 @@
+use anyhow::Context as _;
+
+use crate::parser::Parser;
+
+#[derive(Debug)]
+pub struct BootTimeParser;
+
+#[derive(Debug, PartialEq)]
+pub struct BootTimeState {
+    pub total_seconds: f64,
+    pub phases: Vec<BootTimePhase>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct BootTimePhase {
+    pub phase: String,
+    pub seconds: f64,
+}
+
+impl Parser for BootTimeParser {
+    type Item = BootTimeState;
+
+    // e.g. `Startup finished in 2.345s (kernel) + 1min 8.123s (userspace) = 1min 10.468s`,
+    // where firmware and loader phases are only reported on EFI systems
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Item> {
+        let invalid_input_error = || format!("invalid input: {input}");
+
+        let line = input.lines().next().with_context(invalid_input_error)?;
+        let (phases, total) = line
+            .strip_prefix("Startup finished in ")
+            .and_then(|v| v.split_once(" = "))
+            .with_context(invalid_input_error)?;
+
+        let phases = phases
+            .split(" + ")
+            .map(|phase| {
+                let (duration, name) = phase
+                    .strip_suffix(')')
+                    .and_then(|v| v.split_once(" ("))
+                    .with_context(invalid_input_error)?;
+
+                Ok(BootTimePhase {
+                    phase: name.to_string(),
+                    seconds: parse_timespan(duration).with_context(invalid_input_error)?,
+                })
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(Self::Item {
+            total_seconds: parse_timespan(total.trim()).with_context(invalid_input_error)?,
+            phases,
+        })
+    }
+}
+
+// Parses a timespan of systemd such as `1min 2.345s` or `345ms` into seconds
+fn parse_timespan(input: &str) -> Option<f64> {
+    input
+        .split(' ')
+        .map(|part| {
+            let index = part.find(|c: char| c.is_ascii_alphabetic())?;
+            let (value, unit) = part.split_at(index);
+            let value = value.parse::<f64>().ok()?;
+            let scale = match unit {
+                "d" => 24.0 * 60.0 * 60.0,
+                "h" => 60.0 * 60.0,
+                "min" => 60.0,
+                "s" => 1.0,
+                "ms" => 1e-3,
+                "us" => 1e-6,
+                _ => return None,
+            };
+
+            Some(value * scale)
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{boot_time::{BootTimeParser, BootTimePhase, BootTimeState}, Parser};
+
+    #[test]
+    fn parse() {
+        let boot_time_parser = BootTimeParser;
+        let result = boot_time_parser.parse(concat!(
+            "Startup finished in 2.345s (kernel) + 1min 8.125s (userspace) = 1min 10.470s \n",
+            "graphical.target reached after 1min 7.900s in userspace.\n",
+        )).unwrap();
+
+        assert_eq!(
+            result,
+            BootTimeState {
+                total_seconds: 70.47,
+                phases: vec![
+                    BootTimePhase {
+                        phase: "kernel".to_string(),
+                        seconds: 2.345,
+                    },
+                    BootTimePhase {
+                        phase: "userspace".to_string(),
+                        seconds: 68.125,
+                    },
+                ],
+            }
+        )
+    }
+
+    #[test]
+    fn parse_efi() {
+        let boot_time_parser = BootTimeParser;
+        let result = boot_time_parser.parse(
+            "Startup finished in 500ms (firmware) + 250ms (loader) + 1.5s (kernel) + 4s (userspace) = 6.250s \n",
+        ).unwrap();
+
+        assert_eq!(
+            result,
+            BootTimeState {
+                total_seconds: 6.25,
+                phases: vec![
+                    BootTimePhase {
+                        phase: "firmware".to_string(),
+                        seconds: 0.5,
+                    },
+                    BootTimePhase {
+                        phase: "loader".to_string(),
+                        seconds: 0.25,
+                    },
+                    BootTimePhase {
+                        phase: "kernel".to_string(),
+                        seconds: 1.5,
+                    },
+                    BootTimePhase {
+                        phase: "userspace".to_string(),
+                        seconds: 4.0,
+                    },
+                ],
+            }
+        )
+    }
+
+    #[test]
+    fn parse_invalid() {
+        let boot_time_parser = BootTimeParser;
+        let result = boot_time_parser.parse("Bootup is not yet finished (org.freedesktop.systemd1.Manager.FinishTimestampMonotonic=0).\n");
+
+        assert!(result.is_err())
+    }
+}
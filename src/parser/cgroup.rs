@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+
+use anyhow::Context as _;
+
+use crate::parser::Parser;
+
+#[derive(Debug)]
+pub struct CgroupParser;
+
+#[derive(Debug, PartialEq)]
+pub struct CgroupState {
+    pub cpu_usage_seconds: f64,
+    pub cpu_user_seconds: f64,
+    pub cpu_system_seconds: f64,
+    pub cpu_throttling: Option<CgroupCpuThrottlingState>,
+    pub memory_usage_bytes: u64,
+    pub memory_limit_bytes: Option<u64>,
+}
+
+// Only present when the cpu controller is enabled for the cgroup
+#[derive(Debug, PartialEq)]
+pub struct CgroupCpuThrottlingState {
+    pub periods: u64,
+    pub throttled_periods: u64,
+    pub throttled_seconds: f64,
+}
+
+impl Parser for CgroupParser {
+    type Item = CgroupState;
+
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Item> {
+        let invalid_input_error = || format!("invalid input: {input}");
+
+        let values = input
+            .lines()
+            .map(|line| line.split_once(' ').with_context(invalid_input_error))
+            .collect::<anyhow::Result<HashMap<_, _>>>()?;
+        let get = |key: &str| -> anyhow::Result<Option<u64>> {
+            values.get(key).map(|value| value.parse::<u64>().with_context(invalid_input_error)).transpose()
+        };
+        let get_required = |key: &str| get(key)?.with_context(invalid_input_error);
+        let seconds = |usec: u64| usec as f64 / 1_000_000.0;
+
+        let cpu_throttling = match (get("nr_periods")?, get("nr_throttled")?, get("throttled_usec")?) {
+            (Some(periods), Some(throttled_periods), Some(throttled_usec)) => Some(CgroupCpuThrottlingState {
+                periods,
+                throttled_periods,
+                throttled_seconds: seconds(throttled_usec),
+            }),
+            _ => None,
+        };
+        let memory_limit_bytes = match values.get("memory.max") {
+            Some(&"max") => None,
+            _ => Some(get_required("memory.max")?),
+        };
+
+        let state = Self::Item {
+            cpu_usage_seconds: seconds(get_required("usage_usec")?),
+            cpu_user_seconds: seconds(get_required("user_usec")?),
+            cpu_system_seconds: seconds(get_required("system_usec")?),
+            cpu_throttling,
+            memory_usage_bytes: get_required("memory.current")?,
+            memory_limit_bytes,
+        };
+
+        Ok(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{cgroup::{CgroupCpuThrottlingState, CgroupParser, CgroupState}, Parser};
+
+    #[test]
+    fn parse() {
+        let cgroup_parser = CgroupParser;
+        let result = cgroup_parser.parse(concat!(
+            "usage_usec 2308493\n",
+            "user_usec 1683291\n",
+            "system_usec 625202\n",
+            "core_sched.force_idle_usec 0\n",
+            "nr_periods 120\n",
+            "nr_throttled 3\n",
+            "throttled_usec 45000\n",
+            "nr_bursts 0\n",
+            "burst_usec 0\n",
+            "memory.current 52428800\n",
+            "memory.max 268435456\n",
+        )).unwrap();
+
+        assert_eq!(
+            result,
+            CgroupState {
+                cpu_usage_seconds: 2.308493,
+                cpu_user_seconds: 1.683291,
+                cpu_system_seconds: 0.625202,
+                cpu_throttling: Some(CgroupCpuThrottlingState {
+                    periods: 120,
+                    throttled_periods: 3,
+                    throttled_seconds: 0.045,
+                }),
+                memory_usage_bytes: 52428800,
+                memory_limit_bytes: Some(268435456),
+            }
+        )
+    }
+
+    #[test]
+    fn parse_without_limits() {
+        let cgroup_parser = CgroupParser;
+        let result = cgroup_parser.parse(concat!(
+            "usage_usec 2308493\n",
+            "user_usec 1683291\n",
+            "system_usec 625202\n",
+            "memory.current 52428800\n",
+            "memory.max max\n",
+        )).unwrap();
+
+        assert_eq!(
+            result,
+            CgroupState {
+                cpu_usage_seconds: 2.308493,
+                cpu_user_seconds: 1.683291,
+                cpu_system_seconds: 0.625202,
+                cpu_throttling: None,
+                memory_usage_bytes: 52428800,
+                memory_limit_bytes: None,
+            }
+        )
+    }
+
+    #[test]
+    fn parse_invalid() {
+        let cgroup_parser = CgroupParser;
+        let result = cgroup_parser.parse("usage_usec 2308493\n");
+
+        assert!(result.is_err())
+    }
+}
@@ -0,0 +1,79 @@
+use anyhow::Context as _;
+
+use crate::parser::Parser;
+
+#[derive(Debug)]
+pub struct ChronyParser;
+
+// Output of `chronyc -c tracking`
+// https://chrony-project.org/doc/4.6/chronyc.html#tracking
+#[derive(Debug, Default, PartialEq)]
+pub struct ChronyState {
+    pub offset_seconds: f64,
+    pub stratum: u8,
+    pub synchronised: bool,
+}
+
+impl Parser for ChronyParser {
+    type Item = ChronyState;
+
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Item> {
+        let invalid_input_error = || format!("invalid input: {input}");
+
+        let fields = input.trim().split(',').collect::<Vec<_>>();
+        let [_, _, stratum, _, offset_seconds, _, _, _, _, _, _, _, _, leap_status] = fields[..] else {
+            anyhow::bail!(invalid_input_error());
+        };
+
+        let state = Self::Item {
+            offset_seconds: offset_seconds.parse().with_context(invalid_input_error)?,
+            stratum: stratum.parse().with_context(invalid_input_error)?,
+            synchronised: leap_status != "Not synchronised",
+        };
+
+        Ok(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{chrony::{ChronyParser, ChronyState}, Parser};
+
+    #[test]
+    fn parse() {
+        let chrony_parser = ChronyParser;
+        let result = chrony_parser.parse("A29FC87B,162.159.200.123,4,1760400000.123456789,-0.000012345,0.000001234,0.000023456,-12.345,0.001,0.020,0.010123,0.000456,64.5,Normal\n").unwrap();
+
+        assert_eq!(
+            result,
+            ChronyState {
+                offset_seconds: -0.000012345,
+                stratum: 4,
+                synchronised: true,
+            }
+        )
+    }
+
+    #[test]
+    fn parse_not_synchronised() {
+        let chrony_parser = ChronyParser;
+        let result = chrony_parser.parse("00000000,,0,0.000000000,0.000000000,0.000000000,0.000000000,0.000,0.000,0.000,1.000000000,1.000000000,0.0,Not synchronised\n").unwrap();
+
+        assert_eq!(
+            result,
+            ChronyState {
+                offset_seconds: 0.0,
+                stratum: 0,
+                synchronised: false,
+            }
+        )
+    }
+
+    #[test]
+    fn parse_invalid() {
+        let chrony_parser = ChronyParser;
+        let result = chrony_parser.parse("506 Cannot talk to daemon\n");
+
+        assert!(result.is_err())
+    }
+}
@@ -0,0 +1,147 @@
+use anyhow::Context as _;
+
+use crate::parser::Parser;
+
+/// Parses the clocks, only the given ones unless none is given.
+#[derive(Debug, Default)]
+pub struct ClockTreeParser {
+    clocks: Vec<String>,
+}
+
+// /sys/kernel/debug/clk/clk_summary, whose rows are indented according to the position in the tree
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ClockTreeState {
+    pub clocks: Vec<Clock>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Clock {
+    pub name: String,
+    pub enable_count: u64,
+    pub prepare_count: u64,
+    pub rate: u64,
+}
+
+impl ClockTreeParser {
+    pub fn new(clocks: Vec<String>) -> Self {
+        Self {
+            clocks,
+        }
+    }
+}
+
+impl Parser for ClockTreeParser {
+    type Item = ClockTreeState;
+
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Item> {
+        let invalid_input_error = || format!("invalid input: {input}");
+
+        // Rows start after the dashed line below the header
+        let (_, rows) = input
+            .split_once("\n-")
+            .and_then(|(_, rows)| rows.split_once('\n'))
+            .with_context(invalid_input_error)?;
+
+        let clocks = rows
+            .lines()
+            .filter_map(|row| {
+                // Rows listing only additional consumers of the clock above have no counts
+                let [name, enable_count, prepare_count, _, rate, ..] = row.split_whitespace().collect::<Vec<_>>()[..] else {
+                    return None;
+                };
+
+                if !self.clocks.is_empty() && !self.clocks.iter().any(|clock| clock == name) {
+                    return None;
+                }
+
+                Some(Clock {
+                    name: name.to_string(),
+                    enable_count: enable_count.parse().ok()?,
+                    prepare_count: prepare_count.parse().ok()?,
+                    rate: rate.parse().ok()?,
+                })
+            })
+            .collect();
+
+        let state = Self::Item {
+            clocks,
+        };
+
+        Ok(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{clock_tree::{Clock, ClockTreeParser, ClockTreeState}, Parser};
+
+    const INPUT: &str = concat!(
+        "                                 enable  prepare  protect                                duty  hardware                            connection\n",
+        "   clock                          count    count    count        rate   accuracy phase  cycle    enable   consumer                         id\n",
+        "---------------------------------------------------------------------------------------------------------------------------------------------\n",
+        " osc                                  5        5        0    54000000          0     0  50000         Y   fe980000.usb                    otg\n",
+        "                                                                                                         fe100000.watchdog               wdt\n",
+        "    otp                               0        0        0    27000000          0     0  50000         N   deviceless                      no_connection_id\n",
+        " fw-clk-hdmi                          0        0        0           0          0     0  50000         N   deviceless                      no_connection_id\n",
+    );
+
+    #[test]
+    fn parse() {
+        let clock_tree_parser = ClockTreeParser::default();
+        let result = clock_tree_parser.parse(INPUT).unwrap();
+
+        assert_eq!(
+            result,
+            ClockTreeState {
+                clocks: vec![
+                    Clock {
+                        name: "osc".to_string(),
+                        enable_count: 5,
+                        prepare_count: 5,
+                        rate: 54000000,
+                    },
+                    Clock {
+                        name: "otp".to_string(),
+                        enable_count: 0,
+                        prepare_count: 0,
+                        rate: 27000000,
+                    },
+                    Clock {
+                        name: "fw-clk-hdmi".to_string(),
+                        enable_count: 0,
+                        prepare_count: 0,
+                        rate: 0,
+                    },
+                ],
+            }
+        )
+    }
+
+    #[test]
+    fn parse_selected() {
+        let clock_tree_parser = ClockTreeParser::new(vec!["otp".to_string(), "arm".to_string()]);
+        let result = clock_tree_parser.parse(INPUT).unwrap();
+
+        assert_eq!(
+            result,
+            ClockTreeState {
+                clocks: vec![
+                    Clock {
+                        name: "otp".to_string(),
+                        enable_count: 0,
+                        prepare_count: 0,
+                        rate: 27000000,
+                    },
+                ],
+            }
+        )
+    }
+
+    #[test]
+    fn parse_invalid() {
+        let clock_tree_parser = ClockTreeParser::default();
+        let result = clock_tree_parser.parse("");
+
+        assert!(result.is_err())
+    }
+}
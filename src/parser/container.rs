@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+
+use anyhow::Context as _;
+use serde::Deserialize;
+
+use crate::parser::Parser;
+
+#[derive(Debug)]
+pub struct ContainerParser;
+
+#[derive(Debug, PartialEq)]
+pub struct ContainerState {
+    pub containers: Vec<ContainerStats>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ContainerStats {
+    pub name: String,
+    pub state: String,
+    pub restarts: u64,
+    pub cpu_seconds: Option<f64>,
+    pub memory_bytes: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct Output {
+    inspect: Inspect,
+    stats: Option<Stats>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Inspect {
+    name: String,
+    restart_count: u64,
+    state: InspectState,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct InspectState {
+    status: String,
+}
+
+#[derive(Deserialize)]
+struct Stats {
+    cpu_stats: CpuStats,
+    memory_stats: MemoryStats,
+}
+
+#[derive(Deserialize)]
+struct CpuStats {
+    cpu_usage: CpuUsage,
+}
+
+#[derive(Deserialize)]
+struct CpuUsage {
+    total_usage: u64,
+}
+
+#[derive(Deserialize)]
+struct MemoryStats {
+    usage: Option<u64>,
+    #[serde(default)]
+    stats: HashMap<String, u64>,
+}
+
+impl Parser for ContainerParser {
+    type Item = ContainerState;
+
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Item> {
+        let containers = input
+            .lines()
+            .map(|line| {
+                let output: Output = serde_json::from_str(line).with_context(|| format!("invalid input: {line}"))?;
+                let stats = output.stats.as_ref();
+
+                // Excludes the page cache that can be reclaimed, in the same way as `docker stats`
+                // (inactive_file on cgroup v2 and total_inactive_file on cgroup v1)
+                let memory_bytes = stats.and_then(|stats| {
+                    let inactive = stats.memory_stats.stats
+                        .get("inactive_file")
+                        .or_else(|| stats.memory_stats.stats.get("total_inactive_file"))
+                        .copied()
+                        .unwrap_or(0);
+                    stats.memory_stats.usage.map(|usage| usage.saturating_sub(inactive))
+                });
+
+                Ok(ContainerStats {
+                    name: output.inspect.name.trim_start_matches('/').to_string(),
+                    state: output.inspect.state.status,
+                    restarts: output.inspect.restart_count,
+                    cpu_seconds: stats.map(|stats| stats.cpu_stats.cpu_usage.total_usage as f64 / 1_000_000_000.0),
+                    memory_bytes,
+                })
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(Self::Item {
+            containers,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{container::{ContainerParser, ContainerState, ContainerStats}, Parser};
+
+    #[test]
+    fn parse() {
+        let container_parser = ContainerParser;
+        let result = container_parser.parse(concat!(
+            r#"{"inspect":{"Id":"4f1c","Name":"/pihole","RestartCount":2,"State":{"Status":"running","Running":true}},"#,
+            r#""stats":{"cpu_stats":{"cpu_usage":{"total_usage":12500000000}},"memory_stats":{"usage":104857600,"stats":{"inactive_file":4194304}}}}"#,
+            "\n",
+            r#"{"inspect":{"Id":"9a0b","Name":"/backup","RestartCount":0,"State":{"Status":"exited","Running":false}},"stats":null}"#,
+        )).unwrap();
+
+        assert_eq!(
+            result,
+            ContainerState {
+                containers: vec![
+                    ContainerStats {
+                        name: "pihole".to_string(),
+                        state: "running".to_string(),
+                        restarts: 2,
+                        cpu_seconds: Some(12.5),
+                        memory_bytes: Some(100663296),
+                    },
+                    ContainerStats {
+                        name: "backup".to_string(),
+                        state: "exited".to_string(),
+                        restarts: 0,
+                        cpu_seconds: None,
+                        memory_bytes: None,
+                    },
+                ],
+            }
+        )
+    }
+
+    #[test]
+    fn parse_empty() {
+        let container_parser = ContainerParser;
+        let result = container_parser.parse("").unwrap();
+
+        assert_eq!(result, ContainerState { containers: vec![] })
+    }
+
+    #[test]
+    fn parse_invalid() {
+        let container_parser = ContainerParser;
+        let result = container_parser.parse(r#"{"inspect":{}}"#);
+
+        assert!(result.is_err())
+    }
+}
@@ -0,0 +1,106 @@
+use anyhow::Context as _;
+
+use crate::{metrics::cpu_vulnerability::CpuVulnerabilityStatus, parser::Parser};
+
+#[derive(Debug)]
+pub struct CpuVulnerabilityParser;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct CpuVulnerabilityState {
+    pub vulnerabilities: Vec<CpuVulnerability>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct CpuVulnerability {
+    pub vulnerability: String,
+    pub status: CpuVulnerabilityStatus,
+    pub description: String,
+}
+
+impl Parser for CpuVulnerabilityParser {
+    type Item = CpuVulnerabilityState;
+
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Item> {
+        let mut vulnerabilities = input
+            .lines()
+            .map(|line| {
+                let (vulnerability, description) = line.split_once(' ').with_context(|| format!("invalid input: {line}"))?;
+
+                // Descriptions of mitigations and vulnerabilities are followed by their details,
+                // e.g. `Mitigation: __user pointer sanitization` or `Vulnerable: Unprivileged eBPF enabled`
+                let status = match description {
+                    "Not affected" => CpuVulnerabilityStatus::NotAffected,
+                    v if v.starts_with("Mitigation") => CpuVulnerabilityStatus::Mitigated,
+                    v if v.starts_with("Vulnerable") => CpuVulnerabilityStatus::Vulnerable,
+                    _ => CpuVulnerabilityStatus::Unknown,
+                };
+
+                Ok(CpuVulnerability {
+                    vulnerability: vulnerability.to_string(),
+                    status,
+                    description: description.to_string(),
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        vulnerabilities.sort_by(|a, b| a.vulnerability.cmp(&b.vulnerability));
+
+        Ok(Self::Item {
+            vulnerabilities,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        metrics::cpu_vulnerability::CpuVulnerabilityStatus,
+        parser::{cpu_vulnerability::{CpuVulnerability, CpuVulnerabilityParser, CpuVulnerabilityState}, Parser},
+    };
+
+    #[test]
+    fn parse() {
+        let cpu_vulnerability_parser = CpuVulnerabilityParser;
+        let result = cpu_vulnerability_parser.parse(concat!(
+            "spectre_v2 Vulnerable\n",
+            "meltdown Not affected\n",
+            "spectre_v1 Mitigation: __user pointer sanitization\n",
+            "tsx_async_abort Unknown: Dependent on hypervisor status\n",
+        )).unwrap();
+
+        assert_eq!(
+            result,
+            CpuVulnerabilityState {
+                vulnerabilities: vec![
+                    CpuVulnerability {
+                        vulnerability: "meltdown".to_string(),
+                        status: CpuVulnerabilityStatus::NotAffected,
+                        description: "Not affected".to_string(),
+                    },
+                    CpuVulnerability {
+                        vulnerability: "spectre_v1".to_string(),
+                        status: CpuVulnerabilityStatus::Mitigated,
+                        description: "Mitigation: __user pointer sanitization".to_string(),
+                    },
+                    CpuVulnerability {
+                        vulnerability: "spectre_v2".to_string(),
+                        status: CpuVulnerabilityStatus::Vulnerable,
+                        description: "Vulnerable".to_string(),
+                    },
+                    CpuVulnerability {
+                        vulnerability: "tsx_async_abort".to_string(),
+                        status: CpuVulnerabilityStatus::Unknown,
+                        description: "Unknown: Dependent on hypervisor status".to_string(),
+                    },
+                ],
+            }
+        )
+    }
+
+    #[test]
+    fn parse_invalid() {
+        let cpu_vulnerability_parser = CpuVulnerabilityParser;
+        let result = cpu_vulnerability_parser.parse("meltdown");
+
+        assert!(result.is_err())
+    }
+}
@@ -0,0 +1,107 @@
+use anyhow::Context as _;
+
+use crate::parser::Parser;
+
+#[derive(Debug)]
+pub struct FileDescriptorParser;
+
+#[derive(Debug)]
+pub struct ProcessFileDescriptorParser;
+
+// https://docs.kernel.org/admin-guide/sysctl/fs.html#file-max-file-nr
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct FileDescriptorState {
+    pub allocated: u64,
+    pub maximum: u64,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ProcessFileDescriptorState {
+    pub open: u64,
+}
+
+impl Parser for FileDescriptorParser {
+    type Item = FileDescriptorState;
+
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Item> {
+        let invalid_input_error = || format!("invalid input: {input}");
+
+        let values = input
+            .split_whitespace()
+            .map(str::parse::<u64>)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| anyhow::anyhow!(invalid_input_error()))?;
+        let [allocated, unused, maximum] = values[..] else {
+            anyhow::bail!(invalid_input_error());
+        };
+
+        let state = Self::Item {
+            allocated: allocated.saturating_sub(unused),
+            maximum,
+        };
+
+        Ok(state)
+    }
+}
+
+impl Parser for ProcessFileDescriptorParser {
+    type Item = ProcessFileDescriptorState;
+
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Item> {
+        let open = input
+            .lines()
+            .filter(|line| !line.is_empty())
+            .count()
+            .try_into()
+            .with_context(|| format!("invalid input: {input}"))?;
+
+        let state = Self::Item {
+            open,
+        };
+
+        Ok(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{
+        file_descriptor::{
+            FileDescriptorParser,
+            FileDescriptorState,
+            ProcessFileDescriptorParser,
+            ProcessFileDescriptorState,
+        },
+        Parser,
+    };
+
+    #[test]
+    fn parse() {
+        let file_descriptor_parser = FileDescriptorParser;
+        let result = file_descriptor_parser.parse("2304\t0\t9223372036854775807\n").unwrap();
+
+        assert_eq!(
+            result,
+            FileDescriptorState {
+                allocated: 2304,
+                maximum: 9223372036854775807,
+            }
+        )
+    }
+
+    #[test]
+    fn parse_invalid() {
+        let file_descriptor_parser = FileDescriptorParser;
+        let result = file_descriptor_parser.parse("2304\t0\n");
+
+        assert!(result.is_err())
+    }
+
+    #[test]
+    fn parse_process() {
+        let process_file_descriptor_parser = ProcessFileDescriptorParser;
+        let result = process_file_descriptor_parser.parse("0\n1\n2\n9").unwrap();
+
+        assert_eq!(result, ProcessFileDescriptorState { open: 4 })
+    }
+}
@@ -0,0 +1,124 @@
+use anyhow::Context as _;
+
+use crate::parser::Parser;
+
+#[derive(Debug)]
+pub struct FilesystemParser;
+
+// Output of `df --block-size=1 --output=fstype,size,avail,itotal,iavail,target`
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct FilesystemState {
+    pub filesystems: Vec<FilesystemStats>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct FilesystemStats {
+    pub mountpoint: String,
+    pub fstype: String,
+    pub size_bytes: u64,
+    pub avail_bytes: u64,
+    // df prints "-" for filesystems without inodes such as vfat
+    pub inodes_total: Option<u64>,
+    pub inodes_free: Option<u64>,
+}
+
+impl Parser for FilesystemParser {
+    type Item = FilesystemState;
+
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Item> {
+        let filesystems = input
+            .lines()
+            .skip(1)
+            .filter(|line| !line.trim().is_empty())
+            .map(parse_line)
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let state = Self::Item {
+            filesystems,
+        };
+
+        Ok(state)
+    }
+}
+
+fn parse_line(line: &str) -> anyhow::Result<FilesystemStats> {
+    let invalid_input_error = || format!("invalid input: {line}");
+
+    // The mountpoint comes last because it may contain whitespace
+    let mut rest = line;
+    let mut fields = [""; 5];
+    for field in &mut fields {
+        (*field, rest) = rest.trim_start().split_once(char::is_whitespace).with_context(invalid_input_error)?;
+    }
+    let [fstype, size_bytes, avail_bytes, inodes_total, inodes_free] = fields;
+
+    let parse_number = |v: &str| v.parse::<u64>().map_err(|_| anyhow::anyhow!(invalid_input_error()));
+    let parse_optional_number = |v: &str| (v != "-").then(|| parse_number(v)).transpose();
+
+    let stats = FilesystemStats {
+        mountpoint: rest.trim().to_string(),
+        fstype: fstype.to_string(),
+        size_bytes: parse_number(size_bytes)?,
+        avail_bytes: parse_number(avail_bytes)?,
+        inodes_total: parse_optional_number(inodes_total)?,
+        inodes_free: parse_optional_number(inodes_free)?,
+    };
+
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{filesystem::{FilesystemParser, FilesystemState, FilesystemStats}, Parser};
+
+    #[test]
+    fn parse() {
+        let filesystem_parser = FilesystemParser;
+        let result = filesystem_parser.parse(concat!(
+            "Type    1B-blocks       Avail   Inodes    IFree Mounted on\n",
+            "ext4  31154688000 24513576960  1936400  1790285 /\n",
+            "vfat    535805952   472137728        -        - /boot/firmware\n",
+            "ext4   1000000000   900000000    65536    65000 /mnt/usb disk\n",
+        )).unwrap();
+
+        assert_eq!(
+            result,
+            FilesystemState {
+                filesystems: vec![
+                    FilesystemStats {
+                        mountpoint: "/".to_string(),
+                        fstype: "ext4".to_string(),
+                        size_bytes: 31154688000,
+                        avail_bytes: 24513576960,
+                        inodes_total: Some(1936400),
+                        inodes_free: Some(1790285),
+                    },
+                    FilesystemStats {
+                        mountpoint: "/boot/firmware".to_string(),
+                        fstype: "vfat".to_string(),
+                        size_bytes: 535805952,
+                        avail_bytes: 472137728,
+                        inodes_total: None,
+                        inodes_free: None,
+                    },
+                    FilesystemStats {
+                        mountpoint: "/mnt/usb disk".to_string(),
+                        fstype: "ext4".to_string(),
+                        size_bytes: 1000000000,
+                        avail_bytes: 900000000,
+                        inodes_total: Some(65536),
+                        inodes_free: Some(65000),
+                    },
+                ],
+            }
+        )
+    }
+
+    #[test]
+    fn parse_invalid() {
+        let filesystem_parser = FilesystemParser;
+        let result = filesystem_parser.parse("Type Mounted on\next4 abc\n");
+
+        assert!(result.is_err())
+    }
+}
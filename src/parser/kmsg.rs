@@ -0,0 +1,137 @@
+use anyhow::Context as _;
+
+use crate::{metrics::kmsg::KmsgSeverity, parser::Parser};
+
+/// Subsystem of messages not starting with any of the given prefixes.
+pub const OTHER_SUBSYSTEM: &str = "other";
+
+#[derive(Debug)]
+pub struct KmsgParser {
+    subsystems: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct KmsgState {
+    pub severity: KmsgSeverity,
+    pub subsystem: String,
+}
+
+impl KmsgParser {
+    pub fn new(subsystems: Vec<String>) -> Self {
+        Self {
+            subsystems,
+        }
+    }
+}
+
+impl Parser for KmsgParser {
+    type Item = Option<KmsgState>;
+
+    // A record is formatted as `<priority>,<sequence>,<timestamp>,<flags>[,...];<message>`,
+    // followed by continuation lines starting with a space
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Item> {
+        if input.starts_with(' ') {
+            return Ok(None);
+        }
+
+        let invalid_input_error = || format!("invalid input: {input}");
+
+        let (prefix, message) = input.split_once(';').with_context(invalid_input_error)?;
+        let priority = prefix
+            .split(',')
+            .next()
+            .and_then(|v| v.parse::<u32>().ok())
+            .with_context(invalid_input_error)?;
+
+        // Messages written from userspace have a facility other than kern
+        if priority >> 3 != 0 {
+            return Ok(None);
+        }
+        let severity = match priority & 0b111 {
+            0 => KmsgSeverity::Emergency,
+            1 => KmsgSeverity::Alert,
+            2 => KmsgSeverity::Critical,
+            3 => KmsgSeverity::Error,
+            4 => KmsgSeverity::Warning,
+            _ => return Ok(None),
+        };
+
+        // Device names like `mmc0` or `usb 1-1` are prefixed with the subsystem
+        let subsystem = self.subsystems
+            .iter()
+            .find(|subsystem| message.starts_with(subsystem.as_str()))
+            .map_or(OTHER_SUBSYSTEM, |subsystem| subsystem.as_str());
+
+        Ok(Some(KmsgState {
+            severity,
+            subsystem: subsystem.to_string(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{metrics::kmsg::KmsgSeverity, parser::{kmsg::{KmsgParser, KmsgState}, Parser}};
+
+    fn kmsg_parser() -> KmsgParser {
+        KmsgParser::new(vec!["mmc".to_string(), "usb".to_string(), "brcmfmac".to_string()])
+    }
+
+    #[test]
+    fn parse_error() {
+        let result = kmsg_parser().parse("3,1024,123456789,-;mmc0: Timeout waiting for hardware cmd interrupt.").unwrap();
+
+        assert_eq!(
+            result,
+            Some(KmsgState {
+                severity: KmsgSeverity::Error,
+                subsystem: "mmc".to_string(),
+            })
+        )
+    }
+
+    #[test]
+    fn parse_warning() {
+        let result = kmsg_parser().parse("4,1025,123456790,-;usb 1-1.3: device descriptor read/64, error -71").unwrap();
+
+        assert_eq!(
+            result,
+            Some(KmsgState {
+                severity: KmsgSeverity::Warning,
+                subsystem: "usb".to_string(),
+            })
+        )
+    }
+
+    #[test]
+    fn parse_other_subsystem() {
+        let result = kmsg_parser().parse("3,1026,123456791,-;EXT4-fs error (device mmcblk0p2): ext4_lookup:1855").unwrap();
+
+        assert_eq!(
+            result,
+            Some(KmsgState {
+                severity: KmsgSeverity::Error,
+                subsystem: "other".to_string(),
+            })
+        )
+    }
+
+    #[test]
+    fn parse_skipped() {
+        let kmsg_parser = kmsg_parser();
+
+        // Informational
+        assert_eq!(kmsg_parser.parse("6,1027,123456792,-;brcmfmac: brcmf_c_preinit_dcmds: Firmware: BCM4345/6").unwrap(), None);
+        // Userspace
+        assert_eq!(kmsg_parser.parse("11,1028,123456793,-;systemd[1]: Failed to start foo.service.").unwrap(), None);
+        // Continuation
+        assert_eq!(kmsg_parser.parse(" SUBSYSTEM=usb").unwrap(), None);
+    }
+
+    #[test]
+    fn parse_invalid() {
+        let result = kmsg_parser().parse("invalid");
+
+        assert!(result.is_err())
+    }
+}
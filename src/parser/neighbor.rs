@@ -0,0 +1,138 @@
+use std::collections::BTreeMap;
+
+use anyhow::Context as _;
+
+use crate::parser::Parser;
+
+#[derive(Debug)]
+pub struct NeighborParser;
+
+#[derive(Debug)]
+pub struct NeighborThresholdParser;
+
+// Number of entries per device in /proc/net/arp
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct NeighborState {
+    pub entries: BTreeMap<String, u64>,
+}
+
+// Output of `sysctl net.ipv4.neigh.default.gc_thresh1 ...`
+// https://docs.kernel.org/networking/ip-sysctl.html#neigh-default-gc-thresh1-integer
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct NeighborThresholdState {
+    pub thresholds: BTreeMap<String, u64>,
+}
+
+impl Parser for NeighborParser {
+    type Item = NeighborState;
+
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Item> {
+        let invalid_input_error = || format!("invalid input: {input}");
+
+        let mut entries = BTreeMap::new();
+        for line in input.lines().skip(1).filter(|line| !line.trim().is_empty()) {
+            let device = line.split_whitespace().nth(5).with_context(invalid_input_error)?;
+            *entries.entry(device.to_string()).or_default() += 1;
+        }
+
+        let state = Self::Item {
+            entries,
+        };
+
+        Ok(state)
+    }
+}
+
+impl Parser for NeighborThresholdParser {
+    type Item = NeighborThresholdState;
+
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Item> {
+        let invalid_input_error = || format!("invalid input: {input}");
+
+        let thresholds = input
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let (key, value) = line.split_once('=').with_context(invalid_input_error)?;
+                let threshold = key.trim().rsplit_once('.').map_or(key.trim(), |(_, v)| v);
+                let value = value.trim().parse().with_context(invalid_input_error)?;
+
+                Ok((threshold.to_string(), value))
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        let state = Self::Item {
+            thresholds,
+        };
+
+        Ok(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::parser::{
+        neighbor::{NeighborParser, NeighborState, NeighborThresholdParser, NeighborThresholdState},
+        Parser,
+    };
+
+    #[test]
+    fn parse() {
+        let neighbor_parser = NeighborParser;
+        let result = neighbor_parser.parse(concat!(
+            "IP address       HW type     Flags       HW address            Mask     Device\n",
+            "192.168.1.1      0x1         0x2         aa:bb:cc:dd:ee:01     *        eth0\n",
+            "192.168.1.20     0x1         0x2         aa:bb:cc:dd:ee:02     *        eth0\n",
+            "10.0.0.5         0x1         0x0         00:00:00:00:00:00     *        wlan0\n",
+        )).unwrap();
+
+        assert_eq!(
+            result,
+            NeighborState {
+                entries: BTreeMap::from([
+                    ("eth0".to_string(), 2),
+                    ("wlan0".to_string(), 1),
+                ]),
+            }
+        )
+    }
+
+    #[test]
+    fn parse_empty() {
+        let neighbor_parser = NeighborParser;
+        let result = neighbor_parser.parse("IP address       HW type     Flags       HW address            Mask     Device\n").unwrap();
+
+        assert_eq!(result, NeighborState::default())
+    }
+
+    #[test]
+    fn parse_threshold() {
+        let neighbor_threshold_parser = NeighborThresholdParser;
+        let result = neighbor_threshold_parser.parse(concat!(
+            "net.ipv4.neigh.default.gc_thresh1 = 128\n",
+            "net.ipv4.neigh.default.gc_thresh2 = 512\n",
+            "net.ipv4.neigh.default.gc_thresh3 = 1024\n",
+        )).unwrap();
+
+        assert_eq!(
+            result,
+            NeighborThresholdState {
+                thresholds: BTreeMap::from([
+                    ("gc_thresh1".to_string(), 128),
+                    ("gc_thresh2".to_string(), 512),
+                    ("gc_thresh3".to_string(), 1024),
+                ]),
+            }
+        )
+    }
+
+    #[test]
+    fn parse_threshold_invalid() {
+        let neighbor_threshold_parser = NeighborThresholdParser;
+        let result = neighbor_threshold_parser.parse("sysctl: cannot stat /proc/sys/net/ipv4/neigh/default/gc_thresh4\n");
+
+        assert!(result.is_err())
+    }
+}
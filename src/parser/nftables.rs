@@ -0,0 +1,182 @@
+use serde::{de::IgnoredAny, Deserialize};
+
+use crate::parser::Parser;
+
+#[derive(Debug)]
+pub struct NftablesParser;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct NftablesState {
+    pub counters: Vec<NftablesCounter>,
+    pub chains: Vec<NftablesChain>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct NftablesCounter {
+    pub family: String,
+    pub table: String,
+    pub name: String,
+    pub packets: u64,
+    pub bytes: u64,
+}
+
+// Sums of the anonymous counters of the rules in the chain
+#[derive(Debug, PartialEq, Eq)]
+pub struct NftablesChain {
+    pub family: String,
+    pub table: String,
+    pub chain: String,
+    pub packets: u64,
+    pub bytes: u64,
+}
+
+#[derive(Deserialize)]
+struct Ruleset {
+    nftables: Vec<Object>,
+}
+
+// Each object has a single key naming its kind
+#[derive(Deserialize)]
+struct Object {
+    counter: Option<Counter>,
+    rule: Option<Rule>,
+}
+
+#[derive(Deserialize)]
+struct Counter {
+    family: String,
+    table: String,
+    name: String,
+    packets: u64,
+    bytes: u64,
+}
+
+#[derive(Deserialize)]
+struct Rule {
+    family: String,
+    table: String,
+    chain: String,
+    expr: Vec<Expression>,
+}
+
+#[derive(Deserialize)]
+struct Expression {
+    counter: Option<RuleCounter>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RuleCounter {
+    Anonymous { packets: u64, bytes: u64 },
+    // Reference to a named counter, which is collected separately
+    Named(IgnoredAny),
+}
+
+impl Parser for NftablesParser {
+    type Item = NftablesState;
+
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Item> {
+        let ruleset: Ruleset = serde_json::from_str(input).map_err(|_| anyhow::anyhow!("invalid input: {input}"))?;
+
+        let mut counters = Vec::new();
+        let mut chains = Vec::<NftablesChain>::new();
+        for object in ruleset.nftables {
+            if let Some(counter) = object.counter {
+                counters.push(NftablesCounter {
+                    family: counter.family,
+                    table: counter.table,
+                    name: counter.name,
+                    packets: counter.packets,
+                    bytes: counter.bytes,
+                });
+            }
+
+            if let Some(rule) = object.rule {
+                let rule_counters = rule.expr
+                    .iter()
+                    .filter_map(|expression| match expression.counter {
+                        Some(RuleCounter::Anonymous { packets, bytes }) => Some((packets, bytes)),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>();
+                if rule_counters.is_empty() {
+                    continue;
+                }
+                let (packets, bytes) = rule_counters.iter().fold((0, 0), |(packets, bytes), (p, b)| (packets + p, bytes + b));
+
+                match chains.iter_mut().find(|chain| chain.family == rule.family && chain.table == rule.table && chain.chain == rule.chain) {
+                    Some(chain) => {
+                        chain.packets += packets;
+                        chain.bytes += bytes;
+                    },
+                    None => chains.push(NftablesChain {
+                        family: rule.family,
+                        table: rule.table,
+                        chain: rule.chain,
+                        packets,
+                        bytes,
+                    }),
+                }
+            }
+        }
+
+        Ok(Self::Item {
+            counters,
+            chains,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{nftables::{NftablesChain, NftablesCounter, NftablesParser, NftablesState}, Parser};
+
+    #[test]
+    fn parse() {
+        let nftables_parser = NftablesParser;
+        let result = nftables_parser.parse(concat!(
+            r#"{"nftables": ["#,
+            r#"{"metainfo": {"version": "1.0.6", "release_name": "Lester Gooch #5", "json_schema_version": 1}}, "#,
+            r#"{"table": {"family": "inet", "name": "filter", "handle": 1}}, "#,
+            r#"{"chain": {"family": "inet", "table": "filter", "name": "input", "handle": 1, "type": "filter", "hook": "input", "prio": 0, "policy": "accept"}}, "#,
+            r#"{"counter": {"family": "inet", "name": "blocked", "table": "filter", "handle": 2, "packets": 12, "bytes": 720}}, "#,
+            r#"{"rule": {"family": "inet", "table": "filter", "chain": "input", "handle": 3, "expr": [{"match": {"op": "==", "left": {"payload": {"protocol": "tcp", "field": "dport"}}, "right": 22}}, {"counter": {"packets": 5, "bytes": 300}}, {"accept": null}]}}, "#,
+            r#"{"rule": {"family": "inet", "table": "filter", "chain": "input", "handle": 4, "expr": [{"counter": {"packets": 2, "bytes": 100}}, {"drop": null}]}}, "#,
+            r#"{"rule": {"family": "inet", "table": "filter", "chain": "input", "handle": 5, "expr": [{"counter": "blocked"}, {"drop": null}]}}, "#,
+            r#"{"rule": {"family": "inet", "table": "filter", "chain": "forward", "handle": 6, "expr": [{"accept": null}]}}"#,
+            r#"]}"#,
+        )).unwrap();
+
+        assert_eq!(
+            result,
+            NftablesState {
+                counters: vec![
+                    NftablesCounter {
+                        family: "inet".to_string(),
+                        table: "filter".to_string(),
+                        name: "blocked".to_string(),
+                        packets: 12,
+                        bytes: 720,
+                    },
+                ],
+                chains: vec![
+                    NftablesChain {
+                        family: "inet".to_string(),
+                        table: "filter".to_string(),
+                        chain: "input".to_string(),
+                        packets: 7,
+                        bytes: 400,
+                    },
+                ],
+            }
+        )
+    }
+
+    #[test]
+    fn parse_invalid() {
+        let nftables_parser = NftablesParser;
+        let result = nftables_parser.parse("Error: Could not process rule: Operation not permitted");
+
+        assert!(result.is_err())
+    }
+}
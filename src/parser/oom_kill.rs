@@ -0,0 +1,63 @@
+use anyhow::Context as _;
+
+use crate::parser::Parser;
+
+#[derive(Debug)]
+pub struct OomKillParser;
+
+// Both /proc/vmstat and memory.events of cgroup v2 are lines of "<key> <value>"
+// https://docs.kernel.org/admin-guide/cgroup-v2.html#memory-interface-files
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct OomKillState {
+    pub oom_kills: u64,
+}
+
+impl Parser for OomKillParser {
+    type Item = OomKillState;
+
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Item> {
+        let invalid_input_error = || format!("invalid input: {input}");
+
+        let oom_kills = input
+            .lines()
+            .filter_map(|line| line.split_once(' '))
+            .find_map(|(key, value)| (key == "oom_kill").then_some(value))
+            .with_context(invalid_input_error)
+            .and_then(|v| v.trim().parse().map_err(|_| anyhow::anyhow!(invalid_input_error())))?;
+
+        let state = Self::Item {
+            oom_kills,
+        };
+
+        Ok(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{oom_kill::{OomKillParser, OomKillState}, Parser};
+
+    #[test]
+    fn parse_vmstat() {
+        let oom_kill_parser = OomKillParser;
+        let result = oom_kill_parser.parse("pgfault 1024\noom_kill 3\nnuma_hit 0\n").unwrap();
+
+        assert_eq!(result, OomKillState { oom_kills: 3 })
+    }
+
+    #[test]
+    fn parse_memory_events() {
+        let oom_kill_parser = OomKillParser;
+        let result = oom_kill_parser.parse("low 0\nhigh 0\nmax 12\noom 2\noom_kill 1\noom_group_kill 0\n").unwrap();
+
+        assert_eq!(result, OomKillState { oom_kills: 1 })
+    }
+
+    #[test]
+    fn parse_missing_key() {
+        let oom_kill_parser = OomKillParser;
+        let result = oom_kill_parser.parse("pgfault 1024\n");
+
+        assert!(result.is_err())
+    }
+}
@@ -0,0 +1,68 @@
+use crate::parser::Parser;
+
+#[derive(Debug)]
+pub struct PackageUpdateParser;
+
+// Output of `apt-get --simulate upgrade`, which lists each upgrade as a line like:
+// Inst libssl3 [3.0.15-1~deb12u1] (3.0.17-1~deb12u2 Debian-Security:12/stable-security [arm64])
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct PackageUpdateState {
+    pub pending: u64,
+    pub security: u64,
+}
+
+impl Parser for PackageUpdateParser {
+    type Item = PackageUpdateState;
+
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Item> {
+        let mut state = Self::Item::default();
+        for line in input.lines().filter(|line| line.starts_with("Inst ")) {
+            state.pending += 1;
+            if line.split_once('(').is_some_and(|(_, origin)| origin.to_lowercase().contains("security")) {
+                state.security += 1;
+            }
+        }
+
+        Ok(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{package_update::{PackageUpdateParser, PackageUpdateState}, Parser};
+
+    #[test]
+    fn parse() {
+        let package_update_parser = PackageUpdateParser;
+        let result = package_update_parser.parse(concat!(
+            "Reading package lists... Done\n",
+            "Building dependency tree... Done\n",
+            "Calculating upgrade... Done\n",
+            "The following packages will be upgraded:\n",
+            "  libssl3 openssl raspi-firmware\n",
+            "3 upgraded, 0 newly installed, 0 to remove and 0 not upgraded.\n",
+            "Inst libssl3 [3.0.15-1~deb12u1] (3.0.17-1~deb12u2 Debian-Security:12/stable-security [arm64])\n",
+            "Inst openssl [3.0.15-1~deb12u1] (3.0.17-1~deb12u2 Debian-Security:12/stable-security [arm64])\n",
+            "Inst raspi-firmware [1:1.20250430-4] (1:1.20250915-1 Raspberry Pi Foundation:stable [all])\n",
+            "Conf libssl3 (3.0.17-1~deb12u2 Debian-Security:12/stable-security [arm64])\n",
+            "Conf openssl (3.0.17-1~deb12u2 Debian-Security:12/stable-security [arm64])\n",
+            "Conf raspi-firmware (1:1.20250915-1 Raspberry Pi Foundation:stable [all])\n",
+        )).unwrap();
+
+        assert_eq!(
+            result,
+            PackageUpdateState {
+                pending: 3,
+                security: 2,
+            }
+        )
+    }
+
+    #[test]
+    fn parse_up_to_date() {
+        let package_update_parser = PackageUpdateParser;
+        let result = package_update_parser.parse("Calculating upgrade... Done\n0 upgraded, 0 newly installed, 0 to remove and 0 not upgraded.\n").unwrap();
+
+        assert_eq!(result, PackageUpdateState::default())
+    }
+}
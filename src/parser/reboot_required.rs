@@ -0,0 +1,120 @@
+use crate::parser::Parser;
+
+/// Looks for the flag file created by packages such as unattended-upgrades in a listing of /run.
+#[derive(Debug)]
+pub struct RebootRequiredFlagParser;
+
+/// Compares a listing of /lib/modules with the release of the running kernel.
+#[derive(Debug)]
+pub struct RebootRequiredKernelParser {
+    running: String,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct RebootRequiredState {
+    pub required: bool,
+}
+
+impl RebootRequiredKernelParser {
+    pub fn new(running: impl Into<String>) -> Self {
+        Self {
+            running: running.into().trim().to_string(),
+        }
+    }
+}
+
+impl Parser for RebootRequiredFlagParser {
+    type Item = RebootRequiredState;
+
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Item> {
+        let state = Self::Item {
+            required: input.lines().any(|name| name == "reboot-required"),
+        };
+
+        Ok(state)
+    }
+}
+
+impl Parser for RebootRequiredKernelParser {
+    type Item = RebootRequiredState;
+
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Item> {
+        let installed = input.lines().filter(|name| !name.is_empty()).collect::<Vec<_>>();
+        let (running_version, running_flavour) = split_release(&self.running);
+
+        // Upgrading a kernel either replaces its modules directory or installs one for a newer version of the same flavour
+        let required = !installed.contains(&self.running.as_str())
+            || installed
+                .iter()
+                .map(|release| split_release(release))
+                .any(|(version, flavour)| flavour == running_flavour && version > running_version);
+
+        let state = Self::Item {
+            required,
+        };
+
+        Ok(state)
+    }
+}
+
+// Splits a release such as "6.12.34+rpt-rpi-v8" or "6.1.0-28-arm64" into its numeric version and flavour
+fn split_release(release: &str) -> (Vec<u64>, &str) {
+    let (version, flavour) = release
+        .rsplit_once('+')
+        .or_else(|| release.rsplit_once('-'))
+        .unwrap_or((release, ""));
+    let version = version
+        .split(['.', '-'])
+        .map_while(|v| v.parse().ok())
+        .collect();
+
+    (version, flavour)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{
+        reboot_required::{RebootRequiredFlagParser, RebootRequiredKernelParser, RebootRequiredState},
+        Parser,
+    };
+
+    #[test]
+    fn parse_flag() {
+        let reboot_required_flag_parser = RebootRequiredFlagParser;
+        let result = reboot_required_flag_parser.parse("lock\nreboot-required\nreboot-required.pkgs\nudev").unwrap();
+
+        assert_eq!(result, RebootRequiredState { required: true })
+    }
+
+    #[test]
+    fn parse_flag_absent() {
+        let reboot_required_flag_parser = RebootRequiredFlagParser;
+        let result = reboot_required_flag_parser.parse("lock\nudev").unwrap();
+
+        assert_eq!(result, RebootRequiredState { required: false })
+    }
+
+    #[test]
+    fn parse_kernel_up_to_date() {
+        let reboot_required_kernel_parser = RebootRequiredKernelParser::new("6.12.34+rpt-rpi-v8\n");
+        let result = reboot_required_kernel_parser.parse("6.12.34+rpt-rpi-2712\n6.12.34+rpt-rpi-v8").unwrap();
+
+        assert_eq!(result, RebootRequiredState { required: false })
+    }
+
+    #[test]
+    fn parse_kernel_newer_installed() {
+        let reboot_required_kernel_parser = RebootRequiredKernelParser::new("6.1.0-28-arm64");
+        let result = reboot_required_kernel_parser.parse("6.1.0-28-arm64\n6.1.0-30-arm64").unwrap();
+
+        assert_eq!(result, RebootRequiredState { required: true })
+    }
+
+    #[test]
+    fn parse_kernel_running_removed() {
+        let reboot_required_kernel_parser = RebootRequiredKernelParser::new("6.12.25+rpt-rpi-v8");
+        let result = reboot_required_kernel_parser.parse("6.12.34+rpt-rpi-2712\n6.12.34+rpt-rpi-v8").unwrap();
+
+        assert_eq!(result, RebootRequiredState { required: true })
+    }
+}
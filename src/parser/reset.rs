@@ -0,0 +1,89 @@
+use anyhow::Context as _;
+
+use crate::parser::Parser;
+
+#[derive(Debug)]
+pub struct ResetParser;
+
+// PM_RSTS register of the power manager, where each cause has the quick, full and hard reset bits
+// Linux reboots through a full reset of the watchdog, so regular reboots are also reported as watchdog
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ResetState {
+    pub register: u32,
+    pub power_on: bool,
+    pub watchdog: bool,
+    pub software: bool,
+    pub debugger: bool,
+}
+
+impl Parser for ResetParser {
+    type Item = ResetState;
+
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Item> {
+        let invalid_input_error = || format!("invalid input: {input}");
+
+        let register = input
+            .trim()
+            .split_once('=')
+            .and_then(|(_, v)| v.strip_prefix("0x"))
+            .with_context(invalid_input_error)
+            .and_then(|v| u32::from_str_radix(v, 16).map_err(|_| anyhow::anyhow!(invalid_input_error())))?;
+
+        let state = Self::Item {
+            register,
+            power_on: register & 0b1 << 12 != 0,
+            software: register & 0b111 << 8 != 0,
+            watchdog: register & 0b111 << 4 != 0,
+            debugger: register & 0b111 != 0,
+        };
+
+        Ok(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{reset::{ResetParser, ResetState}, Parser};
+
+    #[test]
+    fn parse_power_on() {
+        let reset_parser = ResetParser;
+        let result = reset_parser.parse("rsts=0x00001000\n").unwrap();
+
+        assert_eq!(
+            result,
+            ResetState {
+                register: 0x1000,
+                power_on: true,
+                watchdog: false,
+                software: false,
+                debugger: false,
+            }
+        )
+    }
+
+    #[test]
+    fn parse_watchdog() {
+        let reset_parser = ResetParser;
+        let result = reset_parser.parse("rsts=0x00000020\n").unwrap();
+
+        assert_eq!(
+            result,
+            ResetState {
+                register: 0x20,
+                power_on: false,
+                watchdog: true,
+                software: false,
+                debugger: false,
+            }
+        )
+    }
+
+    #[test]
+    fn parse_invalid() {
+        let reset_parser = ResetParser;
+        let result = reset_parser.parse("error=1 error_msg=\"Command not registered\"\n");
+
+        assert!(result.is_err())
+    }
+}
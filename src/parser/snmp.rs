@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+use anyhow::Context as _;
+
+use crate::{metrics::snmp::SnmpProtocol, parser::Parser};
+
+#[derive(Debug)]
+pub struct SnmpParser;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct SnmpState {
+    pub protocols: Vec<SnmpProtocolStats>,
+    pub tcp_retransmitted_segments: u64,
+    pub tcp_connections_established: u64,
+    pub udp_no_port_datagrams: u64,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct SnmpProtocolStats {
+    pub protocol: SnmpProtocol,
+    pub received: u64,
+    pub sent: u64,
+    pub receive_errors: u64,
+    pub checksum_errors: Option<u64>,
+}
+
+// Fields of packets received, packets sent, receive errors and checksum errors for each protocol
+const PROTOCOL_FIELDS: [(SnmpProtocol, &str, [&str; 3], Option<&str>); 4] = [
+    (SnmpProtocol::Ip, "Ip", ["InReceives", "OutRequests", "InHdrErrors"], None),
+    (SnmpProtocol::Icmp, "Icmp", ["InMsgs", "OutMsgs", "InErrors"], Some("InCsumErrors")),
+    (SnmpProtocol::Tcp, "Tcp", ["InSegs", "OutSegs", "InErrs"], Some("InCsumErrors")),
+    (SnmpProtocol::Udp, "Udp", ["InDatagrams", "OutDatagrams", "InErrors"], Some("InCsumErrors")),
+];
+
+impl Parser for SnmpParser {
+    type Item = SnmpState;
+
+    // Each protocol has a line of field names followed by a line of their values, both prefixed with `<protocol>:`
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Item> {
+        let invalid_input_error = || format!("invalid input: {input}");
+
+        let mut values = HashMap::new();
+        let mut lines = input.lines();
+        while let Some(names) = lines.next() {
+            let (protocol, names) = names.split_once(':').with_context(invalid_input_error)?;
+            let (_, fields) = lines.next().and_then(|v| v.split_once(':')).with_context(invalid_input_error)?;
+            for (name, value) in names.split_whitespace().zip(fields.split_whitespace()) {
+                values.insert((protocol, name), value);
+            }
+        }
+        let get = |protocol: &str, name: &str| -> anyhow::Result<u64> {
+            values
+                .get(&(protocol, name))
+                .and_then(|v| v.parse::<u64>().ok())
+                .with_context(invalid_input_error)
+        };
+
+        let protocols = PROTOCOL_FIELDS
+            .iter()
+            .map(|(protocol, prefix, [received, sent, receive_errors], checksum_errors)| {
+                Ok(SnmpProtocolStats {
+                    protocol: *protocol,
+                    received: get(prefix, received)?,
+                    sent: get(prefix, sent)?,
+                    receive_errors: get(prefix, receive_errors)?,
+                    checksum_errors: checksum_errors.map(|name| get(prefix, name)).transpose()?,
+                })
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(Self::Item {
+            protocols,
+            tcp_retransmitted_segments: get("Tcp", "RetransSegs")?,
+            tcp_connections_established: get("Tcp", "CurrEstab")?,
+            udp_no_port_datagrams: get("Udp", "NoPorts")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        metrics::snmp::SnmpProtocol,
+        parser::{snmp::{SnmpParser, SnmpProtocolStats, SnmpState}, Parser},
+    };
+
+    #[test]
+    fn parse() {
+        let snmp_parser = SnmpParser;
+        let result = snmp_parser.parse(concat!(
+            "Ip: Forwarding DefaultTTL InReceives InHdrErrors InAddrErrors ForwDatagrams InUnknownProtos InDiscards InDelivers OutRequests OutDiscards OutNoRoutes ReasmTimeout ReasmReqds ReasmOKs ReasmFails FragOKs FragFails FragCreates OutTransmits\n",
+            "Ip: 2 64 9582 1 0 0 0 0 9582 9805 0 0 0 0 0 0 0 0 0 9805\n",
+            "Icmp: InMsgs InErrors InCsumErrors InDestUnreachs InTimeExcds InParmProbs InSrcQuenchs InRedirects InEchos InEchoReps InTimestamps InTimestampReps InAddrMasks InAddrMaskReps OutMsgs OutErrors OutRateLimitGlobal OutRateLimitHost OutDestUnreachs OutTimeExcds OutParmProbs OutSrcQuenchs OutRedirects OutEchos OutEchoReps OutTimestamps OutTimestampReps OutAddrMasks OutAddrMaskReps\n",
+            "Icmp: 12 2 0 2 0 0 0 0 5 5 0 0 0 0 10 0 0 0 0 0 0 0 0 5 5 0 0 0 0\n",
+            "IcmpMsg: InType0 InType3 InType8 OutType0 OutType8\n",
+            "IcmpMsg: 5 2 5 5 5\n",
+            "Tcp: RtoAlgorithm RtoMin RtoMax MaxConn ActiveOpens PassiveOpens AttemptFails EstabResets CurrEstab InSegs OutSegs RetransSegs InErrs OutRsts InCsumErrors\n",
+            "Tcp: 1 200 120000 -1 28 12 4 13 2 9558 9863 42 3 14 4\n",
+            "Udp: InDatagrams NoPorts InErrors OutDatagrams RcvbufErrors SndbufErrors InCsumErrors IgnoredMulti MemErrors\n",
+            "Udp: 28 7 5 27 0 0 6 0 0\n",
+            "UdpLite: InDatagrams NoPorts InErrors OutDatagrams RcvbufErrors SndbufErrors InCsumErrors IgnoredMulti MemErrors\n",
+            "UdpLite: 0 0 0 0 0 0 0 0 0\n",
+        )).unwrap();
+
+        assert_eq!(
+            result,
+            SnmpState {
+                protocols: vec![
+                    SnmpProtocolStats {
+                        protocol: SnmpProtocol::Ip,
+                        received: 9582,
+                        sent: 9805,
+                        receive_errors: 1,
+                        checksum_errors: None,
+                    },
+                    SnmpProtocolStats {
+                        protocol: SnmpProtocol::Icmp,
+                        received: 12,
+                        sent: 10,
+                        receive_errors: 2,
+                        checksum_errors: Some(0),
+                    },
+                    SnmpProtocolStats {
+                        protocol: SnmpProtocol::Tcp,
+                        received: 9558,
+                        sent: 9863,
+                        receive_errors: 3,
+                        checksum_errors: Some(4),
+                    },
+                    SnmpProtocolStats {
+                        protocol: SnmpProtocol::Udp,
+                        received: 28,
+                        sent: 27,
+                        receive_errors: 5,
+                        checksum_errors: Some(6),
+                    },
+                ],
+                tcp_retransmitted_segments: 42,
+                tcp_connections_established: 2,
+                udp_no_port_datagrams: 7,
+            }
+        )
+    }
+
+    #[test]
+    fn parse_invalid() {
+        let snmp_parser = SnmpParser;
+        let result = snmp_parser.parse("Ip: Forwarding DefaultTTL\n");
+
+        assert!(result.is_err())
+    }
+}
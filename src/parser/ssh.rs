@@ -0,0 +1,67 @@
+use anyhow::Context as _;
+
+use crate::parser::Parser;
+
+#[derive(Debug)]
+pub struct SshAuthFailureParser;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SshAuthFailureState {
+    pub method: String,
+}
+
+impl Parser for SshAuthFailureParser {
+    type Item = Option<SshAuthFailureState>;
+
+    // e.g. `Failed password for invalid user admin from 192.0.2.1 port 51234 ssh2`
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Item> {
+        let Some(failure) = input.strip_prefix("Failed ") else {
+            return Ok(None);
+        };
+
+        let (method, _) = failure.split_once(" for ").with_context(|| format!("invalid input: {input}"))?;
+
+        Ok(Some(SshAuthFailureState {
+            method: method.to_string(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{ssh::{SshAuthFailureParser, SshAuthFailureState}, Parser};
+
+    #[test]
+    fn parse() {
+        let ssh_auth_failure_parser = SshAuthFailureParser;
+
+        assert_eq!(
+            ssh_auth_failure_parser.parse("Failed password for invalid user admin from 192.0.2.1 port 51234 ssh2").unwrap(),
+            Some(SshAuthFailureState {
+                method: "password".to_string(),
+            })
+        );
+        assert_eq!(
+            ssh_auth_failure_parser.parse("Failed publickey for pi from 192.0.2.1 port 51235 ssh2: ED25519 SHA256:abc").unwrap(),
+            Some(SshAuthFailureState {
+                method: "publickey".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn parse_skipped() {
+        let ssh_auth_failure_parser = SshAuthFailureParser;
+
+        assert_eq!(ssh_auth_failure_parser.parse("Invalid user admin from 192.0.2.1 port 51234").unwrap(), None);
+        assert_eq!(ssh_auth_failure_parser.parse("Accepted publickey for pi from 192.0.2.1 port 51236 ssh2").unwrap(), None);
+    }
+
+    #[test]
+    fn parse_invalid() {
+        let ssh_auth_failure_parser = SshAuthFailureParser;
+        let result = ssh_auth_failure_parser.parse("Failed to bind");
+
+        assert!(result.is_err())
+    }
+}
@@ -0,0 +1,58 @@
+use anyhow::Context as _;
+
+use crate::parser::Parser;
+
+#[derive(Debug)]
+pub struct TemperatureParser;
+
+// /sys/class/thermal/thermal_zone0/temp in millidegree Celsius or `temp=48.3'C` of vcgencmd measure_temp
+#[derive(Debug, Default, PartialEq)]
+pub struct TemperatureState {
+    pub celsius: f64,
+}
+
+impl Parser for TemperatureParser {
+    type Item = TemperatureState;
+
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Item> {
+        let celsius = match input.trim().strip_prefix("temp=") {
+            Some(celsius) => celsius.trim_end_matches("'C").parse::<f64>().ok(),
+            None => input.trim().parse::<i64>().ok().map(|millidegree| millidegree as f64 / 1000.0),
+        };
+
+        let state = Self::Item {
+            celsius: celsius.with_context(|| format!("invalid input: {input}"))?,
+        };
+
+        Ok(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{temperature::{TemperatureParser, TemperatureState}, Parser};
+
+    #[test]
+    fn parse() {
+        let temperature_parser = TemperatureParser;
+        let result = temperature_parser.parse("48312\n").unwrap();
+
+        assert_eq!(result, TemperatureState { celsius: 48.312 })
+    }
+
+    #[test]
+    fn parse_vcgencmd() {
+        let temperature_parser = TemperatureParser;
+        let result = temperature_parser.parse("temp=48.3'C\n").unwrap();
+
+        assert_eq!(result, TemperatureState { celsius: 48.3 })
+    }
+
+    #[test]
+    fn parse_invalid() {
+        let temperature_parser = TemperatureParser;
+        let result = temperature_parser.parse("");
+
+        assert!(result.is_err())
+    }
+}
@@ -6,7 +6,7 @@ use crate::parser::Parser;
 pub struct ThrottledParser;
 
 // https://www.raspberrypi.com/documentation/computers/os.html#get_throttled
-#[derive(Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct ThrottledState {
     pub undervoltage_detected: bool,
     pub arm_frequency_capped: bool,
@@ -24,11 +24,11 @@ impl Parser for ThrottledParser {
     fn parse(&self, input: &str) -> anyhow::Result<Self::Item> {
         let invalid_input_error = || format!("invalid input: {input}");
 
-        let decimal = input
-            .trim()
-            .split_once('=')
-            .with_context(invalid_input_error)
-            .and_then(|(_, v)| u32::from_str_radix(&v[2..], 16).map_err(|_| anyhow::anyhow!(invalid_input_error())))?;
+        // `throttled=0x50005` of vcgencmd or `50005` of the sysfs file of the firmware driver
+        let input = input.trim();
+        let hex = input.strip_prefix("throttled=").unwrap_or(input);
+        let hex = hex.strip_prefix("0x").unwrap_or(hex);
+        let decimal = u32::from_str_radix(hex, 16).with_context(invalid_input_error)?;
 
         let state = Self::Item {
             undervoltage_detected: decimal & 0b1 << 0 != 0,
@@ -68,4 +68,13 @@ mod tests {
             }
         )
     }
+
+    #[test]
+    fn parse_sysfs() {
+        let throttled_parser = ThrottledParser;
+
+        assert_eq!(throttled_parser.parse("d0005\n").unwrap(), throttled_parser.parse("throttled=0xd0005").unwrap());
+        assert_eq!(throttled_parser.parse("0\n").unwrap(), ThrottledState::default());
+        assert!(throttled_parser.parse("throttled=").is_err());
+    }
 }
@@ -0,0 +1,82 @@
+use anyhow::Context as _;
+
+use crate::parser::Parser;
+
+#[derive(Debug)]
+pub struct Vl805Parser;
+
+// Output of `rpi-eeprom-update`, whose VL805 section follows the bootloader one:
+//      VL805: up to date
+//    CURRENT: 000138c0
+//     LATEST: 000138c0
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Vl805State {
+    pub current: String,
+    pub latest: String,
+}
+
+impl Parser for Vl805Parser {
+    type Item = Vl805State;
+
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Item> {
+        let invalid_input_error = || format!("invalid input: {input}");
+
+        let mut lines = input
+            .lines()
+            .skip_while(|line| !line.trim_start().starts_with("VL805:"))
+            .skip(1)
+            .filter_map(|line| line.split_once(':'))
+            .map(|(key, value)| (key.trim(), value.trim()));
+        let current = lines.next().filter(|(key, _)| *key == "CURRENT").with_context(invalid_input_error)?.1;
+        let latest = lines.next().filter(|(key, _)| *key == "LATEST").with_context(invalid_input_error)?.1;
+
+        let state = Self::Item {
+            current: current.to_string(),
+            latest: latest.to_string(),
+        };
+
+        Ok(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{vl805::{Vl805Parser, Vl805State}, Parser};
+
+    #[test]
+    fn parse() {
+        let vl805_parser = Vl805Parser;
+        let result = vl805_parser.parse(concat!(
+            "BOOTLOADER: up to date\n",
+            "   CURRENT: Mon 15 Sep 13:29:23 UTC 2025 (1757942963)\n",
+            "    LATEST: Mon 15 Sep 13:29:23 UTC 2025 (1757942963)\n",
+            "   RELEASE: default (/usr/lib/firmware/raspberrypi/bootloader-2711/default)\n",
+            "            Use raspi-config to change the release.\n",
+            "\n",
+            "  VL805_FW: Using bootloader EEPROM\n",
+            "     VL805: update available\n",
+            "   CURRENT: 000137ad\n",
+            "    LATEST: 000138c0\n",
+        )).unwrap();
+
+        assert_eq!(
+            result,
+            Vl805State {
+                current: "000137ad".to_string(),
+                latest: "000138c0".to_string(),
+            }
+        )
+    }
+
+    #[test]
+    fn parse_without_vl805() {
+        let vl805_parser = Vl805Parser;
+        let result = vl805_parser.parse(concat!(
+            "BOOTLOADER: up to date\n",
+            "   CURRENT: Mon 15 Sep 13:29:23 UTC 2025 (1757942963)\n",
+            "    LATEST: Mon 15 Sep 13:29:23 UTC 2025 (1757942963)\n",
+        ));
+
+        assert!(result.is_err())
+    }
+}
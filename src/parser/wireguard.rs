@@ -0,0 +1,102 @@
+use anyhow::Context as _;
+
+use crate::parser::Parser;
+
+#[derive(Debug)]
+pub struct WireguardParser;
+
+// Output of `wg show all dump`
+// https://man7.org/linux/man-pages/man8/wg.8.html
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct WireguardState {
+    pub peers: Vec<WireguardPeer>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct WireguardPeer {
+    pub interface: String,
+    pub public_key: String,
+    // Unix timestamp, or none if no handshake has completed yet
+    pub latest_handshake: Option<u64>,
+    pub transfer_rx: u64,
+    pub transfer_tx: u64,
+}
+
+impl Parser for WireguardParser {
+    type Item = WireguardState;
+
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Item> {
+        let mut peers = Vec::new();
+        for line in input.lines().filter(|line| !line.trim().is_empty()) {
+            let invalid_input_error = || format!("invalid input: {line}");
+
+            let fields = line.split('\t').collect::<Vec<_>>();
+            match fields[..] {
+                // Interface lines: interface, private key, public key, listen port, fwmark
+                [_, _, _, _, _] => {},
+                [interface, public_key, _, _, _, latest_handshake, transfer_rx, transfer_tx, _] => {
+                    let latest_handshake = latest_handshake.parse().with_context(invalid_input_error)?;
+                    peers.push(WireguardPeer {
+                        interface: interface.to_string(),
+                        public_key: public_key.to_string(),
+                        latest_handshake: (latest_handshake != 0).then_some(latest_handshake),
+                        transfer_rx: transfer_rx.parse().with_context(invalid_input_error)?,
+                        transfer_tx: transfer_tx.parse().with_context(invalid_input_error)?,
+                    });
+                },
+                _ => anyhow::bail!(invalid_input_error()),
+            }
+        }
+
+        let state = Self::Item {
+            peers,
+        };
+
+        Ok(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{wireguard::{WireguardParser, WireguardPeer, WireguardState}, Parser};
+
+    #[test]
+    fn parse() {
+        let wireguard_parser = WireguardParser;
+        let result = wireguard_parser.parse(concat!(
+            "wg0\tcFJpdmF0ZUtleQ==\tUHVibGljS2V5MA==\t51820\toff\n",
+            "wg0\tUGVlcktleTE=\t(none)\t203.0.113.5:51820\t10.0.0.2/32\t1760400000\t1024\t2048\t25\n",
+            "wg0\tUGVlcktleTI=\t(none)\t(none)\t10.0.0.3/32\t0\t0\t0\toff\n",
+        )).unwrap();
+
+        assert_eq!(
+            result,
+            WireguardState {
+                peers: vec![
+                    WireguardPeer {
+                        interface: "wg0".to_string(),
+                        public_key: "UGVlcktleTE=".to_string(),
+                        latest_handshake: Some(1760400000),
+                        transfer_rx: 1024,
+                        transfer_tx: 2048,
+                    },
+                    WireguardPeer {
+                        interface: "wg0".to_string(),
+                        public_key: "UGVlcktleTI=".to_string(),
+                        latest_handshake: None,
+                        transfer_rx: 0,
+                        transfer_tx: 0,
+                    },
+                ],
+            }
+        )
+    }
+
+    #[test]
+    fn parse_invalid() {
+        let wireguard_parser = WireguardParser;
+        let result = wireguard_parser.parse("Unable to access interface: Operation not permitted\n");
+
+        assert!(result.is_err())
+    }
+}
@@ -0,0 +1,121 @@
+use std::{
+    fs::File,
+    future::Future,
+    io::{BufWriter, Write},
+    path::Path,
+    time::{Duration, SystemTime},
+};
+
+use anyhow::Context;
+use tokio::time::{self, MissedTickBehavior};
+
+use crate::{
+    cli::RecordFormat,
+    format::{csv, parquet::ParquetWriter},
+    metrics::{Filter, Handler},
+};
+
+enum Output {
+    Csv(BufWriter<File>),
+    Parquet(ParquetWriter<BufWriter<File>>),
+}
+
+/// Collects the metrics on `interval` into `out` in `format` until `stop` completes, returning how many collections have
+/// been recorded.
+///
+/// A collection that fails is logged and skipped rather than stopping the recording.
+pub async fn record<H>(handler: &H, interval: Duration, out: &Path, format: RecordFormat, stop: impl Future<Output = ()>) -> anyhow::Result<usize>
+where
+    H: Handler,
+{
+    let file = BufWriter::new(File::create(out).with_context(|| format!("file creation error: {out:?}"))?);
+    let mut output = match format {
+        RecordFormat::Csv => {
+            let mut file = file;
+            file.write_all(csv::HEADER.as_bytes())?;
+            Output::Csv(file)
+        },
+        RecordFormat::Parquet => Output::Parquet(ParquetWriter::new(file)?),
+    };
+
+    let mut interval = time::interval(interval);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut recorded = 0;
+    tokio::pin!(stop);
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {},
+            () = &mut stop => break,
+        }
+
+        match collect(handler, &mut output).await {
+            Ok(()) => recorded += 1,
+            Err(err) => tracing::error!("failed to record metrics\nError: {err:?}"),
+        }
+    }
+
+    // Only the footer makes a Parquet file readable
+    match output {
+        Output::Csv(mut file) => file.flush()?,
+        Output::Parquet(writer) => {
+            writer.finish()?;
+        },
+    }
+
+    Ok(recorded)
+}
+
+async fn collect<H>(handler: &H, output: &mut Output) -> anyhow::Result<()>
+where
+    H: Handler,
+{
+    let time = SystemTime::now();
+    let metrics = handler.handle(&Filter::default(), None).await?;
+
+    match output {
+        // Flushes every collection, so that the rows so far survive the recording being killed
+        Output::Csv(file) => {
+            file.write_all(csv::encode(&metrics, &humantime::format_rfc3339_seconds(time).to_string())?.as_bytes())?;
+            file.flush()?;
+        },
+        Output::Parquet(writer) => writer.write(&metrics, time.duration_since(SystemTime::UNIX_EPOCH)?.as_millis() as i64)?,
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process, sync::{Arc, Mutex}, time::Duration};
+
+    use prometheus_client::{metrics::gauge::Gauge, registry::Registry};
+
+    use crate::{cli::RecordFormat, metrics::{MetricsHandler, MockCollector}, record::record};
+
+    #[tokio::test(start_paused = true)]
+    async fn record_csv() {
+        let mut mock_collector = MockCollector::new();
+        mock_collector
+            .expect_collect()
+            .times(3)
+            .returning(|| Ok(()));
+        mock_collector
+            .expect_name()
+            .return_const("temperature");
+        let registry = Arc::new(Mutex::new(Registry::default()));
+        registry.lock().unwrap().register("soc_temperature_celsius", "", Gauge::<i64>::default());
+        let metrics_handler = MetricsHandler::new(vec![Box::new(mock_collector)], registry);
+
+        let out = env::temp_dir().join(format!("raspi_exporter_record_{}.csv", process::id()));
+        // Collects right away and then twice more
+        let recorded = record(&metrics_handler, Duration::from_secs(10), &out, RecordFormat::Csv, tokio::time::sleep(Duration::from_secs(25)))
+            .await
+            .unwrap();
+        let csv = fs::read_to_string(&out).unwrap();
+        fs::remove_file(&out).unwrap();
+
+        assert_eq!(recorded, 3);
+        assert!(csv.starts_with("time,name,labels,value\n"));
+        assert_eq!(csv.lines().filter(|line| line.contains(",soc_temperature_celsius,{},0")).count(), 3);
+    }
+}
@@ -1 +1,50 @@
+use std::{hash::Hash, ops::Sub, sync::Mutex};
+
+use prometheus_client::metrics::{counter::{Atomic, Counter}, family::Family};
+
+pub mod access_point;
+pub mod backlight;
+pub mod boot_config;
+pub mod boot_time;
+pub mod cgroup;
+pub mod chrony;
+pub mod clock_tree;
+pub mod container;
+pub mod cpu_vulnerability;
+pub mod file_descriptor;
+pub mod filesystem;
+pub mod kmsg;
+pub mod neighbor;
+pub mod nftables;
+pub mod oom_kill;
+pub mod package_update;
+pub mod reboot_required;
+pub mod reset;
+pub mod snmp;
+pub mod ssh;
+pub mod temperature;
 pub mod throttled;
+pub mod vl805;
+pub mod wireguard;
+
+// Held while a counter is mirrored, so that concurrent collections of the same collector, e.g. of different collect[]
+// selections, can't both add the same delta
+static COUNTERS: Mutex<()> = Mutex::new(());
+
+/// Mirrors a monotonic value maintained elsewhere (e.g. by the kernel) into a counter series.
+///
+/// The series is recreated when the source has been reset, since a counter can't go backwards.
+pub(crate) fn set_counter<S, N, A>(family: &Family<S, Counter<N, A>>, labels: &S, value: N)
+where
+    S: Clone + Hash + Eq,
+    N: PartialOrd + Sub<Output = N> + Copy,
+    A: Atomic<N> + Default,
+{
+    let _lock = COUNTERS.lock().expect("failed to lock counters mutex");
+    if family.get(labels).is_some_and(|metric| metric.get() > value) {
+        family.remove(labels);
+    }
+
+    let metric = family.get_or_create(labels);
+    metric.inc_by(value - metric.get());
+}
@@ -0,0 +1,130 @@
+use std::sync::{atomic::AtomicU64, Arc, Mutex};
+
+use prometheus_client::{
+    metrics::{family::Family, gauge::Gauge},
+    registry::{Registry, Unit},
+};
+
+use crate::{
+    metrics::{access_point::{AccessPointLabels, AccessPointStationLabels}, Registerer},
+    parser::access_point::AccessPointState,
+};
+
+#[derive(Clone, Debug)]
+pub struct AccessPointRegisterer {
+    stations: Family<AccessPointLabels, Gauge>,
+    signal: Family<AccessPointStationLabels, Gauge>,
+    transmit_bitrate: Family<AccessPointStationLabels, Gauge<f64, AtomicU64>>,
+    interface: String,
+    // The families are shared with the other interfaces, so only the series of this interface can be dropped
+    previous_stations: Arc<Mutex<Vec<AccessPointStationLabels>>>,
+}
+
+impl AccessPointRegisterer {
+    pub fn new(registry: &mut Registry) -> Self {
+        let stations = Family::<AccessPointLabels, Gauge>::default();
+        let signal = Family::<AccessPointStationLabels, Gauge>::default();
+        let transmit_bitrate = Family::<AccessPointStationLabels, Gauge<f64, AtomicU64>>::default();
+        registry.register(
+            "wifi_stations",
+            "Number of stations connected to the access point",
+            stations.clone(),
+        );
+        registry.register_with_unit(
+            "wifi_station_signal",
+            "Signal strength of the last frame received from the station",
+            Unit::Other("dbm".to_string()),
+            signal.clone(),
+        );
+        registry.register_with_unit(
+            "wifi_station_transmit_bitrate",
+            "Bitrate of the last frame transmitted to the station",
+            Unit::Other("bits_per_second".to_string()),
+            transmit_bitrate.clone(),
+        );
+
+        Self {
+            stations,
+            signal,
+            transmit_bitrate,
+            interface: String::new(),
+            previous_stations: Arc::default(),
+        }
+    }
+
+    /// Returns a registerer sharing the same families that labels the series with `interface`.
+    pub fn with_interface(&self, interface: impl Into<String>) -> Self {
+        Self {
+            interface: interface.into(),
+            previous_stations: Arc::default(),
+            ..self.clone()
+        }
+    }
+}
+
+impl Registerer for AccessPointRegisterer {
+    type Item = AccessPointState;
+
+    async fn update(&self, state: Self::Item) -> anyhow::Result<()> {
+        self.stations.get_or_create(&AccessPointLabels { interface: self.interface.clone() }).set(state.stations.len().try_into()?);
+
+        let mut previous_stations = self.previous_stations.lock().expect("failed to lock stations mutex");
+        // Drops series of stations disconnected since the last collection
+        for labels in previous_stations.drain(..) {
+            self.signal.remove(&labels);
+            self.transmit_bitrate.remove(&labels);
+        }
+
+        for station in state.stations {
+            let labels = AccessPointStationLabels {
+                interface: self.interface.clone(),
+                station: station.address,
+            };
+            if let Some(signal_dbm) = station.signal_dbm {
+                self.signal.get_or_create(&labels).set(signal_dbm);
+            }
+            if let Some(transmit_bitrate) = station.transmit_bitrate {
+                self.transmit_bitrate.get_or_create(&labels).set(transmit_bitrate);
+            }
+            previous_stations.push(labels);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use prometheus_client::{encoding::text, registry::Registry};
+
+    use crate::{
+        metrics::Registerer,
+        parser::access_point::{AccessPointState, AccessPointStation},
+        registerer::access_point::AccessPointRegisterer,
+    };
+
+    #[tokio::test]
+    async fn register_drops_disconnected_stations() {
+        let mut registry = Registry::with_prefix("raspi");
+        let registerer = AccessPointRegisterer::new(&mut registry);
+        let wlan0 = registerer.with_interface("wlan0");
+        let wlan1 = registerer.with_interface("wlan1");
+        let station = |address: &str| AccessPointStation {
+            address: address.to_string(),
+            signal_dbm: Some(-50),
+            transmit_bitrate: None,
+        };
+
+        wlan0.update(AccessPointState { stations: vec![station("aa"), station("bb")] }).await.unwrap();
+        wlan1.update(AccessPointState { stations: vec![station("cc")] }).await.unwrap();
+        wlan0.update(AccessPointState { stations: vec![station("bb")] }).await.unwrap();
+
+        let mut buffer = String::new();
+        text::encode(&mut buffer, &registry).unwrap();
+
+        assert!(!buffer.contains(r#"station="aa""#));
+        assert!(buffer.contains(r#"raspi_wifi_station_signal_dbm{interface="wlan0",station="bb"} -50"#));
+        assert!(buffer.contains(r#"raspi_wifi_station_signal_dbm{interface="wlan1",station="cc"} -50"#));
+        assert!(buffer.contains(r#"raspi_wifi_stations{interface="wlan0"} 1"#));
+    }
+}
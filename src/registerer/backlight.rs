@@ -0,0 +1,55 @@
+use prometheus_client::{
+    metrics::{family::Family, gauge::Gauge},
+    registry::Registry,
+};
+
+use crate::{
+    metrics::{backlight::BacklightLabels, Registerer},
+    parser::backlight::BacklightState,
+};
+
+#[derive(Debug)]
+pub struct BacklightRegisterer {
+    brightness: Family<BacklightLabels, Gauge>,
+    max_brightness: Family<BacklightLabels, Gauge>,
+}
+
+impl BacklightRegisterer {
+    pub fn new(registry: &mut Registry) -> Self {
+        let brightness = Family::<BacklightLabels, Gauge>::default();
+        let max_brightness = Family::<BacklightLabels, Gauge>::default();
+        registry.register(
+            "backlight_brightness",
+            "Brightness level of the backlight",
+            brightness.clone(),
+        );
+        registry.register(
+            "backlight_max_brightness",
+            "Maximum brightness level of the backlight",
+            max_brightness.clone(),
+        );
+
+        Self {
+            brightness,
+            max_brightness,
+        }
+    }
+}
+
+impl Registerer for BacklightRegisterer {
+    type Item = BacklightState;
+
+    async fn update(&self, state: Self::Item) -> anyhow::Result<()> {
+        // Drops series of displays disconnected since the last collection
+        self.brightness.clear();
+        self.max_brightness.clear();
+
+        for device in state.devices {
+            let labels = BacklightLabels { device: device.device };
+            self.brightness.get_or_create(&labels).set(device.brightness.try_into()?);
+            self.max_brightness.get_or_create(&labels).set(device.max_brightness.try_into()?);
+        }
+
+        Ok(())
+    }
+}
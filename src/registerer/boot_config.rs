@@ -0,0 +1,102 @@
+use std::sync::{Arc, Mutex};
+
+use prometheus_client::{
+    metrics::{family::Family, gauge::Gauge},
+    registry::Registry,
+};
+
+use crate::{
+    metrics::{boot_config::{BootConfigInfoLabels, BootConfigLabels}, Registerer},
+    parser::boot_config::BootConfigState,
+};
+
+#[derive(Clone, Debug)]
+pub struct BootConfigRegisterer {
+    info: Family<BootConfigInfoLabels, Gauge>,
+    changed: Family<BootConfigLabels, Gauge>,
+    file: String,
+    hashes: Arc<Mutex<BootConfigHashes>>,
+}
+
+#[derive(Debug, Default)]
+struct BootConfigHashes {
+    // Taken by the first collection at startup
+    baseline: Option<String>,
+    current: Option<String>,
+}
+
+impl BootConfigRegisterer {
+    pub fn new(registry: &mut Registry) -> Self {
+        // Substitutes Gauge for Info because Info can't change its labels after registration
+        let info = Family::<BootConfigInfoLabels, Gauge>::default();
+        let changed = Family::<BootConfigLabels, Gauge>::default();
+        registry.register(
+            "boot_config_info",
+            "Hash of the boot configuration file",
+            info.clone(),
+        );
+        registry.register(
+            "boot_config_changed_since_boot",
+            "Whether the boot configuration file differs from when the exporter started, assuming it starts at boot",
+            changed.clone(),
+        );
+
+        Self {
+            info,
+            changed,
+            file: String::new(),
+            hashes: Arc::default(),
+        }
+    }
+
+    /// Returns a registerer sharing the same families that labels the series with `file`.
+    pub fn with_file(&self, file: impl Into<String>) -> Self {
+        Self {
+            file: file.into(),
+            hashes: Arc::default(),
+            ..self.clone()
+        }
+    }
+}
+
+impl Registerer for BootConfigRegisterer {
+    type Item = BootConfigState;
+
+    async fn update(&self, state: Self::Item) -> anyhow::Result<()> {
+        let mut hashes = self.hashes.lock().expect("failed to lock hashes mutex");
+
+        if let Some(current) = hashes.current.replace(state.sha256.clone()) {
+            self.info.remove(&BootConfigInfoLabels { file: self.file.clone(), sha256: current });
+        }
+        self.info.get_or_create(&BootConfigInfoLabels { file: self.file.clone(), sha256: state.sha256.clone() }).set(1);
+
+        let baseline = hashes.baseline.get_or_insert(state.sha256.clone());
+        self.changed.get_or_create(&BootConfigLabels { file: self.file.clone() }).set((*baseline != state.sha256).into());
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use prometheus_client::{encoding::text, registry::Registry};
+
+    use crate::{metrics::Registerer, parser::boot_config::BootConfigState, registerer::boot_config::BootConfigRegisterer};
+
+    #[tokio::test]
+    async fn register_changed() {
+        let mut registry = Registry::with_prefix("raspi");
+        let registerer = BootConfigRegisterer::new(&mut registry).with_file("config.txt");
+        let state = |sha256: &str| BootConfigState { sha256: sha256.to_string() };
+
+        registerer.update(state("aaaa")).await.unwrap();
+        registerer.update(state("bbbb")).await.unwrap();
+
+        let mut buffer = String::new();
+        text::encode(&mut buffer, &registry).unwrap();
+
+        assert!(!buffer.contains(r#"sha256="aaaa""#));
+        assert!(buffer.contains(r#"raspi_boot_config_info{file="config.txt",sha256="bbbb"} 1"#));
+        assert!(buffer.contains(r#"raspi_boot_config_changed_since_boot{file="config.txt"} 1"#));
+    }
+}
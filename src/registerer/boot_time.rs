@@ -0,0 +1,54 @@
+use std::sync::atomic::AtomicU64;
+
+use prometheus_client::{
+    metrics::{family::Family, gauge::Gauge},
+    registry::{Registry, Unit},
+};
+
+use crate::{
+    metrics::{boot_time::BootTimePhaseLabels, Registerer},
+    parser::boot_time::BootTimeState,
+};
+
+#[derive(Debug)]
+pub struct BootTimeRegisterer {
+    total: Gauge<f64, AtomicU64>,
+    phases: Family<BootTimePhaseLabels, Gauge<f64, AtomicU64>>,
+}
+
+impl BootTimeRegisterer {
+    pub fn new(registry: &mut Registry) -> Self {
+        let total = Gauge::<f64, AtomicU64>::default();
+        let phases = Family::<BootTimePhaseLabels, Gauge<f64, AtomicU64>>::default();
+        registry.register_with_unit(
+            "boot_duration",
+            "Time the last boot took to finish",
+            Unit::Seconds,
+            total.clone(),
+        );
+        registry.register_with_unit(
+            "boot_phase_duration",
+            "Time the phase of the last boot took",
+            Unit::Seconds,
+            phases.clone(),
+        );
+
+        Self {
+            total,
+            phases,
+        }
+    }
+}
+
+impl Registerer for BootTimeRegisterer {
+    type Item = BootTimeState;
+
+    async fn update(&self, state: Self::Item) -> anyhow::Result<()> {
+        self.total.set(state.total_seconds);
+        for phase in state.phases {
+            self.phases.get_or_create(&BootTimePhaseLabels { phase: phase.phase }).set(phase.seconds);
+        }
+
+        Ok(())
+    }
+}
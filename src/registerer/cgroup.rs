@@ -0,0 +1,141 @@
+use std::sync::atomic::AtomicU64;
+
+use prometheus_client::{
+    metrics::{counter::Counter, family::Family, gauge::Gauge},
+    registry::{Registry, Unit},
+};
+
+use crate::{
+    metrics::{cgroup::CgroupLabels, Registerer},
+    parser::cgroup::CgroupState,
+    registerer::set_counter,
+};
+
+#[derive(Clone, Debug)]
+pub struct CgroupRegisterer {
+    cpu_usage: Family<CgroupLabels, Counter<f64, AtomicU64>>,
+    cpu_user: Family<CgroupLabels, Counter<f64, AtomicU64>>,
+    cpu_system: Family<CgroupLabels, Counter<f64, AtomicU64>>,
+    cpu_periods: Family<CgroupLabels, Counter>,
+    cpu_throttled_periods: Family<CgroupLabels, Counter>,
+    cpu_throttled: Family<CgroupLabels, Counter<f64, AtomicU64>>,
+    memory_usage: Family<CgroupLabels, Gauge>,
+    memory_limit: Family<CgroupLabels, Gauge>,
+    cgroup: String,
+}
+
+impl CgroupRegisterer {
+    pub fn new(registry: &mut Registry) -> Self {
+        let cpu_usage = Family::<CgroupLabels, Counter<f64, AtomicU64>>::default();
+        let cpu_user = Family::<CgroupLabels, Counter<f64, AtomicU64>>::default();
+        let cpu_system = Family::<CgroupLabels, Counter<f64, AtomicU64>>::default();
+        let cpu_periods = Family::<CgroupLabels, Counter>::default();
+        let cpu_throttled_periods = Family::<CgroupLabels, Counter>::default();
+        let cpu_throttled = Family::<CgroupLabels, Counter<f64, AtomicU64>>::default();
+        let memory_usage = Family::<CgroupLabels, Gauge>::default();
+        let memory_limit = Family::<CgroupLabels, Gauge>::default();
+        registry.register_with_unit(
+            "cgroup_cpu_usage",
+            "CPU time consumed by the cgroup",
+            Unit::Seconds,
+            cpu_usage.clone(),
+        );
+        registry.register_with_unit(
+            "cgroup_cpu_user",
+            "CPU time consumed by the cgroup in user mode",
+            Unit::Seconds,
+            cpu_user.clone(),
+        );
+        registry.register_with_unit(
+            "cgroup_cpu_system",
+            "CPU time consumed by the cgroup in kernel mode",
+            Unit::Seconds,
+            cpu_system.clone(),
+        );
+        registry.register(
+            "cgroup_cpu_periods",
+            "Number of enforcement periods of the CPU bandwidth limit of the cgroup",
+            cpu_periods.clone(),
+        );
+        registry.register(
+            "cgroup_cpu_throttled_periods",
+            "Number of enforcement periods the cgroup was throttled in",
+            cpu_throttled_periods.clone(),
+        );
+        registry.register_with_unit(
+            "cgroup_cpu_throttled",
+            "Time the cgroup was throttled for",
+            Unit::Seconds,
+            cpu_throttled.clone(),
+        );
+        registry.register_with_unit(
+            "cgroup_memory_usage",
+            "Memory currently used by the cgroup",
+            Unit::Bytes,
+            memory_usage.clone(),
+        );
+        registry.register_with_unit(
+            "cgroup_memory_limit",
+            "Memory limit of the cgroup",
+            Unit::Bytes,
+            memory_limit.clone(),
+        );
+
+        Self {
+            cpu_usage,
+            cpu_user,
+            cpu_system,
+            cpu_periods,
+            cpu_throttled_periods,
+            cpu_throttled,
+            memory_usage,
+            memory_limit,
+            cgroup: String::new(),
+        }
+    }
+
+    /// Returns a registerer sharing the same families that labels the series with `cgroup`.
+    pub fn with_cgroup(&self, cgroup: impl Into<String>) -> Self {
+        Self {
+            cgroup: cgroup.into(),
+            ..self.clone()
+        }
+    }
+}
+
+impl Registerer for CgroupRegisterer {
+    type Item = CgroupState;
+
+    async fn update(&self, state: Self::Item) -> anyhow::Result<()> {
+        let labels = CgroupLabels { cgroup: self.cgroup.clone() };
+
+        set_counter(&self.cpu_usage, &labels, state.cpu_usage_seconds);
+        set_counter(&self.cpu_user, &labels, state.cpu_user_seconds);
+        set_counter(&self.cpu_system, &labels, state.cpu_system_seconds);
+        match state.cpu_throttling {
+            Some(throttling) => {
+                set_counter(&self.cpu_periods, &labels, throttling.periods);
+                set_counter(&self.cpu_throttled_periods, &labels, throttling.throttled_periods);
+                set_counter(&self.cpu_throttled, &labels, throttling.throttled_seconds);
+            },
+            None => {
+                self.cpu_periods.remove(&labels);
+                self.cpu_throttled_periods.remove(&labels);
+                self.cpu_throttled.remove(&labels);
+            },
+        }
+
+        self.memory_usage.get_or_create(&labels).set(state.memory_usage_bytes.try_into()?);
+        match state.memory_limit_bytes {
+            Some(limit) => {
+                self.memory_limit.get_or_create(&labels).set(limit.try_into()?);
+            },
+            // Unlimited
+            None => {
+                self.memory_limit.remove(&labels);
+            },
+        }
+
+        Ok(())
+    }
+}
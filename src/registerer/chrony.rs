@@ -0,0 +1,57 @@
+use std::sync::atomic::AtomicU64;
+
+use prometheus_client::{
+    metrics::gauge::Gauge,
+    registry::{Registry, Unit},
+};
+
+use crate::{metrics::Registerer, parser::chrony::ChronyState};
+
+#[derive(Debug)]
+pub struct ChronyRegisterer {
+    offset: Gauge<f64, AtomicU64>,
+    stratum: Gauge,
+    synchronised: Gauge,
+}
+
+impl ChronyRegisterer {
+    pub fn new(registry: &mut Registry) -> Self {
+        let offset = Gauge::<f64, AtomicU64>::default();
+        let stratum = Gauge::default();
+        let synchronised = Gauge::default();
+        registry.register_with_unit(
+            "time_offset",
+            "Offset of the system clock from NTP time reported by chrony",
+            Unit::Seconds,
+            offset.clone(),
+        );
+        registry.register(
+            "time_stratum",
+            "NTP stratum of the system clock",
+            stratum.clone(),
+        );
+        registry.register(
+            "time_synchronised",
+            "Whether the system clock is synchronised to an NTP source",
+            synchronised.clone(),
+        );
+
+        Self {
+            offset,
+            stratum,
+            synchronised,
+        }
+    }
+}
+
+impl Registerer for ChronyRegisterer {
+    type Item = ChronyState;
+
+    async fn update(&self, state: Self::Item) -> anyhow::Result<()> {
+        self.offset.set(state.offset_seconds);
+        self.stratum.set(state.stratum.into());
+        self.synchronised.set(state.synchronised.into());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,65 @@
+use std::sync::atomic::AtomicU64;
+
+use prometheus_client::{
+    metrics::{family::Family, gauge::Gauge},
+    registry::{Registry, Unit},
+};
+
+use crate::{metrics::{clock_tree::ClockLabels, Registerer}, parser::clock_tree::ClockTreeState};
+
+type ClockFamily = Family<ClockLabels, Gauge<u64, AtomicU64>>;
+
+#[derive(Debug)]
+pub struct ClockTreeRegisterer {
+    enable_count: ClockFamily,
+    prepare_count: ClockFamily,
+    rate: ClockFamily,
+}
+
+impl ClockTreeRegisterer {
+    pub fn new(registry: &mut Registry) -> Self {
+        let enable_count = ClockFamily::default();
+        let prepare_count = ClockFamily::default();
+        let rate = ClockFamily::default();
+        registry.register(
+            "clock_enable_count",
+            "Number of consumers that enabled the clock",
+            enable_count.clone(),
+        );
+        registry.register(
+            "clock_prepare_count",
+            "Number of consumers that prepared the clock",
+            prepare_count.clone(),
+        );
+        registry.register_with_unit(
+            "clock_rate",
+            "Rate of the clock",
+            Unit::Other("hertz".to_string()),
+            rate.clone(),
+        );
+
+        Self {
+            enable_count,
+            prepare_count,
+            rate,
+        }
+    }
+}
+
+impl Registerer for ClockTreeRegisterer {
+    type Item = ClockTreeState;
+
+    async fn update(&self, state: Self::Item) -> anyhow::Result<()> {
+        for clock in state.clocks {
+            let labels = ClockLabels {
+                clock: clock.name,
+            };
+
+            self.enable_count.get_or_create(&labels).set(clock.enable_count);
+            self.prepare_count.get_or_create(&labels).set(clock.prepare_count);
+            self.rate.get_or_create(&labels).set(clock.rate);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,85 @@
+use std::sync::atomic::AtomicU64;
+
+use prometheus_client::{
+    metrics::{counter::Counter, family::Family, gauge::Gauge},
+    registry::{Registry, Unit},
+};
+
+use crate::{
+    metrics::{container::{ContainerLabels, ContainerStateLabels}, Registerer},
+    parser::container::ContainerState,
+    registerer::set_counter,
+};
+
+#[derive(Debug)]
+pub struct ContainerRegisterer {
+    containers: Family<ContainerStateLabels, Gauge>,
+    restarts: Family<ContainerLabels, Counter>,
+    cpu_usage: Family<ContainerLabels, Counter<f64, AtomicU64>>,
+    memory_usage: Family<ContainerLabels, Gauge>,
+}
+
+impl ContainerRegisterer {
+    pub fn new(registry: &mut Registry) -> Self {
+        let containers = Family::<ContainerStateLabels, Gauge>::default();
+        let restarts = Family::<ContainerLabels, Counter>::default();
+        let cpu_usage = Family::<ContainerLabels, Counter<f64, AtomicU64>>::default();
+        let memory_usage = Family::<ContainerLabels, Gauge>::default();
+        registry.register(
+            "containers",
+            "Number of containers",
+            containers.clone(),
+        );
+        registry.register(
+            "container_restarts",
+            "Number of times the container was restarted by its restart policy",
+            restarts.clone(),
+        );
+        registry.register_with_unit(
+            "container_cpu_usage",
+            "CPU time consumed by the container",
+            Unit::Seconds,
+            cpu_usage.clone(),
+        );
+        registry.register_with_unit(
+            "container_memory_usage",
+            "Memory used by the container excluding the inactive page cache",
+            Unit::Bytes,
+            memory_usage.clone(),
+        );
+
+        Self {
+            containers,
+            restarts,
+            cpu_usage,
+            memory_usage,
+        }
+    }
+}
+
+impl Registerer for ContainerRegisterer {
+    type Item = ContainerState;
+
+    async fn update(&self, state: Self::Item) -> anyhow::Result<()> {
+        // Drops series of containers removed since the last collection
+        self.containers.clear();
+        self.restarts.clear();
+        self.cpu_usage.clear();
+        self.memory_usage.clear();
+
+        for container in state.containers {
+            self.containers.get_or_create(&ContainerStateLabels { state: container.state }).inc();
+
+            let labels = ContainerLabels { name: container.name };
+            set_counter(&self.restarts, &labels, container.restarts);
+            if let Some(cpu_seconds) = container.cpu_seconds {
+                set_counter(&self.cpu_usage, &labels, cpu_seconds);
+            }
+            if let Some(memory_bytes) = container.memory_bytes {
+                self.memory_usage.get_or_create(&labels).set(memory_bytes.try_into()?);
+            }
+        }
+
+        Ok(())
+    }
+}
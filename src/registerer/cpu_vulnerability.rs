@@ -0,0 +1,51 @@
+use prometheus_client::{
+    metrics::{family::Family, gauge::Gauge},
+    registry::Registry,
+};
+
+use crate::{
+    metrics::{cpu_vulnerability::CpuVulnerabilityLabels, Registerer},
+    parser::cpu_vulnerability::CpuVulnerabilityState,
+};
+
+#[derive(Debug)]
+pub struct CpuVulnerabilityRegisterer {
+    info: Family<CpuVulnerabilityLabels, Gauge>,
+}
+
+impl CpuVulnerabilityRegisterer {
+    pub fn new(registry: &mut Registry) -> Self {
+        // Substitutes Gauge for Info because Info can't change its labels after registration
+        let info = Family::<CpuVulnerabilityLabels, Gauge>::default();
+        registry.register(
+            "cpu_vulnerability_info",
+            "Mitigation state of the CPU vulnerability reported by the kernel",
+            info.clone(),
+        );
+
+        Self {
+            info,
+        }
+    }
+}
+
+impl Registerer for CpuVulnerabilityRegisterer {
+    type Item = CpuVulnerabilityState;
+
+    async fn update(&self, state: Self::Item) -> anyhow::Result<()> {
+        // Drops series of a previous status
+        self.info.clear();
+
+        for vulnerability in state.vulnerabilities {
+            self.info
+                .get_or_create(&CpuVulnerabilityLabels {
+                    vulnerability: vulnerability.vulnerability,
+                    status: vulnerability.status,
+                    description: vulnerability.description,
+                })
+                .set(1);
+        }
+
+        Ok(())
+    }
+}
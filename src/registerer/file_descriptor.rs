@@ -0,0 +1,77 @@
+use std::sync::atomic::AtomicU64;
+
+use prometheus_client::{metrics::gauge::Gauge, registry::Registry};
+
+use crate::{
+    metrics::Registerer,
+    parser::file_descriptor::{FileDescriptorState, ProcessFileDescriptorState},
+};
+
+#[derive(Debug)]
+pub struct FileDescriptorRegisterer {
+    allocated: Gauge<u64, AtomicU64>,
+    maximum: Gauge<u64, AtomicU64>,
+}
+
+#[derive(Debug)]
+pub struct ProcessFileDescriptorRegisterer {
+    open: Gauge<u64, AtomicU64>,
+}
+
+impl FileDescriptorRegisterer {
+    pub fn new(registry: &mut Registry) -> Self {
+        let allocated = Gauge::<u64, AtomicU64>::default();
+        let maximum = Gauge::<u64, AtomicU64>::default();
+        registry.register(
+            "file_descriptors_allocated",
+            "Number of file descriptors allocated by the system",
+            allocated.clone(),
+        );
+        registry.register(
+            "file_descriptors_maximum",
+            "Maximum number of file descriptors the system allows",
+            maximum.clone(),
+        );
+
+        Self {
+            allocated,
+            maximum,
+        }
+    }
+}
+
+impl ProcessFileDescriptorRegisterer {
+    pub fn new(registry: &mut Registry) -> Self {
+        let open = Gauge::<u64, AtomicU64>::default();
+        registry.register(
+            "exporter_file_descriptors_open",
+            "Number of file descriptors opened by the exporter itself",
+            open.clone(),
+        );
+
+        Self {
+            open,
+        }
+    }
+}
+
+impl Registerer for FileDescriptorRegisterer {
+    type Item = FileDescriptorState;
+
+    async fn update(&self, state: Self::Item) -> anyhow::Result<()> {
+        self.allocated.set(state.allocated);
+        self.maximum.set(state.maximum);
+
+        Ok(())
+    }
+}
+
+impl Registerer for ProcessFileDescriptorRegisterer {
+    type Item = ProcessFileDescriptorState;
+
+    async fn update(&self, state: Self::Item) -> anyhow::Result<()> {
+        self.open.set(state.open);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,86 @@
+use std::sync::atomic::AtomicU64;
+
+use prometheus_client::{
+    metrics::{family::Family, gauge::Gauge},
+    registry::{Registry, Unit},
+};
+
+use crate::{metrics::{filesystem::FilesystemLabels, Registerer}, parser::filesystem::FilesystemState};
+
+type FilesystemFamily = Family<FilesystemLabels, Gauge<u64, AtomicU64>>;
+
+#[derive(Debug)]
+pub struct FilesystemRegisterer {
+    size: FilesystemFamily,
+    avail: FilesystemFamily,
+    inodes_total: FilesystemFamily,
+    inodes_free: FilesystemFamily,
+}
+
+impl FilesystemRegisterer {
+    pub fn new(registry: &mut Registry) -> Self {
+        let size = FilesystemFamily::default();
+        let avail = FilesystemFamily::default();
+        let inodes_total = FilesystemFamily::default();
+        let inodes_free = FilesystemFamily::default();
+        registry.register_with_unit(
+            "filesystem_size",
+            "Size of the filesystem",
+            Unit::Bytes,
+            size.clone(),
+        );
+        registry.register_with_unit(
+            "filesystem_avail",
+            "Space available to unprivileged users on the filesystem",
+            Unit::Bytes,
+            avail.clone(),
+        );
+        registry.register(
+            "filesystem_inodes_total",
+            "Total number of inodes on the filesystem",
+            inodes_total.clone(),
+        );
+        registry.register(
+            "filesystem_inodes_free",
+            "Number of free inodes on the filesystem",
+            inodes_free.clone(),
+        );
+
+        Self {
+            size,
+            avail,
+            inodes_total,
+            inodes_free,
+        }
+    }
+}
+
+impl Registerer for FilesystemRegisterer {
+    type Item = FilesystemState;
+
+    async fn update(&self, state: Self::Item) -> anyhow::Result<()> {
+        // Drops series of filesystems unmounted since the last collection
+        self.size.clear();
+        self.avail.clear();
+        self.inodes_total.clear();
+        self.inodes_free.clear();
+
+        for filesystem in state.filesystems {
+            let labels = FilesystemLabels {
+                mountpoint: filesystem.mountpoint,
+                fstype: filesystem.fstype,
+            };
+
+            self.size.get_or_create(&labels).set(filesystem.size_bytes);
+            self.avail.get_or_create(&labels).set(filesystem.avail_bytes);
+            if let Some(inodes_total) = filesystem.inodes_total {
+                self.inodes_total.get_or_create(&labels).set(inodes_total);
+            }
+            if let Some(inodes_free) = filesystem.inodes_free {
+                self.inodes_free.get_or_create(&labels).set(inodes_free);
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,44 @@
+use prometheus_client::{
+    metrics::{counter::Counter, family::Family},
+    registry::Registry,
+};
+
+use crate::{
+    metrics::{kmsg::KmsgLabels, Registerer},
+    parser::kmsg::KmsgState,
+};
+
+#[derive(Debug)]
+pub struct KmsgRegisterer {
+    messages: Family<KmsgLabels, Counter>,
+}
+
+impl KmsgRegisterer {
+    pub fn new(registry: &mut Registry) -> Self {
+        let messages = Family::<KmsgLabels, Counter>::default();
+        registry.register(
+            "kernel_messages",
+            "Number of kernel messages at warning level or more severe",
+            messages.clone(),
+        );
+
+        Self {
+            messages,
+        }
+    }
+}
+
+impl Registerer for KmsgRegisterer {
+    type Item = KmsgState;
+
+    async fn update(&self, state: Self::Item) -> anyhow::Result<()> {
+        self.messages
+            .get_or_create(&KmsgLabels {
+                severity: state.severity,
+                subsystem: state.subsystem,
+            })
+            .inc();
+
+        Ok(())
+    }
+}
@@ -0,0 +1,77 @@
+use std::sync::atomic::AtomicU64;
+
+use prometheus_client::{
+    metrics::{family::Family, gauge::Gauge},
+    registry::Registry,
+};
+
+use crate::{
+    metrics::{neighbor::{NeighborLabels, NeighborThresholdLabels}, Registerer},
+    parser::neighbor::{NeighborState, NeighborThresholdState},
+};
+
+#[derive(Debug)]
+pub struct NeighborRegisterer {
+    entries: Family<NeighborLabels, Gauge<u64, AtomicU64>>,
+}
+
+#[derive(Debug)]
+pub struct NeighborThresholdRegisterer {
+    thresholds: Family<NeighborThresholdLabels, Gauge<u64, AtomicU64>>,
+}
+
+impl NeighborRegisterer {
+    pub fn new(registry: &mut Registry) -> Self {
+        let entries = Family::<NeighborLabels, Gauge<u64, AtomicU64>>::default();
+        registry.register(
+            "neighbor_entries",
+            "Number of entries in the IPv4 neighbor table",
+            entries.clone(),
+        );
+
+        Self {
+            entries,
+        }
+    }
+}
+
+impl NeighborThresholdRegisterer {
+    pub fn new(registry: &mut Registry) -> Self {
+        let thresholds = Family::<NeighborThresholdLabels, Gauge<u64, AtomicU64>>::default();
+        registry.register(
+            "neighbor_gc_threshold",
+            "Garbage collection thresholds of the IPv4 neighbor table",
+            thresholds.clone(),
+        );
+
+        Self {
+            thresholds,
+        }
+    }
+}
+
+impl Registerer for NeighborRegisterer {
+    type Item = NeighborState;
+
+    async fn update(&self, state: Self::Item) -> anyhow::Result<()> {
+        // Drops series of devices whose entries have all expired
+        self.entries.clear();
+        for (device, entries) in state.entries {
+            self.entries.get_or_create(&NeighborLabels { device }).set(entries);
+        }
+
+        Ok(())
+    }
+}
+
+impl Registerer for NeighborThresholdRegisterer {
+    type Item = NeighborThresholdState;
+
+    async fn update(&self, state: Self::Item) -> anyhow::Result<()> {
+        for (threshold, value) in state.thresholds {
+            self.thresholds.get_or_create(&NeighborThresholdLabels { threshold }).set(value);
+        }
+
+        Ok(())
+    }
+}
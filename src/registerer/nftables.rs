@@ -0,0 +1,89 @@
+use prometheus_client::{
+    metrics::{counter::Counter, family::Family},
+    registry::{Registry, Unit},
+};
+
+use crate::{
+    metrics::{nftables::{NftablesChainLabels, NftablesCounterLabels}, Registerer},
+    parser::nftables::NftablesState,
+    registerer::set_counter,
+};
+
+#[derive(Debug)]
+pub struct NftablesRegisterer {
+    counter_packets: Family<NftablesCounterLabels, Counter>,
+    counter_bytes: Family<NftablesCounterLabels, Counter>,
+    chain_packets: Family<NftablesChainLabels, Counter>,
+    chain_bytes: Family<NftablesChainLabels, Counter>,
+}
+
+impl NftablesRegisterer {
+    pub fn new(registry: &mut Registry) -> Self {
+        let counter_packets = Family::<NftablesCounterLabels, Counter>::default();
+        let counter_bytes = Family::<NftablesCounterLabels, Counter>::default();
+        let chain_packets = Family::<NftablesChainLabels, Counter>::default();
+        let chain_bytes = Family::<NftablesChainLabels, Counter>::default();
+        registry.register(
+            "nftables_counter_packets",
+            "Packets counted by the named nftables counter",
+            counter_packets.clone(),
+        );
+        registry.register_with_unit(
+            "nftables_counter",
+            "Data counted by the named nftables counter",
+            Unit::Bytes,
+            counter_bytes.clone(),
+        );
+        registry.register(
+            "nftables_chain_packets",
+            "Packets counted by the rule counters in the nftables chain",
+            chain_packets.clone(),
+        );
+        registry.register_with_unit(
+            "nftables_chain",
+            "Data counted by the rule counters in the nftables chain",
+            Unit::Bytes,
+            chain_bytes.clone(),
+        );
+
+        Self {
+            counter_packets,
+            counter_bytes,
+            chain_packets,
+            chain_bytes,
+        }
+    }
+}
+
+impl Registerer for NftablesRegisterer {
+    type Item = NftablesState;
+
+    async fn update(&self, state: Self::Item) -> anyhow::Result<()> {
+        // Drops series of counters and chains removed by reloading the ruleset
+        self.counter_packets.clear();
+        self.counter_bytes.clear();
+        self.chain_packets.clear();
+        self.chain_bytes.clear();
+
+        for counter in state.counters {
+            let labels = NftablesCounterLabels {
+                family: counter.family,
+                table: counter.table,
+                name: counter.name,
+            };
+            set_counter(&self.counter_packets, &labels, counter.packets);
+            set_counter(&self.counter_bytes, &labels, counter.bytes);
+        }
+        for chain in state.chains {
+            let labels = NftablesChainLabels {
+                family: chain.family,
+                table: chain.table,
+                chain: chain.chain,
+            };
+            set_counter(&self.chain_packets, &labels, chain.packets);
+            set_counter(&self.chain_bytes, &labels, chain.bytes);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,50 @@
+use prometheus_client::registry::Registry;
+
+use crate::{
+    metrics::{oom_kill::{OomKillLabels, OomKills}, Registerer},
+    parser::oom_kill::OomKillState,
+    registerer::set_counter,
+};
+
+#[derive(Clone, Debug)]
+pub struct OomKillRegisterer {
+    oom_kills: OomKills,
+    cgroup: Option<String>,
+}
+
+impl OomKillRegisterer {
+    pub fn new(registry: &mut Registry) -> Self {
+        let oom_kills = OomKills::default();
+        registry.register(
+            "oom_kills",
+            "Number of processes killed by the OOM killer",
+            oom_kills.clone(),
+        );
+
+        Self {
+            oom_kills,
+            cgroup: None,
+        }
+    }
+
+    /// Returns a registerer sharing the same family that labels the series with `cgroup`.
+    pub fn with_cgroup(&self, cgroup: impl Into<String>) -> Self {
+        Self {
+            oom_kills: self.oom_kills.clone(),
+            cgroup: Some(cgroup.into()),
+        }
+    }
+}
+
+impl Registerer for OomKillRegisterer {
+    type Item = OomKillState;
+
+    async fn update(&self, state: Self::Item) -> anyhow::Result<()> {
+        match &self.cgroup {
+            Some(cgroup) => set_counter(&self.oom_kills.cgroups, &OomKillLabels { cgroup: cgroup.clone() }, state.oom_kills),
+            None => set_counter(&self.oom_kills.total, &(), state.oom_kills),
+        }
+
+        Ok(())
+    }
+}
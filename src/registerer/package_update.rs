@@ -0,0 +1,44 @@
+use std::sync::atomic::AtomicU64;
+
+use prometheus_client::{metrics::gauge::Gauge, registry::Registry};
+
+use crate::{metrics::Registerer, parser::package_update::PackageUpdateState};
+
+#[derive(Debug)]
+pub struct PackageUpdateRegisterer {
+    pending: Gauge<u64, AtomicU64>,
+    security: Gauge<u64, AtomicU64>,
+}
+
+impl PackageUpdateRegisterer {
+    pub fn new(registry: &mut Registry) -> Self {
+        let pending = Gauge::<u64, AtomicU64>::default();
+        let security = Gauge::<u64, AtomicU64>::default();
+        registry.register(
+            "package_updates_pending",
+            "Number of packages with an upgrade available",
+            pending.clone(),
+        );
+        registry.register(
+            "package_security_updates_pending",
+            "Number of packages with an upgrade available from a security archive",
+            security.clone(),
+        );
+
+        Self {
+            pending,
+            security,
+        }
+    }
+}
+
+impl Registerer for PackageUpdateRegisterer {
+    type Item = PackageUpdateState;
+
+    async fn update(&self, state: Self::Item) -> anyhow::Result<()> {
+        self.pending.set(state.pending);
+        self.security.set(state.security);
+
+        Ok(())
+    }
+}
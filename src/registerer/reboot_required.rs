@@ -0,0 +1,49 @@
+use prometheus_client::{
+    metrics::{family::Family, gauge::Gauge},
+    registry::Registry,
+};
+
+use crate::{
+    metrics::{reboot_required::{RebootRequiredLabels, RebootRequiredReason}, Registerer},
+    parser::reboot_required::RebootRequiredState,
+};
+
+#[derive(Clone, Debug)]
+pub struct RebootRequiredRegisterer {
+    reboot_required: Family<RebootRequiredLabels, Gauge>,
+    reason: RebootRequiredReason,
+}
+
+impl RebootRequiredRegisterer {
+    pub fn new(registry: &mut Registry) -> Self {
+        let reboot_required = Family::<RebootRequiredLabels, Gauge>::default();
+        registry.register(
+            "reboot_required",
+            "Whether a reboot is required to apply installed updates",
+            reboot_required.clone(),
+        );
+
+        Self {
+            reboot_required,
+            reason: RebootRequiredReason::FlagFile,
+        }
+    }
+
+    /// Returns a registerer sharing the same family that labels the series with `reason`.
+    pub fn with_reason(&self, reason: RebootRequiredReason) -> Self {
+        Self {
+            reboot_required: self.reboot_required.clone(),
+            reason,
+        }
+    }
+}
+
+impl Registerer for RebootRequiredRegisterer {
+    type Item = RebootRequiredState;
+
+    async fn update(&self, state: Self::Item) -> anyhow::Result<()> {
+        self.reboot_required.get_or_create(&RebootRequiredLabels { reason: self.reason.clone() }).set(state.required.into());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,50 @@
+use prometheus_client::{
+    metrics::{family::Family, gauge::Gauge},
+    registry::Registry,
+};
+
+use crate::{metrics::{reset::{ResetCause, ResetCauseLabels}, Registerer}, parser::reset::ResetState};
+
+#[derive(Debug)]
+pub struct ResetRegisterer {
+    register: Gauge,
+    cause: Family<ResetCauseLabels, Gauge>,
+}
+
+impl ResetRegisterer {
+    pub fn new(registry: &mut Registry) -> Self {
+        let register = Gauge::default();
+        // Substitutes Gauge for StateSet of OpenMetrics because prometheus_client doens't implement it
+        let cause = Family::<ResetCauseLabels, Gauge>::default();
+        registry.register(
+            "reset_status_register",
+            "Raw value of the reset status register",
+            register.clone(),
+        );
+        registry.register(
+            "reset_cause",
+            "Cause of the last reset",
+            cause.clone(),
+        );
+
+        Self {
+            register,
+            cause,
+        }
+    }
+}
+
+impl Registerer for ResetRegisterer {
+    type Item = ResetState;
+
+    async fn update(&self, state: Self::Item) -> anyhow::Result<()> {
+        self.register.set(state.register.into());
+
+        self.cause.get_or_create(&ResetCauseLabels { cause: ResetCause::PowerOn }).set(state.power_on.into());
+        self.cause.get_or_create(&ResetCauseLabels { cause: ResetCause::Watchdog }).set(state.watchdog.into());
+        self.cause.get_or_create(&ResetCauseLabels { cause: ResetCause::Software }).set(state.software.into());
+        self.cause.get_or_create(&ResetCauseLabels { cause: ResetCause::Debugger }).set(state.debugger.into());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,101 @@
+use prometheus_client::{
+    metrics::{counter::Counter, family::Family, gauge::Gauge},
+    registry::Registry,
+};
+
+use crate::{
+    metrics::{snmp::SnmpLabels, Registerer},
+    parser::snmp::SnmpState,
+    registerer::set_counter,
+};
+
+#[derive(Debug)]
+pub struct SnmpRegisterer {
+    received: Family<SnmpLabels, Counter>,
+    sent: Family<SnmpLabels, Counter>,
+    receive_errors: Family<SnmpLabels, Counter>,
+    checksum_errors: Family<SnmpLabels, Counter>,
+    tcp_retransmitted_segments: Counter,
+    tcp_connections_established: Gauge,
+    udp_no_port_datagrams: Counter,
+}
+
+impl SnmpRegisterer {
+    pub fn new(registry: &mut Registry) -> Self {
+        let received = Family::<SnmpLabels, Counter>::default();
+        let sent = Family::<SnmpLabels, Counter>::default();
+        let receive_errors = Family::<SnmpLabels, Counter>::default();
+        let checksum_errors = Family::<SnmpLabels, Counter>::default();
+        let tcp_retransmitted_segments = Counter::default();
+        let tcp_connections_established = Gauge::default();
+        let udp_no_port_datagrams = Counter::default();
+        registry.register(
+            "network_protocol_received",
+            "Number of packets, messages, segments or datagrams received with the protocol",
+            received.clone(),
+        );
+        registry.register(
+            "network_protocol_sent",
+            "Number of packets, messages, segments or datagrams sent with the protocol",
+            sent.clone(),
+        );
+        registry.register(
+            "network_protocol_receive_errors",
+            "Number of packets, messages, segments or datagrams received with errors with the protocol",
+            receive_errors.clone(),
+        );
+        registry.register(
+            "network_protocol_checksum_errors",
+            "Number of packets, messages, segments or datagrams received with checksum errors with the protocol",
+            checksum_errors.clone(),
+        );
+        registry.register(
+            "tcp_retransmitted_segments",
+            "Number of TCP segments retransmitted",
+            tcp_retransmitted_segments.clone(),
+        );
+        registry.register(
+            "tcp_connections_established",
+            "Number of TCP connections currently established or closing by the peer",
+            tcp_connections_established.clone(),
+        );
+        registry.register(
+            "udp_no_port_datagrams",
+            "Number of UDP datagrams received for a port nothing listens on",
+            udp_no_port_datagrams.clone(),
+        );
+
+        Self {
+            received,
+            sent,
+            receive_errors,
+            checksum_errors,
+            tcp_retransmitted_segments,
+            tcp_connections_established,
+            udp_no_port_datagrams,
+        }
+    }
+}
+
+impl Registerer for SnmpRegisterer {
+    type Item = SnmpState;
+
+    async fn update(&self, state: Self::Item) -> anyhow::Result<()> {
+        for protocol in state.protocols {
+            let labels = SnmpLabels { protocol: protocol.protocol };
+            set_counter(&self.received, &labels, protocol.received);
+            set_counter(&self.sent, &labels, protocol.sent);
+            set_counter(&self.receive_errors, &labels, protocol.receive_errors);
+            if let Some(checksum_errors) = protocol.checksum_errors {
+                set_counter(&self.checksum_errors, &labels, checksum_errors);
+            }
+        }
+
+        // The kernel counters are only reset by rebooting, which restarts the exporter too
+        self.tcp_retransmitted_segments.inc_by(state.tcp_retransmitted_segments.saturating_sub(self.tcp_retransmitted_segments.get()));
+        self.tcp_connections_established.set(state.tcp_connections_established.try_into()?);
+        self.udp_no_port_datagrams.inc_by(state.udp_no_port_datagrams.saturating_sub(self.udp_no_port_datagrams.get()));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,46 @@
+use prometheus_client::{
+    metrics::{counter::Counter, family::Family},
+    registry::Registry,
+};
+
+use crate::{
+    metrics::{ssh::SshAuthFailureLabels, Registerer},
+    parser::ssh::SshAuthFailureState,
+};
+
+#[derive(Debug)]
+pub struct SshAuthFailureRegisterer {
+    failures: Family<SshAuthFailureLabels, Counter>,
+    by_method: bool,
+}
+
+impl SshAuthFailureRegisterer {
+    pub fn new(registry: &mut Registry, by_method: bool) -> Self {
+        let failures = Family::<SshAuthFailureLabels, Counter>::default();
+        registry.register(
+            "ssh_auth_failures",
+            "Number of failed SSH authentication attempts",
+            failures.clone(),
+        );
+        // Exposes zero before the first failure, when the series is known in advance
+        if !by_method {
+            let _ = failures.get_or_create(&SshAuthFailureLabels { method: None });
+        }
+
+        Self {
+            failures,
+            by_method,
+        }
+    }
+}
+
+impl Registerer for SshAuthFailureRegisterer {
+    type Item = SshAuthFailureState;
+
+    async fn update(&self, state: Self::Item) -> anyhow::Result<()> {
+        let method = self.by_method.then_some(state.method);
+        self.failures.get_or_create(&SshAuthFailureLabels { method }).inc();
+
+        Ok(())
+    }
+}
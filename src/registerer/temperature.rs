@@ -0,0 +1,188 @@
+use std::sync::{atomic::AtomicU64, Arc, Mutex};
+
+use prometheus_client::{
+    metrics::gauge::Gauge,
+    registry::{Registry, Unit},
+};
+
+use crate::{metrics::Registerer, parser::temperature::TemperatureState};
+
+type TemperatureGauge = Gauge<f64, AtomicU64>;
+
+/// Exposes the current temperature on each scrape, along with the extremes sampled since the previous scrape.
+#[derive(Debug)]
+pub struct TemperatureRegisterer {
+    current: TemperatureGauge,
+    max_since_last_scrape: TemperatureGauge,
+    min_since_last_scrape: TemperatureGauge,
+    max: TemperatureGauge,
+    min: TemperatureGauge,
+    window: Arc<Mutex<Extrema>>,
+}
+
+/// Tracks the extremes of the temperature, meant to be fed by a sampler.
+#[derive(Debug)]
+pub struct TemperatureExtremaRegisterer {
+    max: TemperatureGauge,
+    min: TemperatureGauge,
+    window: Arc<Mutex<Extrema>>,
+}
+
+#[derive(Debug, Default)]
+struct Extrema {
+    max: Option<f64>,
+    min: Option<f64>,
+}
+
+impl Extrema {
+    fn observe(&mut self, value: f64) {
+        self.max = Some(self.max.map_or(value, |max| max.max(value)));
+        self.min = Some(self.min.map_or(value, |min| min.min(value)));
+    }
+}
+
+impl TemperatureRegisterer {
+    pub fn new(registry: &mut Registry) -> Self {
+        let current = TemperatureGauge::default();
+        let max_since_last_scrape = TemperatureGauge::default();
+        let min_since_last_scrape = TemperatureGauge::default();
+        let max = TemperatureGauge::default();
+        let min = TemperatureGauge::default();
+        registry.register_with_unit(
+            "soc_temperature",
+            "Temperature of the SoC",
+            Unit::Celsius,
+            current.clone(),
+        );
+        registry.register_with_unit(
+            "soc_temperature_max_since_last_scrape",
+            "Maximum temperature of the SoC sampled since the previous scrape",
+            Unit::Celsius,
+            max_since_last_scrape.clone(),
+        );
+        registry.register_with_unit(
+            "soc_temperature_min_since_last_scrape",
+            "Minimum temperature of the SoC sampled since the previous scrape",
+            Unit::Celsius,
+            min_since_last_scrape.clone(),
+        );
+        registry.register_with_unit(
+            "soc_temperature_max",
+            "Maximum temperature of the SoC sampled since the exporter started",
+            Unit::Celsius,
+            max.clone(),
+        );
+        registry.register_with_unit(
+            "soc_temperature_min",
+            "Minimum temperature of the SoC sampled since the exporter started",
+            Unit::Celsius,
+            min.clone(),
+        );
+        // Nothing has been observed yet, which 0 would misrepresent
+        for gauge in [&current, &max_since_last_scrape, &min_since_last_scrape, &max, &min] {
+            gauge.set(f64::NAN);
+        }
+
+        Self {
+            current,
+            max_since_last_scrape,
+            min_since_last_scrape,
+            max,
+            min,
+            window: Arc::default(),
+        }
+    }
+
+    /// Returns a registerer for a sampler updating the extremes exposed by this registerer.
+    pub fn extrema(&self) -> TemperatureExtremaRegisterer {
+        TemperatureExtremaRegisterer {
+            max: self.max.clone(),
+            min: self.min.clone(),
+            window: self.window.clone(),
+        }
+    }
+}
+
+impl Registerer for TemperatureRegisterer {
+    type Item = TemperatureState;
+
+    async fn update(&self, state: Self::Item) -> anyhow::Result<()> {
+        self.current.set(state.celsius);
+
+        let mut window = self.window.lock().expect("failed to lock temperature window mutex");
+        window.observe(state.celsius);
+        let Extrema { max, min } = std::mem::take(&mut *window);
+        self.max_since_last_scrape.set(max.unwrap_or(f64::NAN));
+        self.min_since_last_scrape.set(min.unwrap_or(f64::NAN));
+
+        Ok(())
+    }
+}
+
+impl Registerer for TemperatureExtremaRegisterer {
+    type Item = TemperatureState;
+
+    async fn update(&self, state: Self::Item) -> anyhow::Result<()> {
+        self.window.lock().expect("failed to lock temperature window mutex").observe(state.celsius);
+
+        if self.max.get().is_nan() || state.celsius > self.max.get() {
+            self.max.set(state.celsius);
+        }
+        if self.min.get().is_nan() || state.celsius < self.min.get() {
+            self.min.set(state.celsius);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use prometheus_client::{encoding::text, registry::Registry};
+
+    use crate::{metrics::Registerer, parser::temperature::TemperatureState, registerer::temperature::TemperatureRegisterer};
+
+    fn encode(registry: &Registry) -> Vec<String> {
+        let mut buffer = String::new();
+        text::encode(&mut buffer, registry).unwrap();
+
+        buffer.lines().filter(|line| !line.starts_with('#')).map(ToString::to_string).collect()
+    }
+
+    #[tokio::test]
+    async fn update() {
+        let mut registry = Registry::with_prefix("raspi");
+        let registerer = TemperatureRegisterer::new(&mut registry);
+        let extrema_registerer = registerer.extrema();
+
+        for celsius in [50.0, 62.5, 45.0] {
+            extrema_registerer.update(TemperatureState { celsius }).await.unwrap();
+        }
+        registerer.update(TemperatureState { celsius: 48.0 }).await.unwrap();
+
+        assert_eq!(
+            encode(&registry),
+            [
+                "raspi_soc_temperature_celsius 48.0",
+                "raspi_soc_temperature_max_since_last_scrape_celsius 62.5",
+                "raspi_soc_temperature_min_since_last_scrape_celsius 45.0",
+                "raspi_soc_temperature_max_celsius 62.5",
+                "raspi_soc_temperature_min_celsius 45.0",
+            ]
+        );
+
+        extrema_registerer.update(TemperatureState { celsius: 55.0 }).await.unwrap();
+        registerer.update(TemperatureState { celsius: 52.0 }).await.unwrap();
+
+        assert_eq!(
+            encode(&registry),
+            [
+                "raspi_soc_temperature_celsius 52.0",
+                "raspi_soc_temperature_max_since_last_scrape_celsius 55.0",
+                "raspi_soc_temperature_min_since_last_scrape_celsius 52.0",
+                "raspi_soc_temperature_max_celsius 62.5",
+                "raspi_soc_temperature_min_celsius 45.0",
+            ]
+        );
+    }
+}
@@ -1,72 +1,449 @@
-use std::sync::{Arc, Mutex};
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::ErrorKind,
+    path::PathBuf,
+    sync::{atomic::AtomicU64, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
 
+use anyhow::Context;
 use prometheus_client::{
-    metrics::{family::Family, gauge::Gauge},
-    registry::Registry,
+    metrics::{counter::Counter, family::Family, gauge::Gauge},
+    registry::{Registry, Unit},
 };
+use tokio::time::Instant;
 
-use crate::{metrics::{throttled::{ThrottlingActiveLabels, ThrottlingKind, ThrottlingOccurredLabels}, Registerer}, parser::throttled::ThrottledState};
+use crate::{
+    metrics::{state_set::StateSet, throttled::{ThrottlingActiveLabels, ThrottlingKind, ThrottlingOccurredLabels}, Registerer},
+    parser::throttled::ThrottledState,
+};
 
 #[derive(Debug)]
 pub struct ThrottledRegisterer {
-    pub registry: Arc<Mutex<Registry>>,
+    // Exposing nothing until the first update, when the state becomes known
+    throttling_active: StateSet<ThrottlingKind>,
+    throttling_occurred: StateSet<ThrottlingKind>,
+    first_occurrence: Family<ThrottlingOccurredLabels, Gauge>,
+    last_seen: ThrottledLastOccurrenceRegisterer,
+    // Unix timestamps when the kinds were first seen occurred by their names, persisted in the state file if any so that
+    // neither restarts nor reboots clearing the bits of the firmware forget them
+    occurrences: Mutex<BTreeMap<String, i64>>,
+    state_file: Option<PathBuf>,
+}
+
+/// Accumulates how long each throttling kind has been active, meant to be fed by a sampler.
+#[derive(Debug)]
+pub struct ThrottledDurationRegisterer {
+    active_seconds: Family<ThrottlingActiveLabels, Counter<f64, AtomicU64>>,
+    last_sampled_at: Mutex<Option<Instant>>,
+}
+
+/// Records when each throttling kind was last seen active, meant to be fed by a sampler or by the collections of
+/// [`ThrottledRegisterer`].
+#[derive(Debug)]
+pub struct ThrottledLastOccurrenceRegisterer {
+    last_occurrence: Family<ThrottlingActiveLabels, Gauge>,
+}
+
+/// Counts how many times each throttling kind has become active, meant to be fed by a sampler.
+#[derive(Debug)]
+pub struct ThrottledTransitionRegisterer {
+    transitions: Family<ThrottlingActiveLabels, Counter>,
+    // Whether each kind was active at the previous sample
+    previous: Mutex<Option<[bool; 4]>>,
+}
+
+impl ThrottledRegisterer {
+    pub fn new(registry: &mut Registry) -> Self {
+        let throttling_active = StateSet::new("kind");
+        let throttling_occurred = StateSet::new("kind");
+        registry.register(
+            "throttling_active",
+            "State about throttling active currently",
+            throttling_active.clone(),
+        );
+        registry.register(
+            "throttling_occurred",
+            "State about throttling occurred in the past",
+            throttling_occurred.clone(),
+        );
+        let first_occurrence = Family::<ThrottlingOccurredLabels, Gauge>::default();
+        registry.register_with_unit(
+            "throttling_first_occurrence",
+            "Unix timestamp when throttling was first seen occurred by the exporter",
+            Unit::Seconds,
+            first_occurrence.clone(),
+        );
+        let last_seen = ThrottledLastOccurrenceRegisterer::with_name(
+            registry,
+            "throttling_last_seen_timestamp",
+            "Unix timestamp when throttling was last seen active in a collection",
+        );
+
+        Self {
+            throttling_active,
+            throttling_occurred,
+            first_occurrence,
+            last_seen,
+            occurrences: Mutex::default(),
+            state_file: None,
+        }
+    }
+
+    /// Keeps the occurrences in `state_file`, starting with the ones it already has.
+    pub fn with_state_file(self, state_file: impl Into<PathBuf>) -> Self {
+        let state_file = state_file.into();
+        let occurrences = match fs::read_to_string(&state_file) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|err| {
+                tracing::warn!("ignoring invalid throttling state file: {state_file:?}\nError: {err:?}");
+                BTreeMap::new()
+            }),
+            Err(err) if err.kind() == ErrorKind::NotFound => BTreeMap::new(),
+            Err(err) => {
+                tracing::warn!("failed to read throttling state file: {state_file:?}\nError: {err:?}");
+                BTreeMap::new()
+            },
+        };
+
+        Self {
+            occurrences: Mutex::new(occurrences),
+            state_file: Some(state_file),
+            ..self
+        }
+    }
+
+    // Replaces the state file at once, so that a crash while writing doesn't leave it broken
+    async fn persist(&self, occurrences: &BTreeMap<String, i64>) -> anyhow::Result<()> {
+        let Some(state_file) = &self.state_file else {
+            return Ok(());
+        };
+
+        let mut temporary = state_file.clone().into_os_string();
+        temporary.push(".tmp");
+        tokio::fs::write(&temporary, serde_json::to_string(occurrences)?)
+            .await
+            .with_context(|| format!("file write error: {temporary:?}"))?;
+        tokio::fs::rename(&temporary, state_file)
+            .await
+            .with_context(|| format!("file rename error: {state_file:?}"))?;
+
+        Ok(())
+    }
 }
 
 impl Registerer for ThrottledRegisterer {
     type Item = ThrottledState;
 
-    async fn register(&self, state: Self::Item) -> anyhow::Result<()> {
-        // Substitutes Gauge for StateSet of OpenMetrics because prometheus_client doens't implement it
-        let throttling_active_family = Family::<ThrottlingActiveLabels, Gauge>::default();
-        // Substitutes Gauge for StateSet of OpenMetrics because prometheus_client doens't implement it
-        let throttling_occurred_family = Family::<ThrottlingOccurredLabels, Gauge>::default();
-        {
-            let mut registry = self.registry.lock().expect("failed to lock registry mutex");
-            registry.register(
-                "raspi_throttling_active",
-                "State about throttling active currently",
-                throttling_active_family.clone(),
-            );
-            registry.register(
-                "raspi_throttling_occurred",
-                "State about throttling occurred in the past",
-                throttling_occurred_family.clone(),
-            );
+    async fn update(&self, state: Self::Item) -> anyhow::Result<()> {
+        let kinds = [ThrottlingKind::Undervoltage, ThrottlingKind::ArmFrequency, ThrottlingKind::Throttled, ThrottlingKind::SoftTemperatureLimit];
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs().try_into()?;
+
+        let (occurrences, changed) = {
+            let mut occurrences = self.occurrences.lock().expect("failed to lock occurrences mutex");
+            let mut changed = false;
+            for (kind, (is_active, has_occurred)) in kinds.into_iter().zip([
+                (state.undervoltage_detected, state.undervoltage_has_occurred),
+                (state.arm_frequency_capped, state.arm_frequency_capping_has_occurred),
+                (state.currently_throttled, state.throttling_has_occurred),
+                (state.soft_temperature_limit_active, state.soft_temperature_limit_has_occurred),
+            ]) {
+                self.throttling_active.set(kind.clone(), is_active);
+                if has_occurred && !occurrences.contains_key(&kind.to_string()) {
+                    occurrences.insert(kind.to_string(), now);
+                    changed = true;
+                }
+                let first_occurrence = occurrences.get(&kind.to_string());
+                self.throttling_occurred.set(kind.clone(), first_occurrence.is_some());
+                if let Some(first_occurrence) = first_occurrence {
+                    self.first_occurrence.get_or_create(&ThrottlingOccurredLabels { kind }).set(*first_occurrence);
+                }
+            }
+            (occurrences.clone(), changed)
+        };
+        self.last_seen.update(state).await?;
+
+        if changed {
+            self.persist(&occurrences).await?;
         }
 
-        throttling_active_family.get_or_create(&ThrottlingActiveLabels { kind: ThrottlingKind::Undervoltage }).set(state.undervoltage_detected.into());
-        throttling_active_family.get_or_create(&ThrottlingActiveLabels { kind: ThrottlingKind::ArmFrequency }).set(state.arm_frequency_capped.into());
-        throttling_active_family.get_or_create(&ThrottlingActiveLabels { kind: ThrottlingKind::Throttled }).set(state.currently_throttled.into());
-        throttling_active_family.get_or_create(&ThrottlingActiveLabels { kind: ThrottlingKind::SoftTemperatureLimit }).set(state.soft_temperature_limit_active.into());
+        Ok(())
+    }
+}
+
+impl ThrottledDurationRegisterer {
+    pub fn new(registry: &mut Registry) -> Self {
+        let active_seconds = Family::<ThrottlingActiveLabels, Counter<f64, AtomicU64>>::default();
+        registry.register_with_unit(
+            "throttling_active",
+            "Time spent with throttling active",
+            Unit::Seconds,
+            active_seconds.clone(),
+        );
+        // Exposes every kind from the start so that increase() also works for the first activation
+        for kind in [ThrottlingKind::Undervoltage, ThrottlingKind::ArmFrequency, ThrottlingKind::Throttled, ThrottlingKind::SoftTemperatureLimit] {
+            let _ = active_seconds.get_or_create(&ThrottlingActiveLabels { kind });
+        }
 
-        {
-            let metric = throttling_occurred_family.get_or_create(&ThrottlingOccurredLabels { kind: ThrottlingKind::Undervoltage });
-            if state.undervoltage_has_occurred && metric.get() == 0 {
-                metric.inc();
-            }
+        Self {
+            active_seconds,
+            last_sampled_at: Mutex::new(None),
         }
+    }
+}
 
-        {
-            let metric = throttling_occurred_family.get_or_create(&ThrottlingOccurredLabels { kind: ThrottlingKind::ArmFrequency });
-            if state.arm_frequency_capping_has_occurred && metric.get() == 0 {
-                metric.inc();
+impl Registerer for ThrottledDurationRegisterer {
+    type Item = ThrottledState;
+
+    async fn update(&self, state: Self::Item) -> anyhow::Result<()> {
+        let now = Instant::now();
+        let Some(last_sampled_at) = self.last_sampled_at.lock().expect("failed to lock last sampled mutex").replace(now) else {
+            return Ok(());
+        };
+        // The state is assumed to have held since the previous sample
+        let elapsed = now.duration_since(last_sampled_at).as_secs_f64();
+
+        for (kind, active) in [
+            (ThrottlingKind::Undervoltage, state.undervoltage_detected),
+            (ThrottlingKind::ArmFrequency, state.arm_frequency_capped),
+            (ThrottlingKind::Throttled, state.currently_throttled),
+            (ThrottlingKind::SoftTemperatureLimit, state.soft_temperature_limit_active),
+        ] {
+            if active {
+                self.active_seconds.get_or_create(&ThrottlingActiveLabels { kind }).inc_by(elapsed);
             }
         }
 
-        {
-            let metric = throttling_occurred_family.get_or_create(&ThrottlingOccurredLabels { kind: ThrottlingKind::Throttled });
-            if state.throttling_has_occurred && metric.get() == 0 {
-                metric.inc();
+        Ok(())
+    }
+}
+
+impl ThrottledLastOccurrenceRegisterer {
+    pub fn new(registry: &mut Registry) -> Self {
+        Self::with_name(registry, "throttling_last_occurrence", "Unix timestamp when throttling was last seen active by the exporter")
+    }
+
+    fn with_name(registry: &mut Registry, name: &str, help: &str) -> Self {
+        let last_occurrence = Family::<ThrottlingActiveLabels, Gauge>::default();
+        registry.register_with_unit(name, help, Unit::Seconds, last_occurrence.clone());
+
+        Self {
+            last_occurrence,
+        }
+    }
+}
+
+impl Registerer for ThrottledLastOccurrenceRegisterer {
+    type Item = ThrottledState;
+
+    async fn update(&self, state: Self::Item) -> anyhow::Result<()> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs().try_into()?;
+
+        for (kind, active) in [
+            (ThrottlingKind::Undervoltage, state.undervoltage_detected),
+            (ThrottlingKind::ArmFrequency, state.arm_frequency_capped),
+            (ThrottlingKind::Throttled, state.currently_throttled),
+            (ThrottlingKind::SoftTemperatureLimit, state.soft_temperature_limit_active),
+        ] {
+            if active {
+                self.last_occurrence.get_or_create(&ThrottlingActiveLabels { kind }).set(now);
             }
         }
 
-        {
-            let metric = throttling_occurred_family.get_or_create(&ThrottlingOccurredLabels { kind: ThrottlingKind::SoftTemperatureLimit });
-            if state.soft_temperature_limit_has_occurred && metric.get() == 0 {
-                metric.inc();
+        Ok(())
+    }
+}
+
+impl ThrottledTransitionRegisterer {
+    pub fn new(registry: &mut Registry) -> Self {
+        let transitions = Family::<ThrottlingActiveLabels, Counter>::default();
+        registry.register(
+            "throttling_transitions",
+            "Number of times throttling has become active",
+            transitions.clone(),
+        );
+        // Exposes every kind from the start so that increase() also works for the first transition
+        for kind in [ThrottlingKind::Undervoltage, ThrottlingKind::ArmFrequency, ThrottlingKind::Throttled, ThrottlingKind::SoftTemperatureLimit] {
+            let _ = transitions.get_or_create(&ThrottlingActiveLabels { kind });
+        }
+
+        Self {
+            transitions,
+            previous: Mutex::new(None),
+        }
+    }
+}
+
+impl Registerer for ThrottledTransitionRegisterer {
+    type Item = ThrottledState;
+
+    async fn update(&self, state: Self::Item) -> anyhow::Result<()> {
+        let current = [
+            state.undervoltage_detected,
+            state.arm_frequency_capped,
+            state.currently_throttled,
+            state.soft_temperature_limit_active,
+        ];
+        // Kinds already active at the first sample may have become so before the exporter started, so they aren't counted
+        let Some(previous) = self.previous.lock().expect("failed to lock previous mutex").replace(current) else {
+            return Ok(());
+        };
+
+        let kinds = [ThrottlingKind::Undervoltage, ThrottlingKind::ArmFrequency, ThrottlingKind::Throttled, ThrottlingKind::SoftTemperatureLimit];
+        for ((kind, was_active), is_active) in kinds.into_iter().zip(previous).zip(current) {
+            if !was_active && is_active {
+                self.transitions.get_or_create(&ThrottlingActiveLabels { kind }).inc();
             }
         }
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, process, time::Duration};
+
+    use prometheus_client::{encoding::text, registry::Registry};
+
+    use crate::{
+        format,
+        metrics::Registerer,
+        parser::throttled::ThrottledState,
+        registerer::throttled::{
+            ThrottledDurationRegisterer,
+            ThrottledLastOccurrenceRegisterer,
+            ThrottledRegisterer,
+            ThrottledTransitionRegisterer,
+        },
+    };
+
+    #[tokio::test]
+    async fn register_persisted_occurrences() {
+        let state_file = std::env::temp_dir().join(format!("raspi-exporter-throttled-{}.json", process::id()));
+        fs::write(&state_file, r#"{"undervoltage":1700000000}"#).unwrap();
+
+        let mut registry = Registry::with_prefix("raspi");
+        let registerer = ThrottledRegisterer::new(&mut registry).with_state_file(&state_file);
+        // The firmware has forgotten the undervoltage over a reboot
+        registerer.update(ThrottledState { throttling_has_occurred: true, ..Default::default() }).await.unwrap();
+
+        let mut buffer = String::new();
+        text::encode(&mut buffer, &registry).unwrap();
+        let buffer = format::state_sets(buffer, false).unwrap();
+        let persisted = serde_json::from_str::<serde_json::Value>(&fs::read_to_string(&state_file).unwrap()).unwrap();
+        fs::remove_file(&state_file).unwrap();
+
+        assert!(buffer.contains("# TYPE raspi_throttling_occurred stateset\n"));
+        assert!(buffer.contains("raspi_throttling_occurred{raspi_throttling_occurred=\"undervoltage\"} 1\n"));
+        assert!(buffer.contains("raspi_throttling_occurred{raspi_throttling_occurred=\"throttled\"} 1\n"));
+        assert!(buffer.contains("raspi_throttling_occurred{raspi_throttling_occurred=\"arm frequency\"} 0\n"));
+        assert!(buffer.contains("raspi_throttling_first_occurrence_seconds{kind=\"undervoltage\"} 1700000000\n"));
+        assert_eq!(persisted["undervoltage"], 1700000000);
+        assert!(persisted["throttled"].as_i64().is_some_and(|timestamp| timestamp > 1700000000));
+        assert!(persisted.get("arm frequency").is_none());
+    }
+
+    #[tokio::test]
+    async fn register_last_seen() {
+        let mut registry = Registry::with_prefix("raspi");
+        let registerer = ThrottledRegisterer::new(&mut registry);
+
+        registerer.update(ThrottledState { undervoltage_detected: true, ..Default::default() }).await.unwrap();
+        registerer.update(ThrottledState { currently_throttled: true, ..Default::default() }).await.unwrap();
+
+        let mut buffer = String::new();
+        text::encode(&mut buffer, &registry).unwrap();
+        let mut metrics = buffer
+            .lines()
+            .filter(|line| line.starts_with("raspi_throttling_last_seen_timestamp_seconds"))
+            .collect::<Vec<_>>();
+        metrics.sort();
+
+        // Kept after the bit is cleared
+        assert_eq!(metrics.len(), 2);
+        assert!(metrics[0].starts_with("raspi_throttling_last_seen_timestamp_seconds{kind=\"throttled\"} "));
+        assert!(metrics[1].starts_with("raspi_throttling_last_seen_timestamp_seconds{kind=\"undervoltage\"} "));
+        assert!(metrics.iter().all(|metric| metric.rsplit_once(' ').is_some_and(|(_, v)| v.parse::<i64>().unwrap() > 0)));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn register_duration() {
+        let mut registry = Registry::with_prefix("raspi");
+        let registerer = ThrottledDurationRegisterer::new(&mut registry);
+
+        let active = || ThrottledState {
+            undervoltage_detected: true,
+            currently_throttled: true,
+            ..Default::default()
+        };
+        registerer.update(active()).await.unwrap();
+        tokio::time::advance(Duration::from_secs(5)).await;
+        registerer.update(active()).await.unwrap();
+        tokio::time::advance(Duration::from_secs(5)).await;
+        registerer.update(ThrottledState { currently_throttled: true, ..Default::default() }).await.unwrap();
+
+        let mut buffer = String::new();
+        text::encode(&mut buffer, &registry).unwrap();
+        let mut metrics = buffer.lines().filter(|line| !line.starts_with('#')).collect::<Vec<_>>();
+        metrics.sort();
+
+        assert_eq!(
+            metrics,
+            [
+                "raspi_throttling_active_seconds_total{kind=\"arm frequency\"} 0.0",
+                "raspi_throttling_active_seconds_total{kind=\"soft temperature limit\"} 0.0",
+                "raspi_throttling_active_seconds_total{kind=\"throttled\"} 10.0",
+                "raspi_throttling_active_seconds_total{kind=\"undervoltage\"} 5.0",
+            ]
+        )
+    }
+
+    #[tokio::test]
+    async fn register_last_occurrence() {
+        let mut registry = Registry::with_prefix("raspi");
+        let registerer = ThrottledLastOccurrenceRegisterer::new(&mut registry);
+
+        registerer.update(ThrottledState { undervoltage_detected: true, ..Default::default() }).await.unwrap();
+
+        let mut buffer = String::new();
+        text::encode(&mut buffer, &registry).unwrap();
+        let metrics = buffer.lines().filter(|line| !line.starts_with('#')).collect::<Vec<_>>();
+
+        assert_eq!(metrics.len(), 1);
+        assert!(metrics[0].starts_with("raspi_throttling_last_occurrence_seconds{kind=\"undervoltage\"} "));
+        assert!(metrics[0].rsplit_once(' ').is_some_and(|(_, v)| v.parse::<i64>().unwrap() > 0));
+    }
+
+    #[tokio::test]
+    async fn register_transitions() {
+        let mut registry = Registry::with_prefix("raspi");
+        let registerer = ThrottledTransitionRegisterer::new(&mut registry);
+
+        for state in [
+            ThrottledState { undervoltage_detected: true, ..Default::default() },
+            ThrottledState { currently_throttled: true, ..Default::default() },
+            ThrottledState { currently_throttled: true, ..Default::default() },
+            ThrottledState { undervoltage_detected: true, ..Default::default() },
+            ThrottledState::default(),
+            ThrottledState { undervoltage_detected: true, ..Default::default() },
+        ] {
+            registerer.update(state).await.unwrap();
+        }
+
+        let mut buffer = String::new();
+        text::encode(&mut buffer, &registry).unwrap();
+        let mut metrics = buffer.lines().filter(|line| !line.starts_with('#')).collect::<Vec<_>>();
+        metrics.sort();
+
+        assert_eq!(
+            metrics,
+            [
+                "raspi_throttling_transitions_total{kind=\"arm frequency\"} 0",
+                "raspi_throttling_transitions_total{kind=\"soft temperature limit\"} 0",
+                "raspi_throttling_transitions_total{kind=\"throttled\"} 1",
+                "raspi_throttling_transitions_total{kind=\"undervoltage\"} 2",
+            ]
+        )
+    }
+}
@@ -0,0 +1,38 @@
+use prometheus_client::{
+    metrics::{family::Family, gauge::Gauge},
+    registry::Registry,
+};
+
+use crate::{metrics::{vl805::Vl805FirmwareLabels, Registerer}, parser::vl805::Vl805State};
+
+#[derive(Debug)]
+pub struct Vl805Registerer {
+    firmware_info: Family<Vl805FirmwareLabels, Gauge>,
+}
+
+impl Vl805Registerer {
+    pub fn new(registry: &mut Registry) -> Self {
+        // Substitutes Gauge for Info because Info can't change its labels after registration
+        let firmware_info = Family::<Vl805FirmwareLabels, Gauge>::default();
+        registry.register(
+            "vl805_firmware_info",
+            "Firmware version of the VL805 USB controller",
+            firmware_info.clone(),
+        );
+
+        Self {
+            firmware_info,
+        }
+    }
+}
+
+impl Registerer for Vl805Registerer {
+    type Item = Vl805State;
+
+    async fn update(&self, state: Self::Item) -> anyhow::Result<()> {
+        self.firmware_info.clear();
+        self.firmware_info.get_or_create(&Vl805FirmwareLabels { current: state.current, latest: state.latest }).set(1);
+
+        Ok(())
+    }
+}
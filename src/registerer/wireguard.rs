@@ -0,0 +1,113 @@
+use std::{sync::atomic::AtomicU64, time::{SystemTime, UNIX_EPOCH}};
+
+use prometheus_client::{
+    metrics::{counter::Counter, family::Family, gauge::Gauge},
+    registry::{Registry, Unit},
+};
+
+use crate::{
+    metrics::{wireguard::WireguardPeerLabels, Registerer},
+    parser::wireguard::WireguardState,
+    registerer::set_counter,
+};
+
+#[derive(Debug)]
+pub struct WireguardRegisterer {
+    last_handshake_age: Family<WireguardPeerLabels, Gauge<u64, AtomicU64>>,
+    receive: Family<WireguardPeerLabels, Counter>,
+    transmit: Family<WireguardPeerLabels, Counter>,
+}
+
+impl WireguardRegisterer {
+    pub fn new(registry: &mut Registry) -> Self {
+        let last_handshake_age = Family::<WireguardPeerLabels, Gauge<u64, AtomicU64>>::default();
+        let receive = Family::<WireguardPeerLabels, Counter>::default();
+        let transmit = Family::<WireguardPeerLabels, Counter>::default();
+        registry.register_with_unit(
+            "wireguard_peer_last_handshake_age",
+            "Time since the latest handshake with the WireGuard peer",
+            Unit::Seconds,
+            last_handshake_age.clone(),
+        );
+        registry.register_with_unit(
+            "wireguard_peer_receive",
+            "Data received from the WireGuard peer",
+            Unit::Bytes,
+            receive.clone(),
+        );
+        registry.register_with_unit(
+            "wireguard_peer_transmit",
+            "Data transmitted to the WireGuard peer",
+            Unit::Bytes,
+            transmit.clone(),
+        );
+
+        Self {
+            last_handshake_age,
+            receive,
+            transmit,
+        }
+    }
+}
+
+impl Registerer for WireguardRegisterer {
+    type Item = WireguardState;
+
+    async fn update(&self, state: Self::Item) -> anyhow::Result<()> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+
+        // Drops series of peers removed since the last collection
+        self.last_handshake_age.clear();
+        self.receive.clear();
+        self.transmit.clear();
+
+        for peer in state.peers {
+            let labels = WireguardPeerLabels {
+                interface: peer.interface,
+                public_key: peer.public_key,
+            };
+
+            if let Some(latest_handshake) = peer.latest_handshake {
+                self.last_handshake_age.get_or_create(&labels).set(now.saturating_sub(latest_handshake));
+            }
+            set_counter(&self.receive, &labels, peer.transfer_rx);
+            set_counter(&self.transmit, &labels, peer.transfer_tx);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use prometheus_client::{encoding::text, registry::Registry};
+
+    use crate::{
+        metrics::Registerer,
+        parser::wireguard::{WireguardPeer, WireguardState},
+        registerer::wireguard::WireguardRegisterer,
+    };
+
+    #[tokio::test]
+    async fn register_drops_removed_peers() {
+        let mut registry = Registry::with_prefix("raspi");
+        let registerer = WireguardRegisterer::new(&mut registry);
+        let peer = |public_key: &str| WireguardPeer {
+            interface: "wg0".to_string(),
+            public_key: public_key.to_string(),
+            latest_handshake: Some(0),
+            transfer_rx: 100,
+            transfer_tx: 200,
+        };
+
+        registerer.update(WireguardState { peers: vec![peer("aa"), peer("bb")] }).await.unwrap();
+        registerer.update(WireguardState { peers: vec![peer("bb")] }).await.unwrap();
+
+        let mut buffer = String::new();
+        text::encode(&mut buffer, &registry).unwrap();
+
+        assert!(!buffer.contains(r#"public_key="aa""#));
+        assert!(buffer.contains(r#"raspi_wireguard_peer_receive_bytes_total{interface="wg0",public_key="bb"} 100"#));
+        assert!(buffer.contains(r#"raspi_wireguard_peer_transmit_bytes_total{interface="wg0",public_key="bb"} 200"#));
+    }
+}
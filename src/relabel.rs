@@ -0,0 +1,182 @@
+//! Rules rewriting the metrics before encoding, read from the `relabel` section of the config file.
+
+use std::collections::BTreeMap;
+
+use anyhow::Context;
+use serde::Deserialize;
+
+use crate::format::{join_sample, split_sample};
+
+/// Rule applied to the metric families named by `metric` as in their `# TYPE` line, such as `raspi_oom_kills` for the
+/// samples of `raspi_oom_kills_total`.
+///
+/// ```toml
+/// [[relabel]]
+/// action = "rename"
+/// metric = "raspi_soc_temperature_celsius"
+/// name = "rpi_cpu_temperature_celsius"
+///
+/// [[relabel]]
+/// action = "replace"
+/// label = "mount_point"
+/// value = "/boot/firmware"
+/// replacement = "/boot"
+///
+/// [[relabel]]
+/// action = "drop"
+/// metric = "raspi_filesystem_size_bytes"
+/// labels = { type = "vfat" }
+/// ```
+///
+/// Rules apply in order, so that a rule following a rename names the metric by its new name.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Rule {
+    /// Renames the metric to `name`.
+    Rename {
+        metric: String,
+        name: String,
+    },
+    /// Replaces the value of a label of the metric, or of every metric when `metric` is omitted.
+    Replace {
+        #[serde(default)]
+        metric: Option<String>,
+        label: String,
+        value: String,
+        replacement: String,
+    },
+    /// Drops the series of the metric having all of `labels`, or the whole metric when `labels` is omitted.
+    Drop {
+        metric: String,
+        #[serde(default)]
+        labels: BTreeMap<String, String>,
+    },
+}
+
+/// Applies `rules` to metrics encoded in OpenMetrics.
+pub fn apply(rules: &[Rule], openmetrics: String) -> anyhow::Result<String> {
+    if rules.is_empty() {
+        return Ok(openmetrics);
+    }
+
+    let mut buffer = String::with_capacity(openmetrics.len());
+    // Family of the samples following, by its name before the rules
+    let mut family = "";
+    for line in openmetrics.lines() {
+        if line == "# EOF" {
+            buffer.push_str(line);
+            buffer.push('\n');
+            continue;
+        }
+
+        if let Some(metadata) = line.strip_prefix("# ") {
+            let (kind, rest) = metadata.split_once(' ').with_context(|| format!("invalid metadata: {line}"))?;
+            let (name, description) = rest.split_once(' ').map_or((rest, None), |(name, description)| (name, Some(description)));
+            family = name;
+            if let Some(name) = relabel(rules, family, &mut Vec::new()) {
+                match description {
+                    Some(description) => buffer.push_str(&format!("# {kind} {name} {description}\n")),
+                    None => buffer.push_str(&format!("# {kind} {name}\n")),
+                }
+            }
+            continue;
+        }
+
+        let (name, mut labels, rest) = split_sample(line).with_context(|| format!("invalid sample: {line}"))?;
+        // Such as `_total` of counters
+        let suffix = name.strip_prefix(family).unwrap_or_default();
+        let Some(family) = relabel(rules, family, &mut labels) else {
+            continue;
+        };
+
+        buffer.push_str(&join_sample(&format!("{family}{suffix}"), &labels, rest));
+        buffer.push('\n');
+    }
+
+    Ok(buffer)
+}
+
+// New name of a family, rewriting `labels` of its sample, or none when dropped
+fn relabel(rules: &[Rule], family: &str, labels: &mut [(String, String)]) -> Option<String> {
+    let mut family = family.to_string();
+    for rule in rules {
+        match rule {
+            Rule::Rename { metric, name } if *metric == family => family = name.clone(),
+            Rule::Replace { metric, label, value, replacement } if metric.as_ref().is_none_or(|metric| *metric == family) => {
+                for (_, current) in labels.iter_mut().filter(|(name, current)| name == label && current == value) {
+                    *current = replacement.clone();
+                }
+            },
+            // Metadata has no labels, so that only a rule without them drops it
+            Rule::Drop { metric, labels: matchers }
+                if *metric == family && matchers.iter().all(|(name, value)| labels.iter().any(|label| (&label.0, &label.1) == (name, value))) =>
+            {
+                return None;
+            },
+            _ => {},
+        }
+    }
+
+    Some(family)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::relabel::{apply, Rule};
+
+    #[test]
+    fn apply_rules() {
+        let rules = serde_yaml::from_str::<Vec<Rule>>(&[
+            "- action: rename",
+            "  metric: raspi_oom_kills",
+            "  name: oom_kills",
+            "- action: replace",
+            "  label: mount_point",
+            "  value: /boot/firmware",
+            "  replacement: /boot",
+            "- action: drop",
+            "  metric: raspi_filesystem_size_bytes",
+            "  labels: { type: vfat }",
+            "- action: drop",
+            "  metric: raspi_vl805",
+        ].join("\n")).unwrap();
+        let openmetrics = [
+            "# HELP raspi_oom_kills Number of processes killed by the OOM killer.",
+            "# TYPE raspi_oom_kills counter",
+            "raspi_oom_kills_total 3",
+            "# HELP raspi_filesystem_size_bytes Size of the filesystem.",
+            "# TYPE raspi_filesystem_size_bytes gauge",
+            "raspi_filesystem_size_bytes{mount_point=\"/boot/firmware\",type=\"vfat\"} 1000",
+            "raspi_filesystem_size_bytes{mount_point=\"/\",type=\"ext4\"} 2000",
+            "# HELP raspi_vl805 VL805 firmware.",
+            "# TYPE raspi_vl805 info",
+            "raspi_vl805_info{version=\"000138c0\"} 1",
+            "# HELP raspi_mount Mount.",
+            "# TYPE raspi_mount gauge",
+            "raspi_mount{mount_point=\"/boot/firmware\",note=\"a\\\"b\"} 1.0",
+            "# EOF",
+        ].join("\n") + "\n";
+
+        assert_eq!(
+            apply(&rules, openmetrics).unwrap(),
+            [
+                "# HELP oom_kills Number of processes killed by the OOM killer.",
+                "# TYPE oom_kills counter",
+                "oom_kills_total 3",
+                "# HELP raspi_filesystem_size_bytes Size of the filesystem.",
+                "# TYPE raspi_filesystem_size_bytes gauge",
+                "raspi_filesystem_size_bytes{mount_point=\"/\",type=\"ext4\"} 2000",
+                "# HELP raspi_mount Mount.",
+                "# TYPE raspi_mount gauge",
+                "raspi_mount{mount_point=\"/boot\",note=\"a\\\"b\"} 1.0",
+                "# EOF",
+            ].join("\n") + "\n",
+        );
+    }
+
+    #[test]
+    fn invalid_rule() {
+        assert!(serde_yaml::from_str::<Vec<Rule>>("- action: rename\n  metric: a").is_err());
+        assert!(serde_yaml::from_str::<Vec<Rule>>("- action: keep\n  metric: a").is_err());
+    }
+}
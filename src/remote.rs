@@ -0,0 +1,84 @@
+use std::{fmt::Debug, path::Path};
+
+use anyhow::Context;
+use tokio::process::Command;
+use tracing::Level;
+
+use crate::executor::Executor;
+
+/// Runs a command on a remote host over SSH, with the `ssh` client and the SSH config of the user running the exporter.
+///
+/// `destination` is anything `ssh` takes, such as `pi-01` of a `Host` in ~/.ssh/config or `pi@192.168.1.10`. Password
+/// prompts are disabled, so that the host must accept a key.
+#[derive(Debug)]
+pub struct RemoteExecutor {
+    destination: String,
+    command: Vec<String>,
+}
+
+impl RemoteExecutor {
+    pub fn command<'a>(destination: impl Into<String>, command: &'a str, args: impl IntoIterator<Item = &'a str>) -> Self {
+        Self {
+            destination: destination.into(),
+            command: [command].into_iter().chain(args).map(String::from).collect(),
+        }
+    }
+
+    /// Reads a file of the remote host, such as one of sysfs.
+    pub fn file(destination: impl Into<String>, path: impl AsRef<Path>) -> Self {
+        Self::command(destination, "cat", ["--", &path.as_ref().to_string_lossy()])
+    }
+}
+
+impl Executor for RemoteExecutor {
+    #[tracing::instrument(skip_all, fields(destination = %self.destination, command = ?self.command), ret(level = Level::DEBUG))]
+    async fn execute(&self) -> anyhow::Result<String> {
+        // The remote shell splits the command again, so that each argument is quoted
+        let command = self.command.iter().map(|arg| quote(arg)).collect::<Vec<_>>().join(" ");
+        let output = Command::new("ssh")
+            .args(["-o", "BatchMode=yes", "--", &self.destination, &command])
+            .output()
+            .await
+            .with_context(|| format!("ssh execution error: {self:?}"))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            match output.status.code() {
+                Some(code) => anyhow::bail!(format!("ssh exited with status code {code}: {self:?}\n{}", stderr.trim())),
+                None => anyhow::bail!(format!("ssh terminated by signal: {self:?}")),
+            }
+        }
+
+        let result = String::from_utf8(output.stdout)?;
+        Ok(result)
+    }
+}
+
+// Quotes an argument for a POSIX shell
+fn quote(arg: &str) -> String {
+    if !arg.is_empty() && arg.chars().all(|c| c.is_ascii_alphanumeric() || "-_./=:,@".contains(c)) {
+        return arg.to_string();
+    }
+
+    format!("'{}'", arg.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::remote::{quote, RemoteExecutor};
+
+    #[test]
+    fn quote_args() {
+        assert_eq!(quote("get_throttled"), "get_throttled");
+        assert_eq!(quote("/sys/class/thermal/thermal_zone0/temp"), "/sys/class/thermal/thermal_zone0/temp");
+        assert_eq!(quote(""), "''");
+        assert_eq!(quote("a b"), "'a b'");
+        assert_eq!(quote("it's; rm"), r"'it'\''s; rm'");
+    }
+
+    #[test]
+    fn file() {
+        let executor = RemoteExecutor::file("pi-01", "/proc/net/snmp");
+        assert_eq!(executor.destination, "pi-01");
+        assert_eq!(executor.command, ["cat", "--", "/proc/net/snmp"]);
+    }
+}
@@ -0,0 +1,80 @@
+use std::time::{Duration, SystemTime};
+
+use http_body_util::Full;
+use hyper::{header::{AUTHORIZATION, CONTENT_ENCODING, CONTENT_TYPE, USER_AGENT}, Request, Uri};
+use tokio::time::{self, MissedTickBehavior};
+
+use crate::{
+    client::{Client, Credentials},
+    format::remote_write,
+    metrics::{Filter, Handler},
+};
+
+/// Pushes the metrics with the Prometheus remote write protocol on an interval, for exporters that can't be scraped.
+pub struct RemoteWrite<H> {
+    url: Uri,
+    interval: Duration,
+    handler: H,
+    client: Client,
+    credentials: Option<Credentials>,
+}
+
+impl<H> RemoteWrite<H>
+where
+    H: Handler,
+{
+    pub fn new(url: Uri, interval: Duration, handler: H, client: Client) -> Self {
+        Self {
+            url,
+            interval,
+            handler,
+            client,
+            credentials: None,
+        }
+    }
+
+    pub fn credentials(self, credentials: Option<Credentials>) -> Self {
+        Self {
+            credentials,
+            ..self
+        }
+    }
+
+    pub async fn start(self) {
+        let mut interval = time::interval(self.interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+
+            // Samples that failed are dropped rather than retried, as the next push is due soon
+            match time::timeout(self.interval, self.push()).await {
+                Ok(Ok(())) => {},
+                Ok(Err(err)) => tracing::error!("failed to push metrics\nError: {err:?}"),
+                Err(_) => tracing::error!("pushing metrics timed out"),
+            }
+        }
+    }
+
+    pub async fn push(&self) -> anyhow::Result<()> {
+        let timestamp = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)?.as_millis() as i64;
+        let metrics = self.handler.handle(&Filter::default(), Some(self.interval)).await?;
+        let body = remote_write::compress(&remote_write::encode(&metrics, timestamp)?);
+
+        let mut request = Request::post(&self.url)
+            .header(CONTENT_TYPE, "application/x-protobuf")
+            .header(CONTENT_ENCODING, "snappy")
+            .header("X-Prometheus-Remote-Write-Version", "0.1.0")
+            .header(USER_AGENT, concat!("raspi_exporter/", env!("CARGO_PKG_VERSION")));
+        if let Some(credentials) = &self.credentials {
+            request = request.header(AUTHORIZATION, credentials.header());
+        }
+
+        let response = self.client.send(request.body(Full::from(body))?).await?;
+        if !response.status().is_success() {
+            anyhow::bail!("remote write failed with status {}: {}", response.status(), String::from_utf8_lossy(response.body()));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,74 @@
+use std::time::Duration;
+
+use anyhow::Context;
+use tokio::{task::JoinHandle, time::{self, MissedTickBehavior}};
+
+use crate::metrics::Collector;
+
+/// Runs a collector on its own interval in the background, independently of scrapes.
+///
+/// Used for metrics that depend on how a state changes between scrapes, such as durations.
+pub struct Sampler {
+    collector: Box<dyn Collector>,
+    interval: Duration,
+}
+
+impl Sampler {
+    pub fn new(collector: Box<dyn Collector>, interval: Duration) -> Self {
+        Self {
+            collector,
+            interval,
+        }
+    }
+
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = time::interval(self.interval);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+            loop {
+                interval.tick().await;
+
+                if let Err(err) = self.collector.collect().await.with_context(|| sampler_error(self.collector.name())) {
+                    tracing::error!("{err:?}");
+                }
+            }
+        })
+    }
+}
+
+fn sampler_error(name: &str) -> String {
+    format!("{name} sampler error")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::{atomic::{AtomicUsize, Ordering}, Arc}, time::Duration};
+
+    use crate::{metrics::MockCollector, sampler::Sampler};
+
+    #[tokio::test(start_paused = true)]
+    async fn spawn() {
+        let count = Arc::new(AtomicUsize::new(0));
+        let mut mock_collector = MockCollector::new();
+        mock_collector
+            .expect_collect()
+            .returning({
+                let count = count.clone();
+                move || {
+                    count.fetch_add(1, Ordering::SeqCst);
+                    Err(anyhow::anyhow!("failed"))
+                }
+            });
+        mock_collector
+            .expect_name()
+            .return_const("mock");
+
+        let handle = Sampler::new(Box::new(mock_collector), Duration::from_secs(5)).spawn();
+        // Ticks immediately, then after 5 and 10 seconds, and keeps going after failures
+        tokio::time::sleep(Duration::from_secs(12)).await;
+        handle.abort();
+
+        assert_eq!(count.load(Ordering::SeqCst), 3);
+    }
+}
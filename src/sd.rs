@@ -0,0 +1,91 @@
+//! Targets of the fleet and probe modes in the format of the HTTP service discovery of Prometheus.
+
+use std::collections::BTreeMap;
+
+use hyper::Uri;
+use serde::Serialize;
+
+/// Targets sharing labels, an element of the JSON array that `http_sd_configs` of Prometheus takes.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct TargetGroup {
+    pub targets: Vec<String>,
+    pub labels: BTreeMap<String, String>,
+}
+
+/// Pis known to the exporter, which are the instances of `--fleet-instance` and the targets of `--probe-target`.
+#[derive(Clone, Debug)]
+pub struct ServiceDiscovery {
+    fleet_instances: Vec<Uri>,
+    probe_targets: Vec<String>,
+    https: bool,
+}
+
+impl ServiceDiscovery {
+    /// `https` tells whether the exporter serves the probes over HTTPS.
+    pub fn new(fleet_instances: Vec<Uri>, probe_targets: Vec<String>, https: bool) -> Self {
+        Self {
+            fleet_instances,
+            probe_targets,
+            https,
+        }
+    }
+
+    /// Groups of the fleet instances to scrape directly, and of the probe targets to scrape through the exporter at
+    /// `address`, which is how Prometheus reaches it such as the `Host` header of its request.
+    pub fn target_groups(&self, address: &str) -> Vec<TargetGroup> {
+        // Without the queries of the URLs, as parameters such as collect[] can't be label names
+        let instances = self.fleet_instances.iter().map(|url| TargetGroup {
+            targets: vec![url.authority().map_or_else(String::new, |authority| authority.to_string())],
+            labels: BTreeMap::from([
+                ("__metrics_path__".to_string(), url.path().to_string()),
+                ("__scheme__".to_string(), url.scheme_str().unwrap_or("http").to_string()),
+            ]),
+        });
+        let probes = self.probe_targets.iter().map(|target| TargetGroup {
+            targets: vec![address.to_string()],
+            labels: BTreeMap::from([
+                ("__metrics_path__".to_string(), "/probe".to_string()),
+                ("__param_target".to_string(), target.clone()),
+                ("__scheme__".to_string(), if self.https { "https" } else { "http" }.to_string()),
+                // Tells the probes apart, which share the address
+                ("instance".to_string(), target.clone()),
+            ]),
+        });
+
+        instances.chain(probes).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::Uri;
+
+    use crate::sd::ServiceDiscovery;
+
+    #[test]
+    fn target_groups() {
+        let sd = ServiceDiscovery::new(
+            vec![Uri::from_static("http://pi-01:9100/metrics"), Uri::from_static("https://pi-02:8021/raspi/metrics?collect[]=throttled")],
+            vec!["pi-03".to_string()],
+            false,
+        );
+
+        assert_eq!(
+            serde_json::to_value(sd.target_groups("exporter:8021")).unwrap(),
+            serde_json::json!([
+                {
+                    "targets": ["pi-01:9100"],
+                    "labels": { "__metrics_path__": "/metrics", "__scheme__": "http" },
+                },
+                {
+                    "targets": ["pi-02:8021"],
+                    "labels": { "__metrics_path__": "/raspi/metrics", "__scheme__": "https" },
+                },
+                {
+                    "targets": ["exporter:8021"],
+                    "labels": { "__metrics_path__": "/probe", "__param_target": "pi-03", "__scheme__": "http", "instance": "pi-03" },
+                },
+            ]),
+        );
+    }
+}
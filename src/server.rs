@@ -1,52 +1,542 @@
-use std::{net::Ipv4Addr, sync::Arc};
+use std::{
+    collections::HashMap,
+    env,
+    fmt::{self, Display},
+    fs,
+    net::SocketAddr,
+    os::{fd::{FromRawFd, RawFd}, unix::fs::FileTypeExt},
+    path::{Path, PathBuf},
+    process,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
 
-use axum::{extract::State, http::{header::CONTENT_TYPE, StatusCode}, response::IntoResponse, routing::get, Router};
-use tokio::{net::TcpListener, signal::unix::{self, SignalKind}};
+use anyhow::Context;
 
-use crate::metrics::{Handler};
+use axum::{
+    body::{self, Body},
+    extract::{connect_info::Connected, ConnectInfo, Path as UrlPath, RawQuery, Request, State},
+    http::{
+        header::{
+            ACCEPT,
+            ACCEPT_ENCODING,
+            ACCESS_CONTROL_ALLOW_CREDENTIALS,
+            ACCESS_CONTROL_ALLOW_HEADERS,
+            ACCESS_CONTROL_ALLOW_METHODS,
+            ACCESS_CONTROL_ALLOW_ORIGIN,
+            ACCESS_CONTROL_MAX_AGE,
+            ACCESS_CONTROL_REQUEST_METHOD,
+            AUTHORIZATION,
+            CONTENT_ENCODING,
+            CONTENT_LENGTH,
+            CONTENT_TYPE,
+            HOST,
+            ORIGIN,
+            VARY,
+            WWW_AUTHENTICATE,
+        },
+        HeaderMap,
+        HeaderValue,
+        Method,
+        StatusCode,
+        Uri,
+    },
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    serve::IncomingStream,
+    Router,
+};
+use axum_server::{tls_rustls::{RustlsAcceptor, RustlsConfig}, Handle};
+use percent_encoding::percent_decode_str;
+use rustls::ServerConfig;
+use socket2::{Domain, Socket, Type};
+use tokio::{net::{TcpListener, UnixListener}, signal::unix::{self, SignalKind}, sync::{watch, Semaphore}, task::JoinSet};
+
+use crate::{allowlist::Allowlist, basic_auth::BasicAuth, cors::Cors, fleet::Fleet, format::Format, gzip, history::History, limit::{ConnectionLimit, LimitedListener}, metrics::{Filter, Handler}, sd::ServiceDiscovery, tls::TlsConfig};
+
+const SCRAPE_TIMEOUT_OFFSET: Duration = Duration::from_millis(500);
+
+/// Address to listen on.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ListenAddress {
+    Tcp(SocketAddr),
+    Unix(PathBuf),
+}
 
 pub struct Server<MetricsHandler> {
-    port: u16,
+    addresses: Vec<ListenAddress>,
+    metrics_path: String,
+    ipv6_only: bool,
+    tls: Option<watch::Receiver<TlsConfig>>,
+    basic_auth: Option<Arc<BasicAuth>>,
+    request_timeout: Option<Duration>,
+    max_connections: Option<usize>,
+    max_in_flight_requests: Option<usize>,
+    admin_address: Option<SocketAddr>,
+    http2: bool,
+    allowlist: Option<Allowlist>,
+    cors: Option<Cors>,
     metrics_handler: MetricsHandler,
+    probe_targets: HashMap<String, MetricsHandler>,
+    fleet: Option<Fleet>,
+    history: Option<History>,
+}
+
+// Address of the client, available to middlewares through ConnectInfo, None on a unix socket
+#[derive(Clone, Copy, Debug)]
+struct RemoteAddress(Option<SocketAddr>);
+
+enum Bound {
+    Tcp(std::net::TcpListener),
+    Unix(UnixListener),
 }
 
 impl<MetricsHandler> Server<MetricsHandler>
 where
     MetricsHandler: Handler + Send + Sync + 'static,
 {
-    pub fn new(port: u16, metrics_handler: MetricsHandler) -> Self {
+    pub fn new(addresses: Vec<ListenAddress>, metrics_handler: MetricsHandler) -> Self {
         Self {
-            port,
+            addresses,
+            metrics_path: "/metrics".to_string(),
+            ipv6_only: false,
+            tls: None,
+            basic_auth: None,
+            request_timeout: None,
+            max_connections: None,
+            max_in_flight_requests: None,
+            admin_address: None,
+            http2: true,
+            allowlist: None,
+            cors: None,
             metrics_handler,
+            probe_targets: HashMap::new(),
+            fleet: None,
+            history: None,
+        }
+    }
+
+    /// Serves the metrics on `metrics_path` instead of /metrics.
+    pub fn metrics_path(self, metrics_path: impl Into<String>) -> Self {
+        Self {
+            metrics_path: metrics_path.into(),
+            ..self
+        }
+    }
+
+    /// Stops an IPv6 address from also accepting IPv4 connections, which it does by default.
+    pub fn ipv6_only(self, ipv6_only: bool) -> Self {
+        Self {
+            ipv6_only,
+            ..self
+        }
+    }
+
+    /// Serves HTTPS instead of HTTP when `tls` is given, reloading the certificate whenever a config is sent through it.
+    pub fn tls(self, tls: Option<watch::Receiver<TlsConfig>>) -> Self {
+        Self {
+            tls,
+            ..self
+        }
+    }
+
+    /// Requires the credentials of one of the users when `basic_auth` is given.
+    pub fn basic_auth(self, basic_auth: Option<Arc<BasicAuth>>) -> Self {
+        Self {
+            basic_auth,
+            ..self
+        }
+    }
+
+    /// Responds with 503 to requests taking longer than `request_timeout`, such as ones waiting for a hung collector.
+    pub fn request_timeout(self, request_timeout: Duration) -> Self {
+        Self {
+            request_timeout: Some(request_timeout),
+            ..self
+        }
+    }
+
+    /// Closes connections accepted while `max_connections` are open.
+    pub fn max_connections(self, max_connections: usize) -> Self {
+        Self {
+            max_connections: Some(max_connections),
+            ..self
+        }
+    }
+
+    /// Responds with 503 to scrapes made while `max_in_flight_requests` are being handled.
+    pub fn max_in_flight_requests(self, max_in_flight_requests: usize) -> Self {
+        Self {
+            max_in_flight_requests: Some(max_in_flight_requests),
+            ..self
+        }
+    }
+
+    /// Serves the health, readiness and events endpoints on `admin_address` instead of alongside the metrics when it is
+    /// given, together with the endpoints to list, turn on and off, and collect the metrics under /collectors.
+    ///
+    /// Starting fails when it isn't a loopback address and no `basic_auth` is given to protect those endpoints.
+    pub fn admin_address(self, admin_address: Option<SocketAddr>) -> Self {
+        Self {
+            admin_address,
+            ..self
+        }
+    }
+
+    /// Stops offering HTTP/2 to TLS clients through ALPN when `http2` is false.
+    ///
+    /// HTTP/2 over cleartext (h2c) with prior knowledge is accepted regardless.
+    pub fn http2(self, http2: bool) -> Self {
+        Self {
+            http2,
+            ..self
+        }
+    }
+
+    /// Responds with 403 to scrapes from outside the networks of `allowlist` when it is given.
+    pub fn allowlist(self, allowlist: Option<Allowlist>) -> Self {
+        Self {
+            allowlist,
+            ..self
+        }
+    }
+
+    /// Lets browsers fetch from the origins of `cors` when it is given, answering preflight requests without credentials.
+    pub fn cors(self, cors: Option<Cors>) -> Self {
+        Self {
+            cors,
+            ..self
+        }
+    }
+
+    /// Serves the metrics of each of `probe_targets` on /probe?target=<name>, like the probes of blackbox_exporter.
+    pub fn probe_targets(self, probe_targets: HashMap<String, MetricsHandler>) -> Self {
+        Self {
+            probe_targets,
+            ..self
+        }
+    }
+
+    /// Serves the merged metrics of the instances of `fleet` on /fleet when it is given.
+    pub fn fleet(self, fleet: Option<Fleet>) -> Self {
+        Self {
+            fleet,
+            ..self
+        }
+    }
+
+    /// Serves the samples of a metric recorded in `history` on /history?metric=<name>&since=<duration> when it is given.
+    pub fn history(self, history: Option<History>) -> Self {
+        Self {
+            history,
+            ..self
         }
     }
 
     pub async fn start(self) -> anyhow::Result<()> {
-        let app = Router::new()
-            .route("/metrics", get(handle))
-            .with_state(Arc::new(self.metrics_handler));
+        // The endpoints under /collectors can turn the metrics off, which anyone reaching the admin port could do otherwise
+        if let Some(admin_address) = self.admin_address
+            && !admin_address.ip().is_loopback()
+            && self.basic_auth.is_none()
+        {
+            anyhow::bail!("admin address must be a loopback address unless basic auth is configured: {admin_address}");
+        }
+
+        let metrics_handler = Arc::new(self.metrics_handler);
+        let mut app = router(&self.metrics_path, metrics_handler.clone());
+        if !self.probe_targets.is_empty() || self.fleet.is_some() {
+            let mut probe_targets = self.probe_targets.keys().cloned().collect::<Vec<_>>();
+            probe_targets.sort();
+            let fleet_instances = self.fleet.as_ref().map(|fleet| fleet.instances().to_vec()).unwrap_or_default();
+            let sd = ServiceDiscovery::new(fleet_instances, probe_targets, self.tls.is_some());
+            app = app.route("/sd", get(service_discovery).with_state(Arc::new(sd)));
+        }
+        if !self.probe_targets.is_empty() {
+            app = app.merge(Router::new().route("/probe", get(probe)).with_state(Arc::new(self.probe_targets)));
+        }
+        if let Some(fleet) = self.fleet {
+            app = app.merge(Router::new().route("/fleet", get(handle)).with_state(Arc::new(fleet)));
+        }
+        if let Some(history) = self.history {
+            app = app.merge(Router::new().route("/history", get(query_history)).with_state(Arc::new(history)));
+        }
+        app = app.layer(middleware::from_fn(compress));
+        let mut admin = Router::new()
+            .route("/readyz", get(readyz))
+            .route("/events", get(events))
+            .with_state(metrics_handler.clone());
+        if self.admin_address.is_none() {
+            app = app.merge(admin);
+            admin = Router::new();
+        }
+        // Over the admin endpoints merged too, which collect the metrics and tell the errors of the collectors
+        if let Some(allowlist) = self.allowlist {
+            app = app.layer(middleware::from_fn_with_state(Arc::new(allowlist), allow));
+        }
+        if let Some(max_in_flight_requests) = self.max_in_flight_requests {
+            app = app.layer(middleware::from_fn_with_state(Arc::new(Semaphore::new(max_in_flight_requests)), limit_in_flight));
+        }
+        let basic_auth = self.basic_auth;
+        if let Some(basic_auth) = &basic_auth {
+            app = app.layer(middleware::from_fn_with_state(basic_auth.clone(), authenticate));
+        }
+        // Only on the admin port, which can be kept from the network unlike the metrics
+        if self.admin_address.is_some() {
+            let mut collectors = Router::new()
+                .route("/collectors", get(collectors))
+                .route("/collectors/{name}/enable", post(enable_collector))
+                .route("/collectors/{name}/disable", post(disable_collector))
+                .route("/collectors/{name}/collect", post(collect_collector))
+                .with_state(metrics_handler);
+            if let Some(basic_auth) = basic_auth {
+                collectors = collectors.layer(middleware::from_fn_with_state(basic_auth, authenticate));
+            }
+            admin = admin.merge(collectors);
+        }
+        // Health checks are usually made without credentials
+        match self.admin_address {
+            Some(_) => admin = admin.route("/healthz", get(healthz)),
+            None => app = app.route("/healthz", get(healthz)),
+        }
+        if let Some(cors) = self.cors {
+            let cors = Arc::new(cors);
+            app = app.layer(middleware::from_fn_with_state(cors.clone(), allow_origin));
+            admin = admin.layer(middleware::from_fn_with_state(cors, allow_origin));
+        }
+        if let Some(request_timeout) = self.request_timeout {
+            app = app.layer(middleware::from_fn_with_state(request_timeout, timeout));
+            admin = admin.layer(middleware::from_fn_with_state(request_timeout, timeout));
+        }
+
+        if let Some(admin_address) = self.admin_address {
+            let listener = TcpListener::bind(admin_address).await?;
+            tracing::info!("listening on {} for admin endpoints", listener.local_addr()?);
+            tokio::spawn(async move {
+                if let Err(err) = axum::serve(listener, admin).with_graceful_shutdown(shutdown_signal()).await {
+                    tracing::error!("admin server error\nError: {err:?}");
+                }
+            });
+        }
+
+        let listeners = match activated_fd(env::var("LISTEN_PID").ok().as_deref(), env::var("LISTEN_FDS").ok().as_deref(), process::id()) {
+            Some(fd) => {
+                tracing::info!("using the socket passed by systemd");
+                // SAFETY: systemd passes the listening socket as this fd to this process only
+                let listener = unsafe { std::net::TcpListener::from_raw_fd(fd) };
+                listener.set_nonblocking(true)?;
+                vec![Bound::Tcp(listener)]
+            },
+            None => self.addresses
+                .iter()
+                .map(|address| match address {
+                    ListenAddress::Tcp(address) => Ok(Bound::Tcp(bind(*address, self.ipv6_only)?)),
+                    ListenAddress::Unix(_) if self.tls.is_some() => anyhow::bail!("TLS isn't supported on unix sockets: {address}"),
+                    ListenAddress::Unix(path) => Ok(Bound::Unix(bind_unix(path)?)),
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?,
+        };
+        // Shared by all the listeners
+        let connection_limit = ConnectionLimit::new(self.max_connections.unwrap_or(Semaphore::MAX_PERMITS));
 
-        let listener = TcpListener::bind((Ipv4Addr::UNSPECIFIED, self.port)).await?;
+        let tls = match self.tls {
+            Some(mut tls) => {
+                let config = RustlsConfig::from_config(Arc::new(server_config(&tls.borrow_and_update(), self.http2)?));
+                // Picks up renewed certificates without dropping the listener
+                tokio::spawn({
+                    let config = config.clone();
+                    async move {
+                        while tls.changed().await.is_ok() {
+                            let tls = tls.borrow_and_update().clone();
+                            match server_config(&tls, self.http2) {
+                                Ok(server_config) => {
+                                    config.reload_from_config(Arc::new(server_config));
+                                    tracing::info!("reloaded TLS certificate");
+                                },
+                                Err(err) => tracing::error!("failed to reload TLS certificate, keeping the current one\nError: {err:?}"),
+                            }
+                        }
+                    }
+                });
+                let handle = Handle::new();
+                tokio::spawn({
+                    let handle = handle.clone();
+                    async move {
+                        shutdown_signal().await;
+                        handle.graceful_shutdown(None);
+                    }
+                });
 
-        tracing::info!("listening on {}", listener.local_addr()?);
+                tracing::info!("serving over TLS");
+                Some((config, handle))
+            },
+            None => None,
+        };
 
-        axum::serve(listener, app)
-            .with_graceful_shutdown(shutdown_signal())
-            .await?;
+        let mut servers = JoinSet::new();
+        for listener in listeners {
+            let service = app.clone().into_make_service_with_connect_info::<RemoteAddress>();
+            match (listener, &tls) {
+                (Bound::Tcp(listener), Some((config, handle))) => {
+                    tracing::info!("listening on {}", listener.local_addr()?);
+                    let server = axum_server::from_tcp(listener)
+                        .acceptor(RustlsAcceptor::new(config.clone()).acceptor(connection_limit.clone()))
+                        .handle(handle.clone());
+                    servers.spawn(async move { server.serve(service).await });
+                },
+                (Bound::Tcp(listener), None) => {
+                    tracing::info!("listening on {}", listener.local_addr()?);
+                    let server = axum::serve(connection_limit.clone().listener(TcpListener::from_std(listener)?), service)
+                        .with_graceful_shutdown(shutdown_signal());
+                    servers.spawn(async move { server.await });
+                },
+                (Bound::Unix(listener), _) => {
+                    if let Some(path) = listener.local_addr()?.as_pathname() {
+                        tracing::info!("listening on unix:{}", path.display());
+                    }
+                    let server = axum::serve(connection_limit.clone().listener(listener), service)
+                        .with_graceful_shutdown(shutdown_signal());
+                    servers.spawn(async move { server.await });
+                },
+            }
+        }
+        while let Some(result) = servers.join_next().await {
+            result??;
+        }
 
         Ok(())
     }
 }
 
+/// Router serving the metrics of `metrics_handler` on `metrics_path`, without any of the middlewares of [`Server`].
+pub fn router<H>(metrics_path: &str, metrics_handler: Arc<H>) -> Router
+where
+    H: Handler + Send + Sync + 'static,
+{
+    Router::new().route(metrics_path, get(handle)).with_state(metrics_handler)
+}
+
+fn server_config(tls: &TlsConfig, http2: bool) -> anyhow::Result<ServerConfig> {
+    let mut config = tls.server_config()?;
+    config.alpn_protocols = match http2 {
+        true => vec![b"h2".to_vec(), b"http/1.1".to_vec()],
+        false => vec![b"http/1.1".to_vec()],
+    };
+
+    Ok(config)
+}
+
+impl Display for ListenAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tcp(address) => write!(f, "{address}"),
+            Self::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+impl Connected<IncomingStream<'_, LimitedListener<TcpListener>>> for RemoteAddress {
+    fn connect_info(stream: IncomingStream<'_, LimitedListener<TcpListener>>) -> Self {
+        Self(Some(*stream.remote_addr()))
+    }
+}
+
+impl Connected<IncomingStream<'_, LimitedListener<UnixListener>>> for RemoteAddress {
+    fn connect_info(_: IncomingStream<'_, LimitedListener<UnixListener>>) -> Self {
+        Self(None)
+    }
+}
+
+impl Connected<SocketAddr> for RemoteAddress {
+    fn connect_info(address: SocketAddr) -> Self {
+        Self(Some(address))
+    }
+}
+
+// The first fd passed by systemd socket activation, see sd_listen_fds(3)
+fn activated_fd(listen_pid: Option<&str>, listen_fds: Option<&str>, pid: u32) -> Option<RawFd> {
+    const SD_LISTEN_FDS_START: RawFd = 3;
+
+    let listen_pid = listen_pid?.parse::<u32>().ok()?;
+    let listen_fds = listen_fds?.parse::<u32>().ok()?;
+    if listen_pid != pid || listen_fds == 0 {
+        return None;
+    }
+    if listen_fds > 1 {
+        tracing::warn!("only the first of {listen_fds} sockets passed by systemd is used");
+    }
+
+    Some(SD_LISTEN_FDS_START)
+}
+
+// Sets IPV6_V6ONLY explicitly, since its default depends on net.ipv6.bindv6only
+fn bind(address: SocketAddr, ipv6_only: bool) -> anyhow::Result<std::net::TcpListener> {
+    let socket = Socket::new(Domain::for_address(address), Type::STREAM, None)?;
+    if address.is_ipv6() {
+        socket.set_only_v6(ipv6_only)?;
+    }
+    socket.set_reuse_address(true)?;
+    socket.set_nonblocking(true)?;
+    socket.bind(&address.into())?;
+    socket.listen(1024)?;
+
+    Ok(socket.into())
+}
+
+// Replaces the socket file left by a previous run, but nothing else
+fn bind_unix(path: &Path) -> anyhow::Result<UnixListener> {
+    if fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_type().is_socket()) {
+        fs::remove_file(path).with_context(|| format!("failed to remove stale socket: {path:?}"))?;
+    }
+
+    UnixListener::bind(path).with_context(|| format!("failed to bind: {path:?}"))
+}
+
+#[tracing::instrument(skip_all)]
+async fn handle<S>(State(service): State<Arc<S>>, RawQuery(query): RawQuery, headers: HeaderMap) -> Response
+where
+    S: Handler,
+{
+    scrape(service.as_ref(), query, headers).await
+}
+
 #[tracing::instrument(skip_all)]
-async fn handle<S>(State(service): State<Arc<S>>) -> impl IntoResponse
+async fn probe<S>(State(targets): State<Arc<HashMap<String, S>>>, RawQuery(query): RawQuery, headers: HeaderMap) -> Response
 where
     S: Handler,
 {
-    match service.handle().await {
+    let Some(target) = query.as_deref().and_then(|query| query_parameter(query, "target")) else {
+        return (StatusCode::BAD_REQUEST, "target parameter is missing").into_response();
+    };
+    // Only the configured targets, so that a scrape can't make the exporter connect anywhere
+    let Some(service) = targets.get(&target) else {
+        return (StatusCode::BAD_REQUEST, format!("unknown target: {target}")).into_response();
+    };
+
+    scrape(service, query, headers).await
+}
+
+async fn scrape<S>(service: &S, query: Option<String>, headers: HeaderMap) -> Response
+where
+    S: Handler,
+{
+    let filter = query.as_deref().map(Filter::from_query).unwrap_or_default();
+    let unknown = filter.unknown(&service.names());
+    if !unknown.is_empty() {
+        return (StatusCode::BAD_REQUEST, format!("unknown metrics: {}", unknown.join(","))).into_response();
+    }
+
+    let format = Format::negotiate(headers.get(ACCEPT).and_then(|v| v.to_str().ok()));
+    let timeout = headers
+        .get("X-Prometheus-Scrape-Timeout-Seconds")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| Duration::try_from_secs_f64(v.parse().ok()?).ok())
+        // Leaves time to encode and send the response before Prometheus gives up
+        .map(|timeout| timeout.saturating_sub(SCRAPE_TIMEOUT_OFFSET).max(timeout / 2));
+    match service.handle(&filter, timeout).await.and_then(|res| format.encode(res)) {
         Ok(res) => (
             StatusCode::OK,
-            [(CONTENT_TYPE, "application/openmetrics-text; version=1.0.0; charset=utf-8")],
+            [(CONTENT_TYPE, format.content_type())],
             res,
         ).into_response(),
         Err(err) => {
@@ -56,6 +546,259 @@ where
     }
 }
 
+#[tracing::instrument(skip_all)]
+async fn readyz<S>(State(service): State<Arc<S>>) -> impl IntoResponse
+where
+    S: Handler,
+{
+    let statuses = service.readiness().await;
+    let status = match statuses.iter().all(|(_, ready)| *ready) {
+        true => StatusCode::OK,
+        false => StatusCode::SERVICE_UNAVAILABLE,
+    };
+    let body = statuses
+        .iter()
+        .map(|(name, ready)| format!("{name}: {}\n", if *ready { "ok" } else { "not ready" }))
+        .collect::<String>();
+
+    (status, body)
+}
+
+async fn events<S>(State(service): State<Arc<S>>) -> Response
+where
+    S: Handler,
+{
+    match serde_json::to_string(&service.latest_events()) {
+        Ok(body) => (StatusCode::OK, [(CONTENT_TYPE, "application/json")], body).into_response(),
+        Err(err) => {
+            tracing::error!("{err:?}");
+            (StatusCode::INTERNAL_SERVER_ERROR, "").into_response()
+        },
+    }
+}
+
+async fn collectors<S>(State(service): State<Arc<S>>) -> impl IntoResponse
+where
+    S: Handler,
+{
+    service
+        .toggles()
+        .iter()
+        .map(|(name, enabled)| format!("{name}: {}\n", if *enabled { "enabled" } else { "disabled" }))
+        .collect::<String>()
+}
+
+async fn enable_collector<S>(State(service): State<Arc<S>>, UrlPath(name): UrlPath<String>) -> Response
+where
+    S: Handler,
+{
+    toggle_collector(service.as_ref(), &name, true)
+}
+
+async fn disable_collector<S>(State(service): State<Arc<S>>, UrlPath(name): UrlPath<String>) -> Response
+where
+    S: Handler,
+{
+    toggle_collector(service.as_ref(), &name, false)
+}
+
+fn toggle_collector<S: Handler>(service: &S, name: &str, enabled: bool) -> Response {
+    if !service.toggle(name, enabled) {
+        return (StatusCode::NOT_FOUND, format!("unknown collector: {name}")).into_response();
+    }
+
+    let state = if enabled { "enabled" } else { "disabled" };
+    tracing::info!("{state} {name} collector");
+    (StatusCode::OK, format!("{name}: {state}\n")).into_response()
+}
+
+#[tracing::instrument(skip(service))]
+async fn collect_collector<S>(State(service): State<Arc<S>>, UrlPath(name): UrlPath<String>) -> Response
+where
+    S: Handler,
+{
+    let Some(results) = service.collect_now(&name).await else {
+        return (StatusCode::NOT_FOUND, format!("unknown collector: {name}")).into_response();
+    };
+
+    let status = match results.iter().all(|(_, result)| result.is_ok()) {
+        true => StatusCode::OK,
+        false => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    let body = results
+        .iter()
+        .map(|(name, result)| match result {
+            Ok(()) => format!("{name}: ok\n"),
+            Err(err) => format!("{name}: {err:#}\n"),
+        })
+        .collect::<String>();
+
+    (status, body).into_response()
+}
+
+async fn service_discovery(State(sd): State<Arc<ServiceDiscovery>>, uri: Uri, headers: HeaderMap) -> Response {
+    // HTTP/2 gives the authority in the URI instead of the header
+    let address = headers.get(HOST).and_then(|v| v.to_str().ok()).or_else(|| uri.authority().map(|authority| authority.as_str()));
+    let Some(address) = address else {
+        return (StatusCode::BAD_REQUEST, "Host header is missing").into_response();
+    };
+
+    match serde_json::to_string(&sd.target_groups(address)) {
+        Ok(body) => (StatusCode::OK, [(CONTENT_TYPE, "application/json")], body).into_response(),
+        Err(err) => {
+            tracing::error!("{err:?}");
+            (StatusCode::INTERNAL_SERVER_ERROR, "").into_response()
+        },
+    }
+}
+
+async fn query_history(State(history): State<Arc<History>>, RawQuery(query): RawQuery) -> Response {
+    let query = query.unwrap_or_default();
+    let Some(metric) = query_parameter(&query, "metric") else {
+        return (StatusCode::BAD_REQUEST, "metric parameter is missing").into_response();
+    };
+    let since = match query_parameter(&query, "since").map(|since| humantime::parse_duration(&since)).transpose() {
+        Ok(since) => since.unwrap_or(Duration::from_secs(60 * 60)),
+        Err(err) => return (StatusCode::BAD_REQUEST, format!("invalid since parameter: {err}")).into_response(),
+    };
+    let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default();
+
+    let samples = match history.query(&metric, now.saturating_sub(since).as_secs()).await {
+        Ok(samples) => samples,
+        Err(err) => {
+            tracing::error!("failed to query the history\nError: {err:?}");
+            return (StatusCode::INTERNAL_SERVER_ERROR, "failed to query the history").into_response();
+        },
+    };
+    match serde_json::to_string(&samples) {
+        Ok(body) => (StatusCode::OK, [(CONTENT_TYPE, "application/json")], body).into_response(),
+        Err(err) => {
+            tracing::error!("{err:?}");
+            (StatusCode::INTERNAL_SERVER_ERROR, "").into_response()
+        },
+    }
+}
+
+// Value of the query parameter `name` if it isn't empty
+fn query_parameter(query: &str, name: &str) -> Option<String> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| percent_decode_str(&value.replace('+', " ")).decode_utf8_lossy().into_owned())
+        .filter(|value| !value.is_empty())
+}
+
+// Doesn't collect anything, so that frequent health checks don't run commands such as vcgencmd
+async fn healthz() -> impl IntoResponse {
+    (StatusCode::OK, "OK")
+}
+
+async fn timeout(State(request_timeout): State<Duration>, request: Request, next: Next) -> Response {
+    match tokio::time::timeout(request_timeout, next.run(request)).await {
+        Ok(response) => response,
+        Err(_) => {
+            let message = format!("request timed out after {}", humantime::format_duration(request_timeout));
+            tracing::warn!("{message}");
+            (StatusCode::SERVICE_UNAVAILABLE, message).into_response()
+        },
+    }
+}
+
+// Compresses the response for clients accepting gzip, since the metrics grow large over slow WiFi links
+async fn compress(request: Request, next: Next) -> Response {
+    let accepts_gzip = request.headers().get(ACCEPT_ENCODING).and_then(|v| v.to_str().ok()).is_some_and(gzip::accepts);
+    let mut response = next.run(request).await;
+    response.headers_mut().append(VARY, HeaderValue::from_static("accept-encoding"));
+    if !accepts_gzip || response.headers().contains_key(CONTENT_ENCODING) {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let body = match body::to_bytes(body, usize::MAX).await {
+        Ok(body) => body,
+        Err(err) => {
+            tracing::error!("failed to read the response to compress\nError: {err:?}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        },
+    };
+    parts.headers.insert(CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+    parts.headers.remove(CONTENT_LENGTH);
+
+    Response::from_parts(parts, Body::from(gzip::compress(&body)))
+}
+
+async fn limit_in_flight(State(semaphore): State<Arc<Semaphore>>, request: Request, next: Next) -> Response {
+    // Rejects rather than queues, so that a misbehaving scraper can't pile requests up
+    let Ok(_permit) = semaphore.try_acquire() else {
+        tracing::warn!("rejecting a request beyond the in-flight request limit");
+        return (StatusCode::SERVICE_UNAVAILABLE, "too many requests in flight").into_response();
+    };
+
+    next.run(request).await
+}
+
+async fn allow(
+    State(allowlist): State<Arc<Allowlist>>,
+    ConnectInfo(RemoteAddress(address)): ConnectInfo<RemoteAddress>,
+    request: Request,
+    next: Next,
+) -> Response {
+    // Access to a unix socket is controlled by its file permissions instead
+    let Some(address) = address else {
+        return next.run(request).await;
+    };
+    if !allowlist.allows(address.ip()) {
+        tracing::warn!("rejecting a scrape from {address} outside the allowed networks");
+        return (StatusCode::FORBIDDEN, "Forbidden").into_response();
+    }
+
+    next.run(request).await
+}
+
+async fn allow_origin(State(cors): State<Arc<Cors>>, request: Request, next: Next) -> Response {
+    let Some(origin) = request.headers().get(ORIGIN).and_then(|v| v.to_str().ok()) else {
+        return next.run(request).await;
+    };
+    let Some(allowed) = cors.allow_origin(origin).and_then(|v| HeaderValue::from_str(v).ok()) else {
+        return next.run(request).await;
+    };
+
+    let preflight = request.method() == Method::OPTIONS && request.headers().contains_key(ACCESS_CONTROL_REQUEST_METHOD);
+    let mut response = match preflight {
+        true => {
+            let mut response = StatusCode::NO_CONTENT.into_response();
+            let headers = response.headers_mut();
+            headers.insert(ACCESS_CONTROL_ALLOW_METHODS, HeaderValue::from_static("GET"));
+            headers.insert(ACCESS_CONTROL_ALLOW_HEADERS, HeaderValue::from_static("accept, authorization"));
+            headers.insert(ACCESS_CONTROL_MAX_AGE, HeaderValue::from_static("600"));
+            response
+        },
+        false => next.run(request).await,
+    };
+    let headers = response.headers_mut();
+    // Credentials aren't allowed along with the wildcard
+    if allowed != "*" {
+        headers.insert(ACCESS_CONTROL_ALLOW_CREDENTIALS, HeaderValue::from_static("true"));
+    }
+    headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, allowed);
+    headers.append(VARY, HeaderValue::from_static("origin"));
+
+    response
+}
+
+async fn authenticate(State(basic_auth): State<Arc<BasicAuth>>, request: Request, next: Next) -> Response {
+    let authorization = request.headers().get(AUTHORIZATION).and_then(|v| v.to_str().ok());
+    match authorization {
+        Some(authorization) if basic_auth.verify(authorization).await => next.run(request).await,
+        _ => (
+            StatusCode::UNAUTHORIZED,
+            [(WWW_AUTHENTICATE, "Basic")],
+            "Unauthorized",
+        ).into_response(),
+    }
+}
+
 async fn shutdown_signal() {
     let mut sigint = unix::signal(SignalKind::interrupt()).expect("SIGINT error");
     let mut sigterm = unix::signal(SignalKind::terminate()).expect("SIGTERM error");
@@ -65,3 +808,59 @@ async fn shutdown_signal() {
         _ = sigterm.recv() => {},
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{net::TcpListener, time::Duration};
+
+    use tokio::{io::{AsyncReadExt, AsyncWriteExt}, net::TcpStream};
+
+    use crate::{allowlist::Allowlist, metrics::MetricsHandler, server::{activated_fd, query_parameter, ListenAddress, Server}};
+
+    #[test]
+    fn activated() {
+        assert_eq!(activated_fd(Some("100"), Some("1"), 100), Some(3));
+        // Passed to another process, e.g. the parent of this one
+        assert_eq!(activated_fd(Some("99"), Some("1"), 100), None);
+        assert_eq!(activated_fd(Some("100"), Some("0"), 100), None);
+        assert_eq!(activated_fd(None, None, 100), None);
+    }
+
+    #[test]
+    fn target() {
+        assert_eq!(query_parameter("target=pi-01", "target").as_deref(), Some("pi-01"));
+        assert_eq!(query_parameter("collect[]=throttled&target=pi%4010.0.0.2", "target").as_deref(), Some("pi@10.0.0.2"));
+        assert_eq!(query_parameter("target=", "target"), None);
+        assert_eq!(query_parameter("collect[]=throttled", "target"), None);
+    }
+
+    #[tokio::test]
+    async fn public_admin_address() {
+        let server = Server::new(Vec::new(), MetricsHandler::default()).admin_address(Some("0.0.0.0:8022".parse().unwrap()));
+        let err = server.start().await.unwrap_err();
+
+        assert!(err.to_string().starts_with("admin address must be a loopback address"));
+    }
+
+    #[tokio::test]
+    async fn disallowed_admin_endpoints() {
+        let address = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let server = Server::new(vec![ListenAddress::Tcp(address)], MetricsHandler::default())
+            .allowlist(Some(Allowlist::new(vec!["10.0.0.0/8".parse().unwrap()])));
+        tokio::spawn(server.start());
+
+        for path in ["/readyz", "/events"] {
+            let mut stream = loop {
+                match TcpStream::connect(address).await {
+                    Ok(stream) => break stream,
+                    Err(_) => tokio::time::sleep(Duration::from_millis(10)).await,
+                }
+            };
+            stream.write_all(format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n").as_bytes()).await.unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).await.unwrap();
+
+            assert!(response.starts_with("HTTP/1.1 403"), "{path}: {response}");
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use std::{net::{Ipv4Addr, Ipv6Addr, SocketAddr}, time::Duration};
+
+use anyhow::Context;
+use tokio::{
+    net::{self, UdpSocket},
+    time::{self, MissedTickBehavior},
+};
+
+use crate::{format::statsd, metrics::{Filter, Handler}};
+
+// Keeps datagrams within the MTU of Ethernet with the IP and UDP headers
+const MAX_DATAGRAM_SIZE: usize = 1432;
+
+/// Sends the metrics to a StatsD or DogStatsD server over UDP on an interval.
+pub struct StatsD<H> {
+    address: String,
+    interval: Duration,
+    handler: H,
+    prefix: String,
+    tags: bool,
+}
+
+impl<H> StatsD<H>
+where
+    H: Handler,
+{
+    /// `address` is a host with an optional port, 8125 by default.
+    pub fn new(address: impl ToString, interval: Duration, handler: H) -> Self {
+        Self {
+            address: address.to_string(),
+            interval,
+            handler,
+            prefix: String::new(),
+            tags: false,
+        }
+    }
+
+    /// Dotted path prepended to the metric names.
+    pub fn prefix(self, prefix: impl ToString) -> Self {
+        Self {
+            prefix: prefix.to_string(),
+            ..self
+        }
+    }
+
+    /// Sends labels as DogStatsD tags rather than appending their values to the names.
+    pub fn tags(self, tags: bool) -> Self {
+        Self {
+            tags,
+            ..self
+        }
+    }
+
+    pub async fn start(self) {
+        let mut interval = time::interval(self.interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+
+            match time::timeout(self.interval, self.send()).await {
+                Ok(Ok(())) => {},
+                Ok(Err(err)) => tracing::error!("failed to send metrics to StatsD\nError: {err:?}"),
+                Err(_) => tracing::error!("sending metrics to StatsD timed out"),
+            }
+        }
+    }
+
+    pub async fn send(&self) -> anyhow::Result<()> {
+        let metrics = self.handler.handle(&Filter::default(), Some(self.interval)).await?;
+        let lines = statsd::encode(&metrics, &self.prefix, self.tags)?;
+
+        let address = match self.address.contains(':') {
+            true => self.address.clone(),
+            false => format!("{}:8125", self.address),
+        };
+        let address = net::lookup_host(&address)
+            .await
+            .with_context(|| format!("address resolution error: {address}"))?
+            .next()
+            .with_context(|| format!("address resolution error: {address}"))?;
+        let local = match address {
+            SocketAddr::V4(_) => SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)),
+            SocketAddr::V6(_) => SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0)),
+        };
+        let socket = UdpSocket::bind(local).await?;
+        socket.connect(address).await?;
+
+        for datagram in datagrams(&lines) {
+            socket.send(datagram.as_bytes()).await.with_context(|| format!("send error: {address}"))?;
+        }
+
+        Ok(())
+    }
+}
+
+// Joins lines with newlines into datagrams as large as possible
+fn datagrams(lines: &[String]) -> Vec<String> {
+    let mut datagrams = Vec::<String>::new();
+    for line in lines {
+        match datagrams.last_mut() {
+            Some(datagram) if datagram.len() + 1 + line.len() <= MAX_DATAGRAM_SIZE => {
+                datagram.push('\n');
+                datagram.push_str(line);
+            },
+            _ => datagrams.push(line.clone()),
+        }
+    }
+
+    datagrams
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::statsd::datagrams;
+
+    #[test]
+    fn split_datagrams() {
+        let lines = ["a".repeat(1000), "b".repeat(400), "c".repeat(100)];
+
+        assert_eq!(datagrams(&lines), [format!("{}\n{}", lines[0], lines[1]), lines[2].clone()]);
+    }
+}
@@ -0,0 +1,81 @@
+use std::{path::PathBuf, time::Duration};
+
+use anyhow::Context;
+use tokio::time::{self, MissedTickBehavior};
+
+use crate::{format::Format, metrics::{Filter, Handler}};
+
+/// Writes the metrics into a file on an interval, for the textfile collector of node_exporter to pick up.
+pub struct Textfile<H> {
+    path: PathBuf,
+    interval: Duration,
+    handler: H,
+}
+
+impl<H> Textfile<H>
+where
+    H: Handler,
+{
+    pub fn new(path: PathBuf, interval: Duration, handler: H) -> Self {
+        Self {
+            path,
+            interval,
+            handler,
+        }
+    }
+
+    pub async fn start(self) {
+        let mut interval = time::interval(self.interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+
+            if let Err(err) = self.write().await {
+                tracing::error!("failed to write textfile\nError: {err:?}");
+            }
+        }
+    }
+
+    /// Collects all the metrics and replaces the file with them at once, so that node_exporter never reads it half-written.
+    pub async fn write(&self) -> anyhow::Result<()> {
+        let metrics = self.handler.handle(&Filter::default(), Some(self.interval)).await?;
+        let content = Format::Text.encode(metrics)?;
+
+        // Ignored by node_exporter, which only reads *.prom
+        let mut temporary = self.path.clone().into_os_string();
+        temporary.push(".tmp");
+        tokio::fs::write(&temporary, content)
+            .await
+            .with_context(|| format!("file write error: {temporary:?}"))?;
+        tokio::fs::rename(&temporary, &self.path)
+            .await
+            .with_context(|| format!("file rename error: {:?}", self.path))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, process, time::Duration};
+
+    use crate::{metrics::MetricsHandler, textfile::Textfile};
+
+    #[tokio::test]
+    async fn write() {
+        let dir = std::env::temp_dir().join(format!("raspi-exporter-textfile-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("raspi.prom");
+
+        Textfile::new(path.clone(), Duration::from_secs(15), MetricsHandler::default()).write().await.unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        let entries = fs::read_dir(&dir).unwrap().count();
+        fs::remove_dir_all(&dir).unwrap();
+
+        // In the text format, without the temporary file left
+        assert!(content.starts_with("# HELP raspi_exporter_build_info "));
+        assert!(!content.contains("# EOF"));
+        assert_eq!(entries, 1);
+    }
+}
@@ -0,0 +1,137 @@
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+
+use crate::format::{samples, Labels};
+
+/// Threshold of the `thresholds` section of the config file, exceeded by a sample of `metric` with all of `labels` whose
+/// value is above `above` or below `below`.
+///
+/// ```toml
+/// [[thresholds]]
+/// name = "soc_hot"
+/// metric = "raspi_soc_temperature_celsius"
+/// above = 75
+///
+/// # More than 90% used, as the available space is less than 10% of the size
+/// [[thresholds]]
+/// name = "filesystem_full"
+/// metric = "raspi_filesystem_avail_bytes"
+/// ratio_of = "raspi_filesystem_size_bytes"
+/// below = 0.1
+/// labels = { mountpoint = "/" }
+/// ```
+///
+/// With `ratio_of`, the value is divided by that of the sample of `ratio_of` having the same labels.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Threshold {
+    pub name: String,
+    pub metric: String,
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+    #[serde(default)]
+    pub ratio_of: Option<String>,
+    #[serde(default)]
+    pub above: Option<f64>,
+    #[serde(default)]
+    pub below: Option<f64>,
+}
+
+/// Whether each sample matched by `thresholds` exceeds its threshold, labeled by the name of the threshold followed by the
+/// labels of the sample.
+pub fn evaluate(thresholds: &[Threshold], openmetrics: &str) -> anyhow::Result<Vec<(Labels, bool)>> {
+    let samples = samples(openmetrics).collect::<anyhow::Result<Vec<_>>>()?;
+
+    let mut results = Vec::new();
+    for threshold in thresholds {
+        let matched = samples
+            .iter()
+            .filter(|sample| sample.name == threshold.metric)
+            .filter(|sample| threshold.labels.iter().all(|(name, value)| sample.labels.contains(&(name.clone(), value.clone()))));
+        for sample in matched {
+            let value = match &threshold.ratio_of {
+                Some(ratio_of) => {
+                    // Skips the samples without a denominator rather than telling them apart from ones not exceeding
+                    let Some(denominator) = samples.iter().find(|other| other.name == *ratio_of && other.labels == sample.labels) else {
+                        continue;
+                    };
+                    sample.value / denominator.value
+                },
+                None => sample.value,
+            };
+            let exceeded = threshold.above.is_some_and(|above| value > above) || threshold.below.is_some_and(|below| value < below);
+
+            let labels = [("name".to_string(), threshold.name.clone())].into_iter().chain(sample.labels.iter().cloned()).collect();
+            results.push((labels, exceeded));
+        }
+    }
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::threshold::{evaluate, Threshold};
+
+    #[test]
+    fn evaluate_thresholds() {
+        let openmetrics = [
+            "# TYPE raspi_soc_temperature_celsius gauge",
+            "raspi_soc_temperature_celsius 76.5",
+            "# TYPE raspi_filesystem_size_bytes gauge",
+            "raspi_filesystem_size_bytes{mountpoint=\"/\",fstype=\"ext4\"} 1000",
+            "raspi_filesystem_size_bytes{mountpoint=\"/boot/firmware\",fstype=\"vfat\"} 1000",
+            "# TYPE raspi_filesystem_avail_bytes gauge",
+            "raspi_filesystem_avail_bytes{mountpoint=\"/\",fstype=\"ext4\"} 50",
+            "raspi_filesystem_avail_bytes{mountpoint=\"/boot/firmware\",fstype=\"vfat\"} 500",
+            "# EOF",
+        ].join("\n");
+        let thresholds = [
+            Threshold {
+                name: "soc_hot".to_string(),
+                metric: "raspi_soc_temperature_celsius".to_string(),
+                labels: BTreeMap::new(),
+                ratio_of: None,
+                above: Some(75.0),
+                below: None,
+            },
+            Threshold {
+                name: "filesystem_full".to_string(),
+                metric: "raspi_filesystem_avail_bytes".to_string(),
+                labels: BTreeMap::from([("fstype".to_string(), "ext4".to_string())]),
+                ratio_of: Some("raspi_filesystem_size_bytes".to_string()),
+                above: None,
+                below: Some(0.1),
+            },
+            Threshold {
+                name: "soc_cold".to_string(),
+                metric: "raspi_soc_temperature_celsius".to_string(),
+                labels: BTreeMap::new(),
+                ratio_of: None,
+                above: None,
+                below: Some(0.0),
+            },
+        ];
+        let labels = |labels: &[(&str, &str)]| labels.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect::<Vec<_>>();
+
+        assert_eq!(
+            evaluate(&thresholds, &openmetrics).unwrap(),
+            [
+                (labels(&[("name", "soc_hot")]), true),
+                (labels(&[("name", "filesystem_full"), ("mountpoint", "/"), ("fstype", "ext4")]), true),
+                (labels(&[("name", "soc_cold")]), false),
+            ],
+        );
+    }
+
+    #[test]
+    fn deserialize() {
+        let threshold = serde_yaml::from_str::<Threshold>("{ name: soc_hot, metric: raspi_soc_temperature_celsius, above: 75 }").unwrap();
+
+        assert_eq!(threshold.above, Some(75.0));
+        assert!(serde_yaml::from_str::<Threshold>("{ name: soc_hot, metric: raspi_soc_temperature_celsius, over: 75 }").is_err());
+    }
+}
@@ -0,0 +1,298 @@
+pub mod self_signed;
+
+use std::{path::PathBuf, sync::Arc};
+
+use anyhow::Context;
+use rustls::{
+    client::danger::HandshakeSignatureValid,
+    crypto::ring,
+    pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer, ServerName, UnixTime},
+    server::{
+        danger::{ClientCertVerified, ClientCertVerifier},
+        WebPkiClientVerifier,
+    },
+    version::{TLS12, TLS13},
+    CertificateError,
+    DigitallySignedStruct,
+    DistinguishedName,
+    NamedGroup,
+    RootCertStore,
+    ServerConfig,
+    SignatureScheme,
+    SupportedProtocolVersion,
+};
+use serde::Deserialize;
+use webpki::EndEntityCert;
+
+/// Certificate chain and private key to serve HTTPS with.
+#[derive(Clone, Debug)]
+pub struct TlsConfig {
+    cert_file: PathBuf,
+    key_file: PathBuf,
+    min_version: TlsVersion,
+    max_version: TlsVersion,
+    client_auth: ClientAuth,
+    cipher_suites: Vec<String>,
+    curve_preferences: Vec<String>,
+    prefer_server_cipher_suites: bool,
+    client_allowed_sans: Vec<String>,
+}
+
+// TLS 1.0 and 1.1 aren't implemented by rustls
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+pub enum TlsVersion {
+    #[serde(rename = "TLS12")]
+    Tls12,
+    #[serde(rename = "TLS13")]
+    Tls13,
+}
+
+/// Verification of client certificates against the CA certificates in a PEM file.
+#[derive(Clone, Debug)]
+pub enum ClientAuth {
+    None,
+    VerifyIfGiven(PathBuf),
+    Require(PathBuf),
+}
+
+// Verifier accepting only the client certificates that the inner one does and that are valid for one of the names
+#[derive(Debug)]
+struct AllowedSans {
+    inner: Arc<dyn ClientCertVerifier>,
+    names: Vec<ServerName<'static>>,
+}
+
+impl TlsConfig {
+    pub fn new(cert_file: impl Into<PathBuf>, key_file: impl Into<PathBuf>) -> Self {
+        Self {
+            cert_file: cert_file.into(),
+            key_file: key_file.into(),
+            min_version: TlsVersion::Tls12,
+            max_version: TlsVersion::Tls13,
+            client_auth: ClientAuth::None,
+            cipher_suites: Vec::new(),
+            curve_preferences: Vec::new(),
+            prefer_server_cipher_suites: false,
+            client_allowed_sans: Vec::new(),
+        }
+    }
+
+    pub fn versions(self, min_version: TlsVersion, max_version: TlsVersion) -> Self {
+        Self {
+            min_version,
+            max_version,
+            ..self
+        }
+    }
+
+    pub fn client_auth(self, client_auth: ClientAuth) -> Self {
+        Self {
+            client_auth,
+            ..self
+        }
+    }
+
+    /// Restricts the TLS 1.2 cipher suites to `cipher_suites` by their IANA names, all of them when empty. TLS 1.3 cipher
+    /// suites aren't configurable like in Go.
+    pub fn cipher_suites(self, cipher_suites: Vec<String>) -> Self {
+        Self {
+            cipher_suites,
+            ..self
+        }
+    }
+
+    /// Key exchange groups by the names of Go such as X25519 and CurveP256 in the order of preference, all of them when
+    /// empty.
+    pub fn curve_preferences(self, curve_preferences: Vec<String>) -> Self {
+        Self {
+            curve_preferences,
+            ..self
+        }
+    }
+
+    /// Picks the cipher suite by the order of the server rather than of the client.
+    pub fn prefer_server_cipher_suites(self, prefer_server_cipher_suites: bool) -> Self {
+        Self {
+            prefer_server_cipher_suites,
+            ..self
+        }
+    }
+
+    /// Accepts only the client certificates with one of `client_allowed_sans` in their DNS names or IP addresses, any
+    /// when empty.
+    pub fn client_allowed_sans(self, client_allowed_sans: Vec<String>) -> Self {
+        Self {
+            client_allowed_sans,
+            ..self
+        }
+    }
+
+    /// Loads the certificate, the private key and the client CA certificates, without serving them.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        self.server_config().map(|_| ())
+    }
+
+    pub(crate) fn server_config(&self) -> anyhow::Result<ServerConfig> {
+        let provider = Arc::new(self.provider()?);
+        let certs = CertificateDer::pem_file_iter(&self.cert_file)
+            .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+            .with_context(|| format!("certificate read error: {:?}", self.cert_file))?;
+        let key = PrivateKeyDer::from_pem_file(&self.key_file)
+            .with_context(|| format!("private key read error: {:?}", self.key_file))?;
+
+        let versions = [(TlsVersion::Tls12, &TLS12), (TlsVersion::Tls13, &TLS13)]
+            .into_iter()
+            .filter(|(version, _)| (self.min_version..=self.max_version).contains(version))
+            .map(|(_, version)| version)
+            .collect::<Vec<&SupportedProtocolVersion>>();
+        if versions.is_empty() {
+            anyhow::bail!("minimum TLS version is higher than the maximum");
+        }
+
+        let builder = ServerConfig::builder_with_provider(provider.clone()).with_protocol_versions(&versions)?;
+        let builder = match &self.client_auth {
+            ClientAuth::None => builder.with_no_client_auth(),
+            ClientAuth::VerifyIfGiven(ca_file) | ClientAuth::Require(ca_file) => {
+                let mut roots = RootCertStore::empty();
+                for cert in CertificateDer::pem_file_iter(ca_file).with_context(|| format!("client CA read error: {ca_file:?}"))? {
+                    roots.add(cert.with_context(|| format!("client CA read error: {ca_file:?}"))?)?;
+                }
+
+                let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider);
+                let verifier = match self.client_auth {
+                    ClientAuth::VerifyIfGiven(_) => verifier.allow_unauthenticated(),
+                    _ => verifier,
+                };
+                let verifier = verifier.build()?;
+                match self.client_allowed_sans.is_empty() {
+                    true => builder.with_client_cert_verifier(verifier),
+                    false => builder.with_client_cert_verifier(Arc::new(AllowedSans::new(verifier, &self.client_allowed_sans))),
+                }
+            },
+        };
+
+        let mut config = builder
+            .with_single_cert(certs, key)
+            .context("invalid certificate or private key")?;
+        config.ignore_client_order = self.prefer_server_cipher_suites;
+
+        Ok(config)
+    }
+
+    // Cipher suites and key exchange groups of ring restricted to the configured ones, skipping the names that Go knows
+    // but rustls doesn't implement, such as CBC cipher suites and P-521
+    fn provider(&self) -> anyhow::Result<rustls::crypto::CryptoProvider> {
+        let mut provider = ring::default_provider();
+
+        if !self.cipher_suites.is_empty() {
+            for name in self.cipher_suites.iter().filter(|name| !provider.cipher_suites.iter().any(|suite| is_cipher_suite(name, suite))) {
+                tracing::warn!("cipher suite {name} isn't supported and is ignored");
+            }
+            provider.cipher_suites.retain(|suite| {
+                suite.tls13().is_some() || self.cipher_suites.iter().any(|name| is_cipher_suite(name, suite))
+            });
+        }
+
+        if !self.curve_preferences.is_empty() {
+            let groups = self.curve_preferences
+                .iter()
+                .filter_map(|name| {
+                    let group = match name.as_str() {
+                        "X25519" => Some(NamedGroup::X25519),
+                        "CurveP256" => Some(NamedGroup::secp256r1),
+                        "CurveP384" => Some(NamedGroup::secp384r1),
+                        _ => None,
+                    };
+                    let group = group.and_then(|group| provider.kx_groups.iter().find(|kx_group| kx_group.name() == group));
+                    if group.is_none() {
+                        tracing::warn!("curve {name} isn't supported and is ignored");
+                    }
+                    group.copied()
+                })
+                .collect::<Vec<_>>();
+            if groups.is_empty() {
+                anyhow::bail!("none of the curve preferences is supported");
+            }
+            provider.kx_groups = groups;
+        }
+
+        Ok(provider)
+    }
+}
+
+impl AllowedSans {
+    // Names that aren't DNS names or IP addresses, such as email addresses and URIs, never match
+    fn new(inner: Arc<dyn ClientCertVerifier>, names: &[String]) -> Self {
+        let names = names
+            .iter()
+            .filter_map(|name| {
+                let server_name = ServerName::try_from(name.clone()).ok();
+                if server_name.is_none() {
+                    tracing::warn!("client SAN {name} isn't a DNS name or an IP address and matches no certificate");
+                }
+                server_name
+            })
+            .collect();
+
+        Self {
+            inner,
+            names,
+        }
+    }
+}
+
+impl ClientCertVerifier for AllowedSans {
+    fn offer_client_auth(&self) -> bool {
+        self.inner.offer_client_auth()
+    }
+
+    fn client_auth_mandatory(&self) -> bool {
+        self.inner.client_auth_mandatory()
+    }
+
+    fn root_hint_subjects(&self) -> &[DistinguishedName] {
+        self.inner.root_hint_subjects()
+    }
+
+    fn verify_client_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        now: UnixTime,
+    ) -> Result<ClientCertVerified, rustls::Error> {
+        let verified = self.inner.verify_client_cert(end_entity, intermediates, now)?;
+        let cert = EndEntityCert::try_from(end_entity).map_err(|_| rustls::Error::InvalidCertificate(CertificateError::BadEncoding))?;
+        match self.names.iter().any(|name| cert.verify_is_valid_for_subject_name(name).is_ok()) {
+            true => Ok(verified),
+            false => Err(rustls::Error::InvalidCertificate(CertificateError::NotValidForName)),
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.inner.supported_verify_schemes()
+    }
+}
+
+// Whether `name` is the IANA name of `suite`, or the name of Go lacking the hash of ChaCha20-Poly1305
+fn is_cipher_suite(name: &str, suite: &rustls::SupportedCipherSuite) -> bool {
+    let suite = format!("{:?}", suite.suite());
+    name == suite || name.ends_with("_CHACHA20_POLY1305") && suite == format!("{name}_SHA256")
+}
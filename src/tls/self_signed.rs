@@ -0,0 +1,193 @@
+//! Generator of a self-signed ECDSA P-256 certificate, so that scrapes can be encrypted before a real one is issued.
+
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    os::unix::fs::OpenOptionsExt,
+    path::Path,
+    time::SystemTime,
+};
+
+use anyhow::Context;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ring::{
+    rand::{SecureRandom, SystemRandom},
+    signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_ASN1_SIGNING},
+};
+
+const TAG_INTEGER: u8 = 0x02;
+const TAG_BIT_STRING: u8 = 0x03;
+const TAG_OCTET_STRING: u8 = 0x04;
+const TAG_UTF8_STRING: u8 = 0x0c;
+const TAG_UTC_TIME: u8 = 0x17;
+const TAG_GENERALIZED_TIME: u8 = 0x18;
+const TAG_SEQUENCE: u8 = 0x30;
+const TAG_SET: u8 = 0x31;
+const TAG_VERSION: u8 = 0xa0;
+const TAG_EXTENSIONS: u8 = 0xa3;
+const TAG_DNS_NAME: u8 = 0x82;
+
+// 1.2.840.10045.4.3.2
+const OID_ECDSA_WITH_SHA256: &[u8] = &[0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02];
+// 1.2.840.10045.2.1
+const OID_EC_PUBLIC_KEY: &[u8] = &[0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01];
+// 1.2.840.10045.3.1.7
+const OID_PRIME256V1: &[u8] = &[0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07];
+// 2.5.4.3
+const OID_COMMON_NAME: &[u8] = &[0x06, 0x03, 0x55, 0x04, 0x03];
+// 2.5.29.17
+const OID_SUBJECT_ALT_NAME: &[u8] = &[0x06, 0x03, 0x55, 0x1d, 0x11];
+
+// No well-defined expiration date, see RFC 5280 section 4.1.2.5
+const NOT_AFTER: &str = "99991231235959Z";
+
+/// Generates a certificate for `hostname` and its private key into the PEM files unless both of them exist.
+///
+/// Returns whether they were generated.
+pub fn ensure(cert_file: &Path, key_file: &Path, hostname: &str) -> anyhow::Result<bool> {
+    if cert_file.exists() && key_file.exists() {
+        return Ok(false);
+    }
+
+    let (cert, key) = generate(hostname, SystemTime::now())?;
+    for file in [cert_file, key_file] {
+        if let Some(dir) = file.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("failed to create directory: {dir:?}"))?;
+        }
+    }
+    write(key_file, &pem("PRIVATE KEY", &key), 0o600)?;
+    write(cert_file, &pem("CERTIFICATE", &cert), 0o644)?;
+
+    Ok(true)
+}
+
+/// Returns the DER of a certificate valid from `now` and its PKCS #8 private key.
+pub fn generate(hostname: &str, now: SystemTime) -> anyhow::Result<(Vec<u8>, Vec<u8>)> {
+    let rng = SystemRandom::new();
+    let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, &rng).context("failed to generate private key")?;
+    let key_pair = EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, pkcs8.as_ref(), &rng).context("invalid private key")?;
+
+    let mut serial = [0; 16];
+    rng.fill(&mut serial).context("failed to generate serial number")?;
+    // Positive and without a leading zero
+    serial[0] = serial[0] & 0x7f | 0x40;
+
+    let name = sequence(&[set(&[sequence(&[OID_COMMON_NAME.to_vec(), der(TAG_UTF8_STRING, hostname.as_bytes())])])]);
+    let alt_names = [hostname, "localhost"]
+        .into_iter()
+        .map(|name| der(TAG_DNS_NAME, name.as_bytes()))
+        .collect::<Vec<_>>();
+    let tbs = sequence(&[
+        der(TAG_VERSION, &der(TAG_INTEGER, &[2])),
+        der(TAG_INTEGER, &serial),
+        sequence(&[OID_ECDSA_WITH_SHA256.to_vec()]),
+        name.clone(),
+        sequence(&[der(TAG_UTC_TIME, utc_time(now)?.as_bytes()), der(TAG_GENERALIZED_TIME, NOT_AFTER.as_bytes())]),
+        name,
+        sequence(&[
+            sequence(&[OID_EC_PUBLIC_KEY.to_vec(), OID_PRIME256V1.to_vec()]),
+            bit_string(key_pair.public_key().as_ref()),
+        ]),
+        der(TAG_EXTENSIONS, &sequence(&[
+            sequence(&[OID_SUBJECT_ALT_NAME.to_vec(), der(TAG_OCTET_STRING, &sequence(&alt_names))]),
+        ])),
+    ]);
+    let signature = key_pair.sign(&rng, &tbs).context("failed to sign certificate")?;
+    let cert = sequence(&[tbs, sequence(&[OID_ECDSA_WITH_SHA256.to_vec()]), bit_string(signature.as_ref())]);
+
+    Ok((cert, pkcs8.as_ref().to_vec()))
+}
+
+fn write(path: &Path, content: &str, mode: u32) -> anyhow::Result<()> {
+    OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(mode)
+        .open(path)
+        .and_then(|mut file| file.write_all(content.as_bytes()))
+        .with_context(|| format!("failed to write: {path:?}"))
+}
+
+fn pem(label: &str, der: &[u8]) -> String {
+    let encoded = STANDARD.encode(der);
+    let lines = encoded
+        .as_bytes()
+        .chunks(64)
+        .map(|line| String::from_utf8_lossy(line) + "\n")
+        .collect::<String>();
+
+    format!("-----BEGIN {label}-----\n{lines}-----END {label}-----\n")
+}
+
+// YYMMDDHHMMSSZ, which is valid until 2049
+fn utc_time(time: SystemTime) -> anyhow::Result<String> {
+    let rfc3339 = humantime::format_rfc3339_seconds(time).to_string();
+    let digits = rfc3339.chars().filter(char::is_ascii_digit).collect::<String>();
+    digits.get(2..14).map(|digits| format!("{digits}Z")).context("time out of range")
+}
+
+fn der(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut buffer = vec![tag];
+    match content.len() {
+        length @ 0..0x80 => buffer.push(length as u8),
+        length => {
+            let bytes = length.to_be_bytes();
+            let bytes = &bytes[bytes.iter().position(|byte| *byte != 0).unwrap_or(bytes.len() - 1)..];
+            buffer.push(0x80 | bytes.len() as u8);
+            buffer.extend_from_slice(bytes);
+        },
+    }
+    buffer.extend_from_slice(content);
+
+    buffer
+}
+
+fn sequence(items: &[Vec<u8>]) -> Vec<u8> {
+    der(TAG_SEQUENCE, &items.concat())
+}
+
+fn set(items: &[Vec<u8>]) -> Vec<u8> {
+    der(TAG_SET, &items.concat())
+}
+
+// Without unused bits
+fn bit_string(bytes: &[u8]) -> Vec<u8> {
+    der(TAG_BIT_STRING, &[[0].as_slice(), bytes].concat())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+
+    use crate::tls::self_signed::{der, generate, utc_time};
+
+    #[test]
+    fn der_length() {
+        assert_eq!(der(0x04, &[1, 2]), [0x04, 0x02, 1, 2]);
+        assert_eq!(der(0x04, &[0; 0x80])[..3], [0x04, 0x81, 0x80]);
+        assert_eq!(der(0x04, &[0; 0x100])[..4], [0x04, 0x82, 0x01, 0x00]);
+    }
+
+    #[test]
+    fn utc() {
+        let time = SystemTime::UNIX_EPOCH + Duration::from_secs(1_791_772_431);
+
+        assert_eq!(utc_time(time).unwrap(), "261012023351Z");
+    }
+
+    #[test]
+    fn certificate() {
+        let (cert, key) = generate("raspberrypi", SystemTime::now()).unwrap();
+
+        // Fails unless the certificate parses and its public key matches the private key
+        rustls::ServerConfig::builder_with_provider(rustls::crypto::ring::default_provider().into())
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_no_client_auth()
+            .with_single_cert(vec![CertificateDer::from(cert)], PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(key)))
+            .unwrap();
+    }
+}
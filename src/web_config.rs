@@ -0,0 +1,215 @@
+use std::{collections::HashMap, fs, path::{Path, PathBuf}};
+
+use anyhow::Context;
+use serde::{de::IgnoredAny, Deserialize};
+
+use crate::{basic_auth::BasicAuth, tls::{ClientAuth, TlsConfig, TlsVersion}};
+
+/// Web configuration file in the format of the Prometheus exporter-toolkit.
+///
+/// Relative paths in the file are resolved against the directory of the file like the exporter-toolkit does.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebConfig {
+    #[serde(default)]
+    tls_server_config: Option<TlsServerConfig>,
+    #[serde(default)]
+    http_server_config: HttpServerConfig,
+    #[serde(default)]
+    basic_auth_users: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TlsServerConfig {
+    cert_file: PathBuf,
+    key_file: PathBuf,
+    #[serde(default)]
+    client_auth_type: ClientAuthType,
+    #[serde(default)]
+    client_ca_file: Option<PathBuf>,
+    #[serde(default = "default_min_version")]
+    min_version: TlsVersion,
+    #[serde(default = "default_max_version")]
+    max_version: TlsVersion,
+    #[serde(default)]
+    cipher_suites: Vec<String>,
+    #[serde(default)]
+    curve_preferences: Vec<String>,
+    #[serde(default = "default_prefer_server_cipher_suites")]
+    prefer_server_cipher_suites: bool,
+    #[serde(default)]
+    client_allowed_sans: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct HttpServerConfig {
+    #[serde(default = "default_http2")]
+    http2: bool,
+    // Parsed for compatibility, custom response headers aren't supported
+    #[serde(default, rename = "headers")]
+    _headers: IgnoredAny,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Deserialize)]
+enum ClientAuthType {
+    #[default]
+    NoClientCert,
+    VerifyClientCertIfGiven,
+    RequireAndVerifyClientCert,
+}
+
+impl WebConfig {
+    pub fn from_file(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path).with_context(|| format!("web config read error: {path:?}"))?;
+        let config = Self::parse(&content, path.parent().unwrap_or(Path::new("")))
+            .with_context(|| format!("invalid web config: {path:?}"))?;
+
+        Ok(config)
+    }
+
+    fn parse(content: &str, base: &Path) -> anyhow::Result<Self> {
+        // An empty file is valid and enables nothing
+        if content.trim().is_empty() {
+            return Ok(Self::default());
+        }
+
+        let mut config = serde_yaml::from_str::<Self>(content)?;
+        if let Some(tls) = &mut config.tls_server_config {
+            tls.cert_file = base.join(&tls.cert_file);
+            tls.key_file = base.join(&tls.key_file);
+            tls.client_ca_file = tls.client_ca_file.as_ref().map(|file| base.join(file));
+        }
+
+        Ok(config)
+    }
+
+    pub fn tls(&self) -> anyhow::Result<Option<TlsConfig>> {
+        let Some(tls) = &self.tls_server_config else {
+            return Ok(None);
+        };
+
+        let client_auth = match (&tls.client_auth_type, &tls.client_ca_file) {
+            (ClientAuthType::NoClientCert, _) => ClientAuth::None,
+            (ClientAuthType::VerifyClientCertIfGiven, Some(ca_file)) => ClientAuth::VerifyIfGiven(ca_file.clone()),
+            (ClientAuthType::RequireAndVerifyClientCert, Some(ca_file)) => ClientAuth::Require(ca_file.clone()),
+            (client_auth_type, None) => anyhow::bail!("client_ca_file is required for {client_auth_type:?}"),
+        };
+
+        let config = TlsConfig::new(&tls.cert_file, &tls.key_file)
+            .versions(tls.min_version, tls.max_version)
+            .client_auth(client_auth)
+            .cipher_suites(tls.cipher_suites.clone())
+            .curve_preferences(tls.curve_preferences.clone())
+            .prefer_server_cipher_suites(tls.prefer_server_cipher_suites)
+            .client_allowed_sans(tls.client_allowed_sans.clone());
+
+        Ok(Some(config))
+    }
+
+    pub fn http2(&self) -> bool {
+        self.http_server_config.http2
+    }
+
+    pub fn basic_auth(&self) -> Option<BasicAuth> {
+        (!self.basic_auth_users.is_empty()).then(|| BasicAuth::new(self.basic_auth_users.clone()))
+    }
+}
+
+impl Default for HttpServerConfig {
+    fn default() -> Self {
+        Self {
+            http2: default_http2(),
+            _headers: IgnoredAny,
+        }
+    }
+}
+
+fn default_http2() -> bool {
+    true
+}
+
+fn default_prefer_server_cipher_suites() -> bool {
+    true
+}
+
+fn default_min_version() -> TlsVersion {
+    TlsVersion::Tls12
+}
+
+fn default_max_version() -> TlsVersion {
+    TlsVersion::Tls13
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::web_config::{ClientAuthType, WebConfig};
+
+    #[test]
+    fn parse() {
+        let content = r#"
+tls_server_config:
+  cert_file: server.crt
+  key_file: /etc/ssl/server.key
+  client_auth_type: RequireAndVerifyClientCert
+  client_ca_file: ca.crt
+  min_version: TLS13
+  cipher_suites:
+    - TLS_ECDHE_ECDSA_WITH_AES_128_GCM_SHA256
+  curve_preferences:
+    - X25519
+  prefer_server_cipher_suites: false
+  client_allowed_sans:
+    - prometheus.example.com
+http_server_config:
+  http2: false
+basic_auth_users:
+  prometheus: $2y$10$abcdefghijklmnopqrstuu
+"#;
+        let config = WebConfig::parse(content, Path::new("/etc/raspi-exporter")).unwrap();
+        let tls = config.tls_server_config.as_ref().unwrap();
+
+        assert_eq!(tls.cert_file, Path::new("/etc/raspi-exporter/server.crt"));
+        assert_eq!(tls.key_file, Path::new("/etc/ssl/server.key"));
+        assert_eq!(tls.client_ca_file.as_deref(), Some(Path::new("/etc/raspi-exporter/ca.crt")));
+        assert_eq!(tls.client_auth_type, ClientAuthType::RequireAndVerifyClientCert);
+        assert_eq!(tls.cipher_suites, ["TLS_ECDHE_ECDSA_WITH_AES_128_GCM_SHA256"]);
+        assert_eq!(tls.curve_preferences, ["X25519"]);
+        assert!(!tls.prefer_server_cipher_suites);
+        assert_eq!(tls.client_allowed_sans, ["prometheus.example.com"]);
+        assert!(config.tls().unwrap().is_some());
+        assert!(!config.http2());
+        assert!(config.basic_auth().is_some());
+    }
+
+    #[test]
+    fn parse_empty() {
+        let config = WebConfig::parse("", Path::new("")).unwrap();
+
+        assert!(config.tls().unwrap().is_none());
+        assert!(config.http2());
+        assert!(config.basic_auth().is_none());
+    }
+
+    #[test]
+    fn parse_without_client_ca() {
+        let content = r#"
+tls_server_config:
+  cert_file: server.crt
+  key_file: server.key
+  client_auth_type: VerifyClientCertIfGiven
+"#;
+        let config = WebConfig::parse(content, Path::new("")).unwrap();
+
+        assert!(config.tls().is_err());
+    }
+
+    #[test]
+    fn parse_unknown_field() {
+        assert!(WebConfig::parse("unknown: true", Path::new("")).is_err());
+    }
+}
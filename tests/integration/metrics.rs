@@ -2,28 +2,60 @@ use std::sync::{Arc, Mutex};
 
 use prometheus_client::registry::Registry;
 use raspi_exporter::{
-    collector::throttled::Throttled,
-    executor::throttled::ThrottledExecutor,
-    metrics::{ Handler, MetricsHandler },
-    parser::throttled::ThrottledParser,
-    registerer::throttled::ThrottledRegisterer,
+    collector::{
+        file_descriptor::{FileDescriptor, ProcessFileDescriptor},
+        filesystem::Filesystem,
+        oom_kill::OomKill,
+        snmp::Snmp,
+        throttled::Throttled,
+    },
+    executor::{
+        file_descriptor::{FileDescriptorExecutor, ProcessFileDescriptorExecutor},
+        filesystem::FilesystemExecutor,
+        oom_kill::OomKillExecutor,
+        snmp::SnmpExecutor,
+        throttled::ThrottledExecutor,
+    },
+    format::Format,
+    metrics::{ Filter, Handler, MetricsHandler },
+    parser::{
+        file_descriptor::{FileDescriptorParser, ProcessFileDescriptorParser},
+        filesystem::FilesystemParser,
+        oom_kill::OomKillParser,
+        snmp::SnmpParser,
+        throttled::ThrottledParser,
+    },
+    registerer::{
+        file_descriptor::{FileDescriptorRegisterer, ProcessFileDescriptorRegisterer},
+        filesystem::FilesystemRegisterer,
+        oom_kill::OomKillRegisterer,
+        snmp::SnmpRegisterer,
+        throttled::ThrottledRegisterer,
+    },
 };
 
+// Number of samples, without the HELP and TYPE lines, which families without samples have or not depending on the version
+// of prometheus-client
+fn samples(output: &str) -> usize {
+    output.lines().filter(|line| !line.starts_with('#')).count()
+}
+
 #[tokio::test]
 async fn metrics() {
-    let registry = Arc::new(Mutex::new(Registry::default()));
+    let registry = Arc::new(Mutex::new(Registry::with_prefix("raspi")));
     let throttled = Throttled::new(
         ThrottledExecutor::new("echo", ["throttled=0xd0005"]),
         ThrottledParser,
-        ThrottledRegisterer { registry: registry.clone() }
+        ThrottledRegisterer::new(&mut registry.lock().unwrap()),
     );
-    let metrics_handler = MetricsHandler::new(Some(throttled), registry.clone());
-    let result = metrics_handler.handle().await.unwrap();
+    let metrics_handler = MetricsHandler::new(vec![Box::new(throttled)], registry.clone());
+    let output = metrics_handler.handle(&Filter::default(), None).await.unwrap();
+    let result = String::from_utf8(Format::OpenMetrics.encode(output.clone()).unwrap()).unwrap();
     let mut lines = result.lines();
 
-    assert_eq!(lines.clone().count(), 13);
+    assert_eq!(samples(&result), 15);
     assert_eq!(lines.next(), Some("# HELP raspi_throttling_active State about throttling active currently."));
-    assert_eq!(lines.next(), Some("# TYPE raspi_throttling_active gauge"));
+    assert_eq!(lines.next(), Some("# TYPE raspi_throttling_active stateset"));
 
     let mut metrics = lines.by_ref().take(4).collect::<Vec<_>>();
     metrics.sort();
@@ -32,15 +64,15 @@ async fn metrics() {
     assert_eq!(
         metrics,
         [
-            "raspi_throttling_active{kind=\"arm frequency\"} 0",
-            "raspi_throttling_active{kind=\"soft temperature limit\"} 0",
-            "raspi_throttling_active{kind=\"throttled\"} 1",
-            "raspi_throttling_active{kind=\"undervoltage\"} 1",
+            "raspi_throttling_active{raspi_throttling_active=\"arm frequency\"} 0",
+            "raspi_throttling_active{raspi_throttling_active=\"soft temperature limit\"} 0",
+            "raspi_throttling_active{raspi_throttling_active=\"throttled\"} 1",
+            "raspi_throttling_active{raspi_throttling_active=\"undervoltage\"} 1",
         ]
     );
 
     assert_eq!(lines.next(), Some("# HELP raspi_throttling_occurred State about throttling occurred in the past."));
-    assert_eq!(lines.next(), Some("# TYPE raspi_throttling_occurred gauge"));
+    assert_eq!(lines.next(), Some("# TYPE raspi_throttling_occurred stateset"));
 
     let mut metrics = lines.by_ref().take(4).collect::<Vec<_>>();
     metrics.sort();
@@ -48,27 +80,194 @@ async fn metrics() {
     assert_eq!(
         metrics,
         [
-            "raspi_throttling_occurred{kind=\"arm frequency\"} 0",
-            "raspi_throttling_occurred{kind=\"soft temperature limit\"} 1",
-            "raspi_throttling_occurred{kind=\"throttled\"} 1",
-            "raspi_throttling_occurred{kind=\"undervoltage\"} 1",
+            "raspi_throttling_occurred{raspi_throttling_occurred=\"arm frequency\"} 0",
+            "raspi_throttling_occurred{raspi_throttling_occurred=\"soft temperature limit\"} 1",
+            "raspi_throttling_occurred{raspi_throttling_occurred=\"throttled\"} 1",
+            "raspi_throttling_occurred{raspi_throttling_occurred=\"undervoltage\"} 1",
         ]
     );
-    assert_eq!(lines.next(), Some("# EOF"))
+
+    assert_eq!(lines.next(), Some("# HELP raspi_throttling_first_occurrence_seconds Unix timestamp when throttling was first seen occurred by the exporter."));
+    assert_eq!(lines.next(), Some("# TYPE raspi_throttling_first_occurrence_seconds gauge"));
+    assert_eq!(lines.next(), Some("# UNIT raspi_throttling_first_occurrence_seconds seconds"));
+    // Only of the kinds that have occurred
+    assert_eq!(lines.by_ref().take_while(|line| !line.starts_with('#')).count(), 3);
+    assert_eq!(lines.next(), Some("# TYPE raspi_throttling_last_seen_timestamp_seconds gauge"));
+    assert_eq!(lines.next(), Some("# UNIT raspi_throttling_last_seen_timestamp_seconds seconds"));
+    // Only of the kinds active currently
+    let mut metrics = lines.by_ref().take_while(|line| !line.starts_with('#')).collect::<Vec<_>>();
+    metrics.sort();
+    assert_eq!(metrics.len(), 2);
+    assert!(metrics[0].starts_with("raspi_throttling_last_seen_timestamp_seconds{kind=\"throttled\"} "));
+    assert!(metrics[1].starts_with("raspi_throttling_last_seen_timestamp_seconds{kind=\"undervoltage\"} "));
+    // Followed by the metrics of the handler itself
+    assert_eq!(lines.last(), Some("# EOF"));
+
+    // Gauges keeping the label in the text format
+    let text = String::from_utf8(Format::Text.encode(output).unwrap()).unwrap();
+    assert!(text.contains("# TYPE raspi_throttling_active gauge\n"));
+    assert!(text.contains("raspi_throttling_active{kind=\"undervoltage\"} 1\n"));
+
+    // Families are registered once rather than on every scrape
+    let result = metrics_handler.handle(&Filter::default(), None).await.unwrap();
+    assert_eq!(samples(&result), 15);
 }
 
 #[tokio::test]
 async fn command_not_found() {
-    let registry = Arc::new(Mutex::new(Registry::default()));
+    let registry = Arc::new(Mutex::new(Registry::with_prefix("raspi")));
     let throttled = Throttled::new(
         ThrottledExecutor::new("command_not_found", []),
         ThrottledParser,
-        ThrottledRegisterer { registry: registry.clone() }
+        ThrottledRegisterer::new(&mut registry.lock().unwrap()),
+    );
+    let metrics_handler = MetricsHandler::new(vec![Box::new(throttled)], registry.clone());
+    let result = metrics_handler.handle(&Filter::default(), None).await.unwrap();
+
+    // Only the samples of the handler itself
+    assert_eq!(samples(&result), 2);
+    assert!(result.contains("raspi_collector_success{collector=\"throttled\"} 0\n"));
+    assert_eq!(result.lines().last(), Some("# EOF"));
+}
+
+#[tokio::test]
+async fn oom_kill() {
+    let registry = Arc::new(Mutex::new(Registry::with_prefix("raspi")));
+    let registerer = OomKillRegisterer::new(&mut registry.lock().unwrap());
+    let oom_kill = OomKill::new(
+        OomKillExecutor::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/vmstat")),
+        OomKillParser,
+        registerer.clone(),
+    );
+    let oom_kill_cgroup = OomKill::new(
+        OomKillExecutor::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/memory.events")),
+        OomKillParser,
+        registerer.with_cgroup("system.slice"),
+    );
+    let metrics_handler = MetricsHandler::new(vec![Box::new(oom_kill), Box::new(oom_kill_cgroup)], registry.clone());
+    let result = metrics_handler.handle(&Filter::default(), None).await.unwrap();
+    let mut lines = result.lines();
+
+    assert_eq!(samples(&result), 4);
+    assert_eq!(lines.next(), Some("# HELP raspi_oom_kills Number of processes killed by the OOM killer."));
+    assert_eq!(lines.next(), Some("# TYPE raspi_oom_kills counter"));
+
+    let mut metrics = lines.by_ref().take(2).collect::<Vec<_>>();
+    metrics.sort();
+
+    assert_eq!(
+        metrics,
+        [
+            "raspi_oom_kills_total 3",
+            "raspi_oom_kills_total{cgroup=\"system.slice\"} 1",
+        ]
+    );
+    // Followed by the metrics of the handler itself
+    assert_eq!(lines.last(), Some("# EOF"));
+}
+
+#[tokio::test]
+async fn file_descriptor() {
+    let registry = Arc::new(Mutex::new(Registry::with_prefix("raspi")));
+    let file_descriptor = FileDescriptor::new(
+        FileDescriptorExecutor::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/file-nr")),
+        FileDescriptorParser,
+        FileDescriptorRegisterer::new(&mut registry.lock().unwrap()),
+    );
+    let process_file_descriptor = ProcessFileDescriptor::new(
+        ProcessFileDescriptorExecutor::new("/proc/self/fd"),
+        ProcessFileDescriptorParser,
+        ProcessFileDescriptorRegisterer::new(&mut registry.lock().unwrap()),
+    );
+    let metrics_handler = MetricsHandler::new(vec![Box::new(file_descriptor), Box::new(process_file_descriptor)], registry.clone());
+    let result = metrics_handler.handle(&Filter::default(), None).await.unwrap();
+    let mut lines = result.lines();
+
+    assert_eq!(samples(&result), 6);
+    assert_eq!(lines.next(), Some("# HELP raspi_file_descriptors_allocated Number of file descriptors allocated by the system."));
+    assert_eq!(lines.next(), Some("# TYPE raspi_file_descriptors_allocated gauge"));
+    assert_eq!(lines.next(), Some("raspi_file_descriptors_allocated 2304"));
+    assert_eq!(lines.next(), Some("# HELP raspi_file_descriptors_maximum Maximum number of file descriptors the system allows."));
+    assert_eq!(lines.next(), Some("# TYPE raspi_file_descriptors_maximum gauge"));
+    assert_eq!(lines.next(), Some("raspi_file_descriptors_maximum 9223372036854775807"));
+    assert_eq!(lines.next(), Some("# HELP raspi_exporter_file_descriptors_open Number of file descriptors opened by the exporter itself."));
+    assert_eq!(lines.next(), Some("# TYPE raspi_exporter_file_descriptors_open gauge"));
+    assert!(lines.next().is_some_and(|line| line.starts_with("raspi_exporter_file_descriptors_open ") && !line.ends_with(" 0")));
+    // Followed by the metrics of the handler itself
+    assert_eq!(lines.last(), Some("# EOF"));
+}
+
+#[tokio::test]
+async fn filesystem() {
+    let registry = Arc::new(Mutex::new(Registry::with_prefix("raspi")));
+    let filesystem = Filesystem::new(
+        FilesystemExecutor::new("printf", ["Type 1B-blocks Avail Inodes IFree Mounted on\\next4 1000 400 100 60 /\\nvfat 500 200 - - /boot/firmware\\n"]),
+        FilesystemParser,
+        FilesystemRegisterer::new(&mut registry.lock().unwrap()),
     );
-    let metrics_handler = MetricsHandler::new(Some(throttled), registry.clone());
-    let result = metrics_handler.handle().await.unwrap();
+    let metrics_handler = MetricsHandler::new(vec![Box::new(filesystem)], registry.clone());
+    let result = metrics_handler.handle(&Filter::default(), None).await.unwrap();
     let mut lines = result.lines();
 
-    assert_eq!(lines.clone().count(), 1);
-    assert_eq!(lines.next(), Some("# EOF"));
+    assert_eq!(samples(&result), 8);
+    assert_eq!(lines.next(), Some("# HELP raspi_filesystem_size_bytes Size of the filesystem."));
+    assert_eq!(lines.next(), Some("# TYPE raspi_filesystem_size_bytes gauge"));
+    assert_eq!(lines.next(), Some("# UNIT raspi_filesystem_size_bytes bytes"));
+
+    let mut metrics = lines.by_ref().take(2).collect::<Vec<_>>();
+    metrics.sort();
+
+    assert_eq!(
+        metrics,
+        [
+            "raspi_filesystem_size_bytes{mountpoint=\"/\",fstype=\"ext4\"} 1000",
+            "raspi_filesystem_size_bytes{mountpoint=\"/boot/firmware\",fstype=\"vfat\"} 500",
+        ]
+    );
+
+    assert_eq!(lines.next(), Some("# HELP raspi_filesystem_avail_bytes Space available to unprivileged users on the filesystem."));
+    assert_eq!(lines.next(), Some("# TYPE raspi_filesystem_avail_bytes gauge"));
+    assert_eq!(lines.next(), Some("# UNIT raspi_filesystem_avail_bytes bytes"));
+
+    let mut metrics = lines.by_ref().take(2).collect::<Vec<_>>();
+    metrics.sort();
+
+    assert_eq!(
+        metrics,
+        [
+            "raspi_filesystem_avail_bytes{mountpoint=\"/\",fstype=\"ext4\"} 400",
+            "raspi_filesystem_avail_bytes{mountpoint=\"/boot/firmware\",fstype=\"vfat\"} 200",
+        ]
+    );
+
+    assert_eq!(lines.next(), Some("# HELP raspi_filesystem_inodes_total Total number of inodes on the filesystem."));
+    assert_eq!(lines.next(), Some("# TYPE raspi_filesystem_inodes_total gauge"));
+    assert_eq!(lines.next(), Some("raspi_filesystem_inodes_total{mountpoint=\"/\",fstype=\"ext4\"} 100"));
+    assert_eq!(lines.next(), Some("# HELP raspi_filesystem_inodes_free Number of free inodes on the filesystem."));
+    assert_eq!(lines.next(), Some("# TYPE raspi_filesystem_inodes_free gauge"));
+    assert_eq!(lines.next(), Some("raspi_filesystem_inodes_free{mountpoint=\"/\",fstype=\"ext4\"} 60"));
+    // Followed by the metrics of the handler itself
+    assert_eq!(lines.last(), Some("# EOF"));
+}
+
+#[tokio::test]
+async fn snmp() {
+    let registry = Arc::new(Mutex::new(Registry::with_prefix("raspi")));
+    let snmp = Snmp::new(
+        SnmpExecutor::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/snmp")),
+        SnmpParser,
+        SnmpRegisterer::new(&mut registry.lock().unwrap()),
+    );
+    let metrics_handler = MetricsHandler::new(vec![Box::new(snmp)], registry.clone());
+    let result = metrics_handler.handle(&Filter::default(), None).await.unwrap();
+    let lines = result.lines().collect::<Vec<_>>();
+
+    assert!(lines.contains(&"raspi_network_protocol_received_total{protocol=\"ip\"} 9582"));
+    assert!(lines.contains(&"raspi_network_protocol_sent_total{protocol=\"udp\"} 27"));
+    assert!(lines.contains(&"raspi_network_protocol_checksum_errors_total{protocol=\"tcp\"} 4"));
+    assert!(!lines.iter().any(|line| line.starts_with("raspi_network_protocol_checksum_errors_total{protocol=\"ip\"}")));
+    assert!(lines.contains(&"raspi_tcp_retransmitted_segments_total 42"));
+    assert!(lines.contains(&"raspi_tcp_connections_established 2"));
+    assert!(lines.contains(&"raspi_udp_no_port_datagrams_total 7"));
+    assert_eq!(lines.last(), Some(&"# EOF"));
 }